@@ -12,7 +12,6 @@
 //!
 //! This example demonstrates how to filter messages by forum topic ID.
 
-use futures_util::future::{Either, select};
 use grammers_client::session::Session;
 use grammers_client::{Client, Config, InitParams, Update};
 use simple_logger::SimpleLogger;
@@ -103,17 +102,10 @@ async fn async_main() -> Result {
 
     println!("Waiting for messages...");
 
-    // This code uses `select` on Ctrl+C to gracefully stop the client and have a chance to
-    // save the session.
-    loop {
-        let exit = pin!(async { tokio::signal::ctrl_c().await });
-        let upd = pin!(async { client.next_update().await });
-
-        let update = match select(exit, upd).await {
-            Either::Left(_) => break,
-            Either::Right((u, _)) => u?,
-        };
-
+    // Stop on Ctrl+C to gracefully stop the client and have a chance to save the session.
+    // Updates which arrived before then are still handled.
+    let mut exit = pin!(tokio::signal::ctrl_c());
+    while let Some(update) = client.next_update_until(exit.as_mut()).await? {
         let handle = client.clone();
         let topics_clone = allowed_topics.clone();
         task::spawn(async move {
@@ -0,0 +1,850 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Methods related to the settings and personalization of the logged-in account.
+
+use super::Client;
+use super::auth::code_settings;
+use super::chats::channel_invalid;
+use crate::types::{
+    Birthday, BusinessHours, BusinessLocation, ContentSettings, NotifyScope, PhoneChangeToken,
+    ReactionNotifySettings, SentEmailCode, StoryNotifySettings, Uploaded, User,
+};
+use chrono::{DateTime, Utc};
+use grammers_mtproto::mtp::DeserializeError;
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types::{self as tl, Identifiable};
+use std::time::Duration;
+
+/// The outcome of [`Client::delete_account`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountDeletion {
+    /// The account was deleted.
+    Deleted,
+    /// The account is protected by a two-factor authentication password which was not provided,
+    /// so Telegram scheduled its deletion instead. Deleting the account is possible once the
+    /// given time has passed, which gives the owner a chance to cancel the process.
+    Scheduled { wait: Duration },
+}
+
+/// Method implementations related to the logged-in account.
+impl Client {
+    /// Fetch the catalogue of themes that can be applied to chats.
+    ///
+    /// The emoticon of each theme can be used with [`Client::set_chat_theme`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for theme in client.get_chat_themes().await? {
+    ///     println!("{}: {:?}", theme.title, theme.emoticon);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_chat_themes(&self) -> Result<Vec<tl::types::Theme>, InvocationError> {
+        match self
+            .invoke(&tl::functions::account::GetChatThemes { hash: 0 })
+            .await?
+        {
+            tl::enums::account::Themes::Themes(themes) => Ok(themes
+                .themes
+                .into_iter()
+                .map(|tl::enums::Theme::Theme(theme)| theme)
+                .collect()),
+            tl::enums::account::Themes::NotModified => {
+                panic!("API returned Themes::NotModified even though hash = 0")
+            }
+        }
+    }
+
+    /// Change the color used to display the name of the logged-in account.
+    ///
+    /// The `color` is one of the palette indices exposed by Telegram, and the optional
+    /// `background_emoji_id` is the custom emoji used as the pattern behind replies and link
+    /// previews. Passing `None` for both resets them to their default values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_name_color(Some(3), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_name_color(
+        &self,
+        color: Option<i32>,
+        background_emoji_id: Option<i64>,
+    ) -> Result<(), InvocationError> {
+        self.update_color(false, color, background_emoji_id).await
+    }
+
+    /// Change the accent color used in the profile page of the logged-in account.
+    ///
+    /// This behaves like [`Client::set_name_color`], but the color is applied to the profile
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_profile_color(Some(5), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_profile_color(
+        &self,
+        color: Option<i32>,
+        background_emoji_id: Option<i64>,
+    ) -> Result<(), InvocationError> {
+        self.update_color(true, color, background_emoji_id).await
+    }
+
+    /// Set the emoji status shown next to the name of the logged-in account.
+    ///
+    /// The `document_id` is the identifier of the custom emoji to use. If `until` is set, the
+    /// status will be cleared automatically at that Unix timestamp.
+    ///
+    /// Only premium accounts are allowed to set an emoji status.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let document_id = 5373141891321699086;
+    /// client.set_emoji_status(document_id, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_emoji_status(
+        &self,
+        document_id: i64,
+        until: Option<i32>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdateEmojiStatus {
+            emoji_status: tl::types::EmojiStatus { document_id, until }.into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Remove the emoji status of the logged-in account, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.clear_emoji_status().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_emoji_status(&self) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdateEmojiStatus {
+            emoji_status: tl::enums::EmojiStatus::Empty,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Fetch the emoji statuses that Telegram suggests by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for status in client.get_default_emoji_statuses().await? {
+    ///     println!("{:?}", status);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_default_emoji_statuses(
+        &self,
+    ) -> Result<Vec<tl::enums::EmojiStatus>, InvocationError> {
+        match self
+            .invoke(&tl::functions::account::GetDefaultEmojiStatuses { hash: 0 })
+            .await?
+        {
+            tl::enums::account::EmojiStatuses::Statuses(statuses) => Ok(statuses.statuses),
+            tl::enums::account::EmojiStatuses::NotModified => {
+                panic!("API returned EmojiStatuses::NotModified even though hash = 0")
+            }
+        }
+    }
+
+    /// Change the birthday shown in the profile of the logged-in account.
+    ///
+    /// Passing `None` will remove the birthday from the profile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::Birthday;
+    ///
+    /// client.set_birthday(Some(Birthday { day: 24, month: 12, year: None })).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_birthday(&self, birthday: Option<Birthday>) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdateBirthday {
+            birthday: birthday.map(Into::into),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change the channel shown in the profile of the logged-in account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_personal_channel(&channel).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_personal_channel<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+    ) -> Result<(), InvocationError> {
+        let channel = channel
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::account::UpdatePersonalChannel { channel })
+            .await
+            .map(drop)
+    }
+
+    /// Remove the channel shown in the profile of the logged-in account, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.clear_personal_channel().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_personal_channel(&self) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdatePersonalChannel {
+            channel: tl::enums::InputChannel::Empty,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change the opening hours of the business of the logged-in account.
+    ///
+    /// Passing `None` will remove the opening hours from the profile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::BusinessHours;
+    ///
+    /// const DAY: i32 = 24 * 60;
+    /// client.set_business_hours(Some(BusinessHours {
+    ///     timezone_id: "Europe/Madrid".to_string(),
+    ///     open_now: false,
+    ///     // From 9:00 to 17:00, Monday to Friday.
+    ///     weekly_open: (0..5).map(|day| day * DAY + 9 * 60..day * DAY + 17 * 60).collect(),
+    /// })).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_business_hours(
+        &self,
+        hours: Option<BusinessHours>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdateBusinessWorkHours {
+            business_work_hours: hours.map(Into::into),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change the location of the business of the logged-in account.
+    ///
+    /// Passing `None` will remove the location from the profile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::BusinessLocation;
+    ///
+    /// client.set_business_location(Some(BusinessLocation {
+    ///     address: "Puerta del Sol, Madrid".to_string(),
+    ///     coordinates: Some((40.4169, -3.7035)),
+    /// })).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_business_location(
+        &self,
+        location: Option<BusinessLocation>,
+    ) -> Result<(), InvocationError> {
+        let (address, geo_point) = match location {
+            Some(location) => (
+                Some(location.address),
+                location.coordinates.map(|(lat, long)| {
+                    tl::types::InputGeoPoint {
+                        lat,
+                        long,
+                        accuracy_radius: None,
+                    }
+                    .into()
+                }),
+            ),
+            None => (None, None),
+        };
+        self.invoke(&tl::functions::account::UpdateBusinessLocation { geo_point, address })
+            .await
+            .map(drop)
+    }
+
+    /// Change whether the logged-in account is shown as online or offline to other users.
+    ///
+    /// Telegram considers the account offline after a few minutes without calling this method
+    /// with `offline` set to `false`, so it should be called periodically to stay online.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_offline(false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_offline(&self, offline: bool) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdateStatus { offline })
+            .await
+            .map(drop)
+    }
+
+    /// Send a verification code to the email address that should become the new login email
+    /// of the logged-in account.
+    ///
+    /// The code must then be given to [`Client::verify_login_email_change`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// fn ask_code_to_user() -> String {
+    ///     unimplemented!()
+    /// }
+    ///
+    /// let sent = client.request_login_email_change_code("ferris@example.com").await?;
+    /// println!("Enter the {}-digit code sent to {}", sent.length, sent.email_pattern);
+    /// let email = client.verify_login_email_change(&ask_code_to_user()).await?;
+    /// println!("The login email is now {}", email);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_login_email_change_code(
+        &self,
+        email: &str,
+    ) -> Result<SentEmailCode, InvocationError> {
+        self.invoke(&tl::functions::account::SendVerifyEmailCode {
+            purpose: tl::enums::EmailVerifyPurpose::LoginChange,
+            email: email.to_string(),
+        })
+        .await
+        .map(SentEmailCode::from_raw)
+    }
+
+    /// Verify the new login email of the logged-in account with the code sent to it, completing
+    /// the change.
+    ///
+    /// Returns the new login email.
+    pub async fn verify_login_email_change(&self, code: &str) -> Result<String, InvocationError> {
+        match self
+            .invoke(&tl::functions::account::VerifyEmail {
+                purpose: tl::enums::EmailVerifyPurpose::LoginChange,
+                verification: tl::types::EmailVerificationCode {
+                    code: code.to_string(),
+                }
+                .into(),
+            })
+            .await?
+        {
+            tl::enums::account::EmailVerified::Verified(verified) => Ok(verified.email),
+            tl::enums::account::EmailVerified::Login(verified) => Ok(verified.email),
+        }
+    }
+
+    /// Get the settings of the logged-in account about which content can be shown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let settings = client.get_content_settings().await?;
+    /// if settings.sensitive_can_change && !settings.sensitive_enabled {
+    ///     client.set_sensitive_content_enabled(true).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_content_settings(&self) -> Result<ContentSettings, InvocationError> {
+        self.invoke(&tl::functions::account::GetContentSettings {})
+            .await
+            .map(ContentSettings::from_raw)
+    }
+
+    /// Change whether sensitive content is shown to the logged-in account.
+    ///
+    /// This will fail if [`ContentSettings::sensitive_can_change`] is `false`.
+    pub async fn set_sensitive_content_enabled(
+        &self,
+        enabled: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::SetContentSettings {
+            sensitive_enabled: enabled,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Request a code to be sent to a new phone number, in order to change the phone number of
+    /// the logged-in account to it.
+    ///
+    /// The code must then be given to [`Client::change_phone`] along with the returned token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// fn ask_code_to_user() -> String {
+    ///     unimplemented!()
+    /// }
+    ///
+    /// let token = client.request_phone_change_code("+1 415 555 0132").await?;
+    /// let user = client.change_phone(&token, &ask_code_to_user()).await?;
+    /// println!("Phone changed to {:?}", user.phone());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_phone_change_code(
+        &self,
+        phone: &str,
+    ) -> Result<PhoneChangeToken, InvocationError> {
+        match self
+            .invoke(&tl::functions::account::SendChangePhoneCode {
+                phone_number: phone.to_string(),
+                settings: code_settings(),
+            })
+            .await?
+        {
+            tl::enums::auth::SentCode::Code(code) => Ok(PhoneChangeToken {
+                phone: phone.to_string(),
+                phone_code_hash: code.phone_code_hash,
+            }),
+            tl::enums::auth::SentCode::Success(_) => {
+                panic!("API returned SentCode::Success when changing the phone number")
+            }
+        }
+    }
+
+    /// Change the phone number of the logged-in account, using the code sent to the new number.
+    ///
+    /// Returns the updated logged-in user.
+    pub async fn change_phone(
+        &self,
+        token: &PhoneChangeToken,
+        code: &str,
+    ) -> Result<User, InvocationError> {
+        self.invoke(&tl::functions::account::ChangePhone {
+            phone_number: token.phone.clone(),
+            phone_code_hash: token.phone_code_hash.clone(),
+            phone_code: code.to_string(),
+        })
+        .await
+        .map(User::from_raw)
+    }
+
+    /// Request the code to change the phone number to be sent again.
+    ///
+    /// The returned token must be used to [`Client::change_phone`] instead of the previous one.
+    pub async fn resend_phone_change_code(
+        &self,
+        token: &PhoneChangeToken,
+    ) -> Result<PhoneChangeToken, InvocationError> {
+        let sent_code = self
+            .resend_code(&token.phone, &token.phone_code_hash)
+            .await?;
+        Ok(PhoneChangeToken {
+            phone: token.phone.clone(),
+            phone_code_hash: sent_code.phone_code_hash,
+        })
+    }
+
+    /// Invalidate the code sent to change the phone number, so that it can no longer be used.
+    pub async fn cancel_phone_change_code(
+        &self,
+        token: &PhoneChangeToken,
+    ) -> Result<(), InvocationError> {
+        self.cancel_code(&token.phone, &token.phone_code_hash).await
+    }
+
+    /// Get the number of days without activity after which the logged-in account self-destructs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// println!("Account deleted after {} days offline", client.get_account_ttl().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_account_ttl(&self) -> Result<i32, InvocationError> {
+        let tl::enums::AccountDaysTtl::Ttl(ttl) = self
+            .invoke(&tl::functions::account::GetAccountTtl {})
+            .await?;
+        Ok(ttl.days)
+    }
+
+    /// Change the number of days without activity after which the logged-in account
+    /// self-destructs.
+    ///
+    /// Telegram only accepts values between 30 and 730 days, and fails with `TTL_DAYS_INVALID`
+    /// otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_account_ttl(365).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_account_ttl(&self, days: i32) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::SetAccountTtl {
+            ttl: tl::types::AccountDaysTtl { days }.into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Delete the logged-in account, along with all of its messages, chats and contacts.
+    ///
+    /// **This cannot be undone.** The `reason` is optional feedback sent to Telegram.
+    ///
+    /// If the account is protected by a two-factor authentication password, providing it deletes
+    /// the account immediately. Otherwise, Telegram schedules its deletion, and this method
+    /// returns how long to wait until it can be deleted by calling this method again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::client::account::AccountDeletion;
+    ///
+    /// match client.delete_account("", None).await? {
+    ///     AccountDeletion::Deleted => println!("Goodbye!"),
+    ///     AccountDeletion::Scheduled { wait } => println!("Try again in {wait:?}"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_account(
+        &self,
+        reason: &str,
+        password: Option<&[u8]>,
+    ) -> Result<AccountDeletion, InvocationError> {
+        let password = match password {
+            Some(password) => Some(self.check_password_srp(None, password).await?),
+            None => None,
+        };
+
+        match self
+            .invoke(&tl::functions::account::DeleteAccount {
+                reason: reason.to_string(),
+                password,
+            })
+            .await
+        {
+            Ok(_) => Ok(AccountDeletion::Deleted),
+            // The error starts with a digit, so its value has to be extracted manually.
+            Err(InvocationError::Rpc(err)) if err.name.starts_with("2FA_CONFIRM_WAIT") => {
+                let seconds = err
+                    .name
+                    .rsplit('_')
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .or(err.value)
+                    .unwrap_or(0);
+                Ok(AccountDeletion::Scheduled {
+                    wait: Duration::from_secs(seconds as u64),
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn update_color(
+        &self,
+        for_profile: bool,
+        color: Option<i32>,
+        background_emoji_id: Option<i64>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::account::UpdateColor {
+            for_profile,
+            color,
+            background_emoji_id,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Fetch the settings for the notifications caused by reactions to the logged-in account's
+    /// messages and stories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ReactionNotifyFrom;
+    ///
+    /// // Only get notified about reactions from contacts.
+    /// let mut settings = client.get_reaction_notify_settings().await?;
+    /// settings.messages_from = Some(ReactionNotifyFrom::Contacts);
+    /// settings.stories_from = None;
+    /// client.set_reaction_notify_settings(&settings).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_reaction_notify_settings(
+        &self,
+    ) -> Result<ReactionNotifySettings, InvocationError> {
+        self.invoke(&tl::functions::account::GetReactionsNotifySettings {})
+            .await
+            .map(ReactionNotifySettings::from_raw)
+    }
+
+    /// Change the settings for the notifications caused by reactions.
+    ///
+    /// Returns the settings as they were saved by Telegram.
+    pub async fn set_reaction_notify_settings(
+        &self,
+        settings: &ReactionNotifySettings,
+    ) -> Result<ReactionNotifySettings, InvocationError> {
+        self.invoke(&tl::functions::account::SetReactionsNotifySettings {
+            settings: settings.to_raw(),
+        })
+        .await
+        .map(ReactionNotifySettings::from_raw)
+    }
+
+    /// Fetch the settings for the notifications caused by new stories in the given scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::NotifyScope;
+    ///
+    /// let settings = client.get_story_notify_settings(NotifyScope::Users).await?;
+    /// println!("Stories are muted: {:?}", settings.muted);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_story_notify_settings(
+        &self,
+        scope: NotifyScope,
+    ) -> Result<StoryNotifySettings, InvocationError> {
+        let tl::enums::PeerNotifySettings::Settings(settings) = self
+            .invoke(&tl::functions::account::GetNotifySettings {
+                peer: scope.to_raw(),
+            })
+            .await?;
+
+        Ok(StoryNotifySettings {
+            muted: settings.stories_muted,
+            hide_sender: settings.stories_hide_sender,
+        })
+    }
+
+    /// Change the settings for the notifications caused by new stories in the given scope.
+    ///
+    /// Settings which are `None` are left unchanged, as are the notification settings for
+    /// messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::{NotifyScope, StoryNotifySettings};
+    ///
+    /// let settings = StoryNotifySettings {
+    ///     muted: Some(true),
+    ///     ..Default::default()
+    /// };
+    /// client.set_story_notify_settings(NotifyScope::Chat(chat.pack()), &settings).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_story_notify_settings(
+        &self,
+        scope: NotifyScope,
+        settings: &StoryNotifySettings,
+    ) -> Result<(), InvocationError> {
+        // Omitted settings would be reset, so the current ones must be sent back.
+        let tl::enums::PeerNotifySettings::Settings(current) = self
+            .invoke(&tl::functions::account::GetNotifySettings {
+                peer: scope.to_raw(),
+            })
+            .await?;
+
+        self.invoke(&tl::functions::account::UpdateNotifySettings {
+            peer: scope.to_raw(),
+            settings: tl::types::InputPeerNotifySettings {
+                show_previews: current.show_previews,
+                silent: current.silent,
+                mute_until: current.mute_until,
+                sound: current.other_sound,
+                stories_muted: settings.muted.or(current.stories_muted),
+                stories_hide_sender: settings.hide_sender.or(current.stories_hide_sender),
+                stories_sound: current.stories_other_sound,
+            }
+            .into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Mute the notifications for new messages in the given scope, until the given date, or
+    /// forever if it's `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::NotifyScope;
+    ///
+    /// let topic_id = 123;
+    /// client.mute_notifications(NotifyScope::Topic(chat.pack(), topic_id), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mute_notifications(
+        &self,
+        scope: NotifyScope,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), InvocationError> {
+        let mute_until = until.map_or(i32::MAX, |until| until.timestamp() as i32);
+        self.set_mute_until(scope, mute_until).await
+    }
+
+    /// Unmute the notifications for new messages in the given scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::NotifyScope;
+    ///
+    /// client.unmute_notifications(NotifyScope::Chat(chat.pack())).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unmute_notifications(&self, scope: NotifyScope) -> Result<(), InvocationError> {
+        self.set_mute_until(scope, 0).await
+    }
+
+    /// Upload an image to be used as a chat wallpaper.
+    ///
+    /// The returned wallpaper can be set in a private conversation with
+    /// [`Client::set_chat_wallpaper`] or [`Client::set_shared_chat_wallpaper`]. If no `settings`
+    /// are given, the defaults are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let uploaded = client.upload_file("wallpaper.jpg").await?;
+    /// let wallpaper = client.upload_wallpaper(&uploaded, "image/jpeg", None).await?;
+    /// println!("Uploaded wallpaper with slug {}", wallpaper.slug);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_wallpaper(
+        &self,
+        file: &Uploaded,
+        mime_type: &str,
+        settings: Option<tl::enums::WallPaperSettings>,
+    ) -> Result<tl::types::WallPaper, InvocationError> {
+        match self
+            .invoke(&tl::functions::account::UploadWallPaper {
+                for_chat: true,
+                file: file.raw.clone(),
+                mime_type: mime_type.to_string(),
+                settings: settings.unwrap_or_else(|| {
+                    tl::types::WallPaperSettings {
+                        blur: false,
+                        motion: false,
+                        background_color: None,
+                        second_background_color: None,
+                        third_background_color: None,
+                        fourth_background_color: None,
+                        intensity: None,
+                        rotation: None,
+                        emoticon: None,
+                    }
+                    .into()
+                }),
+            })
+            .await?
+        {
+            tl::enums::WallPaper::Paper(wallpaper) => Ok(wallpaper),
+            // Uploaded wallpapers always have a file, so anything else is an unexpected response.
+            tl::enums::WallPaper::NoFile(_) => Err(InvocationError::Read(
+                DeserializeError::UnexpectedConstructor {
+                    id: tl::types::WallPaperNoFile::CONSTRUCTOR_ID,
+                }
+                .into(),
+            )),
+        }
+    }
+
+    async fn set_mute_until(
+        &self,
+        scope: NotifyScope,
+        mute_until: i32,
+    ) -> Result<(), InvocationError> {
+        // Omitted settings would be reset, so the current ones must be sent back.
+        let tl::enums::PeerNotifySettings::Settings(current) = self
+            .invoke(&tl::functions::account::GetNotifySettings {
+                peer: scope.to_raw(),
+            })
+            .await?;
+
+        self.invoke(&tl::functions::account::UpdateNotifySettings {
+            peer: scope.to_raw(),
+            settings: tl::types::InputPeerNotifySettings {
+                show_previews: current.show_previews,
+                silent: current.silent,
+                mute_until: Some(mute_until),
+                sound: current.other_sound,
+                stories_muted: current.stories_muted,
+                stories_hide_sender: current.stories_hide_sender,
+                stories_sound: current.stories_other_sound,
+            }
+            .into(),
+        })
+        .await
+        .map(drop)
+    }
+}
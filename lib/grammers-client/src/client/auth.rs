@@ -7,12 +7,13 @@
 // except according to those terms.
 use super::Client;
 use super::net::connect_sender;
-use crate::types::{LoginToken, PasswordToken, TermsOfService, User};
+use crate::types::{LoginToken, PasswordToken, SentEmailCode, TermsOfService, User};
 use crate::utils;
 use grammers_crypto::two_factor_auth::{calculate_2fa, check_p_and_g};
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_tl_types as tl;
 use std::fmt;
+use std::sync::atomic::Ordering;
 
 /// The error type which is returned when signing in fails.
 #[derive(Debug)]
@@ -33,10 +34,7 @@ impl fmt::Display for SignInError {
         match self {
             SignUpRequired {
                 terms_of_service: tos,
-            } => write!(
-                f,
-                "sign in error: sign up with official client required: {tos:?}"
-            ),
+            } => write!(f, "sign in error: sign up required: {tos:?}"),
             PasswordRequired(_password) => write!(f, "2fa password required"),
             InvalidCode => write!(f, "sign in error: invalid code"),
             InvalidPassword => write!(f, "invalid password"),
@@ -89,14 +87,17 @@ impl Client {
 
         let user = User::from_raw(auth.user);
 
+        self.0.config.session.set_user(
+            user.id(),
+            self.0.dc_id.load(Ordering::Relaxed),
+            user.is_bot(),
+        );
+        self.0
+            .chat_hashes
+            .update(|chat_hashes| chat_hashes.set_self_user(user.pack()));
+
         let sync_state = {
             let mut state = self.0.state.write().unwrap();
-            self.0
-                .config
-                .session
-                .set_user(user.id(), state.dc_id, user.is_bot());
-
-            state.chat_hashes.set_self_user(user.pack());
             if let Some(us) = update_state {
                 state.message_box.set_state(us);
                 true
@@ -161,8 +162,7 @@ impl Client {
                 {
                     *self.0.conn.sender.lock().await = sender;
                     *self.0.conn.request_tx.write().unwrap() = request_tx;
-                    let mut state = self.0.state.write().unwrap();
-                    state.dc_id = dc_id;
+                    self.0.dc_id.store(dc_id, Ordering::Relaxed);
                 }
                 self.invoke(&request).await?
             }
@@ -208,18 +208,7 @@ impl Client {
             phone_number: phone.to_string(),
             api_id: self.0.config.api_id,
             api_hash: self.0.config.api_hash.clone(),
-            settings: tl::types::CodeSettings {
-                allow_flashcall: false,
-                current_number: false,
-                allow_app_hash: false,
-                allow_missed_call: false,
-                allow_firebase: false,
-                logout_tokens: None,
-                token: None,
-                app_sandbox: None,
-                unknown_number: false,
-            }
-            .into(),
+            settings: code_settings(),
         };
 
         use tl::enums::auth::SentCode as SC;
@@ -241,8 +230,7 @@ impl Client {
                 {
                     *self.0.conn.sender.lock().await = sender;
                     *self.0.conn.request_tx.write().unwrap() = request_tx;
-                    let mut state = self.0.state.write().unwrap();
-                    state.dc_id = dc_id;
+                    self.0.dc_id.store(dc_id, Ordering::Relaxed);
                 }
                 match self.invoke(&request).await? {
                     SC::Code(code) => code,
@@ -252,10 +240,151 @@ impl Client {
             Err(e) => return Err(e.into()),
         };
 
-        Ok(LoginToken {
-            phone: phone.to_string(),
-            phone_code_hash: sent_code.phone_code_hash,
+        Ok(LoginToken::from_raw(phone, sent_code))
+    }
+
+    /// Request the login code to be sent again, possibly in a different way (for example, as an
+    /// SMS instead of a message in another session).
+    ///
+    /// The returned token must be used to [`Client::sign_in`] instead of the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// # const PHONE: &str = "";
+    /// let token = client.request_login_code(PHONE).await?;
+    /// // The code didn't arrive, so try again.
+    /// let token = client.resend_login_code(&token).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resend_login_code(
+        &self,
+        token: &LoginToken,
+    ) -> Result<LoginToken, InvocationError> {
+        let sent_code = self
+            .resend_code(&token.phone, &token.phone_code_hash)
+            .await?;
+        Ok(LoginToken::from_raw(&token.phone, sent_code))
+    }
+
+    /// Invalidate the login code that was sent, so that it can no longer be used to sign in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// # const PHONE: &str = "";
+    /// let token = client.request_login_code(PHONE).await?;
+    /// // The user changed their mind.
+    /// client.cancel_login_code(&token).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cancel_login_code(&self, token: &LoginToken) -> Result<(), InvocationError> {
+        self.cancel_code(&token.phone, &token.phone_code_hash).await
+    }
+
+    pub(crate) async fn resend_code(
+        &self,
+        phone: &str,
+        phone_code_hash: &str,
+    ) -> Result<tl::types::auth::SentCode, InvocationError> {
+        match self
+            .invoke(&tl::functions::auth::ResendCode {
+                phone_number: phone.to_string(),
+                phone_code_hash: phone_code_hash.to_string(),
+                reason: None,
+            })
+            .await?
+        {
+            tl::enums::auth::SentCode::Code(code) => Ok(code),
+            tl::enums::auth::SentCode::Success(_) => panic!("should not have logged in yet"),
+        }
+    }
+
+    pub(crate) async fn cancel_code(
+        &self,
+        phone: &str,
+        phone_code_hash: &str,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::auth::CancelCode {
+            phone_number: phone.to_string(),
+            phone_code_hash: phone_code_hash.to_string(),
         })
+        .await
+        .map(drop)
+    }
+
+    /// Send a verification code to the email address that will be set up as the login email.
+    ///
+    /// This is only needed when [`LoginToken::is_email_setup_required`], after which the code
+    /// must be given to [`Client::verify_login_email_setup`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// # const PHONE: &str = "";
+    /// # const EMAIL: &str = "";
+    /// fn ask_code_to_user() -> String {
+    ///     unimplemented!()
+    /// }
+    ///
+    /// let mut token = client.request_login_code(PHONE).await?;
+    /// if token.is_email_setup_required() {
+    ///     let sent = client.request_login_email_setup_code(&token, EMAIL).await?;
+    ///     println!("Enter the code sent to {}", sent.email_pattern);
+    ///     token = client.verify_login_email_setup(&token, &ask_code_to_user()).await?;
+    /// }
+    ///
+    /// client.sign_in(&token, &ask_code_to_user()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_login_email_setup_code(
+        &self,
+        token: &LoginToken,
+        email: &str,
+    ) -> Result<SentEmailCode, InvocationError> {
+        self.invoke(&tl::functions::account::SendVerifyEmailCode {
+            purpose: token.email_setup_purpose(),
+            email: email.to_string(),
+        })
+        .await
+        .map(SentEmailCode::from_raw)
+    }
+
+    /// Verify the login email being set up with the code sent to it.
+    ///
+    /// On success, the login code is sent and the returned token must be used to
+    /// [`Client::sign_in`] instead of the previous one.
+    pub async fn verify_login_email_setup(
+        &self,
+        token: &LoginToken,
+        code: &str,
+    ) -> Result<LoginToken, InvocationError> {
+        match self
+            .invoke(&tl::functions::account::VerifyEmail {
+                purpose: token.email_setup_purpose(),
+                verification: tl::types::EmailVerificationCode {
+                    code: code.to_string(),
+                }
+                .into(),
+            })
+            .await?
+        {
+            tl::enums::account::EmailVerified::Login(verified) => match verified.sent_code {
+                tl::enums::auth::SentCode::Code(sent_code) => {
+                    Ok(LoginToken::from_raw(&token.phone, sent_code))
+                }
+                tl::enums::auth::SentCode::Success(_) => panic!("should not have logged in yet"),
+            },
+            tl::enums::account::EmailVerified::Verified(_) => {
+                panic!("API returned EmailVerified::Verified for the login setup purpose")
+            }
+        }
     }
 
     /// Signs in to the user account.
@@ -329,6 +458,86 @@ impl Client {
         }
     }
 
+    /// Signs up a new user account, after [`Client::sign_in`] failed with
+    /// [`SignInError::SignUpRequired`] because the phone number is not registered yet.
+    ///
+    /// The same login token used to sign in must be used here. If terms of service were
+    /// returned, they should be shown to the user and, once agreed upon, accepted with
+    /// [`Client::accept_terms_of_service`] after signing up.
+    ///
+    /// As with [`Client::sign_in`], it is recommended to save the [`Client::session()`] on
+    /// successful sign up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use grammers_client::SignInError;
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// # const PHONE: &str = "";
+    /// fn ask_code_to_user() -> String {
+    ///     unimplemented!()
+    /// }
+    ///
+    /// let token = client.request_login_code(PHONE).await?;
+    /// let user = match client.sign_in(&token, &ask_code_to_user()).await {
+    ///     Ok(user) => user,
+    ///     Err(SignInError::SignUpRequired { terms_of_service }) => {
+    ///         let user = client.sign_up(&token, "Ferris", "").await?;
+    ///         if let Some(tos) = terms_of_service {
+    ///             println!("{}", tos.text());
+    ///             client.accept_terms_of_service(&tos).await?;
+    ///         }
+    ///         user
+    ///     }
+    ///     Err(err) => return Err(err.into()),
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign_up(
+        &self,
+        token: &LoginToken,
+        first_name: &str,
+        last_name: &str,
+    ) -> Result<User, SignInError> {
+        match self
+            .invoke(&tl::functions::auth::SignUp {
+                no_joined_notifications: false,
+                phone_number: token.phone.clone(),
+                phone_code_hash: token.phone_code_hash.clone(),
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+            })
+            .await
+        {
+            Ok(tl::enums::auth::Authorization::Authorization(x)) => {
+                self.complete_login(x).await.map_err(SignInError::Other)
+            }
+            Ok(tl::enums::auth::Authorization::SignUpRequired(x)) => {
+                Err(SignInError::SignUpRequired {
+                    terms_of_service: x.terms_of_service.map(TermsOfService::from_raw),
+                })
+            }
+            Err(err) if err.is("PHONE_CODE_*") => Err(SignInError::InvalidCode),
+            Err(error) => Err(SignInError::Other(error)),
+        }
+    }
+
+    /// Accept the terms of service on behalf of the logged-in user.
+    ///
+    /// This should only be done after the user has agreed upon them, as shown in
+    /// [`Client::sign_up`].
+    pub async fn accept_terms_of_service(
+        &self,
+        terms_of_service: &TermsOfService,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::help::AcceptTermsOfService {
+            id: terms_of_service.raw.id.clone(),
+        })
+        .await
+        .map(drop)
+    }
+
     /// Extract information needed for the two-factor authentication
     /// It's called automatically when we get SESSION_PASSWORD_NEEDED error during sign in.
     async fn get_password_information(&self) -> Result<PasswordToken, InvocationError> {
@@ -339,6 +548,48 @@ impl Client {
         Ok(PasswordToken::new(password))
     }
 
+    /// Compute the proof that the logged-in account's two-factor authentication password is
+    /// known, as needed by the requests which are protected by it.
+    ///
+    /// The password information is fetched if it's not given, or if the given one is invalid.
+    pub(crate) async fn check_password_srp(
+        &self,
+        password_info: Option<tl::types::account::Password>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<tl::enums::InputCheckPasswordSrp, InvocationError> {
+        let password_info = match password_info {
+            Some(info)
+                if info.current_algo.as_ref().is_none_or(|algo| {
+                    let (_, _, p, g) = utils::extract_password_parameters(algo);
+                    check_p_and_g(p, g)
+                }) =>
+            {
+                info
+            }
+            // Telegram sent us incorrect parameters, trying to get them again
+            _ => self.get_password_information().await?.password,
+        };
+        let Some(current_algo) = password_info.current_algo.as_ref() else {
+            return Ok(tl::enums::InputCheckPasswordSrp::InputCheckPasswordEmpty);
+        };
+
+        let (salt1, salt2, p, g) = utils::extract_password_parameters(current_algo);
+        if !check_p_and_g(p, g) {
+            panic!("Failed to get correct password information from Telegram")
+        }
+
+        let g_b = password_info.srp_b.unwrap();
+        let a: Vec<u8> = password_info.secure_random;
+        let (m1, g_a) = calculate_2fa(salt1, salt2, p, g, g_b, a, password);
+
+        Ok(tl::types::InputCheckPasswordSrp {
+            srp_id: password_info.srp_id.unwrap(),
+            a: g_a.to_vec(),
+            m1: m1.to_vec(),
+        }
+        .into())
+    }
+
     /// Sign in using two-factor authentication (user password).
     ///
     /// [`PasswordToken`] can be obtained from [`SignInError::PasswordRequired`] error after the
@@ -382,38 +633,11 @@ impl Client {
         password_token: PasswordToken,
         password: impl AsRef<[u8]>,
     ) -> Result<User, SignInError> {
-        let mut password_info = password_token.password;
-        let current_algo = password_info.current_algo.unwrap();
-        let mut params = utils::extract_password_parameters(&current_algo);
-
-        // Telegram sent us incorrect parameters, trying to get them again
-        if !check_p_and_g(params.2, params.3) {
-            password_info = self
-                .get_password_information()
-                .await
-                .map_err(SignInError::Other)?
-                .password;
-            params =
-                utils::extract_password_parameters(password_info.current_algo.as_ref().unwrap());
-            if !check_p_and_g(params.2, params.3) {
-                panic!("Failed to get correct password information from Telegram")
-            }
-        }
-
-        let (salt1, salt2, p, g) = params;
-
-        let g_b = password_info.srp_b.unwrap();
-        let a: Vec<u8> = password_info.secure_random;
-
-        let (m1, g_a) = calculate_2fa(salt1, salt2, p, g, g_b, a, password);
-
-        let check_password = tl::functions::auth::CheckPassword {
-            password: tl::enums::InputCheckPasswordSrp::Srp(tl::types::InputCheckPasswordSrp {
-                srp_id: password_info.srp_id.unwrap(),
-                a: g_a.to_vec(),
-                m1: m1.to_vec(),
-            }),
-        };
+        let password = self
+            .check_password_srp(Some(password_token.password), password)
+            .await
+            .map_err(SignInError::Other)?;
+        let check_password = tl::functions::auth::CheckPassword { password };
 
         match self.invoke(&check_password).await {
             Ok(tl::enums::auth::Authorization::Authorization(x)) => {
@@ -468,3 +692,19 @@ impl Client {
         panic!("disconnect now only works via dropping");
     }
 }
+
+/// The settings used when requesting a code to be sent to a phone number.
+pub(crate) fn code_settings() -> tl::enums::CodeSettings {
+    tl::types::CodeSettings {
+        allow_flashcall: false,
+        current_number: false,
+        allow_app_hash: false,
+        allow_missed_call: false,
+        allow_firebase: false,
+        logout_tokens: None,
+        token: None,
+        app_sandbox: None,
+        unknown_number: false,
+    }
+    .into()
+}
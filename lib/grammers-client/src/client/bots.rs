@@ -6,12 +6,14 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use crate::Client;
-use crate::client::messages::parse_mention_entities;
+use crate::client::messages::{map_random_ids_to_messages, parse_mention_entities};
+use crate::types::{Chat, ChatMap, Message, Photo, User, media::Document};
 use crate::utils::generate_random_id;
 use crate::{InputMessage, types::IterBuffer};
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::PackedChat;
 use grammers_tl_types as tl;
+use std::fmt;
 
 const MAX_LIMIT: usize = 50;
 
@@ -21,13 +23,112 @@ pub struct InlineResult {
     pub raw: tl::enums::BotInlineResult,
 }
 
+/// The score of a user in a game, as shown in the high score table.
+#[derive(Clone, Debug)]
+pub struct GameHighScore {
+    /// The position of the user in the high score table, starting at 1.
+    pub position: i32,
+    /// The score of the user.
+    pub score: i32,
+    /// The user who achieved the score.
+    pub user: User,
+}
+
+/// A bot which can be added to the attachment or side menu, as returned by
+/// [`Client::get_attach_menu_bots`].
+#[derive(Clone, Debug)]
+pub struct AttachMenuBot {
+    pub raw: tl::types::AttachMenuBot,
+    /// The bot user.
+    pub user: User,
+}
+
+/// A web app opened with [`Client::open_web_app`].
+#[derive(Clone, Debug)]
+pub struct WebApp {
+    /// The URL of the web app, which should be opened in a browser.
+    pub url: String,
+    chat: PackedChat,
+    bot: PackedChat,
+    query_id: Option<i64>,
+}
+
 pub type InlineResultIter = IterBuffer<tl::functions::messages::GetInlineBotResults, InlineResult>;
 
+impl AttachMenuBot {
+    fn from_raw(bot: tl::enums::AttachMenuBot, chats: &ChatMap) -> Self {
+        let tl::enums::AttachMenuBot::Bot(raw) = bot;
+        let peer = tl::types::PeerUser {
+            user_id: raw.bot_id,
+        }
+        .into();
+        let user = match chats.get(&peer) {
+            Some(Chat::User(user)) => user.clone(),
+            _ => User::from_raw(tl::types::UserEmpty { id: raw.bot_id }.into()),
+        };
+        Self { raw, user }
+    }
+
+    /// The name shown in the menu.
+    pub fn short_name(&self) -> &str {
+        &self.raw.short_name
+    }
+
+    /// Whether the bot was removed from the menu by the logged-in account.
+    pub fn is_inactive(&self) -> bool {
+        self.raw.inactive
+    }
+
+    /// Whether the bot is shown in the attachment menu.
+    pub fn shows_in_attach_menu(&self) -> bool {
+        self.raw.show_in_attach_menu
+    }
+
+    /// Whether the bot is shown in the side menu.
+    pub fn shows_in_side_menu(&self) -> bool {
+        self.raw.show_in_side_menu
+    }
+
+    /// Whether the bot asks for permission to send messages to the logged-in account when it
+    /// is added to the menu.
+    pub fn requests_write_access(&self) -> bool {
+        self.raw.request_write_access
+    }
+}
+
 impl InlineResult {
     /// Send this inline result to the specified chat.
-    // TODO return the produced message
+    ///
+    /// See [`InlineResult::send_to`] to also obtain the message that was sent.
     pub async fn send<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
-        self.client
+        self.send_to(chat).await.map(drop)
+    }
+
+    /// Send this inline result to the specified chat, returning the message that was sent.
+    ///
+    /// The message may be `None` if Telegram did not include it in the response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(bot: grammers_client::types::User, chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut results = client.inline_query(&bot, "cats").chat(&chat);
+    ///
+    /// if let Some(result) = results.next().await? {
+    ///     if let Some(message) = result.send_to(&chat).await? {
+    ///         println!("Sent message {}", message.id());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_to<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Option<Message>, InvocationError> {
+        let random_id = generate_random_id();
+        let updates = self
+            .client
             .invoke(&tl::functions::messages::SendInlineBotResult {
                 silent: false,
                 background: false,
@@ -35,15 +136,20 @@ impl InlineResult {
                 hide_via: false,
                 peer: chat.into().to_input_peer(),
                 reply_to: None,
-                random_id: generate_random_id(),
+                random_id,
                 query_id: self.query_id,
                 id: self.id().to_string(),
                 schedule_date: None,
                 send_as: None,
                 quick_reply_shortcut: None,
             })
-            .await
-            .map(drop)
+            .await?;
+
+        Ok(
+            map_random_ids_to_messages(&self.client, &[random_id], updates)
+                .pop()
+                .flatten(),
+        )
     }
 
     /// The ID for this result.
@@ -56,6 +162,16 @@ impl InlineResult {
         }
     }
 
+    /// The type of this result, such as `"photo"`, `"gif"` or `"article"`.
+    pub fn kind(&self) -> &str {
+        use tl::enums::BotInlineResult::*;
+
+        match &self.raw {
+            Result(r) => &r.r#type,
+            BotInlineMediaResult(r) => &r.r#type,
+        }
+    }
+
     /// The title for this result, if any.
     pub fn title(&self) -> Option<&String> {
         use tl::enums::BotInlineResult::*;
@@ -65,6 +181,68 @@ impl InlineResult {
             BotInlineMediaResult(r) => r.title.as_ref(),
         }
     }
+
+    /// The description for this result, if any.
+    pub fn description(&self) -> Option<&str> {
+        use tl::enums::BotInlineResult::*;
+
+        match &self.raw {
+            Result(r) => r.description.as_deref(),
+            BotInlineMediaResult(r) => r.description.as_deref(),
+        }
+    }
+
+    /// The URL this result points to, if any.
+    pub fn url(&self) -> Option<&str> {
+        use tl::enums::BotInlineResult::*;
+
+        match &self.raw {
+            Result(r) => r.url.as_deref(),
+            BotInlineMediaResult(_) => None,
+        }
+    }
+
+    /// The photo that would be sent with this result, if it's a photo stored in Telegram.
+    pub fn photo(&self) -> Option<Photo> {
+        use tl::enums::BotInlineResult::*;
+
+        match &self.raw {
+            Result(_) => None,
+            BotInlineMediaResult(r) => r.photo.clone().map(Photo::from_raw),
+        }
+    }
+
+    /// The document that would be sent with this result, if it's a document stored in Telegram.
+    pub fn document(&self) -> Option<Document> {
+        use tl::enums::BotInlineResult::*;
+
+        match &self.raw {
+            Result(_) => None,
+            BotInlineMediaResult(r) => r.document.clone().map(|document| {
+                Document::from_raw_media(tl::types::MessageMediaDocument {
+                    nopremium: false,
+                    spoiler: false,
+                    video: false,
+                    round: false,
+                    voice: false,
+                    document: Some(document),
+                    alt_documents: None,
+                    video_cover: None,
+                    video_timestamp: None,
+                    ttl_seconds: None,
+                })
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for InlineResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InlineResult")
+            .field("query_id", &self.query_id)
+            .field("raw", &self.raw)
+            .finish()
+    }
 }
 
 impl InlineResultIter {
@@ -200,4 +378,303 @@ impl Client {
             .await
         }
     }
+
+    /// Set the score of a user in the game sent by the logged-in bot in the given message.
+    ///
+    /// The message will be edited to show the new high score table. Unless `force` is `true`,
+    /// the score can only increase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_game_score(&message.chat(), message.id(), &user, 1500, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_game_score<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_id: i32,
+        user: U,
+        score: i32,
+        force: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SetGameScore {
+            edit_message: true,
+            force,
+            peer: chat.into().to_input_peer(),
+            id: message_id,
+            user_id: user.into().to_input_user_lossy(),
+            score,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Get the high scores of the game sent by the logged-in bot in the given message.
+    ///
+    /// The table will contain the scores around the given user, including theirs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for high_score in client.get_game_high_scores(&message.chat(), message.id(), &user).await? {
+    ///     println!("{}. {:?}: {}", high_score.position, high_score.user.first_name(), high_score.score);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_game_high_scores<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_id: i32,
+        user: U,
+    ) -> Result<Vec<GameHighScore>, InvocationError> {
+        let tl::enums::messages::HighScores::Scores(high_scores) = self
+            .invoke(&tl::functions::messages::GetGameHighScores {
+                peer: chat.into().to_input_peer(),
+                id: message_id,
+                user_id: user.into().to_input_user_lossy(),
+            })
+            .await?;
+
+        self.0.chat_hashes.extend(&high_scores.users, &[]);
+
+        let chats = ChatMap::new(high_scores.users, Vec::new());
+        Ok(high_scores
+            .scores
+            .into_iter()
+            .filter_map(|score| {
+                let tl::enums::HighScore::Score(score) = score;
+                let peer = tl::types::PeerUser {
+                    user_id: score.user_id,
+                }
+                .into();
+                match chats.get(&peer) {
+                    Some(Chat::User(user)) => Some(GameHighScore {
+                        position: score.pos,
+                        score: score.score,
+                        user: user.clone(),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    /// Fetch the bots installed in the attachment and side menus of the logged-in account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for bot in client.get_attach_menu_bots().await? {
+    ///     println!("{} (by @{:?})", bot.short_name(), bot.user.username());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_attach_menu_bots(&self) -> Result<Vec<AttachMenuBot>, InvocationError> {
+        let bots = match self
+            .invoke(&tl::functions::messages::GetAttachMenuBots { hash: 0 })
+            .await?
+        {
+            tl::enums::AttachMenuBots::Bots(bots) => bots,
+            tl::enums::AttachMenuBots::NotModified => {
+                panic!("API returned AttachMenuBots::NotModified even though hash = 0")
+            }
+        };
+
+        self.0.chat_hashes.extend(&bots.users, &[]);
+
+        let chats = ChatMap::new(bots.users, Vec::new());
+        Ok(bots
+            .bots
+            .into_iter()
+            .map(|bot| AttachMenuBot::from_raw(bot, &chats))
+            .collect())
+    }
+
+    /// Fetch the attachment menu information of a bot, even if it is not installed.
+    pub async fn get_attach_menu_bot<C: Into<PackedChat>>(
+        &self,
+        bot: C,
+    ) -> Result<AttachMenuBot, InvocationError> {
+        let tl::enums::AttachMenuBotsBot::Bot(bot) = self
+            .invoke(&tl::functions::messages::GetAttachMenuBot {
+                bot: bot.into().to_input_user_lossy(),
+            })
+            .await?;
+
+        self.0.chat_hashes.extend(&bot.users, &[]);
+
+        let chats = ChatMap::new(bot.users, Vec::new());
+        Ok(AttachMenuBot::from_raw(bot.bot, &chats))
+    }
+
+    /// Add or remove a bot from the attachment and side menus.
+    ///
+    /// If `write_allowed` is `true` when adding the bot, it will be allowed to send messages to
+    /// the logged-in account (see [`AttachMenuBot::requests_write_access`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(bot: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.toggle_bot_in_attach_menu(&bot, true, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_bot_in_attach_menu<C: Into<PackedChat>>(
+        &self,
+        bot: C,
+        enabled: bool,
+        write_allowed: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::ToggleBotInAttachMenu {
+            write_allowed: enabled && write_allowed,
+            bot: bot.into().to_input_user_lossy(),
+            enabled,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Open the web app of a bot from the attachment menu of a chat.
+    ///
+    /// The returned URL should be opened in a browser. Messages which the web app sends on
+    /// behalf of the logged-in account will be sent to `chat`, as long as the web app is kept
+    /// alive with [`Client::prolong_web_app`] (about once a minute).
+    ///
+    /// `url` is only needed when opening the web app from a button, and `start_param` can be
+    /// used to pass a parameter to the web app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, bot: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let web_app = client.open_web_app(&chat, &bot, None, Some("hello")).await?;
+    /// println!("Open {} to use the web app", web_app.url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn open_web_app<C: Into<PackedChat>, B: Into<PackedChat>>(
+        &self,
+        chat: C,
+        bot: B,
+        url: Option<&str>,
+        start_param: Option<&str>,
+    ) -> Result<WebApp, InvocationError> {
+        let chat = chat.into();
+        let bot = bot.into();
+        let tl::enums::WebViewResult::Url(result) = self
+            .invoke(&tl::functions::messages::RequestWebView {
+                from_bot_menu: false,
+                silent: false,
+                compact: false,
+                fullscreen: false,
+                peer: chat.to_input_peer(),
+                bot: bot.to_input_user_lossy(),
+                url: url.map(str::to_string),
+                start_param: start_param.map(str::to_string),
+                theme_params: None,
+                platform: "unknown".to_string(),
+                reply_to: None,
+                send_as: None,
+            })
+            .await?;
+
+        Ok(WebApp {
+            url: result.url,
+            chat,
+            bot,
+            query_id: result.query_id,
+        })
+    }
+
+    /// Keep a web app opened with [`Client::open_web_app`] alive, so that it can keep sending
+    /// messages to the chat where it was opened.
+    ///
+    /// Does nothing if the web app cannot send messages.
+    pub async fn prolong_web_app(&self, web_app: &WebApp) -> Result<(), InvocationError> {
+        let Some(query_id) = web_app.query_id else {
+            return Ok(());
+        };
+        self.invoke(&tl::functions::messages::ProlongWebView {
+            silent: false,
+            peer: web_app.chat.to_input_peer(),
+            bot: web_app.bot.to_input_user_lossy(),
+            query_id,
+            reply_to: None,
+            send_as: None,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Answer the query of a web app, sending the result as a message to the chat where the
+    /// web app was opened on behalf of the user who opened it.
+    ///
+    /// This can only be used by bot accounts, with the query ID which the web app received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(query_id: String, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::inline::query::Article;
+    /// use grammers_client::InputMessage;
+    ///
+    /// let result = Article::new("Order", InputMessage::text("I ordered a pizza!"));
+    /// client.answer_web_app_query(&query_id, result).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn answer_web_app_query<R: Into<crate::types::inline::query::InlineResult>>(
+        &self,
+        query_id: &str,
+        result: R,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SendWebViewResultMessage {
+            bot_query_id: query_id.to_string(),
+            result: result.into().into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Tell a user which parts of the Telegram Passport data they shared with the logged-in bot
+    /// contain errors.
+    ///
+    /// The user will not be able to share the data again until the errors are fixed. Each error
+    /// refers to the hash of the element, field or file it applies to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, data_hash: Vec<u8>, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// client.set_passport_errors(&user, vec![tl::types::SecureValueErrorData {
+    ///     r#type: tl::enums::SecureValueType::PersonalDetails,
+    ///     data_hash,
+    ///     field: "birth_date".to_string(),
+    ///     text: "The birth date does not match the one in the passport".to_string(),
+    /// }.into()]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_passport_errors<C: Into<PackedChat>>(
+        &self,
+        user: C,
+        errors: Vec<tl::enums::SecureValueError>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::users::SetSecureValueErrors {
+            id: user.into().to_input_user_lossy(),
+            errors,
+        })
+        .await
+        .map(drop)
+    }
 }
@@ -10,21 +10,42 @@
 
 use super::Client;
 use crate::types::{
-    AdminRightsBuilder, BannedRightsBuilder, Chat, ChatMap, IterBuffer, Message, Participant,
-    Photo, User, chats::AdminRightsBuilderInner, chats::BannedRightsBuilderInner,
+    AdminRightsBuilder, BannedRightsBuilder, Chat, ChatInvite, ChatMap, ChatPermissions,
+    ForumTopic, IterBuffer, LocatedChat, Message, Participant, PeerSettings, Photo, User, UserFull,
+    chats::AdminRightsBuilderInner, chats::BannedRightsBuilderInner,
 };
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_mtproto::mtp::DeserializeError;
 use grammers_mtsender::RpcError;
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::{PackedChat, PackedType};
-use grammers_tl_types as tl;
-use std::collections::VecDeque;
+use grammers_tl_types::{self as tl, Deserializable, Identifiable, Serializable};
+use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::watch;
 
 const MAX_PARTICIPANT_LIMIT: usize = 200;
 const MAX_PHOTO_LIMIT: usize = 100;
+const MAX_JOIN_REQUEST_LIMIT: usize = 100;
 const KICK_BAN_DURATION: i32 = 60; // in seconds, in case the second request fails
+const GENERAL_TOPIC_ID: i32 = 1;
+// Errors Telegram returns when the access hash of a chat is no longer valid.
+const STALE_HASH_ERRORS: [&str; 2] = ["CHANNEL_INVALID", "USER_ID_INVALID"];
+// How many usernames are remembered by `resolve_usernames`, and for how long (in seconds).
+const MAX_RESOLVED_USERNAMES: usize = 1000;
+const RESOLVED_USERNAME_TTL: i32 = 60 * 60;
+// How many dialogs are scanned at most when looking for a chat to refresh its access hash.
+const MAX_REFRESH_DIALOGS: usize = 500;
+// The key under which `get_contacts` saves the last list fetched in the session.
+const CONTACTS_CACHE_KEY: &str = "contacts";
+
+/// Whether a username resolved at the given date should be resolved again by now.
+fn username_expired(resolved: i32, now: i32) -> bool {
+    now.saturating_sub(resolved) >= RESOLVED_USERNAME_TTL
+}
 
 pub enum ParticipantIter {
     Empty,
@@ -116,11 +137,7 @@ impl ParticipantIter {
                     tl::enums::ChatParticipants::Participants(c) => c.participants,
                 };
 
-                {
-                    let mut state = client.0.state.write().unwrap();
-                    // Telegram can return peers without hash (e.g. Users with 'min: true')
-                    let _ = state.chat_hashes.extend(&full.users, &full.chats);
-                }
+                client.0.chat_hashes.extend(&full.users, &full.chats);
 
                 // Don't actually care for the chats, just the users.
                 let mut chats = ChatMap::new(full.users, Vec::new());
@@ -148,11 +165,7 @@ impl ParticipantIter {
                         }
                     };
 
-                {
-                    let mut state = iter.client.0.state.write().unwrap();
-                    // Telegram can return peers without hash (e.g. Users with 'min: true')
-                    let _ = state.chat_hashes.extend(&users, &chats);
-                }
+                iter.client.0.chat_hashes.extend(&users, &chats);
 
                 // Telegram can return less participants than asked for but the count being higher
                 // (for example, count=4825, participants=199, users=200). The missing participant
@@ -335,6 +348,116 @@ impl ProfilePhotoIter {
     }
 }
 
+/// A pending request to join a chat, as returned by [`Client::iter_join_requests`].
+#[derive(Clone, Debug)]
+pub struct JoinRequest {
+    /// The user who requested to join.
+    pub user: User,
+    /// When the request was made.
+    pub date: DateTime<Utc>,
+    /// The message the user sent along with the request, if any.
+    pub about: Option<String>,
+    /// Whether the request was made through a chat folder invite.
+    pub via_chatlist: bool,
+}
+
+/// Where to stream a group call to, as returned by [`Client::get_group_call_stream_rtmp_url`].
+#[derive(Clone, Debug)]
+pub struct StreamRtmpUrl {
+    /// The RTMP server URL.
+    pub url: String,
+    /// The stream key to use with the server.
+    pub key: String,
+}
+
+pub type JoinRequestIter = IterBuffer<tl::functions::messages::GetChatInviteImporters, JoinRequest>;
+
+impl JoinRequestIter {
+    fn new(client: &Client, chat: PackedChat, invite: Option<&str>) -> Self {
+        Self::from_request(
+            client,
+            MAX_JOIN_REQUEST_LIMIT,
+            tl::functions::messages::GetChatInviteImporters {
+                requested: true,
+                subscription_expired: false,
+                peer: chat.to_input_peer(),
+                link: invite.map(str::to_string),
+                q: None,
+                offset_date: 0,
+                offset_user: tl::enums::InputUser::Empty,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Only return the requests of users whose name or username contains `query`.
+    pub fn query(mut self, query: &str) -> Self {
+        self.request.q = Some(query.to_string());
+        self
+    }
+
+    /// Determines how many join requests there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::messages::ChatInviteImporters::Importers(importers) =
+            self.client.invoke(&self.request).await?;
+        let total = importers.count as usize;
+        self.total = Some(total);
+        Ok(total)
+    }
+
+    /// Return the next `JoinRequest` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no requests left.
+    pub async fn next(&mut self) -> Result<Option<JoinRequest>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_JOIN_REQUEST_LIMIT);
+        let tl::enums::messages::ChatInviteImporters::Importers(importers) =
+            self.client.invoke(&self.request).await?;
+
+        self.total = Some(importers.count as usize);
+        if importers.importers.len() < self.request.limit as usize {
+            self.last_chunk = true;
+        }
+
+        self.client.0.chat_hashes.extend(&importers.users, &[]);
+
+        let chats = ChatMap::new(importers.users, Vec::new());
+        for importer in importers.importers {
+            let tl::enums::ChatInviteImporter::Importer(importer) = importer;
+            let peer = tl::types::PeerUser {
+                user_id: importer.user_id,
+            }
+            .into();
+            let user = match utils::always_find_entity(&peer, &chats, &self.client) {
+                Chat::User(user) => user,
+                _ => unreachable!(),
+            };
+
+            self.request.offset_date = importer.date;
+            self.request.offset_user = user.pack().to_input_user_lossy();
+            self.buffer.push_back(JoinRequest {
+                user,
+                date: utils::date(importer.date),
+                about: importer.about,
+                via_chatlist: importer.via_chatlist,
+            });
+        }
+
+        Ok(self.pop_item())
+    }
+}
+
 fn updates_to_chat(id: Option<i64>, updates: tl::enums::Updates) -> Option<Chat> {
     use tl::enums::Updates;
 
@@ -354,6 +477,25 @@ fn updates_to_chat(id: Option<i64>, updates: tl::enums::Updates) -> Option<Chat>
     .map(Chat::from_raw)
 }
 
+/// The error returned when a channel is needed, but the chat given is not one.
+pub(crate) fn channel_invalid() -> InvocationError {
+    InvocationError::Rpc(RpcError {
+        code: 400,
+        name: "CHANNEL_INVALID".to_owned(),
+        value: None,
+        caused_by: None,
+    })
+}
+
+fn input_geo_point(lat: f64, long: f64) -> tl::enums::InputGeoPoint {
+    tl::types::InputGeoPoint {
+        lat,
+        long,
+        accuracy_radius: None,
+    }
+    .into()
+}
+
 /// Method implementations related to dealing with chats or other users.
 impl Client {
     /// Resolves a username into the chat that owns it, if any.
@@ -383,11 +525,7 @@ impl Client {
             Err(err) => return Err(err),
         };
 
-        {
-            let mut state = self.0.state.write().unwrap();
-            // Telegram can return peers without hash (e.g. Users with 'min: true')
-            let _ = state.chat_hashes.extend(&users, &chats);
-        }
+        self.0.chat_hashes.extend(&users, &chats);
 
         Ok(match peer {
             tl::enums::Peer::User(tl::types::PeerUser { user_id }) => users
@@ -404,6 +542,145 @@ impl Client {
         })
     }
 
+    /// Resolves many usernames at once, returning the chats in the same order as the input.
+    ///
+    /// Duplicate usernames (ignoring case and any leading `@`) are only resolved once, and
+    /// successfully-resolved usernames are cached for an hour (up to a thousand of them), so
+    /// that calling this method again with the same usernames won't need to contact Telegram.
+    /// The cache is stored in the session, so it survives restarts if the session is saved.
+    ///
+    /// If the same username is already being resolved by another call (for example, from a
+    /// different task), this method waits for that call to finish instead of resolving it again.
+    ///
+    /// Resolving usernames has very strict flood limits. To avoid hitting them, usernames are
+    /// resolved one at a time. If a flood wait error exceeding the configured threshold occurs,
+    /// it is returned. The usernames resolved until that point remain cached, so retrying later
+    /// won't resolve them again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let chats = client.resolve_usernames(&["telegram", "@durov", "TELEGRAM"]).await?;
+    /// for chat in chats.into_iter().flatten() {
+    ///     println!("Found chat!: {:?}", chat.name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_usernames(
+        &self,
+        usernames: &[&str],
+    ) -> Result<Vec<Option<Chat>>, InvocationError> {
+        let normalized = usernames
+            .iter()
+            .map(|username| username.trim_start_matches('@').to_lowercase())
+            .collect::<Vec<_>>();
+
+        let mut resolved = HashMap::new();
+        for username in normalized.iter() {
+            if !resolved.contains_key(username) {
+                let chat = self.resolve_username_deduplicated(username).await?;
+                resolved.insert(username.clone(), chat);
+            }
+        }
+
+        Ok(normalized
+            .iter()
+            .map(|username| resolved[username].clone())
+            .collect())
+    }
+
+    /// Resolve a normalized username, using the cache or waiting for any resolution of the same
+    /// username which is already in flight.
+    async fn resolve_username_deduplicated(
+        &self,
+        username: &str,
+    ) -> Result<Option<Chat>, InvocationError> {
+        loop {
+            if let Some(chat) = self.cached_username(username) {
+                return Ok(Some(chat));
+            }
+
+            let in_flight = {
+                let mut resolving = self.0.resolving_usernames.lock().unwrap();
+                match resolving.get(username) {
+                    // If the sender is gone, whoever was resolving it gave up (or failed).
+                    Some(rx) if rx.has_changed().is_ok() => Err(rx.clone()),
+                    _ => {
+                        let (tx, rx) = watch::channel(None);
+                        resolving.insert(username.to_string(), rx);
+                        Ok(tx)
+                    }
+                }
+            };
+
+            let mut in_flight = match in_flight {
+                Ok(tx) => {
+                    let result = self.resolve_username(username).await;
+                    if let Ok(Some(chat)) = &result {
+                        self.cache_username(username.to_string(), chat.clone());
+                    }
+                    self.0.resolving_usernames.lock().unwrap().remove(username);
+                    if let Ok(chat) = &result {
+                        let _ = tx.send(Some(chat.clone()));
+                    }
+                    return result;
+                }
+                Err(rx) => rx,
+            };
+
+            if let Ok(chat) = in_flight.wait_for(Option::is_some).await {
+                return Ok(chat.clone().flatten());
+            }
+            // The resolution in flight failed, so try again (possibly resolving it ourselves).
+        }
+    }
+
+    /// Get the chat a username resolved to, unless it was resolved too long ago.
+    fn cached_username(&self, username: &str) -> Option<Chat> {
+        let now = Utc::now().timestamp() as i32;
+        let cache = self.0.resolved_usernames.lock().unwrap();
+        cache
+            .get(username)
+            .filter(|(resolved, _)| !username_expired(*resolved, now))
+            .map(|(_, chat)| chat.clone())
+    }
+
+    /// Remember the chat a username resolved to, evicting the oldest ones if needed.
+    ///
+    /// The session is kept in sync with the cache.
+    fn cache_username(&self, username: String, chat: Chat) {
+        let now = Utc::now().timestamp() as i32;
+        let session = &self.0.config.session;
+        let mut cache = self.0.resolved_usernames.lock().unwrap();
+        cache.retain(|username, (resolved, _)| {
+            let expired = username_expired(*resolved, now);
+            if expired {
+                session.remove_resolved_username(username);
+            }
+            !expired
+        });
+        if cache.len() >= MAX_RESOLVED_USERNAMES && !cache.contains_key(&username) {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, (resolved, _))| *resolved)
+                .map(|(username, _)| username.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+                session.remove_resolved_username(&oldest);
+            }
+        }
+        session.insert_resolved_username(&username, chat.pack(), now);
+        cache.insert(username, (now, chat));
+    }
+
+    /// Forget the chat a username resolved to, both from the cache and the session.
+    fn forget_username(&self, username: &str) {
+        self.0.resolved_usernames.lock().unwrap().remove(username);
+        self.0.config.session.remove_resolved_username(username);
+    }
+
     /// Fetch full information about the currently logged-in user.
     ///
     /// Although this method is cheap to call, you might want to cache the results somewhere.
@@ -488,7 +765,7 @@ impl Client {
         let user = user.into();
         if let Some(channel) = chat.try_to_input_channel() {
             // TODO should PackedChat also know about is user self?
-            let self_id = { self.0.state.read().unwrap().chat_hashes.self_id() };
+            let self_id = self.0.chat_hashes.load().self_id();
             if user.id == self_id {
                 self.invoke(&tl::functions::channels::LeaveChannel { channel })
                     .await
@@ -693,6 +970,111 @@ impl Client {
         })
     }
 
+    /// Find the chat again to obtain a fresh access hash, in case the one known is stale.
+    ///
+    /// The chat is first looked up among the usernames resolved by
+    /// [`Client::resolve_usernames`], which are resolved again. Otherwise, the most recent
+    /// dialogs (up to 500) are scanned until the chat is found. The new access hash is stored in
+    /// the session cache.
+    ///
+    /// Returns `None` if the chat could not be found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(packed_chat: grammers_client::types::chat::PackedChat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(chat) = client.refresh_chat(packed_chat).await? {
+    ///     println!("Refreshed chat: {:?}", chat.access_hash);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn refresh_chat(
+        &self,
+        chat: PackedChat,
+    ) -> Result<Option<PackedChat>, InvocationError> {
+        let username = {
+            let cache = self.0.resolved_usernames.lock().unwrap();
+            cache
+                .iter()
+                .find(|(_, (_, cached))| cached.id() == chat.id)
+                .map(|(username, _)| username.clone())
+        };
+        if let Some(username) = username {
+            match self.resolve_username(&username).await? {
+                Some(resolved) if resolved.id() == chat.id => {
+                    let packed = resolved.pack();
+                    self.cache_username(username, resolved);
+                    return Ok(Some(packed));
+                }
+                _ => self.forget_username(&username),
+            }
+        }
+
+        let mut dialogs = self.iter_dialogs().limit(MAX_REFRESH_DIALOGS);
+        while let Some(dialog) = dialogs.next().await? {
+            if dialog.chat().id() == chat.id {
+                return Ok(Some(dialog.chat().pack()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Invoke a request involving a chat, refreshing its access hash and retrying once if
+    /// Telegram reports it as invalid.
+    ///
+    /// Long-lived sessions may end up with stale access hashes. When the request fails with
+    /// `CHANNEL_INVALID` or `USER_ID_INVALID`, the chat is found again with
+    /// [`Client::refresh_chat`], and the request is built again with the refreshed chat. Chats
+    /// without an access hash, such as small group chats, are never refreshed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let history = client
+    ///     .invoke_with_chat(&chat, |chat| tl::functions::messages::GetHistory {
+    ///         peer: chat.to_input_peer(),
+    ///         offset_id: 0,
+    ///         offset_date: 0,
+    ///         add_offset: 0,
+    ///         limit: 10,
+    ///         max_id: 0,
+    ///         min_id: 0,
+    ///         hash: 0,
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn invoke_with_chat<
+        C: Into<PackedChat>,
+        R: tl::RemoteCall,
+        F: Fn(PackedChat) -> R,
+    >(
+        &self,
+        chat: C,
+        request: F,
+    ) -> Result<R::Return, InvocationError> {
+        let chat = chat.into();
+        match self.invoke(&request(chat)).await {
+            Err(InvocationError::Rpc(err))
+                if chat.access_hash.is_some()
+                    && STALE_HASH_ERRORS.iter().any(|name| err.name == *name) =>
+            {
+                match self.refresh_chat(chat).await? {
+                    Some(refreshed) if refreshed.access_hash != chat.access_hash => {
+                        self.invoke(&request(refreshed)).await
+                    }
+                    _ => Err(InvocationError::Rpc(err)),
+                }
+            }
+            result => result,
+        }
+    }
+
     /// Get permissions of participant `user` from chat `chat`.
     ///
     /// # Panics
@@ -827,6 +1209,39 @@ impl Client {
         }
     }
 
+    /// Check an invite link without joining the corresponding chat.
+    ///
+    /// The hash is the last part of the invite link, after the `+` or `joinchat/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let invite = client.check_invite_link("AbCdEfGhIjKlMnOp").await?;
+    /// if invite.is_scam() || invite.is_fake() {
+    ///     println!("{} should not be trusted", invite.title());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn check_invite_link(&self, hash: &str) -> Result<ChatInvite, InvocationError> {
+        let invite = self
+            .invoke(&tl::functions::messages::CheckChatInvite {
+                hash: hash.to_owned(),
+            })
+            .await?;
+
+        match &invite {
+            tl::enums::ChatInvite::Already(tl::types::ChatInviteAlready { chat })
+            | tl::enums::ChatInvite::Peek(tl::types::ChatInvitePeek { chat, .. }) => {
+                self.0.chat_hashes.extend(&[], std::slice::from_ref(chat));
+            }
+            tl::enums::ChatInvite::Invite(_) => {}
+        }
+
+        Ok(ChatInvite::from_raw(invite))
+    }
+
     #[cfg_attr(
         not(feature = "parse_invite_link"),
         allow(rustdoc::broken_intra_doc_links)
@@ -840,14 +1255,7 @@ impl Client {
         chat: C,
     ) -> Result<Option<Chat>, InvocationError> {
         let chat: PackedChat = chat.into();
-        let channel = chat.try_to_input_channel().ok_or_else(|| {
-            InvocationError::Rpc(RpcError {
-                code: 400,
-                name: "CHANNEL_INVALID".to_owned(),
-                value: None,
-                caused_by: None,
-            })
-        })?;
+        let channel = chat.try_to_input_channel().ok_or_else(channel_invalid)?;
         Ok(updates_to_chat(
             Some(chat.id),
             self.invoke(&tl::functions::channels::JoinChannel { channel })
@@ -913,6 +1321,1077 @@ impl Client {
     pub fn action<C: Into<PackedChat>>(&self, chat: C) -> crate::types::ActionSender {
         crate::types::ActionSender::new(self, chat)
     }
+
+    /// Fetch the extended information about a user, such as their biography, birthday or
+    /// business details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let full = client.get_full_user(&user).await?;
+    /// if let Some(birthday) = full.birthday() {
+    ///     println!("{} was born on {}/{}", user.full_name(), birthday.day, birthday.month);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_full_user<C: Into<PackedChat>>(
+        &self,
+        user: C,
+    ) -> Result<UserFull, InvocationError> {
+        let tl::enums::users::UserFull::Full(full) = self
+            .invoke(&tl::functions::users::GetFullUser {
+                id: user.into().to_input_user_lossy(),
+            })
+            .await?;
+
+        self.0.chat_hashes.extend(&full.users, &full.chats);
+
+        Ok(UserFull::from_raw(full))
+    }
+
+    /// Fetch the delay between the messages members can send to a group with slow mode enabled.
+    ///
+    /// Returns `None` if slow mode is disabled, or if the chat is not a megagroup (the only kind
+    /// of chat where slow mode can be used). Administrators are not affected by slow mode.
+    ///
+    /// Sending messages too fast fails with `SLOWMODE_WAIT`, which the library sleeps on
+    /// according to [`InitParams::slow_mode_sleep_threshold`].
+    ///
+    /// [`InitParams::slow_mode_sleep_threshold`]: crate::InitParams::slow_mode_sleep_threshold
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(delay) = client.get_slow_mode_delay(&group).await? {
+    ///     println!("Can only send a message every {delay:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_slow_mode_delay<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Option<Duration>, InvocationError> {
+        let chat = chat.into();
+        if !chat.is_channel() {
+            return Ok(None);
+        }
+
+        Ok(match self.get_raw_full_chat(chat).await? {
+            tl::enums::ChatFull::ChannelFull(channel) => channel
+                .slowmode_seconds
+                .filter(|&seconds| seconds > 0)
+                .map(|seconds| Duration::from_secs(seconds as u64)),
+            tl::enums::ChatFull::Full(_) => None,
+        })
+    }
+
+    /// Change the theme used by the chat, identified by its emoticon.
+    ///
+    /// The available themes can be fetched with [`Client::get_chat_themes`]. Using an empty
+    /// emoticon will reset the chat theme back to the default one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_chat_theme(&chat, "🏠").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_chat_theme<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        emoticon: &str,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SetChatTheme {
+            peer: chat.into().to_input_peer(),
+            emoticon: emoticon.to_string(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change the wallpaper used in the private conversation with a user.
+    ///
+    /// The wallpaper will only be changed for the logged-in account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let wallpaper = tl::types::InputWallPaperSlug { slug: "abc".to_string() };
+    /// client.set_chat_wallpaper(&chat, wallpaper.into(), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_chat_wallpaper<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        wallpaper: tl::enums::InputWallPaper,
+        settings: Option<tl::enums::WallPaperSettings>,
+    ) -> Result<(), InvocationError> {
+        self.set_wallpaper(chat.into(), wallpaper, settings, false)
+            .await
+    }
+
+    /// Change the wallpaper used in the private conversation with a user, for both the
+    /// logged-in account and the user.
+    ///
+    /// Only users with Telegram Premium can set wallpapers for both sides. The other user can
+    /// undo the change for themselves with [`Client::revert_chat_wallpaper`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let uploaded = client.upload_file("wallpaper.jpg").await?;
+    /// let wallpaper = client.upload_wallpaper(&uploaded, "image/jpeg", None).await?;
+    /// let wallpaper = tl::types::InputWallPaper {
+    ///     id: wallpaper.id,
+    ///     access_hash: wallpaper.access_hash,
+    /// };
+    /// client.set_shared_chat_wallpaper(&chat, wallpaper.into(), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_shared_chat_wallpaper<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        wallpaper: tl::enums::InputWallPaper,
+        settings: Option<tl::enums::WallPaperSettings>,
+    ) -> Result<(), InvocationError> {
+        self.set_wallpaper(chat.into(), wallpaper, settings, true)
+            .await
+    }
+
+    async fn set_wallpaper(
+        &self,
+        chat: PackedChat,
+        wallpaper: tl::enums::InputWallPaper,
+        settings: Option<tl::enums::WallPaperSettings>,
+        for_both: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SetChatWallPaper {
+            for_both,
+            revert: false,
+            peer: chat.to_input_peer(),
+            wallpaper: Some(wallpaper),
+            settings,
+            id: None,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Revert the wallpaper which another user set for both sides of the private conversation
+    /// back to the one used before, only for the logged-in account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.revert_chat_wallpaper(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn revert_chat_wallpaper<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SetChatWallPaper {
+            for_both: false,
+            revert: true,
+            peer: chat.into().to_input_peer(),
+            wallpaper: None,
+            settings: None,
+            id: None,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change the color used to display the name of a channel.
+    ///
+    /// See [`Client::set_name_color`] for the meaning of the parameters. Only channels and
+    /// megagroups have a name color which can be changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_channel_name_color(&chat, Some(3), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_channel_name_color<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        color: Option<i32>,
+        background_emoji_id: Option<i64>,
+    ) -> Result<(), InvocationError> {
+        self.update_channel_color(channel.into(), false, color, background_emoji_id)
+            .await
+    }
+
+    /// Change the accent color used in the profile page of a channel.
+    ///
+    /// See [`Client::set_channel_name_color`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_channel_profile_color(&chat, Some(5), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_channel_profile_color<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        color: Option<i32>,
+        background_emoji_id: Option<i64>,
+    ) -> Result<(), InvocationError> {
+        self.update_channel_color(channel.into(), true, color, background_emoji_id)
+            .await
+    }
+
+    /// Change whether posts in a broadcast channel are signed with the name of their author.
+    ///
+    /// If `profiles` is also `true`, the signature will link to the profile of the author,
+    /// who will also be the sender of the posts. `profiles` has no effect if signatures are
+    /// disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// // Sign posts, but don't link them to the author's profile.
+    /// client.toggle_signatures(&channel, true, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_signatures<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        enabled: bool,
+        profiles: bool,
+    ) -> Result<(), InvocationError> {
+        let channel = channel
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::ToggleSignatures {
+            signatures_enabled: enabled,
+            profiles_enabled: enabled && profiles,
+            channel,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change whether the content of the chat is protected, which prevents members from
+    /// forwarding, copying or saving its messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.toggle_protected_content(&chat, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_protected_content<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        enabled: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::ToggleNoForwards {
+            peer: chat.into().to_input_peer(),
+            enabled,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change whether Telegram's aggressive anti-spam system is enabled in a megagroup.
+    ///
+    /// When enabled, messages which are likely to be spam are automatically deleted. Those
+    /// deleted by mistake can be reported with [`Client::report_anti_spam_false_positive`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.toggle_anti_spam(&group, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_anti_spam<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        enabled: bool,
+    ) -> Result<(), InvocationError> {
+        let channel = channel
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::ToggleAntiSpam { channel, enabled })
+            .await
+            .map(drop)
+    }
+
+    /// Report that a message deleted by the aggressive anti-spam system was not spam.
+    ///
+    /// The message is identified by the ID it had in the megagroup, as shown in the admin log.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let message_id = 123;
+    /// client.report_anti_spam_false_positive(&group, message_id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn report_anti_spam_false_positive<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        message_id: i32,
+    ) -> Result<(), InvocationError> {
+        let channel = channel
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::ReportAntiSpamFalsePositive {
+            channel,
+            msg_id: message_id,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Fetch the settings of the action bar shown at the top of a chat, which tell whether the
+    /// chat can be reported as spam or the user added as a contact, among others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let settings = client.get_peer_settings(&chat).await?;
+    /// if settings.can_report_spam() {
+    ///     client.report_spam(&chat).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_peer_settings<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<PeerSettings, InvocationError> {
+        let tl::enums::messages::PeerSettings::Settings(settings) = self
+            .invoke(&tl::functions::messages::GetPeerSettings {
+                peer: chat.into().to_input_peer(),
+            })
+            .await?;
+
+        self.0.chat_hashes.extend(&settings.users, &settings.chats);
+
+        Ok(PeerSettings::from_raw(settings.settings))
+    }
+
+    /// Report a chat as spam and hide its action bar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.report_spam(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn report_spam<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::ReportSpam {
+            peer: chat.into().to_input_peer(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Hide the action bar of a chat without reporting it as spam.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.hide_report_spam(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hide_report_spam<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::HidePeerSettingsBar {
+            peer: chat.into().to_input_peer(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Accept a user who started a conversation as a contact, sharing the phone number of the
+    /// logged-in account with them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.accept_contact(&user).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn accept_contact<C: Into<PackedChat>>(
+        &self,
+        user: C,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::contacts::AcceptContact {
+            id: user.into().to_input_user_lossy(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Fetch the contacts of the logged-in account.
+    ///
+    /// The last list fetched is saved in the session, so that calling this method again (even
+    /// after restarting the client) only downloads the list if it changed since.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for user in client.get_contacts().await? {
+    ///     println!("{:?} is a contact", user.full_name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_contacts(&self) -> Result<Vec<User>, InvocationError> {
+        let cached = self
+            .0
+            .config
+            .session
+            .get_cached_result(CONTACTS_CACHE_KEY)
+            .and_then(|(hash, data)| {
+                Some((hash, tl::types::contacts::Contacts::from_bytes(&data).ok()?))
+            });
+        let hash = cached.as_ref().map_or(0, |(hash, _)| *hash);
+
+        let contacts = match (
+            self.invoke(&tl::functions::contacts::GetContacts { hash })
+                .await?,
+            cached,
+        ) {
+            (tl::enums::contacts::Contacts::Contacts(contacts), _) => {
+                let mut ids = contacts
+                    .contacts
+                    .iter()
+                    .map(|tl::enums::Contact::Contact(contact)| contact.user_id)
+                    .collect::<Vec<_>>();
+                ids.sort_unstable();
+
+                let hash =
+                    utils::combine_hash(std::iter::once(contacts.saved_count as i64).chain(ids));
+                self.0.config.session.set_cached_result(
+                    CONTACTS_CACHE_KEY,
+                    hash,
+                    contacts.to_bytes(),
+                );
+                contacts
+            }
+            (tl::enums::contacts::Contacts::NotModified, Some((_, contacts))) => contacts,
+            // Nothing was cached, so the hash sent was 0 and the list cannot be unmodified.
+            (tl::enums::contacts::Contacts::NotModified, None) => {
+                return Err(InvocationError::Read(
+                    DeserializeError::UnexpectedConstructor {
+                        id: tl::types::contacts::ContactsNotModified::CONSTRUCTOR_ID,
+                    }
+                    .into(),
+                ));
+            }
+        };
+
+        self.0.chat_hashes.extend(&contacts.users, &[]);
+        Ok(contacts.users.into_iter().map(User::from_raw).collect())
+    }
+
+    /// Find the users and location-based groups near the given coordinates, sorted by distance.
+    ///
+    /// This is what official clients show under "People nearby". Only users who chose to be
+    /// visible are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for located in client.get_located_chats(40.4169, -3.7035).await? {
+    ///     println!("{:?} is {}m away", located.chat().name(), located.distance());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_located_chats(
+        &self,
+        lat: f64,
+        long: f64,
+    ) -> Result<Vec<LocatedChat>, InvocationError> {
+        let (updates, users, chats) = match self
+            .invoke(&tl::functions::contacts::GetLocated {
+                background: false,
+                geo_point: input_geo_point(lat, long),
+                self_expires: None,
+            })
+            .await?
+        {
+            tl::enums::Updates::Updates(u) => (u.updates, u.users, u.chats),
+            tl::enums::Updates::Combined(u) => (u.updates, u.users, u.chats),
+            _ => return Ok(Vec::new()),
+        };
+
+        self.0.chat_hashes.extend(&users, &chats);
+        let chat_map = ChatMap::new(users, chats);
+
+        let mut located = updates
+            .into_iter()
+            .filter_map(|update| match update {
+                tl::enums::Update::PeerLocated(update) => Some(update.peers),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|peer| match peer {
+                tl::enums::PeerLocated::Located(peer) => chat_map
+                    .get(&peer.peer)
+                    .map(|chat| LocatedChat::new(chat.clone(), peer.distance, peer.expires)),
+                tl::enums::PeerLocated::PeerSelfLocated(_) => None,
+            })
+            .collect::<Vec<_>>();
+        located.sort_by_key(|chat| chat.distance());
+        Ok(located)
+    }
+
+    /// Create a location-based group, which is shown to users nearby the given coordinates.
+    ///
+    /// The `address` is a human-readable description of the location.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let group = client
+    ///     .create_geo_group("Madrid Rustaceans", "", 40.4169, -3.7035, "Puerta del Sol, Madrid")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_geo_group(
+        &self,
+        title: &str,
+        about: &str,
+        lat: f64,
+        long: f64,
+        address: &str,
+    ) -> Result<Option<Chat>, InvocationError> {
+        Ok(updates_to_chat(
+            None,
+            self.invoke(&tl::functions::channels::CreateChannel {
+                broadcast: false,
+                megagroup: true,
+                for_import: false,
+                forum: false,
+                title: title.to_string(),
+                about: about.to_string(),
+                geo_point: Some(input_geo_point(lat, long)),
+                address: Some(address.to_string()),
+                ttl_period: None,
+            })
+            .await?,
+        ))
+    }
+
+    /// Change the location of a location-based group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client
+    ///     .set_chat_location(&group, 40.4154, -3.7074, "Plaza Mayor, Madrid")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_chat_location<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        lat: f64,
+        long: f64,
+        address: &str,
+    ) -> Result<(), InvocationError> {
+        let channel = chat
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::EditLocation {
+            channel,
+            geo_point: input_geo_point(lat, long),
+            address: address.to_string(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change what members of a group can do by default, unless they are given different rights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ChatPermissions;
+    ///
+    /// // Keep the group text-only.
+    /// let mut permissions = group.default_permissions().unwrap_or_default();
+    /// permissions.send_photos = false;
+    /// permissions.send_videos = false;
+    /// client.set_default_permissions(&group, &permissions).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_default_permissions<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        permissions: &ChatPermissions,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::EditChatDefaultBannedRights {
+            peer: chat.into().to_input_peer(),
+            banned_rights: permissions.to_raw().into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Change whether the button to translate messages is hidden in the chat.
+    ///
+    /// This only affects the logged-in account, and requires Telegram Premium.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.toggle_chat_translations(&chat, true).await?;
+    /// assert!(client.chat_translations_disabled(&chat).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_chat_translations<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        disabled: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::TogglePeerTranslations {
+            disabled,
+            peer: chat.into().to_input_peer(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Fetch whether the button to translate messages is hidden in the chat.
+    ///
+    /// See [`Client::toggle_chat_translations`].
+    pub async fn chat_translations_disabled<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<bool, InvocationError> {
+        let chat = chat.into();
+        if chat.is_user() {
+            return Ok(self.get_full_user(chat).await?.translations_disabled());
+        }
+
+        Ok(match self.get_raw_full_chat(chat).await? {
+            tl::enums::ChatFull::Full(chat) => chat.translations_disabled,
+            tl::enums::ChatFull::ChannelFull(channel) => channel.translations_disabled,
+        })
+    }
+
+    /// Fetch the full information of a small group or a channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chat is a user.
+    pub(crate) async fn get_raw_full_chat(
+        &self,
+        chat: PackedChat,
+    ) -> Result<tl::enums::ChatFull, InvocationError> {
+        let tl::enums::messages::ChatFull::Full(full) = match chat.try_to_input_channel() {
+            Some(channel) => {
+                self.invoke(&tl::functions::channels::GetFullChannel { channel })
+                    .await?
+            }
+            None => {
+                let chat_id = chat.try_to_chat_id().expect("chat must not be a user");
+                self.invoke(&tl::functions::messages::GetFullChat { chat_id })
+                    .await?
+            }
+        };
+
+        self.0.chat_hashes.extend(&full.users, &full.chats);
+
+        Ok(full.full_chat)
+    }
+
+    async fn update_channel_color(
+        &self,
+        channel: PackedChat,
+        for_profile: bool,
+        color: Option<i32>,
+        background_emoji_id: Option<i64>,
+    ) -> Result<(), InvocationError> {
+        let channel = channel.try_to_input_channel().ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::UpdateColor {
+            for_profile,
+            channel,
+            color,
+            background_emoji_id,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Iterate over the pending requests to join a group or channel, most recent first.
+    ///
+    /// If `invite` is given, only the requests made through that invite link are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut requests = client.iter_join_requests(&chat, None);
+    ///
+    /// while let Some(request) = requests.next().await? {
+    ///     if request.user.username().is_some() {
+    ///         client.approve_join_request(&chat, &request.user).await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_join_requests<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        invite: Option<&str>,
+    ) -> JoinRequestIter {
+        JoinRequestIter::new(self, chat.into(), invite)
+    }
+
+    /// Approve the request of a user to join a group or channel.
+    pub async fn approve_join_request<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        user: U,
+    ) -> Result<(), InvocationError> {
+        self.hide_join_request(chat.into(), user.into(), true).await
+    }
+
+    /// Decline the request of a user to join a group or channel.
+    pub async fn decline_join_request<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        user: U,
+    ) -> Result<(), InvocationError> {
+        self.hide_join_request(chat.into(), user.into(), false)
+            .await
+    }
+
+    /// Approve all the pending requests to join a group or channel.
+    ///
+    /// If `invite` is given, only the requests made through that invite link are approved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.approve_all_join_requests(&chat, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn approve_all_join_requests<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        invite: Option<&str>,
+    ) -> Result<(), InvocationError> {
+        self.hide_all_join_requests(chat.into(), invite, true).await
+    }
+
+    /// Decline all the pending requests to join a group or channel.
+    ///
+    /// If `invite` is given, only the requests made through that invite link are declined.
+    pub async fn decline_all_join_requests<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        invite: Option<&str>,
+    ) -> Result<(), InvocationError> {
+        self.hide_all_join_requests(chat.into(), invite, false)
+            .await
+    }
+
+    async fn hide_join_request(
+        &self,
+        chat: PackedChat,
+        user: PackedChat,
+        approved: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::HideChatJoinRequest {
+            approved,
+            peer: chat.to_input_peer(),
+            user_id: user.to_input_user_lossy(),
+        })
+        .await
+        .map(drop)
+    }
+
+    async fn hide_all_join_requests(
+        &self,
+        chat: PackedChat,
+        invite: Option<&str>,
+        approved: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::HideAllChatJoinRequests {
+            approved,
+            peer: chat.to_input_peer(),
+            link: invite.map(str::to_string),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Get the topics of a forum with the given identifiers.
+    ///
+    /// Topics which no longer exist are not returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for topic in client.get_forum_topics_by_id(&chat, &[123, 456]).await? {
+    ///     println!("{}: {} unread", topic.title(), topic.unread_count());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_forum_topics_by_id<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_ids: &[i32],
+    ) -> Result<Vec<ForumTopic>, InvocationError> {
+        let channel = chat
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        let tl::enums::messages::ForumTopics::Topics(topics) = self
+            .invoke(&tl::functions::channels::GetForumTopicsById {
+                channel,
+                topics: topic_ids.to_vec(),
+            })
+            .await?;
+
+        self.0.chat_hashes.extend(&topics.users, &topics.chats);
+
+        Ok(topics
+            .topics
+            .into_iter()
+            .filter_map(ForumTopic::from_raw)
+            .collect())
+    }
+
+    /// Whether the General topic of a forum is hidden from the topic list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if client.is_general_topic_hidden(&chat).await? {
+    ///     println!("The General topic is hidden");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn is_general_topic_hidden<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<bool, InvocationError> {
+        Ok(self
+            .get_forum_topics_by_id(chat, &[GENERAL_TOPIC_ID])
+            .await?
+            .first()
+            .is_some_and(ForumTopic::is_hidden))
+    }
+
+    /// Hide or show the General topic of a forum in the topic list.
+    ///
+    /// The General topic is the only one which can be hidden.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_general_topic_hidden(&chat, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_general_topic_hidden<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        hidden: bool,
+    ) -> Result<(), InvocationError> {
+        let channel = chat
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::EditForumTopic {
+            channel,
+            topic_id: GENERAL_TOPIC_ID,
+            title: None,
+            icon_emoji_id: None,
+            closed: None,
+            hidden: Some(hidden),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Whether the logged-in user chose to view a forum as a single list of messages, rather
+    /// than as separate topics.
+    ///
+    /// Returns `false` if the chat is not a forum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if client.is_forum_viewed_as_messages(&chat).await? {
+    ///     println!("Topics are shown as messages");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn is_forum_viewed_as_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<bool, InvocationError> {
+        let chat = chat.into();
+        if !chat.is_channel() {
+            return Ok(false);
+        }
+
+        Ok(match self.get_raw_full_chat(chat).await? {
+            tl::enums::ChatFull::ChannelFull(channel) => channel.view_forum_as_messages,
+            tl::enums::ChatFull::Full(_) => false,
+        })
+    }
+
+    /// Choose whether a forum should be viewed as a single list of messages, rather than as
+    /// separate topics. This only affects the logged-in user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_forum_viewed_as_messages(&chat, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_forum_viewed_as_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        enabled: bool,
+    ) -> Result<(), InvocationError> {
+        let channel = chat
+            .into()
+            .try_to_input_channel()
+            .ok_or_else(channel_invalid)?;
+        self.invoke(&tl::functions::channels::ToggleViewForumAsMessages { channel, enabled })
+            .await
+            .map(drop)
+    }
+
+    /// Schedule a group call (or live stream, in channels) to start at the given date.
+    ///
+    /// Members of the chat will be able to see when the call is going to start, and can
+    /// subscribe to be notified when it does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use chrono::{Duration, Utc};
+    ///
+    /// client.schedule_group_call(&chat, Utc::now() + Duration::hours(1)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn schedule_group_call<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        start: DateTime<Utc>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::phone::CreateGroupCall {
+            rtmp_stream: false,
+            peer: chat.into().to_input_peer(),
+            random_id: utils::generate_random_id() as i32,
+            title: None,
+            schedule_date: Some(start.timestamp() as i32),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Get the RTMP server URL and stream key which can be used to stream into the group
+    /// call of a chat with external software.
+    ///
+    /// Only administrators who can manage calls may fetch these.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let rtmp = client.get_group_call_stream_rtmp_url(&chat).await?;
+    /// println!("Stream to {} with key {}", rtmp.url, rtmp.key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_group_call_stream_rtmp_url<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<StreamRtmpUrl, InvocationError> {
+        let tl::enums::phone::GroupCallStreamRtmpUrl::Url(rtmp) = self
+            .invoke(&tl::functions::phone::GetGroupCallStreamRtmpUrl {
+                peer: chat.into().to_input_peer(),
+                revoke: false,
+            })
+            .await?;
+
+        Ok(StreamRtmpUrl {
+            url: rtmp.url,
+            key: rtmp.key,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -5,19 +5,25 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use arc_swap::ArcSwap;
 use grammers_mtproto::mtp;
 use grammers_mtsender::{self as sender, ReconnectionPolicy, Sender, ServerAddr};
 use grammers_session::{ChatHashCache, MessageBox, Session};
 use grammers_tl_types as tl;
 use sender::Enqueuer;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
-use std::sync::atomic::AtomicU32;
-use std::sync::{Arc, RwLock};
-use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::Duration;
+use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock, mpsc, oneshot, watch};
 use web_time::Instant;
 
+use super::files::{MAX_CHUNK_SIZE, MIN_CHUNK_SIZE};
 use super::net;
+use crate::types::{ChatMap, Peer, Uploaded};
 
 /// When no locale is found, use this one instead.
 const DEFAULT_LOCALE: &str = "en";
@@ -62,8 +68,8 @@ pub struct InitParams {
     /// field can be used to override said address, and is most commonly used to connect to one
     /// of Telegram's test servers instead.
     pub server_addr: Option<ServerAddr>,
-    /// The threshold below which the library should automatically sleep on flood-wait and slow
-    /// mode wait errors (inclusive). For instance, if an
+    /// The threshold below which the library should automatically sleep on flood-wait errors
+    /// (inclusive). For instance, if an
     /// `RpcError { name: "FLOOD_WAIT", value: Some(17) }` (flood, must wait 17 seconds) occurs
     /// and `flood_sleep_threshold` is 20 (seconds), the library will `sleep` automatically for
     /// 17 seconds. If the error was for 21s, it would propagate the error instead.
@@ -75,6 +81,13 @@ pub struct InitParams {
     /// On flood, the library will retry *once*. If the flood error occurs a second time after
     /// sleeping, the error will be returned.
     pub flood_sleep_threshold: u32,
+    /// The threshold below which the library should automatically sleep when sending messages
+    /// too fast to a group with slow mode enabled (inclusive). It works like
+    /// [`InitParams::flood_sleep_threshold`], but for `SLOWMODE_WAIT` errors instead.
+    ///
+    /// Bots which send messages to groups with slow mode may want to increase this value, as
+    /// the delay is often a few minutes long. By default, it is the same as the flood threshold.
+    pub slow_mode_sleep_threshold: u32,
     /// How many updates may be buffered by the client at any given time.
     ///
     /// Telegram passively sends updates to the client through the open connection, so they must
@@ -117,32 +130,354 @@ pub struct InitParams {
     /// [`FixedReconnect`]: grammers_mtsender::FixedReconnect
     /// [`ReconnectionPolicy`]: grammers_mtsender::ReconnectionPolicy
     pub reconnection_policy: &'static dyn ReconnectionPolicy,
+
+    /// Handler invoked with every raw update (along with the users and chats that came with it)
+    /// as soon as it is received, before the library converts it into an [`Update`] or drops
+    /// it because the [`InitParams::update_queue_limit`] was reached.
+    ///
+    /// This is useful to access updates which the library does not model yet. The handler does
+    /// not consume the updates, so they will still be returned by [`Client::next_update`].
+    ///
+    /// The handler runs inside the network loop, so it should return quickly. Updates are only
+    /// received while the client is being driven, for example, by calling
+    /// [`Client::next_update`] or making requests.
+    ///
+    /// [`Update`]: crate::Update
+    pub raw_update_handler: Option<RawUpdateHandler>,
+
+    /// Handler invoked with the new time offset, in seconds, whenever the offset from the
+    /// server's time has to be corrected.
+    ///
+    /// Telegram rejects messages whose identifiers are too far off its own time, which happens
+    /// when the clock of the system is not accurate. The library corrects the offset and resends
+    /// the messages automatically, so the handler is only useful to detect skewed clocks. See
+    /// also [`Client::time_offset`].
+    pub time_offset_handler: Option<TimeOffsetHandler>,
+
+    /// Threshold, in bytes, at which outgoing requests are compressed before being sent, if
+    /// doing so makes them smaller.
+    ///
+    /// Lower values save bandwidth at the cost of CPU time. With `None`, requests are never
+    /// compressed. See also [`Client::compression_stats`].
+    pub compression_threshold: Option<usize>,
+
+    /// Automatically save the updates state into the session as updates are processed, so that
+    /// a client which stops unexpectedly can resume from where it left off.
+    ///
+    /// By default, the state is only saved into the session when the client is dropped or
+    /// [`Client::sync_update_state`] is called, and the session must still be saved manually.
+    pub update_state_checkpoint: Option<UpdateStateCheckpoint>,
+
+    /// Whether service messages about video chats, forum topics and chat migrations should be
+    /// returned as their dedicated [`Update`] variants, such as [`Update::VideoChatStarted`].
+    ///
+    /// By default, they're returned as [`Update::NewMessage`], like any other message.
+    ///
+    /// [`Update`]: crate::Update
+    /// [`Update::VideoChatStarted`]: crate::Update::VideoChatStarted
+    /// [`Update::NewMessage`]: crate::Update::NewMessage
+    pub service_message_updates: bool,
+
+    /// Cache where the small profile photos of the chats found in updates will be downloaded
+    /// into, in the background, so that they're ready to be displayed.
+    ///
+    /// Photos which the cache already contains are not downloaded again. The photos are only
+    /// downloaded if the client is connected from within a Tokio runtime. By default, no photos
+    /// are downloaded. See also [`Client::prefetch_avatars`].
+    pub avatar_cache: Option<Arc<dyn AvatarCache>>,
+
+    /// Cache of previously uploaded files, used to avoid uploading the same contents again.
+    ///
+    /// When set, the contents of the files are hashed as they're uploaded, and
+    /// [`Client::upload_file`] checks the cache before uploading anything. By default, every
+    /// file is uploaded in full.
+    pub upload_cache: Option<Arc<dyn UploadCache>>,
+
+    /// How files are split into parts when they're uploaded.
+    ///
+    /// Smaller parts are friendlier to slow or unreliable connections, while uploading more
+    /// parts in parallel speeds up uploads on fast ones.
+    pub upload_options: UploadOptions,
 }
 
+/// How often the updates state should be saved. See [`InitParams::update_state_checkpoint`].
+#[derive(Clone, Debug, Default)]
+pub struct UpdateStateCheckpoint {
+    /// Minimum time between checkpoints. With a zero interval, the state is saved every time an
+    /// update is returned.
+    ///
+    /// A checkpoint only occurs once every update received so far has been returned, so updates
+    /// which were still queued are fetched again after a crash instead of being lost. Updates
+    /// sent to a [`ChatUpdates`](crate::client::updates::ChatUpdates) stream count as returned.
+    pub interval: Duration,
+    /// If set, the session will also be saved to the file at this path on every checkpoint.
+    ///
+    /// The file is only rewritten if the session changed since it was last saved, so new
+    /// authorization keys and updates state are written together rather than one by one. When
+    /// running inside a Tokio runtime, the file is written in a blocking task, so that returning
+    /// updates doesn't wait for it.
+    pub session_path: Option<PathBuf>,
+}
+
+/// How files are split into parts when they're uploaded. See [`InitParams::upload_options`].
+///
+/// The values are validated as they're set. Files which would need more parts than Telegram
+/// allows are still rejected when they're uploaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadOptions {
+    pub(crate) part_size: i32,
+    pub(crate) big_file_threshold: usize,
+    pub(crate) parallel_parts: usize,
+}
+
+/// Files larger than this must be uploaded as big files.
+const MAX_BIG_FILE_THRESHOLD: usize = 10 * 1024 * 1024;
+
+impl UploadOptions {
+    /// Changes the size, in bytes, of each uploaded part. By default, 512 KiB are used.
+    ///
+    /// Telegram limits the amount of parts a file can have (3000, or 4000 for premium users),
+    /// so smaller parts also lower the maximum size of the files that can be uploaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not between 4 KiB and 512 KiB, or if 512 KiB is not divisible by
+    /// `size`.
+    pub fn part_size(mut self, size: i32) -> Self {
+        assert!(
+            (MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&size) && MAX_CHUNK_SIZE % size == 0,
+            "invalid upload part size: {size}"
+        );
+        self.part_size = size;
+        self
+    }
+
+    /// Changes the size, in bytes, above which files are uploaded as big files. By default,
+    /// files over 10 MiB are.
+    ///
+    /// Only the parts of big files are uploaded in parallel, so lowering the threshold lets
+    /// medium-sized files benefit from it too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is larger than 10 MiB, because Telegram requires larger files to be
+    /// uploaded as big files.
+    pub fn big_file_threshold(mut self, size: usize) -> Self {
+        assert!(
+            size <= MAX_BIG_FILE_THRESHOLD,
+            "invalid big file threshold: {size}"
+        );
+        self.big_file_threshold = size;
+        self
+    }
+
+    /// Changes how many parts of a big file are uploaded at the same time. By default, 4 are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is zero.
+    pub fn parallel_parts(mut self, count: usize) -> Self {
+        assert!(count != 0, "at least one part must be uploaded at a time");
+        self.parallel_parts = count;
+        self
+    }
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            part_size: MAX_CHUNK_SIZE,
+            big_file_threshold: MAX_BIG_FILE_THRESHOLD,
+            parallel_parts: 4,
+        }
+    }
+}
+
+/// Storage for the small profile photos of chats. See [`InitParams::avatar_cache`].
+///
+/// Photos are identified by the chat they belong to and their own identifier, which changes
+/// whenever the chat changes its photo.
+pub trait AvatarCache: Send + Sync {
+    /// Whether the cache already contains the given photo of the chat.
+    fn contains(&self, chat_id: i64, photo_id: i64) -> bool;
+
+    /// Store the downloaded photo of the chat, in JPEG format.
+    fn store(&self, chat_id: i64, photo_id: i64, data: Vec<u8>);
+}
+
+/// Storage for files which have already been uploaded. See [`InitParams::upload_cache`].
+///
+/// Files are identified by the SHA-256 hash of their contents. Uploaded files can only be used
+/// for a limited time (less than a day), so implementations should forget entries after a few
+/// hours.
+pub trait UploadCache: Send + Sync {
+    /// The file previously uploaded with the given contents, if it's still known.
+    fn get(&self, sha256: &[u8; 32]) -> Option<Uploaded>;
+
+    /// Remember the file which was just uploaded with the given contents.
+    fn store(&self, sha256: [u8; 32], uploaded: &Uploaded);
+}
+
+/// Handler for raw updates. See [`InitParams::raw_update_handler`].
+pub type RawUpdateHandler = Arc<dyn Fn(&tl::enums::Update, &ChatMap) + Send + Sync>;
+
+/// Handler for time offset corrections. See [`InitParams::time_offset_handler`].
+pub type TimeOffsetHandler = Arc<dyn Fn(i32) + Send + Sync>;
+
 pub(crate) struct ClientInner {
     // Used to implement `PartialEq`.
     pub(crate) id: i64,
     pub(crate) config: Config,
     pub(crate) conn: Connection,
+    // The datacenter and the known chat hashes are read far more often than they change, so
+    // they're kept apart from the rest of the state, and reading them never takes a lock.
+    pub(crate) dc_id: AtomicI32,
+    pub(crate) chat_hashes: ChatHashes,
     pub(crate) state: RwLock<ClientState>,
+    // Photos to download into the `InitParams::avatar_cache`, by a task started on connect.
+    pub(crate) avatar_tx: Option<mpsc::Sender<AvatarDownload>>,
+    // Whether the session is being saved due to `InitParams::update_state_checkpoint`.
+    pub(crate) saving_checkpoint: AtomicBool,
     // Stores per-datacenter downloader instances
     pub(crate) downloader_map: AsyncRwLock<HashMap<i32, Arc<Connection>>>,
+    // Stores per-datacenter connections to the CDN, which use their own authorization keys
+    pub(crate) cdn_map: AsyncRwLock<HashMap<i32, Arc<CdnConnection>>>,
+    // Usernames resolved in bulk, keyed by their normalized form, along with the date when they
+    // were resolved. Mirrored in the session, which it is loaded from. Never held across requests.
+    pub(crate) resolved_usernames: Mutex<HashMap<String, (i32, crate::types::Chat)>>,
+    // Usernames currently being resolved, so that concurrent resolutions wait for the first one.
+    pub(crate) resolving_usernames:
+        Mutex<HashMap<String, watch::Receiver<Option<Option<crate::types::Chat>>>>>,
+    // Last sticker sets fetched along with their hash, so that sets which did not change don't
+    // have to be downloaded again. Unlike contacts and dialogs, they are not saved in the session.
+    // Most recently fetched last, up to `MAX_CACHED_STICKER_SETS`.
+    pub(crate) sticker_set_cache: Mutex<VecDeque<tl::types::messages::StickerSet>>,
+}
+
+/// The known chat hashes, published as a snapshot which can be read without locking.
+///
+/// Changes are made to a copy of the current snapshot, which then replaces it. Copying the cache
+/// is cheap, and its hashes are only copied if a change actually modifies them.
+pub(crate) struct ChatHashes {
+    snapshot: ArcSwap<ChatHashCache>,
+    // Held while changing the snapshot, so that concurrent changes are not lost.
+    write_lock: Mutex<()>,
+}
+
+impl ChatHashes {
+    pub(crate) fn new(cache: ChatHashCache) -> Self {
+        Self {
+            snapshot: ArcSwap::from_pointee(cache),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// The current snapshot of the chat hashes.
+    pub(crate) fn load(&self) -> Arc<ChatHashCache> {
+        self.snapshot.load_full()
+    }
+
+    /// Start changing the chat hashes. The changes are published as the new snapshot when the
+    /// returned guard is dropped, and concurrent changes wait until then.
+    ///
+    /// Other locks, such as the one of the [`ClientState`], may be taken while the guard is held,
+    /// but not the other way around.
+    pub(crate) fn edit(&self) -> ChatHashesEdit<'_> {
+        let guard = self.write_lock.lock().unwrap();
+        ChatHashesEdit {
+            chat_hashes: self,
+            _guard: guard,
+            cache: Some(ChatHashCache::clone(&self.snapshot.load())),
+        }
+    }
+
+    /// Change the chat hashes, publishing the result as the new snapshot.
+    pub(crate) fn update<T>(&self, f: impl FnOnce(&mut ChatHashCache) -> T) -> T {
+        f(&mut self.edit())
+    }
+
+    /// Remember the hashes of the given users and chats.
+    ///
+    /// Telegram can return peers without hash (e.g. Users with 'min: true'), which are ignored.
+    pub(crate) fn extend(&self, users: &[tl::enums::User], chats: &[tl::enums::Chat]) {
+        self.update(|cache| {
+            let _ = cache.extend(users, chats);
+        });
+    }
+}
+
+/// A copy of the chat hashes being changed, see [`ChatHashes::edit`].
+pub(crate) struct ChatHashesEdit<'a> {
+    chat_hashes: &'a ChatHashes,
+    _guard: MutexGuard<'a, ()>,
+    cache: Option<ChatHashCache>,
+}
+
+impl Deref for ChatHashesEdit<'_> {
+    type Target = ChatHashCache;
+
+    fn deref(&self) -> &ChatHashCache {
+        self.cache.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for ChatHashesEdit<'_> {
+    fn deref_mut(&mut self) -> &mut ChatHashCache {
+        self.cache.as_mut().unwrap()
+    }
+}
+
+impl Drop for ChatHashesEdit<'_> {
+    fn drop(&mut self) {
+        if let Some(cache) = self.cache.take() {
+            self.chat_hashes.snapshot.store(Arc::new(cache));
+        }
+    }
 }
 
 pub(crate) struct ClientState {
-    pub(crate) dc_id: i32,
     pub(crate) message_box: MessageBox,
-    pub(crate) chat_hashes: ChatHashCache,
     // When did we last warn the user that the update queue filled up?
     // This is used to avoid spamming the log.
     pub(crate) last_update_limit_warn: Option<Instant>,
+    // When was the updates state last saved due to `InitParams::update_state_checkpoint`?
+    pub(crate) last_update_checkpoint: Option<Instant>,
     pub(crate) updates: VecDeque<(tl::enums::Update, Arc<crate::types::ChatMap>)>,
+    // How many batches were applied to the `message_box` but are not in `updates` yet.
+    // The updates state must not be checkpointed while any of these is pending.
+    pub(crate) unqueued_batches: usize,
+    // Updates from these chats are sent to their `ChatUpdates` instead of the queue above.
+    pub(crate) chat_updates: HashMap<Peer, Vec<ChatUpdateSender>>,
+    // Photos currently being downloaded into the `InitParams::avatar_cache`.
+    pub(crate) avatar_downloads: HashSet<i64>,
+    // Audio transcriptions awaited by `Client::transcribe_audio`, by their identifier.
+    pub(crate) transcriptions: HashMap<i64, oneshot::Sender<String>>,
+    // Audio transcriptions which completed recently, in case their update arrived before
+    // `Client::transcribe_audio` could start waiting for it.
+    pub(crate) completed_transcriptions: VecDeque<(i64, String)>,
 }
 
+/// The chat and photo identifiers of a photo to download into the avatar cache.
+pub(crate) type AvatarDownload = (i64, i64, crate::types::ChatPhoto);
+
+pub(crate) type ChatUpdateSender =
+    mpsc::UnboundedSender<(tl::enums::Update, Arc<crate::types::ChatMap>)>;
+
 pub(crate) struct Connection {
     pub(crate) sender: AsyncMutex<Sender<net::Transport, mtp::Encrypted>>,
     pub(crate) request_tx: RwLock<Enqueuer>,
     pub(crate) step_counter: AtomicU32,
+    // Time offset last known to the sender, used to notice when it gets corrected.
+    pub(crate) time_offset: AtomicI32,
+    // Compression statistics as of the last step, readable without locking the sender.
+    pub(crate) compression_stats: Mutex<mtp::CompressionStats>,
+}
+
+pub(crate) struct CdnConnection {
+    pub(crate) conn: Connection,
+    // CDN data centers only serve files, so the connection is initialized along with the first
+    // request sent through it, and this is set once that succeeds.
+    pub(crate) initialized: AtomicBool,
 }
 
 /// A client capable of connecting to Telegram and invoking requests.
@@ -187,10 +522,30 @@ impl Default for InitParams {
             catch_up: false,
             server_addr: None,
             flood_sleep_threshold: 60,
+            slow_mode_sleep_threshold: 60,
             update_queue_limit: Some(100),
             #[cfg(feature = "proxy")]
             proxy_url: None,
             reconnection_policy: &grammers_mtsender::NoReconnect,
+            raw_update_handler: None,
+            time_offset_handler: None,
+            compression_threshold: grammers_mtproto::DEFAULT_COMPRESSION_THRESHOLD,
+            update_state_checkpoint: None,
+            service_message_updates: false,
+            avatar_cache: None,
+            upload_cache: None,
+            upload_options: UploadOptions::default(),
+        }
+    }
+}
+
+impl InitParams {
+    /// The threshold to use when sleeping on the given 420 error.
+    pub(crate) fn sleep_threshold(&self, error_name: &str) -> u32 {
+        if error_name == "SLOWMODE_WAIT" {
+            self.slow_mode_sleep_threshold
+        } else {
+            self.flood_sleep_threshold
         }
     }
 }
@@ -206,7 +561,7 @@ impl fmt::Debug for Client {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // TODO show more info, like user id and session name if present
         f.debug_struct("Client")
-            .field("dc_id", &self.0.state.read().unwrap().dc_id)
+            .field("dc_id", &self.0.dc_id.load(Ordering::Relaxed))
             .finish()
     }
 }
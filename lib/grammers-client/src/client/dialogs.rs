@@ -5,14 +5,19 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use crate::Client;
-use crate::types::{ChatMap, Dialog, IterBuffer, Message};
+use crate::types::{
+    ChatMap, ChatlistInvite, Dialog, DialogOffset, FolderUnreadCounts, IterBuffer, Message,
+};
+use crate::{Client, utils};
+use grammers_mtproto::mtp::DeserializeError;
 use grammers_mtsender::InvocationError;
 use grammers_session::PackedChat;
-use grammers_tl_types as tl;
+use grammers_tl_types::{self as tl, Cursor, Deserializable, Identifiable, Serializable};
 use std::collections::HashMap;
 
 const MAX_LIMIT: usize = 100;
+/// The key under which the first chunk of dialogs is saved in the session.
+const DIALOGS_CACHE_KEY: &str = "dialogs";
 
 pub type DialogIter = IterBuffer<tl::functions::messages::GetDialogs, Dialog>;
 
@@ -34,6 +39,39 @@ impl DialogIter {
         )
     }
 
+    /// Resume iterating right after the given offset, which is usually obtained from
+    /// [`Dialog::offset`] for the last dialog that was processed.
+    ///
+    /// Pinned dialogs are not returned again when resuming.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::DialogOffset;
+    ///
+    /// # let saved = Vec::new();
+    /// let mut dialogs = client.iter_dialogs();
+    /// if let Some(offset) = DialogOffset::from_bytes(&saved) {
+    ///     dialogs = dialogs.offset(offset);
+    /// }
+    ///
+    /// while let Some(dialog) = dialogs.next().await? {
+    ///     // Process the dialog, then persist `dialog.offset().to_bytes()`.
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn offset(mut self, offset: DialogOffset) -> Self {
+        self.request.exclude_pinned = true;
+        self.request.offset_date = offset.date;
+        self.request.offset_id = offset.id;
+        self.request.offset_peer = offset
+            .peer
+            .map_or(tl::enums::InputPeer::Empty, |peer| peer.to_input_peer());
+        self
+    }
+
     /// Determines how many dialogs there are in total.
     ///
     /// This only performs a network call if `next` has not been called before.
@@ -66,7 +104,48 @@ impl DialogIter {
         use tl::enums::messages::Dialogs;
 
         self.request.limit = self.determine_limit(MAX_LIMIT);
-        let (dialogs, messages, users, chats) = match self.client.invoke(&self.request).await? {
+
+        // Only the first chunk is remembered, which is the one re-fetched by polling.
+        let first_chunk = !self.request.exclude_pinned
+            && self.request.folder_id.is_none()
+            && self.request.offset_date == 0
+            && self.request.offset_id == 0
+            && matches!(self.request.offset_peer, tl::enums::InputPeer::Empty);
+        let cached = if first_chunk {
+            cached_dialogs(&self.client, self.request.limit)
+        } else {
+            None
+        };
+
+        self.request.hash = cached.as_ref().map_or(0, |(hash, _)| *hash);
+        let response = self.client.invoke(&self.request).await;
+        self.request.hash = 0;
+        let response = match (response?, cached) {
+            (Dialogs::NotModified(_), Some((_, dialogs))) => dialogs,
+            // Nothing was cached, so the hash sent was 0 and the dialogs cannot be unmodified.
+            (Dialogs::NotModified(_), None) => {
+                return Err(InvocationError::Read(
+                    DeserializeError::UnexpectedConstructor {
+                        id: tl::types::messages::DialogsNotModified::CONSTRUCTOR_ID,
+                    }
+                    .into(),
+                ));
+            }
+            (response, _) => {
+                if first_chunk && let Some(hash) = dialogs_hash(&response) {
+                    let mut data = self.request.limit.to_bytes();
+                    response.serialize(&mut data);
+                    self.client
+                        .0
+                        .config
+                        .session
+                        .set_cached_result(DIALOGS_CACHE_KEY, hash, data);
+                }
+                response
+            }
+        };
+
+        let (dialogs, messages, users, chats) = match response {
             Dialogs::Dialogs(d) => {
                 self.last_chunk = true;
                 self.total = Some(d.dialogs.len());
@@ -77,16 +156,10 @@ impl DialogIter {
                 self.total = Some(d.count as usize);
                 (d.dialogs, d.messages, d.users, d.chats)
             }
-            Dialogs::NotModified(_) => {
-                panic!("API returned Dialogs::NotModified even though hash = 0")
-            }
+            Dialogs::NotModified(_) => unreachable!("unmodified dialogs are replaced above"),
         };
 
-        {
-            let mut state = self.client.0.state.write().unwrap();
-            // Telegram can return peers without hash (e.g. Users with 'min: true')
-            let _ = state.chat_hashes.extend(&users, &chats);
-        }
+        self.client.0.chat_hashes.extend(&users, &chats);
 
         let chats = ChatMap::new(users, chats);
         let mut messages = messages
@@ -108,7 +181,7 @@ impl DialogIter {
                         .message_box
                         .try_set_channel_state(channel.channel_id, *pts);
                 }
-                Dialog::new(dialog, &mut messages, &chats)
+                Dialog::new(&self.client, dialog, &mut messages, &chats)
             }));
         }
 
@@ -134,6 +207,46 @@ impl DialogIter {
     }
 }
 
+/// The first chunk of dialogs saved in the session for the given `limit`, along with its hash.
+fn cached_dialogs(client: &Client, limit: i32) -> Option<(i64, tl::enums::messages::Dialogs)> {
+    let (hash, data) = client
+        .0
+        .config
+        .session
+        .get_cached_result(DIALOGS_CACHE_KEY)?;
+    let mut cursor = Cursor::from_slice(&data);
+    if i32::deserialize(&mut cursor).ok()? != limit {
+        return None;
+    }
+    match tl::enums::messages::Dialogs::deserialize(&mut cursor).ok()? {
+        tl::enums::messages::Dialogs::NotModified(_) => None,
+        dialogs => Some((hash, dialogs)),
+    }
+}
+
+/// The hash of the dialogs, covering their order and last messages, if there are any.
+fn dialogs_hash(dialogs: &tl::enums::messages::Dialogs) -> Option<i64> {
+    use tl::enums::messages::Dialogs;
+
+    let dialogs = match dialogs {
+        Dialogs::Dialogs(d) => &d.dialogs,
+        Dialogs::Slice(d) => &d.dialogs,
+        Dialogs::NotModified(_) => return None,
+    };
+    Some(utils::combine_hash(dialogs.iter().flat_map(|dialog| {
+        let (peer, top_message) = match dialog {
+            tl::enums::Dialog::Dialog(d) => (&d.peer, d.top_message),
+            tl::enums::Dialog::Folder(f) => (&f.peer, f.top_message),
+        };
+        let id = match peer {
+            tl::enums::Peer::User(user) => user.user_id,
+            tl::enums::Peer::Chat(chat) => chat.chat_id,
+            tl::enums::Peer::Channel(channel) => channel.channel_id,
+        };
+        [id, top_message as i64]
+    })))
+}
+
 /// Method implementations related to open conversations.
 impl Client {
     /// Returns a new iterator over the dialogs.
@@ -236,6 +349,108 @@ impl Client {
         }
     }
 
+    /// Mark a topic of a forum as read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let topic_id = 123;
+    /// client.mark_topic_as_read(&chat, topic_id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mark_topic_as_read<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_id: i32,
+    ) -> Result<(), InvocationError> {
+        let chat = chat.into();
+        // Reading up to the last message of the topic marks all of it as read.
+        let read_max_id = match self.get_forum_topics_by_id(chat, &[topic_id]).await?.pop() {
+            Some(topic) => topic.top_message(),
+            None => return Ok(()),
+        };
+        self.invoke(&tl::functions::messages::ReadDiscussion {
+            peer: chat.to_input_peer(),
+            msg_id: topic_id,
+            read_max_id,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Get the chats which were manually marked as unread.
+    ///
+    /// Chats which are not known to the client yet (they have not been seen in any other
+    /// response) cannot be used to invoke requests, so they are not returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for chat in client.get_dialog_unread_marks().await? {
+    ///     client.mark_as_read(chat).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_dialog_unread_marks(&self) -> Result<Vec<PackedChat>, InvocationError> {
+        let peers = self
+            .invoke(&tl::functions::messages::GetDialogUnreadMarks {})
+            .await?;
+
+        let chat_hashes = self.0.chat_hashes.load();
+        Ok(peers
+            .into_iter()
+            .filter_map(|peer| match peer {
+                tl::enums::DialogPeer::Peer(dialog) => {
+                    let id = match dialog.peer {
+                        tl::enums::Peer::User(user) => user.user_id,
+                        tl::enums::Peer::Chat(chat) => chat.chat_id,
+                        tl::enums::Peer::Channel(channel) => channel.channel_id,
+                    };
+                    chat_hashes.get(id)
+                }
+                tl::enums::DialogPeer::Folder(_) => None,
+            })
+            .collect())
+    }
+
+    /// Get the unread counts of the chats inside a peer folder, which can be used to show the
+    /// same badge numbers as official applications.
+    ///
+    /// The archive is the folder with identifier `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let archive = client.get_folder_unread_counts(1).await?;
+    /// println!("{} unread archived messages", archive.badge(false, true));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_folder_unread_counts(
+        &self,
+        folder_id: i32,
+    ) -> Result<FolderUnreadCounts, InvocationError> {
+        let tl::enums::messages::PeerDialogs::Dialogs(dialogs) = self
+            .invoke(&tl::functions::messages::GetPeerDialogs {
+                peers: vec![tl::types::InputDialogPeerFolder { folder_id }.into()],
+            })
+            .await?;
+
+        Ok(dialogs
+            .dialogs
+            .iter()
+            .find_map(|dialog| match dialog {
+                tl::enums::Dialog::Folder(folder) => Some(FolderUnreadCounts::from_raw(folder)),
+                tl::enums::Dialog::Dialog(_) => None,
+            })
+            .unwrap_or_default())
+    }
+
     /// Clears all pending mentions from a chat, marking them as read.
     ///
     /// # Examples
@@ -250,9 +465,311 @@ impl Client {
         &self,
         chat: C,
     ) -> Result<(), InvocationError> {
-        self.invoke(&tl::functions::messages::ReadMentions {
-            peer: chat.into().to_input_peer(),
-            top_msg_id: None,
+        self.read_mentions(chat.into(), None).await
+    }
+
+    /// Clears all pending mentions from a topic of a forum, marking them as read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let topic_id = 123;
+    /// client.clear_topic_mentions(&chat, topic_id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_topic_mentions<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_id: i32,
+    ) -> Result<(), InvocationError> {
+        self.read_mentions(chat.into(), Some(topic_id)).await
+    }
+
+    /// Clears all pending reactions to the messages sent by the logged-in user in a chat,
+    /// marking them as seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.clear_reactions(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_reactions<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<(), InvocationError> {
+        self.read_reactions(chat.into(), None).await
+    }
+
+    /// Clears all pending reactions to the messages sent by the logged-in user in a topic of a
+    /// forum, marking them as seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let topic_id = 123;
+    /// client.clear_topic_reactions(&chat, topic_id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_topic_reactions<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_id: i32,
+    ) -> Result<(), InvocationError> {
+        self.read_reactions(chat.into(), Some(topic_id)).await
+    }
+
+    // Telegram may only clear some of them at a time, in which case the offset is non-zero and
+    // the request has to be repeated.
+    async fn read_mentions(
+        &self,
+        chat: PackedChat,
+        top_msg_id: Option<i32>,
+    ) -> Result<(), InvocationError> {
+        loop {
+            let tl::enums::messages::AffectedHistory::History(affected) = self
+                .invoke(&tl::functions::messages::ReadMentions {
+                    peer: chat.to_input_peer(),
+                    top_msg_id,
+                })
+                .await?;
+            if affected.offset == 0 {
+                break Ok(());
+            }
+        }
+    }
+
+    async fn read_reactions(
+        &self,
+        chat: PackedChat,
+        top_msg_id: Option<i32>,
+    ) -> Result<(), InvocationError> {
+        loop {
+            let tl::enums::messages::AffectedHistory::History(affected) = self
+                .invoke(&tl::functions::messages::ReadReactions {
+                    peer: chat.to_input_peer(),
+                    top_msg_id,
+                })
+                .await?;
+            if affected.offset == 0 {
+                break Ok(());
+            }
+        }
+    }
+
+    /// Pins a dialog to the top of the dialog list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.pin_dialog(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn pin_dialog<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.toggle_dialog_pin(chat.into(), true).await
+    }
+
+    /// Unpins a dialog, so that it's sorted among the rest of dialogs again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.unpin_dialog(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unpin_dialog<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.toggle_dialog_pin(chat.into(), false).await
+    }
+
+    async fn toggle_dialog_pin(
+        &self,
+        chat: PackedChat,
+        pinned: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::ToggleDialogPin {
+            pinned,
+            peer: tl::types::InputDialogPeer {
+                peer: chat.to_input_peer(),
+            }
+            .into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Changes the order of the pinned dialogs in the main dialog list.
+    ///
+    /// The `order` should contain all the pinned dialogs, with the one that should show first at
+    /// the start. Dialogs which were pinned but are not present in the `order` will be unpinned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(a: grammers_client::types::Chat, b: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// // Show `b` above `a`.
+    /// client.reorder_pinned_dialogs([&b, &a]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reorder_pinned_dialogs<C: Into<PackedChat>, I: IntoIterator<Item = C>>(
+        &self,
+        order: I,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::ReorderPinnedDialogs {
+            force: true,
+            folder_id: 0,
+            order: order
+                .into_iter()
+                .map(|chat| {
+                    tl::types::InputDialogPeer {
+                        peer: chat.into().to_input_peer(),
+                    }
+                    .into()
+                })
+                .collect(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Create a new invite link to share a folder with other people.
+    ///
+    /// The `filter_id` is the identifier of the folder to share, and `chats` are the chats of
+    /// the folder which will be offered to whoever uses the link.
+    ///
+    /// Returns the URL of the newly-created invite link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let filter_id = 2;
+    /// let url = client.export_chatlist_invite(filter_id, "My folder", [&chat]).await?;
+    /// println!("Share this link: {}", url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_chatlist_invite<C: Into<PackedChat>, I: IntoIterator<Item = C>>(
+        &self,
+        filter_id: i32,
+        title: &str,
+        chats: I,
+    ) -> Result<String, InvocationError> {
+        let tl::enums::chatlists::ExportedChatlistInvite::Invite(exported) = self
+            .invoke(&tl::functions::chatlists::ExportChatlistInvite {
+                chatlist: tl::types::InputChatlistDialogFilter { filter_id }.into(),
+                title: title.to_string(),
+                peers: chats
+                    .into_iter()
+                    .map(|chat| chat.into().to_input_peer())
+                    .collect(),
+            })
+            .await?;
+        let tl::enums::ExportedChatlistInvite::Invite(invite) = exported.invite;
+        Ok(invite.url)
+    }
+
+    /// Fetch information about the folder an invite link refers to, without joining it.
+    ///
+    /// The `slug` is the last part of the invite link, after `t.me/addlist/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let invite = client.check_chatlist_invite("AbCdEf123").await?;
+    /// for chat in invite.chats() {
+    ///     println!("Folder contains {:?}", chat.name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn check_chatlist_invite(
+        &self,
+        slug: &str,
+    ) -> Result<ChatlistInvite, InvocationError> {
+        let invite = self
+            .invoke(&tl::functions::chatlists::CheckChatlistInvite {
+                slug: slug.to_string(),
+            })
+            .await?;
+
+        match &invite {
+            tl::enums::chatlists::ChatlistInvite::Already(invite) => {
+                self.0.chat_hashes.extend(&invite.users, &invite.chats)
+            }
+            tl::enums::chatlists::ChatlistInvite::Invite(invite) => {
+                self.0.chat_hashes.extend(&invite.users, &invite.chats)
+            }
+        }
+
+        Ok(ChatlistInvite::from_raw(self, invite))
+    }
+
+    /// Join a shared folder through its invite link, along with the given chats from it.
+    ///
+    /// The chats can be obtained with [`Client::check_chatlist_invite`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let slug = "AbCdEf123";
+    /// let invite = client.check_chatlist_invite(slug).await?;
+    /// client.join_chatlist(slug, &invite.chats()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn join_chatlist<C: Into<PackedChat>, I: IntoIterator<Item = C>>(
+        &self,
+        slug: &str,
+        chats: I,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::chatlists::JoinChatlistInvite {
+            slug: slug.to_string(),
+            peers: chats
+                .into_iter()
+                .map(|chat| chat.into().to_input_peer())
+                .collect(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Leave a shared folder, and optionally some of the chats it contains.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::Chat;
+    ///
+    /// let filter_id = 2;
+    /// client.leave_chatlist(filter_id, Vec::<Chat>::new()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn leave_chatlist<C: Into<PackedChat>, I: IntoIterator<Item = C>>(
+        &self,
+        filter_id: i32,
+        chats: I,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::chatlists::LeaveChatlist {
+            chatlist: tl::types::InputChatlistDialogFilter { filter_id }.into(),
+            peers: chats
+                .into_iter()
+                .map(|chat| chat.into().to_input_peer())
+                .collect(),
         })
         .await
         .map(drop)
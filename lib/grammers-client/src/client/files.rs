@@ -6,48 +6,256 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::Client;
-use crate::types::{Downloadable, Uploaded};
+use super::client::{AvatarDownload, ClientInner};
+use crate::types::{ChatMap, Downloadable, Message, Uploaded};
 use crate::utils::generate_random_id;
+use crate::{Client, InputMessage, UploadCache};
 use futures_util::stream::{FuturesUnordered, StreamExt as _};
-use grammers_mtsender::InvocationError;
+use grammers_mtsender::{InvocationError, ReadError};
+use grammers_session::PackedChat;
 use grammers_tl_types as tl;
-use std::mem;
-use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Weak};
+use std::{fmt, mem};
 use tokio::{
-    io::{self, AsyncRead, AsyncReadExt},
-    sync::Mutex as AsyncMutex,
+    io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::{Mutex as AsyncMutex, mpsc},
 };
 
 #[cfg(feature = "fs")]
 use {
+    crate::types::Chat,
     std::{io::SeekFrom, path::Path},
-    tokio::{
-        fs,
-        io::{AsyncSeekExt, AsyncWriteExt},
-        sync::mpsc::unbounded_channel,
-    },
+    tokio::{fs, io::AsyncSeekExt, sync::mpsc::unbounded_channel},
 };
 
 pub const MIN_CHUNK_SIZE: i32 = 4 * 1024;
 pub const MAX_CHUNK_SIZE: i32 = 512 * 1024;
 const FILE_MIGRATE_ERROR: i32 = 303;
+/// Files in CDN data centers are hashed in parts of this size.
+const CDN_PART_SIZE: i64 = 128 * 1024;
+#[cfg(feature = "fs")]
 const BIG_FILE_SIZE: usize = 10 * 1024 * 1024;
+// Telegram limits the amount of parts a file can have, which is higher for premium accounts.
+const MAX_FILE_PARTS: i32 = 3000;
+const MAX_PREMIUM_FILE_PARTS: i32 = 4000;
+#[cfg(feature = "fs")]
 const WORKER_COUNT: usize = 4;
+/// How many photos may wait to be downloaded into the avatar cache, and how many are
+/// downloaded at the same time.
+pub(crate) const MAX_PENDING_AVATARS: usize = 256;
+const MAX_AVATAR_DOWNLOADS: usize = 4;
+
+/// The data downloaded from a CDN data center did not match the hash Telegram provided for it,
+/// which means the file is corrupted or was tampered with.
+///
+/// Downloads fail with an [`InvocationError::Read`] error containing this as the inner error
+/// of an [`io::Error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CdnHashMismatch {
+    /// The offset of the part of the file which failed verification.
+    pub offset: i64,
+}
+
+impl std::error::Error for CdnHashMismatch {}
+
+impl fmt::Display for CdnHashMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file part at offset {} downloaded from cdn does not match its hash",
+            self.offset
+        )
+    }
+}
+
+impl From<CdnHashMismatch> for InvocationError {
+    fn from(error: CdnHashMismatch) -> Self {
+        Self::Read(ReadError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            error,
+        )))
+    }
+}
+
+/// The error type which is returned when [`Client::send_file`] fails.
+#[derive(Debug)]
+pub enum SendFileError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file could not be uploaded or sent.
+    Invocation(InvocationError),
+}
+
+impl std::error::Error for SendFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Invocation(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for SendFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "send file error: {e}"),
+            Self::Invocation(e) => write!(f, "send file error: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for SendFileError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<InvocationError> for SendFileError {
+    fn from(error: InvocationError) -> Self {
+        Self::Invocation(error)
+    }
+}
+
+/// The hashes of the parts of a file stored in a CDN data center, fetched as needed.
+///
+/// Telegram hashes files in fixed-size parts, so the downloaded chunks must be aligned to them.
+struct CdnHashes {
+    file_token: Vec<u8>,
+    hashes: HashMap<i64, tl::types::FileHash>,
+}
+
+impl CdnHashes {
+    fn new(file_token: Vec<u8>, hashes: Vec<tl::enums::FileHash>) -> Self {
+        let mut this = Self {
+            file_token,
+            hashes: HashMap::new(),
+        };
+        this.extend(hashes);
+        this
+    }
+
+    fn extend(&mut self, hashes: Vec<tl::enums::FileHash>) {
+        self.hashes.extend(hashes.into_iter().map(|hash| {
+            let tl::enums::FileHash::Hash(hash) = hash;
+            (hash.offset, hash)
+        }));
+    }
+
+    /// Verify the chunk of data downloaded at the given offset, fetching the missing hashes
+    /// from the data center where the file was originally stored.
+    async fn verify(
+        &mut self,
+        client: &Client,
+        dc_id: Option<i32>,
+        offset: i64,
+        data: &[u8],
+    ) -> Result<(), InvocationError> {
+        let mut position = 0;
+        while position < data.len() {
+            let part_offset = offset + position as i64;
+            if !self.hashes.contains_key(&part_offset) {
+                let request = tl::functions::upload::GetCdnFileHashes {
+                    file_token: self.file_token.clone(),
+                    offset: part_offset,
+                };
+                let hashes = match dc_id {
+                    None => client.invoke(&request).await?,
+                    Some(dc_id) => client.invoke_in_dc(&request, dc_id).await?,
+                };
+                self.extend(hashes);
+            }
+
+            position += self.check_part(part_offset, &data[position..])?;
+        }
+        Ok(())
+    }
+
+    /// Check the part of the data starting at the given offset against its known hash,
+    /// returning how many bytes the hash covered.
+    fn check_part(&self, offset: i64, data: &[u8]) -> Result<usize, CdnHashMismatch> {
+        let mismatch = CdnHashMismatch { offset };
+        let Some(hash) = self.hashes.get(&offset).filter(|hash| hash.limit > 0) else {
+            return Err(mismatch);
+        };
+        let len = data.len().min(hash.limit as usize);
+        if !grammers_crypto::cdn::check_hash(&data[..len], &hash.hash) {
+            return Err(mismatch);
+        }
+        Ok(len)
+    }
+}
+
+/// The state needed to download a file which Telegram redirected to a CDN data center.
+struct CdnDownload {
+    /// The original request, used to get a new redirect if the current one expires.
+    request: tl::functions::upload::GetFile,
+    /// The data center where the file is stored, if it's not the home data center.
+    origin_dc: Option<i32>,
+    dc_id: i32,
+    encryption_key: [u8; 32],
+    encryption_iv: [u8; 16],
+    hashes: CdnHashes,
+    /// The last part downloaded and its offset, so that smaller chunks can be served from it.
+    part: (i64, Vec<u8>),
+}
+
+impl CdnDownload {
+    fn new(
+        request: tl::functions::upload::GetFile,
+        origin_dc: Option<i32>,
+        redirect: tl::types::upload::FileCdnRedirect,
+    ) -> Result<Self, InvocationError> {
+        let (Ok(encryption_key), Ok(encryption_iv)) = (
+            redirect.encryption_key.as_slice().try_into(),
+            redirect.encryption_iv.as_slice().try_into(),
+        ) else {
+            return Err(ReadError::from(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid cdn encryption key or iv",
+            ))
+            .into());
+        };
+
+        Ok(Self {
+            request,
+            origin_dc,
+            dc_id: redirect.dc_id,
+            encryption_key,
+            encryption_iv,
+            hashes: CdnHashes::new(redirect.file_token, redirect.file_hashes),
+            part: (0, Vec::new()),
+        })
+    }
+}
 
 pub struct DownloadIter {
     client: Client,
     done: bool,
     variant: DownloadIterVariant,
+    hasher: Option<Sha256>,
 }
 
 enum DownloadIterVariant {
     Request(tl::functions::upload::GetFile),
+    Cdn(Box<CdnDownload>),
     PreDownloaded(Vec<u8>),
     PreFailed(io::Error),
     Empty,
 }
 
+impl DownloadIterVariant {
+    fn request_mut(&mut self) -> Option<&mut tl::functions::upload::GetFile> {
+        match self {
+            Self::Request(request) => Some(request),
+            Self::Cdn(cdn) => Some(&mut cdn.request),
+            _ => None,
+        }
+    }
+}
+
 impl DownloadIter {
     /// Changes the chunk size, in bytes, used to make requests. Useful if you only need to get a
     /// small part of a file. By default, `MAX_CHUNK_SIZE` is used.
@@ -58,9 +266,8 @@ impl DownloadIter {
     /// the range `MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE`.
     pub fn chunk_size(mut self, size: i32) -> Self {
         assert!((MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&size) && size % MIN_CHUNK_SIZE == 0);
-        match &mut self.variant {
-            DownloadIterVariant::Request(request) => request.limit = size,
-            _ => {}
+        if let Some(request) = self.variant.request_mut() {
+            request.limit = size;
         }
         self
     }
@@ -69,43 +276,84 @@ impl DownloadIter {
     /// skip less data, modify the `chunk_size` before calling this method, and then reset it to
     /// any value you want.
     pub fn skip_chunks(mut self, n: i32) -> Self {
-        match &mut self.variant {
-            DownloadIterVariant::Request(request) => {
-                request.offset += request.limit as i64 * (n as i64)
-            }
-            _ => {}
+        if let Some(request) = self.variant.request_mut() {
+            request.offset += request.limit as i64 * (n as i64);
         }
         self
     }
 
+    /// Computes the SHA-256 digest of the downloaded data as chunks are returned, which can
+    /// then be retrieved with [`DownloadIter::sha256`].
+    ///
+    /// This is useful to check the integrity of a file against a known digest without having
+    /// to read it again after it has been downloaded.
+    pub fn with_sha256(mut self) -> Self {
+        self.hasher = Some(Sha256::new());
+        self
+    }
+
+    /// The SHA-256 digest of all the chunks returned so far, or `None` if
+    /// [`DownloadIter::with_sha256`] was not used.
+    ///
+    /// Note that chunks skipped with [`DownloadIter::skip_chunks`] are not part of the digest.
+    pub fn sha256(&self) -> Option<[u8; 32]> {
+        self.hasher
+            .as_ref()
+            .map(|hasher| hasher.clone().finalize().into())
+    }
+
     /// Fetch and return the next chunk.
+    ///
+    /// Files which Telegram redirects to a CDN data center are transparently downloaded from
+    /// there, decrypted, and verified against the hashes provided by Telegram. If verification
+    /// fails, a [`CdnHashMismatch`] error is returned.
     pub async fn next(&mut self) -> Result<Option<Vec<u8>>, InvocationError> {
-        if self.done {
-            return Ok(None);
-        }
+        loop {
+            if self.done {
+                return Ok(None);
+            }
 
-        let variant = mem::replace(&mut self.variant, DownloadIterVariant::Empty);
+            let variant = mem::replace(&mut self.variant, DownloadIterVariant::Empty);
+            let step = match variant {
+                DownloadIterVariant::Request(request) => self.next_file(request).await?,
+                DownloadIterVariant::Cdn(cdn) => self.next_cdn(cdn).await?,
+                DownloadIterVariant::PreDownloaded(data) => {
+                    self.done = true;
+                    if let Some(hasher) = self.hasher.as_mut() {
+                        hasher.update(&data);
+                    }
+                    return Ok(Some(data));
+                }
+                DownloadIterVariant::PreFailed(error) => {
+                    return Err(InvocationError::Read(error.into()));
+                }
+                DownloadIterVariant::Empty => return Ok(None),
+            };
 
-        let mut request = match variant {
-            DownloadIterVariant::Request(r) => r,
-            DownloadIterVariant::PreDownloaded(data) => {
-                self.done = true;
-                return Ok(Some(data.clone()));
-            }
-            DownloadIterVariant::PreFailed(error) => {
-                return Err(InvocationError::Read(error.into()));
+            if let ControlFlow::Break(chunk) = step {
+                if let (Some(hasher), Some(chunk)) = (self.hasher.as_mut(), chunk.as_ref()) {
+                    hasher.update(chunk);
+                }
+                return Ok(chunk);
             }
-            DownloadIterVariant::Empty => return Ok(None),
-        };
+        }
+    }
 
+    /// Fetch the next chunk from the data center where the file is stored.
+    ///
+    /// Continues if the file was redirected to a CDN data center.
+    async fn next_file(
+        &mut self,
+        mut request: tl::functions::upload::GetFile,
+    ) -> Result<ControlFlow<Option<Vec<u8>>>, InvocationError> {
         use tl::enums::upload::File;
 
         // TODO handle maybe FILEREF_UPGRADE_NEEDED
-        let mut dc: Option<u32> = None;
+        let mut dc: Option<i32> = None;
         loop {
-            let result = match dc.take() {
+            let result = match dc {
                 None => self.client.invoke(&request).await,
-                Some(dc) => self.client.invoke_in_dc(&request, dc as i32).await,
+                Some(dc) => self.client.invoke_in_dc(&request, dc).await,
             };
 
             break match result {
@@ -113,26 +361,103 @@ impl DownloadIter {
                     if f.bytes.len() < request.limit as usize {
                         self.done = true;
                         if f.bytes.is_empty() {
-                            break Ok(None);
+                            break Ok(ControlFlow::Break(None));
                         }
                     }
 
                     request.offset += request.limit as i64;
                     self.variant = DownloadIterVariant::Request(request);
 
-                    Ok(Some(f.bytes))
+                    Ok(ControlFlow::Break(Some(f.bytes)))
                 }
-                Ok(File::CdnRedirect(_)) => {
-                    panic!("API returned File::CdnRedirect even though cdn_supported = false");
+                Ok(File::CdnRedirect(redirect)) => {
+                    let cdn = CdnDownload::new(request, dc, redirect)?;
+                    self.variant = DownloadIterVariant::Cdn(Box::new(cdn));
+                    Ok(ControlFlow::Continue(()))
                 }
                 Err(InvocationError::Rpc(err)) if err.code == FILE_MIGRATE_ERROR => {
-                    dc = err.value;
+                    dc = err.value.map(|dc| dc as i32);
                     continue;
                 }
                 Err(e) => Err(e),
             };
         }
     }
+
+    /// Fetch the next chunk from the CDN data center the file was redirected to.
+    ///
+    /// Continues if the file must first be reuploaded to the CDN, or if the redirect expired.
+    async fn next_cdn(
+        &mut self,
+        mut cdn: Box<CdnDownload>,
+    ) -> Result<ControlFlow<Option<Vec<u8>>>, InvocationError> {
+        use tl::enums::upload::CdnFile;
+
+        let offset = cdn.request.offset;
+        let limit = cdn.request.limit as i64;
+
+        let (part_offset, part) = &cdn.part;
+        if !(*part_offset..*part_offset + part.len() as i64).contains(&offset) {
+            // Always fetch whole parts, so that they can be verified against their hash.
+            let size = limit.max(CDN_PART_SIZE);
+            let part_offset = offset - offset % size;
+            let request = tl::functions::upload::GetCdnFile {
+                file_token: cdn.hashes.file_token.clone(),
+                offset: part_offset,
+                limit: size as i32,
+            };
+
+            match self.client.get_cdn_file(cdn.dc_id, request).await {
+                Ok(CdnFile::File(file)) => {
+                    let mut bytes = file.bytes;
+                    grammers_crypto::cdn::decrypt(
+                        &mut bytes,
+                        &cdn.encryption_key,
+                        &cdn.encryption_iv,
+                        part_offset,
+                    );
+                    cdn.hashes
+                        .verify(&self.client, cdn.origin_dc, part_offset, &bytes)
+                        .await?;
+                    cdn.part = (part_offset, bytes);
+                }
+                Ok(CdnFile::ReuploadNeeded(reupload)) => {
+                    let request = tl::functions::upload::ReuploadCdnFile {
+                        file_token: cdn.hashes.file_token.clone(),
+                        request_token: reupload.request_token,
+                    };
+                    let hashes = match cdn.origin_dc {
+                        None => self.client.invoke(&request).await?,
+                        Some(dc) => self.client.invoke_in_dc(&request, dc).await?,
+                    };
+                    cdn.hashes.extend(hashes);
+                    self.variant = DownloadIterVariant::Cdn(cdn);
+                    return Ok(ControlFlow::Continue(()));
+                }
+                Err(e) if e.is("FILE_TOKEN_INVALID") => {
+                    // The redirect expired, so request the file again to get a new one.
+                    self.variant = DownloadIterVariant::Request(cdn.request);
+                    return Ok(ControlFlow::Continue(()));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let (part_offset, part) = &cdn.part;
+        let start = ((offset - part_offset) as usize).min(part.len());
+        let end = (start + limit as usize).min(part.len());
+        let chunk = part[start..end].to_vec();
+        if chunk.len() < limit as usize {
+            self.done = true;
+            if chunk.is_empty() {
+                return Ok(ControlFlow::Break(None));
+            }
+        }
+
+        cdn.request.offset += limit;
+        self.variant = DownloadIterVariant::Cdn(cdn);
+        Ok(ControlFlow::Break(Some(chunk)))
+    }
 }
 
 /// Method implementations related to uploading or downloading files.
@@ -160,6 +485,7 @@ impl Client {
                 client: self.clone(),
                 done: false,
                 variant: DownloadIterVariant::PreDownloaded(data),
+                hasher: None,
             }
         } else if let Some(location) = downloadable.to_raw_input_location() {
             DownloadIter {
@@ -167,11 +493,13 @@ impl Client {
                 done: false,
                 variant: DownloadIterVariant::Request(tl::functions::upload::GetFile {
                     precise: false,
-                    cdn_supported: false,
+                    // CDN data centers can't be reached from the browser.
+                    cdn_supported: !cfg!(all(target_arch = "wasm32", target_os = "unknown")),
                     location,
                     offset: 0,
                     limit: MAX_CHUNK_SIZE,
                 }),
+                hasher: None,
             }
         } else {
             DownloadIter {
@@ -181,6 +509,7 @@ impl Client {
                     io::ErrorKind::Other,
                     "media not downloadable",
                 )),
+                hasher: None,
             }
         }
     }
@@ -222,18 +551,237 @@ impl Client {
         Client::load(path, &mut download).await
     }
 
+    /// Downloads the profile picture or chat photo of a chat into the specified path.
+    ///
+    /// If `big` is `true`, the larger version of the photo is downloaded (see [`Chat::photo`]).
+    /// Returns `false` if the chat has no photo, in which case nothing is written.
+    ///
+    /// If the file already exists, it will be overwritten.
+    ///
+    /// [`Chat::photo`]: crate::types::Chat::photo
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if client.download_chat_photo(&chat, "avatar.jpg", true).await? {
+    ///     println!("Saved the photo of {:?}", chat.name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     #[cfg(feature = "fs")]
-    async fn load<P: AsRef<Path>>(path: P, download: &mut DownloadIter) -> Result<(), io::Error> {
-        let mut file = fs::File::create(path).await?;
+    pub async fn download_chat_photo<P: AsRef<Path>>(
+        &self,
+        chat: &Chat,
+        path: P,
+        big: bool,
+    ) -> Result<bool, io::Error> {
+        match chat.photo(big) {
+            Some(photo) => self.download_media(&photo, path).await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Downloads the small profile photos of the given chats into the
+    /// [`InitParams::avatar_cache`], in the background.
+    ///
+    /// This is done automatically for the chats found in updates, but can be used to prefetch
+    /// the photos of other chats, such as those in the dialogs. Photos which the cache already
+    /// contains, or which are already being downloaded, are skipped.
+    ///
+    /// The photos are downloaded a few at a time by a task started when the client connected.
+    /// Does nothing if there is no cache, or if the client was not connected from within a
+    /// Tokio runtime. Photos are also skipped if too many are already waiting to be downloaded.
+    ///
+    /// [`InitParams::avatar_cache`]: crate::InitParams::avatar_cache
+    pub fn prefetch_avatars(&self, chats: &ChatMap) {
+        let (Some(cache), Some(tx)) = (
+            self.0.config.params.avatar_cache.as_ref(),
+            self.0.avatar_tx.as_ref(),
+        ) else {
+            return;
+        };
+
+        let missing = chats
+            .iter_chats()
+            .filter_map(|chat| {
+                let photo = chat.photo(false)?;
+                let photo_id = match &photo.raw {
+                    tl::enums::InputFileLocation::InputPeerPhotoFileLocation(location) => {
+                        location.photo_id
+                    }
+                    _ => return None,
+                };
+                (!cache.contains(chat.id(), photo_id)).then_some((chat.id(), photo_id, photo))
+            })
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return;
+        }
+
+        let mut state = self.0.state.write().unwrap();
+        for (chat_id, photo_id, photo) in missing {
+            if !state.avatar_downloads.insert(photo_id) {
+                continue;
+            }
+            if tx.try_send((chat_id, photo_id, photo)).is_err() {
+                state.avatar_downloads.remove(&photo_id);
+            }
+        }
+    }
+
+    /// Download the photos sent by [`Client::prefetch_avatars`] into the avatar cache, until
+    /// the client is dropped.
+    pub(crate) async fn avatar_worker(
+        client: Weak<ClientInner>,
+        rx: mpsc::Receiver<AvatarDownload>,
+    ) {
+        futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|download| (download, rx))
+        })
+        .for_each_concurrent(MAX_AVATAR_DOWNLOADS, |(chat_id, photo_id, photo)| {
+            let client = client.upgrade().map(Client);
+            async move {
+                let Some(client) = client else {
+                    return;
+                };
+                let Some(cache) = client.0.config.params.avatar_cache.clone() else {
+                    return;
+                };
+
+                let mut download = client.iter_download(&photo);
+                let mut data = Vec::new();
+                let result = loop {
+                    match download.next().await {
+                        Ok(Some(chunk)) => data.extend(chunk),
+                        Ok(None) => break Ok(()),
+                        Err(e) => break Err(e),
+                    }
+                };
+
+                client
+                    .0
+                    .state
+                    .write()
+                    .unwrap()
+                    .avatar_downloads
+                    .remove(&photo_id);
+                match result {
+                    Ok(()) => cache.store(chat_id, photo_id, data),
+                    Err(e) => log::debug!("failed to download the photo of chat {chat_id}: {e}"),
+                }
+            }
+        })
+        .await;
+    }
+
+    /// Downloads `len` bytes of a media file, starting at `offset`.
+    ///
+    /// This is useful to fetch only part of a file, for example, to inspect its header, or to
+    /// serve HTTP range requests. Fewer bytes are returned if the file ends before `len`.
+    ///
+    /// Telegram only allows fetching files in aligned chunks, so the whole chunks containing
+    /// the range are downloaded and then trimmed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(downloadable: grammers_client::types::Media, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let header = client.download_range(&downloadable, 0, 16).await?;
+    /// if header.starts_with(b"\x89PNG") {
+    ///     println!("This is a PNG image");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_range<D: Downloadable>(
+        &self,
+        downloadable: &D,
+        offset: u64,
+        len: usize,
+    ) -> Result<Vec<u8>, InvocationError> {
+        let chunk_size = MAX_CHUNK_SIZE as u64;
+        let mut download = self
+            .iter_download(downloadable)
+            .skip_chunks((offset / chunk_size) as i32);
+
+        // Pre-downloaded data ignores the offset and is returned whole.
+        let mut position = download
+            .variant
+            .request_mut()
+            .map(|request| request.offset as u64)
+            .unwrap_or(0);
+        let end = offset.saturating_add(len as u64);
+
+        // The length may be far larger than the file, so only reserve what's known to be needed
+        // (at most one chunk), and grow as data arrives.
+        let available = downloadable
+            .size()
+            .map_or(len, |size| size.saturating_sub(offset as usize));
+        let mut data = Vec::with_capacity(len.min(available).min(MAX_CHUNK_SIZE as usize));
+        while position < end {
+            let Some(chunk) = download.next().await? else {
+                break;
+            };
+            let chunk_end = position + chunk.len() as u64;
+            if chunk_end > offset {
+                let start = offset.saturating_sub(position) as usize;
+                let stop = (end.min(chunk_end) - position) as usize;
+                data.extend_from_slice(&chunk[start..stop]);
+            }
+            position = chunk_end;
+        }
+
+        Ok(data)
+    }
+
+    /// Downloads a media file into the given writer.
+    ///
+    /// Unlike [`Client::download_media`], this does not require the `fs` feature, and can be
+    /// used to send the file somewhere else (such as a socket) or to process it as it arrives
+    /// (for example, to hash or compress it) without going through a temporary file.
+    ///
+    /// The writer is flushed once the download completes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(downloadable: grammers_client::types::Media, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut buffer = Vec::new();
+    /// client.download_media_to_writer(&downloadable, &mut buffer).await?;
+    /// println!("Downloaded {} bytes", buffer.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_media_to_writer<D: Downloadable, W: AsyncWrite + Unpin>(
+        &self,
+        downloadable: &D,
+        writer: &mut W,
+    ) -> Result<(), io::Error> {
+        let mut download = self.iter_download(downloadable);
+        Client::write_download(&mut download, writer).await
+    }
+
+    async fn write_download<W: AsyncWrite + Unpin>(
+        download: &mut DownloadIter,
+        writer: &mut W,
+    ) -> Result<(), io::Error> {
         while let Some(chunk) = download
             .next()
             .await
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
         {
-            file.write_all(&chunk).await?;
+            writer.write_all(&chunk).await?;
         }
 
-        Ok(())
+        writer.flush().await
+    }
+
+    #[cfg(feature = "fs")]
+    async fn load<P: AsRef<Path>>(path: P, download: &mut DownloadIter) -> Result<(), io::Error> {
+        let mut file = fs::File::create(path).await?;
+        Client::write_download(download, &mut file).await
     }
 
     /// Downloads a `Document` to specified path using multiple connections
@@ -354,6 +902,13 @@ impl Client {
     /// If the input file name is empty, the non-empty dummy value "a" will be used instead.
     /// Because it has no extension, you may not be able to use the file in certain methods.
     ///
+    /// If an [`InitParams::upload_cache`] is configured, the hash of the contents is stored in
+    /// it once the upload completes.
+    ///
+    /// Files which need more parts than Telegram allows (3000, or 4000 for premium accounts)
+    /// with the configured [`InitParams::upload_options`] fail with an `InvalidInput` error
+    /// before anything is uploaded.
+    ///
     /// # Examples
     ///
     /// ```
@@ -371,11 +926,26 @@ impl Client {
     /// ```
     ///
     /// [`InputMessage`]: crate::types::InputMessage
+    /// [`InitParams::upload_cache`]: crate::InitParams::upload_cache
+    /// [`InitParams::upload_options`]: crate::InitParams::upload_options
     pub async fn upload_stream<S: AsyncRead + Unpin>(
         &self,
         stream: &mut S,
         size: usize,
         name: String,
+    ) -> Result<Uploaded, io::Error> {
+        self.upload_stream_with_sha256(stream, size, name, None)
+            .await
+    }
+
+    /// Upload the stream like [`Client::upload_stream`], without hashing its contents again for
+    /// the upload cache if their hash is already known.
+    async fn upload_stream_with_sha256<S: AsyncRead + Unpin>(
+        &self,
+        stream: &mut S,
+        size: usize,
+        name: String,
+        sha256: Option<[u8; 32]>,
     ) -> Result<Uploaded, io::Error> {
         let file_id = generate_random_id();
         let name = if name.is_empty() {
@@ -384,14 +954,32 @@ impl Client {
             name
         };
 
-        let big_file = size > BIG_FILE_SIZE;
-        let parts = PartStream::new(stream, size);
+        let cache = self.0.config.params.upload_cache.as_ref();
+        let options = self.0.config.params.upload_options;
+        let big_file = size > options.big_file_threshold;
+        let parts = PartStream::new(
+            stream,
+            size,
+            options.part_size,
+            cache.is_some() && sha256.is_none(),
+        );
         let total_parts = parts.total_parts();
 
+        // Only files between both limits need to know whether the account is premium.
+        if total_parts > MAX_PREMIUM_FILE_PARTS
+            || (total_parts > MAX_FILE_PARTS
+                && !self.get_me().await.map_err(io::Error::other)?.premium())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("file needs {total_parts} parts, more than the account may upload"),
+            ));
+        }
+
         if big_file {
             let parts = Arc::new(parts);
             let mut tasks = FuturesUnordered::new();
-            for _ in 0..WORKER_COUNT {
+            for _ in 0..options.parallel_parts {
                 let handle = self.clone();
                 let parts = Arc::clone(&parts);
                 let task = async move {
@@ -422,14 +1010,17 @@ impl Client {
                 res?;
             }
 
-            Ok(Uploaded::from_raw(
-                tl::types::InputFileBig {
-                    id: file_id,
-                    parts: total_parts,
-                    name,
-                }
-                .into(),
-            ))
+            let (uploaded, computed) = parts
+                .finish(Uploaded::from_raw(
+                    tl::types::InputFileBig {
+                        id: file_id,
+                        parts: total_parts,
+                        name,
+                    }
+                    .into(),
+                ))
+                .await;
+            Ok(remember_upload(cache, sha256.or(computed), uploaded))
         } else {
             let mut md5 = md5::Context::new();
             while let Some((part, bytes)) = parts.next_part().await? {
@@ -450,15 +1041,18 @@ impl Client {
                     ));
                 }
             }
-            Ok(Uploaded::from_raw(
-                tl::types::InputFile {
-                    id: file_id,
-                    parts: total_parts,
-                    name,
-                    md5_checksum: format!("{:x}", md5.compute()),
-                }
-                .into(),
-            ))
+            let (uploaded, computed) = parts
+                .finish(Uploaded::from_raw(
+                    tl::types::InputFile {
+                        id: file_id,
+                        parts: total_parts,
+                        name,
+                        md5_checksum: format!("{:x}", md5.compute()),
+                    }
+                    .into(),
+                ))
+                .await;
+            Ok(remember_upload(cache, sha256.or(computed), uploaded))
         }
     }
 
@@ -472,6 +1066,9 @@ impl Client {
     /// If you need more control over the uploaded data, such as performing only a partial upload
     /// or with a different name, use [`Client::upload_stream`] instead.
     ///
+    /// If an [`InitParams::upload_cache`] is configured, the file is hashed first, and a file
+    /// previously uploaded with the same contents is reused instead of uploading it again.
+    ///
     /// # Examples
     ///
     /// ```
@@ -486,6 +1083,7 @@ impl Client {
     /// ```
     ///
     /// [`InputMessage`]: crate::InputMessage
+    /// [`InitParams::upload_cache`]: crate::InitParams::upload_cache
     #[cfg(feature = "fs")]
     pub async fn upload_file<P: AsRef<Path>>(&self, path: P) -> Result<Uploaded, io::Error> {
         let path = path.as_ref();
@@ -498,29 +1096,145 @@ impl Client {
         // files, so it's fine to unwrap.
         let name = path.file_name().unwrap().to_string_lossy().to_string();
 
-        self.upload_stream(&mut file, size, name).await
+        let mut sha256 = None;
+        if let Some(cache) = self.0.config.params.upload_cache.as_ref() {
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0; MAX_CHUNK_SIZE as usize];
+            loop {
+                let n = file.read(&mut buffer).await?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+            }
+            let digest = hasher.finalize().into();
+            if let Some(uploaded) = cache.get(&digest) {
+                return Ok(uploaded.with_name(name));
+            }
+            file.seek(SeekFrom::Start(0)).await?;
+            sha256 = Some(digest);
+        }
+
+        self.upload_stream_with_sha256(&mut file, size, name, sha256)
+            .await
+    }
+
+    /// Uploads a file and sends it to the desired chat, returning the sent message.
+    ///
+    /// JPEG and PNG images are sent as photos, unless `force_document` is `true`. Everything
+    /// else is sent as a document, with the attributes detected from its contents, if the
+    /// `infer` feature is enabled.
+    ///
+    /// The `message` is used as the caption, along with any other options, such as the chat to
+    /// reply to. Any media it contained is replaced with the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client, some_vec: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::client::files::FileSource;
+    ///
+    /// client.send_file(&chat, "/home/username/photos/holidays.jpg", "Check this out!", false).await?;
+    ///
+    /// let file = FileSource::Bytes { data: some_vec, name: "report.pdf" };
+    /// client.send_file(&chat, file, "", true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_file<'a, C, F, M>(
+        &self,
+        chat: C,
+        file: F,
+        message: M,
+        force_document: bool,
+    ) -> Result<Message, SendFileError>
+    where
+        C: Into<PackedChat>,
+        F: Into<FileSource<'a>>,
+        M: Into<InputMessage>,
+    {
+        let uploaded = match file.into() {
+            #[cfg(feature = "fs")]
+            FileSource::Path(path) => self.upload_file(path).await?,
+            FileSource::Bytes { data, name } => {
+                let mut stream = std::io::Cursor::new(data);
+                self.upload_stream(&mut stream, data.len(), name.to_string())
+                    .await?
+            }
+        };
+
+        let message = message.into();
+        let message = if !force_document && uploaded.is_photo() {
+            message.photo(uploaded)
+        } else {
+            message.document(uploaded)
+        };
+
+        Ok(self.send_message(chat, message).await?)
     }
 }
 
+/// A file to send with [`Client::send_file`].
+pub enum FileSource<'a> {
+    /// A file on disk.
+    #[cfg(feature = "fs")]
+    Path(&'a Path),
+    /// The contents of a file in memory, and the name to give it.
+    ///
+    /// The name should at least have the right extension, since it's used to determine how
+    /// the file should be sent.
+    Bytes { data: &'a [u8], name: &'a str },
+}
+
+#[cfg(feature = "fs")]
+impl<'a, P: AsRef<Path> + ?Sized> From<&'a P> for FileSource<'a> {
+    fn from(path: &'a P) -> Self {
+        Self::Path(path.as_ref())
+    }
+}
+
+/// Store the uploaded file in the cache, if both the cache and the hash of its contents exist.
+fn remember_upload(
+    cache: Option<&Arc<dyn UploadCache>>,
+    sha256: Option<[u8; 32]>,
+    uploaded: Uploaded,
+) -> Uploaded {
+    if let (Some(cache), Some(sha256)) = (cache, sha256) {
+        cache.store(sha256, &uploaded);
+    }
+    uploaded
+}
+
 struct PartStreamInner<'a, S: AsyncRead + Unpin> {
     stream: &'a mut S,
     current_part: i32,
+    hasher: Option<Sha256>,
+    #[cfg(feature = "infer")]
+    info: Option<crate::infer::FileInfo>,
 }
 
 struct PartStream<'a, S: AsyncRead + Unpin> {
     inner: AsyncMutex<PartStreamInner<'a, S>>,
+    part_size: usize,
     total_parts: i32,
+    size: usize,
 }
 
 impl<'a, S: AsyncRead + Unpin> PartStream<'a, S> {
-    fn new(stream: &'a mut S, size: usize) -> Self {
-        let total_parts = ((size + MAX_CHUNK_SIZE as usize - 1) / MAX_CHUNK_SIZE as usize) as i32;
+    fn new(stream: &'a mut S, size: usize, part_size: i32, hash: bool) -> Self {
+        let part_size = part_size as usize;
+        let total_parts = size.div_ceil(part_size) as i32;
         Self {
             inner: AsyncMutex::new(PartStreamInner {
                 stream,
                 current_part: 0,
+                hasher: hash.then(Sha256::new),
+                #[cfg(feature = "infer")]
+                info: None,
             }),
+            part_size,
             total_parts,
+            size,
         }
     }
 
@@ -528,13 +1242,26 @@ impl<'a, S: AsyncRead + Unpin> PartStream<'a, S> {
         self.total_parts
     }
 
+    /// Attach the size and the information detected from the contents of the stream to the
+    /// uploaded file, and return the hash of the contents if it was requested.
+    async fn finish(&self, mut uploaded: Uploaded) -> (Uploaded, Option<[u8; 32]>) {
+        let mut lock = self.inner.lock().await;
+        uploaded.size = Some(self.size);
+        #[cfg(feature = "infer")]
+        {
+            uploaded.info = lock.info.take();
+        }
+        let sha256 = lock.hasher.take().map(|hasher| hasher.finalize().into());
+        (uploaded, sha256)
+    }
+
     async fn next_part(&self) -> Result<Option<(i32, Vec<u8>)>, io::Error> {
         let mut lock = self.inner.lock().await;
         if lock.current_part >= self.total_parts {
             return Ok(None);
         }
         let mut read = 0;
-        let mut buffer = vec![0; MAX_CHUNK_SIZE as usize];
+        let mut buffer = vec![0; self.part_size];
 
         while read != buffer.len() {
             let n = lock.stream.read(&mut buffer[read..]).await?;
@@ -557,8 +1284,56 @@ impl<'a, S: AsyncRead + Unpin> PartStream<'a, S> {
             buffer[..read].to_vec()
         };
 
+        // Parts are read in order while the lock is held, so the hash covers the whole stream.
+        if let Some(hasher) = lock.hasher.as_mut() {
+            hasher.update(&bytes);
+        }
+
+        // The first part is enough to detect the type of the file.
+        #[cfg(feature = "infer")]
+        if lock.current_part == 0 {
+            lock.info = Some(crate::infer::infer(&bytes, self.size));
+        }
+
         let res = Ok(Some((lock.current_part, bytes)));
         lock.current_part += 1;
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_hash(offset: i64, data: &[u8]) -> tl::enums::FileHash {
+        tl::types::FileHash {
+            offset,
+            limit: data.len() as i32,
+            hash: Sha256::digest(data).to_vec(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn cdn_parts_are_checked_against_their_hash() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let hashes = CdnHashes::new(
+            Vec::new(),
+            vec![file_hash(0, &data[..128]), file_hash(128, &data[128..])],
+        );
+
+        assert_eq!(hashes.check_part(0, &data), Ok(128));
+        assert_eq!(hashes.check_part(128, &data[128..]), Ok(128));
+
+        let mut corrupted = data.clone();
+        corrupted[200] ^= 1;
+        assert_eq!(
+            hashes.check_part(128, &corrupted[128..]),
+            Err(CdnHashMismatch { offset: 128 })
+        );
+        assert_eq!(
+            hashes.check_part(64, &data[64..]),
+            Err(CdnHashMismatch { offset: 64 })
+        );
+    }
+}
@@ -7,19 +7,31 @@
 // except according to those terms.
 
 //! Methods related to sending messages.
+use super::net;
+use crate::types::media::Document;
 use crate::types::message::EMPTY_MESSAGE;
-use crate::types::{InputReactions, IterBuffer, Message};
+use crate::types::{
+    AvailableReaction, EmojiKeywords, InputReactions, IterBuffer, Message, MessageEffect,
+};
 use crate::utils::{generate_random_id, generate_random_ids};
-use crate::{ChatMap, Client, InputMedia, types};
-use chrono::{DateTime, FixedOffset};
+use crate::{ChatMap, Client, InputMedia, types, utils};
+use chrono::{DateTime, FixedOffset, Utc};
+use futures_util::future::{Either, select};
+use grammers_mtproto::mtp::DeserializeError;
+use grammers_mtsender::utils::{sleep, sleep_until};
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::PackedChat;
-use grammers_tl_types as tl;
+use grammers_tl_types::{self as tl, Identifiable};
 use log::{Level, log_enabled, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::pin::pin;
+use std::time::Duration;
 use tl::enums::InputPeer;
+use tokio::sync::oneshot;
+use web_time::Instant;
 
-fn map_random_ids_to_messages(
+pub(crate) fn map_random_ids_to_messages(
     client: &Client,
     random_ids: &[i64],
     updates: tl::enums::Updates,
@@ -97,10 +109,10 @@ pub(crate) fn parse_mention_entities(
         .iter()
         .any(|e| matches!(e, tl::enums::MessageEntity::MentionName(_)))
     {
-        let state = client.0.state.read().unwrap();
+        let chat_hashes = client.0.chat_hashes.load();
         for entity in entities.iter_mut() {
             if let tl::enums::MessageEntity::MentionName(mention_name) = entity {
-                if let Some(packed_user) = state.chat_hashes.get(mention_name.user_id) {
+                if let Some(packed_user) = chat_hashes.get(mention_name.user_id) {
                     *entity = tl::types::InputMessageEntityMentionName {
                         offset: mention_name.offset,
                         length: mention_name.length,
@@ -116,6 +128,53 @@ pub(crate) fn parse_mention_entities(
 }
 
 const MAX_LIMIT: usize = 100;
+const MAX_POLL_VOTES_LIMIT: usize = 50;
+/// How many sticker sets are remembered by [`Client::get_sticker_set`].
+const MAX_CACHED_STICKER_SETS: usize = 64;
+/// How long to wait for a pending transcription to complete.
+const TRANSCRIPTION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// The error type which is returned when [`Client::transcribe_audio`] fails.
+#[derive(Debug)]
+pub enum TranscribeError {
+    /// The transcription could not be requested.
+    Invocation(InvocationError),
+    /// The transcription did not complete in time.
+    TimedOut,
+}
+
+impl std::error::Error for TranscribeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invocation(e) => Some(e),
+            Self::TimedOut => None,
+        }
+    }
+}
+
+impl fmt::Display for TranscribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invocation(e) => write!(f, "transcribe error: {e}"),
+            Self::TimedOut => write!(f, "transcribe error: timed out"),
+        }
+    }
+}
+
+impl From<InvocationError> for TranscribeError {
+    fn from(error: InvocationError) -> Self {
+        Self::Invocation(error)
+    }
+}
+
+/// Whether a chunk of results paginated by `offset_rate` was the last one.
+///
+/// Can't rely on `count(messages) < limit` as the stop condition, because Telegram often returns
+/// short pages even when there are more results. See
+/// https://github.com/LonamiWebs/Telethon/issues/3949 for more.
+fn is_last_rate_chunk(next_rate: Option<i32>, count: usize) -> bool {
+    next_rate.is_none() || count == 0
+}
 
 impl<R: tl::RemoteCall<Return = tl::enums::messages::Messages>> IterBuffer<R, Message> {
     /// Fetches the total unless cached.
@@ -142,6 +201,23 @@ impl<R: tl::RemoteCall<Return = tl::enums::messages::Messages>> IterBuffer<R, Me
     ///
     /// The `request.limit` should be set to the right value before calling this method.
     async fn fill_buffer(&mut self, limit: i32) -> Result<Option<i32>, InvocationError> {
+        self.fetch_chunk(limit).await.map(|(rate, _)| rate)
+    }
+
+    /// Like `fill_buffer`, but for results which come from different chats.
+    ///
+    /// Message identifiers from different chats can't be compared, so the check done by
+    /// `fill_buffer` doesn't apply. Telegram will only include the `next_rate` to use as the
+    /// offset if there are more results.
+    async fn fill_buffer_by_rate(&mut self, limit: i32) -> Result<Option<i32>, InvocationError> {
+        let (rate, count) = self.fetch_chunk(limit).await?;
+        self.last_chunk = is_last_rate_chunk(rate, count);
+        Ok(rate)
+    }
+
+    /// Performs the network call and fills the buffer, returning the `offset_rate` if any, and
+    /// how many messages the response contained (including those which were skipped).
+    async fn fetch_chunk(&mut self, limit: i32) -> Result<(Option<i32>, usize), InvocationError> {
         use tl::enums::messages::Messages;
 
         let (messages, users, chats, rate) = match self.client.invoke(&self.request).await? {
@@ -171,14 +247,11 @@ impl<R: tl::RemoteCall<Return = tl::enums::messages::Messages>> IterBuffer<R, Me
             }
         };
 
-        {
-            let mut state = self.client.0.state.write().unwrap();
-            // Telegram can return peers without hash (e.g. Users with 'min: true')
-            let _ = state.chat_hashes.extend(&users, &chats);
-        }
+        self.client.0.chat_hashes.extend(&users, &chats);
 
         let chats = ChatMap::new(users, chats);
 
+        let count = messages.len();
         let client = self.client.clone();
         self.buffer.extend(
             messages
@@ -186,7 +259,7 @@ impl<R: tl::RemoteCall<Return = tl::enums::messages::Messages>> IterBuffer<R, Me
                 .flat_map(|message| Message::from_raw(&client, message, &chats)),
         );
 
-        Ok(rate)
+        Ok((rate, count))
     }
 }
 
@@ -251,6 +324,158 @@ impl MessageIter {
     }
 }
 
+impl MessageIter {
+    /// Translate the text of the messages into the language with the given ISO 639-1 code
+    /// (such as `"en"`) as they are fetched.
+    ///
+    /// Each page of messages is translated with a single request. See
+    /// [`Client::translate_messages`] for the requirements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut messages = client.iter_messages(&chat).limit(50).translated("en");
+    ///
+    /// while let Some((message, translation)) = messages.next().await? {
+    ///     if let Some(translation) = translation {
+    ///         println!("{} -> {}", message.text(), translation.text);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn translated(self, to_lang: &str) -> TranslatedMessageIter {
+        TranslatedMessageIter {
+            inner: self,
+            to_lang: to_lang.to_string(),
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+/// Iterator over messages along with their translation. See [`MessageIter::translated`].
+pub struct TranslatedMessageIter {
+    inner: MessageIter,
+    to_lang: String,
+    buffer: VecDeque<(Message, Option<tl::types::TextWithEntities>)>,
+}
+
+impl TranslatedMessageIter {
+    /// Return the next `Message` along with its translation, fetching and translating the next
+    /// page of messages if needed.
+    ///
+    /// Messages without text have no translation.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no messages left.
+    pub async fn next(
+        &mut self,
+    ) -> Result<Option<(Message, Option<tl::types::TextWithEntities>)>, InvocationError> {
+        if let Some(item) = self.buffer.pop_front() {
+            return Ok(Some(item));
+        }
+
+        // The first message may need to fetch a new page, and the rest are already buffered.
+        let Some(first) = self.inner.next().await? else {
+            return Ok(None);
+        };
+        let mut page = vec![first];
+        while !self.inner.buffer.is_empty() {
+            match self.inner.next().await? {
+                Some(message) => page.push(message),
+                None => break,
+            }
+        }
+
+        let message_ids = page
+            .iter()
+            .filter(|message| !message.text().is_empty())
+            .map(|message| message.id())
+            .collect::<Vec<_>>();
+        let mut translations = if message_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.inner
+                .client
+                .translate_messages(page[0].chat(), &message_ids, &self.to_lang)
+                .await?
+        }
+        .into_iter();
+
+        self.buffer.extend(page.into_iter().map(|message| {
+            let translation = if message.text().is_empty() {
+                None
+            } else {
+                translations.next()
+            };
+            (message, translation)
+        }));
+        Ok(self.buffer.pop_front())
+    }
+}
+
+pub type TopicMessageIter = IterBuffer<tl::functions::messages::GetReplies, Message>;
+
+impl TopicMessageIter {
+    fn new(client: &Client, peer: PackedChat, topic_id: i32) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::messages::GetReplies {
+                peer: peer.to_input_peer(),
+                msg_id: topic_id,
+                offset_id: 0,
+                offset_date: 0,
+                add_offset: 0,
+                limit: 0,
+                max_id: 0,
+                min_id: 0,
+                hash: 0,
+            },
+        )
+    }
+
+    pub fn offset_id(mut self, offset: i32) -> Self {
+        self.request.offset_id = offset;
+        self
+    }
+
+    pub fn max_date(mut self, offset: i32) -> Self {
+        self.request.offset_date = offset;
+        self
+    }
+
+    /// Determines how many messages there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        self.request.limit = 1;
+        self.get_total().await
+    }
+
+    /// Return the next `Message` from the internal buffer, filling the buffer previously if it's
+    /// empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no messages left.
+    pub async fn next(&mut self) -> Result<Option<Message>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        self.fill_buffer(self.request.limit).await?;
+
+        // Don't bother updating offsets if this is the last time stuff has to be fetched.
+        if !self.last_chunk && !self.buffer.is_empty() {
+            let last = &self.buffer[self.buffer.len() - 1];
+            self.request.offset_id = last.raw.id;
+            self.request.offset_date = last.raw.date;
+        }
+
+        Ok(self.pop_item())
+    }
+}
+
 pub type SearchIter = IterBuffer<tl::functions::messages::Search, Message>;
 
 impl SearchIter {
@@ -419,6 +644,69 @@ impl GlobalSearchIter {
         self
     }
 
+    /// Returns only messages with date bigger than date_time.
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use chrono::DateTime;
+    ///
+    /// // Search messages sent after Jan 1st, 2021
+    /// let min_date = DateTime::parse_from_rfc3339("2021-01-01T00:00:00-00:00").unwrap();
+    ///
+    /// let mut messages = client.search_global("grammers").min_date(&min_date);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn min_date(mut self, date_time: &DateTime<FixedOffset>) -> Self {
+        self.request.min_date = date_time.timestamp() as i32;
+        self
+    }
+
+    /// Returns only messages with date smaller than date_time
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use chrono::DateTime;
+    ///
+    /// // Search messages sent before Dec, 25th 2022
+    /// let max_date = DateTime::parse_from_rfc3339("2022-12-25T00:00:00-00:00").unwrap();
+    ///
+    /// let mut messages = client.search_global("grammers").max_date(&max_date);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn max_date(mut self, date_time: &DateTime<FixedOffset>) -> Self {
+        self.request.max_date = date_time.timestamp() as i32;
+        self
+    }
+
+    /// Restricts results to messages posted in broadcast channels.
+    pub fn broadcasts_only(mut self) -> Self {
+        self.request.broadcasts_only = true;
+        self
+    }
+
+    /// Restricts results to messages sent in groups and megagroups.
+    pub fn groups_only(mut self) -> Self {
+        self.request.groups_only = true;
+        self
+    }
+
+    /// Restricts results to messages sent in private conversations.
+    pub fn users_only(mut self) -> Self {
+        self.request.users_only = true;
+        self
+    }
+
+    /// Restricts results to the chats in the given folder (`0` for the main list, `1` for the
+    /// archive).
+    pub fn folder_id(mut self, folder_id: i32) -> Self {
+        self.request.folder_id = Some(folder_id);
+        self
+    }
+
     /// Determines how many messages there are in total.
     ///
     /// This only performs a network call if `next` has not been called before.
@@ -437,7 +725,7 @@ impl GlobalSearchIter {
         }
 
         self.request.limit = self.determine_limit(MAX_LIMIT);
-        let offset_rate = self.fill_buffer(self.request.limit).await?;
+        let offset_rate = self.fill_buffer_by_rate(self.request.limit).await?;
 
         // Don't bother updating offsets if this is the last time stuff has to be fetched.
         if !self.last_chunk && !self.buffer.is_empty() {
@@ -451,6 +739,331 @@ impl GlobalSearchIter {
     }
 }
 
+pub type PostSearchIter = IterBuffer<tl::functions::channels::SearchPosts, Message>;
+
+impl PostSearchIter {
+    fn new(client: &Client, hashtag: &str) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::channels::SearchPosts {
+                hashtag: hashtag.strip_prefix('#').unwrap_or(hashtag).to_string(),
+                offset_rate: 0,
+                offset_peer: tl::enums::InputPeer::Empty,
+                offset_id: 0,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Determines how many messages there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        self.request.limit = 1;
+        self.get_total().await
+    }
+
+    /// Return the next `Message` from the internal buffer, filling the buffer previously if it's
+    /// empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no messages left.
+    pub async fn next(&mut self) -> Result<Option<Message>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let offset_rate = self.fill_buffer_by_rate(self.request.limit).await?;
+
+        // Don't bother updating offsets if this is the last time stuff has to be fetched.
+        if !self.last_chunk && !self.buffer.is_empty() {
+            let last = &self.buffer[self.buffer.len() - 1];
+            self.request.offset_rate = offset_rate.unwrap_or(0);
+            self.request.offset_peer = last.chat().pack().to_input_peer();
+            self.request.offset_id = last.raw.id;
+        }
+
+        Ok(self.pop_item())
+    }
+}
+
+/// A vote cast in a poll, as returned by [`Client::iter_poll_voters`].
+#[derive(Clone, Debug)]
+pub struct PollVote {
+    /// Who cast the vote.
+    pub voter: types::Chat,
+    /// The options which were voted for.
+    pub options: Vec<Vec<u8>>,
+    /// When the vote was cast.
+    pub date: DateTime<Utc>,
+}
+
+pub type PollVoteIter = IterBuffer<tl::functions::messages::GetPollVotes, PollVote>;
+
+impl PollVoteIter {
+    fn new(client: &Client, chat: PackedChat, message_id: i32, option: Option<Vec<u8>>) -> Self {
+        Self::from_request(
+            client,
+            MAX_POLL_VOTES_LIMIT,
+            tl::functions::messages::GetPollVotes {
+                peer: chat.to_input_peer(),
+                id: message_id,
+                option,
+                offset: None,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Determines how many votes there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::messages::VotesList::List(list) = self.client.invoke(&self.request).await?;
+        let total = list.count as usize;
+        self.total = Some(total);
+        Ok(total)
+    }
+
+    /// Return the next `PollVote` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no votes left.
+    pub async fn next(&mut self) -> Result<Option<PollVote>, InvocationError> {
+        use tl::enums::MessagePeerVote as V;
+
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_POLL_VOTES_LIMIT);
+        let tl::enums::messages::VotesList::List(list) = self.client.invoke(&self.request).await?;
+
+        self.total = Some(list.count as usize);
+        match list.next_offset {
+            Some(offset) => self.request.offset = Some(offset),
+            None => self.last_chunk = true,
+        }
+
+        self.client.0.chat_hashes.extend(&list.users, &list.chats);
+
+        let chats = ChatMap::new(list.users, list.chats);
+        let requested = self.request.option.clone();
+        self.buffer.extend(list.votes.into_iter().map(|vote| {
+            let (peer, options, date) = match vote {
+                V::Vote(v) => (v.peer, vec![v.option], v.date),
+                V::InputOption(v) => (v.peer, requested.clone().into_iter().collect(), v.date),
+                V::Multiple(v) => (v.peer, v.options, v.date),
+            };
+            PollVote {
+                voter: utils::always_find_entity(&peer, &chats, &self.client),
+                options,
+                date: utils::date(date),
+            }
+        }));
+
+        Ok(self.pop_item())
+    }
+}
+
+/// How many times a broadcast will retry sending to the same chat after a flood error.
+const MAX_BROADCAST_RETRIES: usize = 3;
+
+enum BroadcastContent {
+    Message(Box<types::InputMessage>),
+    Forward {
+        source: PackedChat,
+        message_id: i32,
+        drop_author: bool,
+    },
+}
+
+/// A message to be sent to many chats, one after another.
+///
+/// Created with [`Client::broadcast`] or [`Client::broadcast_forward`], and started with
+/// [`Broadcast::send`].
+pub struct Broadcast {
+    client: Client,
+    chats: Vec<PackedChat>,
+    content: BroadcastContent,
+    delay: Duration,
+    max_flood_wait: Duration,
+}
+
+/// The outcome of a [`Broadcast`].
+#[derive(Debug, Default)]
+pub struct BroadcastReport {
+    /// The chats where the message was delivered, in order, along with the message which was
+    /// sent to them (if it could be found in the response).
+    pub sent: Vec<(PackedChat, Option<Message>)>,
+    /// The chats where the message could not be delivered, along with the error which occurred.
+    pub failed: Vec<(PackedChat, InvocationError)>,
+}
+
+impl Broadcast {
+    fn new(client: &Client, chats: Vec<PackedChat>, content: BroadcastContent) -> Self {
+        Self {
+            client: client.clone(),
+            chats,
+            content,
+            delay: Duration::from_millis(50),
+            max_flood_wait: Duration::from_secs(300),
+        }
+    }
+
+    /// How long to wait between messages sent to different chats.
+    ///
+    /// Telegram limits how many messages can be sent per second, so sending them too quickly
+    /// results in flood errors. By default, the delay is 50 milliseconds (about 20 messages per
+    /// second), which should be fine for bots. User accounts should use a much larger delay.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// The longest flood wait which will be slept on before retrying the same chat.
+    ///
+    /// Chats for which a longer wait is requested are considered failed, and the broadcast
+    /// continues with the next chat. By default, waits of up to five minutes are slept on.
+    ///
+    /// This replaces [`InitParams::flood_sleep_threshold`] while broadcasting, so that flood
+    /// waits are only slept on once.
+    ///
+    /// [`InitParams::flood_sleep_threshold`]: crate::InitParams::flood_sleep_threshold
+    pub fn max_flood_wait(mut self, max_flood_wait: Duration) -> Self {
+        self.max_flood_wait = max_flood_wait;
+        self
+    }
+
+    /// When forwarding, whether to hide the original author of the message, so that it looks
+    /// like a copy sent by the logged-in account rather than a forward. Has no effect on
+    /// broadcasts created with [`Client::broadcast`], which always send a new message.
+    pub fn drop_author(mut self, drop_author: bool) -> Self {
+        if let BroadcastContent::Forward {
+            drop_author: ref mut drop,
+            ..
+        } = self.content
+        {
+            *drop = drop_author;
+        }
+        self
+    }
+
+    /// Send the message to every chat, and report which ones succeeded and which ones failed.
+    ///
+    /// Errors do not stop the broadcast. They are collected in the report instead.
+    pub async fn send(self) -> BroadcastReport {
+        let mut report = BroadcastReport::default();
+
+        for (i, chat) in self.chats.iter().copied().enumerate() {
+            if i != 0 && !self.delay.is_zero() {
+                sleep(self.delay).await;
+            }
+
+            let mut retries = 0;
+            loop {
+                match net::without_flood_sleep(self.send_to(chat)).await {
+                    Ok(message) => {
+                        report.sent.push((chat, message));
+                        break;
+                    }
+                    Err(InvocationError::Rpc(err))
+                        if err.code == 420
+                            && retries < MAX_BROADCAST_RETRIES
+                            && err.value.is_some_and(|seconds| {
+                                Duration::from_secs(seconds as _) <= self.max_flood_wait
+                            }) =>
+                    {
+                        let delay = Duration::from_secs(err.value.unwrap() as _);
+                        log::info!("sleeping on {} for {:?} during broadcast", err.name, delay);
+                        sleep(delay).await;
+                        retries += 1;
+                    }
+                    Err(e) => {
+                        report.failed.push((chat, e));
+                        break;
+                    }
+                }
+            }
+        }
+
+        report
+    }
+
+    async fn send_to(&self, chat: PackedChat) -> Result<Option<Message>, InvocationError> {
+        match &self.content {
+            BroadcastContent::Message(message) => self
+                .client
+                .send_message(chat, types::InputMessage::clone(message))
+                .await
+                .map(Some),
+            BroadcastContent::Forward {
+                source,
+                message_id,
+                drop_author,
+            } => {
+                let request = tl::functions::messages::ForwardMessages {
+                    silent: false,
+                    background: false,
+                    with_my_score: false,
+                    drop_author: *drop_author,
+                    drop_media_captions: false,
+                    from_peer: source.to_input_peer(),
+                    id: vec![*message_id],
+                    random_id: vec![generate_random_id()],
+                    to_peer: chat.to_input_peer(),
+                    top_msg_id: None,
+                    schedule_date: None,
+                    send_as: None,
+                    noforwards: false,
+                    quick_reply_shortcut: None,
+                    allow_paid_floodskip: false,
+                    video_timestamp: None,
+                };
+                let result = self.client.invoke(&request).await?;
+                Ok(
+                    map_random_ids_to_messages(&self.client, &request.random_id, result)
+                        .pop()
+                        .flatten(),
+                )
+            }
+        }
+    }
+}
+
+fn input_reply_to(
+    chat: PackedChat,
+    message: &types::InputMessage,
+) -> Option<tl::enums::InputReplyTo> {
+    if let Some(story_id) = message.reply_to_story {
+        return Some(
+            tl::types::InputReplyToStory {
+                peer: chat.to_input_peer(),
+                story_id,
+            }
+            .into(),
+        );
+    }
+
+    message.reply_to.map(|reply_to_msg_id| {
+        tl::types::InputReplyToMessage {
+            reply_to_msg_id,
+            top_msg_id: None,
+            reply_to_peer_id: None,
+            quote_text: None,
+            quote_entities: None,
+            quote_offset: None,
+        }
+        .into()
+    })
+}
+
 /// Method implementations related to sending, modifying or getting messages.
 impl Client {
     /// Sends a message to the desired chat.
@@ -458,7 +1071,7 @@ impl Client {
     /// This method can also be used to send media such as photos, videos, documents, polls, etc.
     ///
     /// If you want to send a local file as media, you will need to use
-    /// [`Client::upload_file`] first.
+    /// [`Client::upload_file`] first, or use [`Client::send_file`] to do both at once.
     ///
     /// Refer to [`InputMessage`] to learn more formatting options, such as using markdown or
     /// adding buttons under your message (if you're logged in as a bot).
@@ -494,17 +1107,7 @@ impl Client {
                 background: message.background,
                 clear_draft: message.clear_draft,
                 peer: chat.to_input_peer(),
-                reply_to: message.reply_to.map(|reply_to_msg_id| {
-                    tl::types::InputReplyToMessage {
-                        reply_to_msg_id,
-                        top_msg_id: None,
-                        reply_to_peer_id: None,
-                        quote_text: None,
-                        quote_entities: None,
-                        quote_offset: None,
-                    }
-                    .into()
-                }),
+                reply_to: input_reply_to(chat, &message),
                 media,
                 message: message.text.clone(),
                 random_id,
@@ -527,17 +1130,7 @@ impl Client {
                 background: message.background,
                 clear_draft: message.clear_draft,
                 peer: chat.to_input_peer(),
-                reply_to: message.reply_to.map(|reply_to_msg_id| {
-                    tl::types::InputReplyToMessage {
-                        reply_to_msg_id,
-                        top_msg_id: None,
-                        reply_to_peer_id: None,
-                        quote_text: None,
-                        quote_entities: None,
-                        quote_offset: None,
-                    }
-                    .into()
-                }),
+                reply_to: input_reply_to(chat, &message),
                 message: message.text.clone(),
                 random_id,
                 reply_markup: message.reply_markup.clone(),
@@ -584,12 +1177,43 @@ impl Client {
         })
     }
 
+    /// Sends a message to the desired chat, splitting it into multiple messages if it's too long.
+    ///
+    /// See [`InputMessage::split_long`] for details on how the message is split.
+    ///
+    /// Returns the messages that were sent, in order.
+    ///
+    /// [`InputMessage::split_long`]: crate::InputMessage::split_long
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let long_text = "Lorem ipsum. ".repeat(1000);
+    /// let messages = client.send_long_message(&chat, long_text).await?;
+    /// println!("Sent {} messages", messages.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_long_message<C: Into<PackedChat>, M: Into<types::InputMessage>>(
+        &self,
+        chat: C,
+        message: M,
+    ) -> Result<Vec<Message>, InvocationError> {
+        let chat = chat.into();
+        let mut messages = Vec::new();
+        for part in message.into().split_long() {
+            messages.push(self.send_message(chat, part).await?);
+        }
+        Ok(messages)
+    }
+
     /// Sends a album to the desired chat.
     ///
     /// This method can also be used to send a bunch of media such as photos, videos, documents, polls, etc.
     ///
     /// If you want to send a local file as media, you will need to use
-    /// [`Client::upload_file`] first.
+    /// [`Client::upload_file`] first, or use [`Client::send_file`] to do both at once.
     ///
     /// Refer to [`InputMedia`] to learn more formatting options, such as using markdown.
     ///
@@ -811,33 +1435,152 @@ impl Client {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn forward_messages<C: Into<PackedChat>, S: Into<PackedChat>>(
+    pub async fn forward_messages<C: Into<PackedChat>, S: Into<PackedChat>>(
+        &self,
+        destination: C,
+        message_ids: &[i32],
+        source: S,
+    ) -> Result<Vec<Option<Message>>, InvocationError> {
+        // TODO let user customize more options
+        let request = tl::functions::messages::ForwardMessages {
+            silent: false,
+            background: false,
+            with_my_score: false,
+            drop_author: false,
+            drop_media_captions: false,
+            from_peer: source.into().to_input_peer(),
+            id: message_ids.to_vec(),
+            random_id: generate_random_ids(message_ids.len()),
+            to_peer: destination.into().to_input_peer(),
+            top_msg_id: None,
+            schedule_date: None,
+            send_as: None,
+            noforwards: false,
+            quick_reply_shortcut: None,
+            allow_paid_floodskip: false,
+            video_timestamp: None,
+        };
+        let result = self.invoke(&request).await?;
+        Ok(map_random_ids_to_messages(self, &request.random_id, result))
+    }
+
+    /// Prepares a broadcast of the same message to many chats.
+    ///
+    /// The message is sent to each chat in turn, waiting a bit in between to avoid hitting
+    /// the rate limits, and retrying when a flood error occurs anyway. Errors for a given chat
+    /// (for example, because the bot was blocked) are collected in the report instead of
+    /// stopping the broadcast.
+    ///
+    /// See [`Broadcast`] for the available options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chats: Vec<grammers_client::types::Chat>, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    ///
+    /// let report = client
+    ///     .broadcast(&chats, "Our weekly newsletter is out!")
+    ///     .delay(Duration::from_millis(100))
+    ///     .send()
+    ///     .await;
+    ///
+    /// for (chat, error) in report.failed {
+    ///     println!("Failed to send to {}: {}", chat.id, error);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn broadcast<I, C, M>(&self, chats: I, message: M) -> Broadcast
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<PackedChat>,
+        M: Into<types::InputMessage>,
+    {
+        Broadcast::new(
+            self,
+            chats.into_iter().map(Into::into).collect(),
+            BroadcastContent::Message(Box::new(message.into())),
+        )
+    }
+
+    /// Prepares a broadcast which forwards an existing message to many chats.
+    ///
+    /// This works like [`Client::broadcast`], except that the message in `source` with the given
+    /// identifier is forwarded. Use [`Broadcast::drop_author`] to send copies instead of
+    /// forwards, which do not show the original author.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chats: Vec<grammers_client::types::Chat>, message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let report = client
+    ///     .broadcast_forward(&chats, &message.chat(), message.id())
+    ///     .drop_author(true)
+    ///     .send()
+    ///     .await;
+    ///
+    /// println!("Copied the message to {} chats", report.sent.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn broadcast_forward<I, C, S>(&self, chats: I, source: S, message_id: i32) -> Broadcast
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<PackedChat>,
+        S: Into<PackedChat>,
+    {
+        Broadcast::new(
+            self,
+            chats.into_iter().map(Into::into).collect(),
+            BroadcastContent::Forward {
+                source: source.into(),
+                message_id,
+                drop_author: false,
+            },
+        )
+    }
+
+    /// Translates the text of several messages from the same chat at once, into the language
+    /// with the given ISO 639-1 code (such as `"en"`).
+    ///
+    /// Returns the translated texts, along with their formatting entities, in the same order as
+    /// the message identifiers. Translating requires Telegram Premium, unless the chat is a
+    /// channel with translations enabled.
+    ///
+    /// To translate the messages as they are fetched, see [`MessageIter::translated`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let translated = client.translate_messages(message.chat(), &[message.id()], "en").await?;
+    /// for text in translated {
+    ///     println!("{} ({} entities)", text.text, text.entities.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn translate_messages<C: Into<PackedChat>>(
         &self,
-        destination: C,
+        chat: C,
         message_ids: &[i32],
-        source: S,
-    ) -> Result<Vec<Option<Message>>, InvocationError> {
-        // TODO let user customize more options
-        let request = tl::functions::messages::ForwardMessages {
-            silent: false,
-            background: false,
-            with_my_score: false,
-            drop_author: false,
-            drop_media_captions: false,
-            from_peer: source.into().to_input_peer(),
-            id: message_ids.to_vec(),
-            random_id: generate_random_ids(message_ids.len()),
-            to_peer: destination.into().to_input_peer(),
-            top_msg_id: None,
-            schedule_date: None,
-            send_as: None,
-            noforwards: false,
-            quick_reply_shortcut: None,
-            allow_paid_floodskip: false,
-            video_timestamp: None,
-        };
-        let result = self.invoke(&request).await?;
-        Ok(map_random_ids_to_messages(self, &request.random_id, result))
+        to_lang: &str,
+    ) -> Result<Vec<tl::types::TextWithEntities>, InvocationError> {
+        let tl::enums::messages::TranslatedText::TranslateResult(result) = self
+            .invoke(&tl::functions::messages::TranslateText {
+                peer: Some(chat.into().to_input_peer()),
+                id: Some(message_ids.to_vec()),
+                text: None,
+                to_lang: to_lang.to_string(),
+            })
+            .await?;
+
+        Ok(result
+            .result
+            .into_iter()
+            .map(|tl::enums::TextWithEntities::Entities(text)| text)
+            .collect())
     }
 
     /// Gets the [`Message`] to which the input message is replying to.
@@ -938,6 +1681,33 @@ impl Client {
         MessageIter::new(self, chat.into())
     }
 
+    /// Iterate over the messages in a topic of a forum, from most recent to oldest.
+    ///
+    /// The `topic_id` is the identifier of the message which created the topic. Only the
+    /// messages in that topic are returned, as Telegram itself determines which messages
+    /// belong to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let topic_id = 123;
+    /// let mut messages = client.iter_topic_messages(&chat, topic_id).limit(100);
+    ///
+    /// while let Some(message) = messages.next().await? {
+    ///     println!("{}", message.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_topic_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_id: i32,
+    ) -> TopicMessageIter {
+        TopicMessageIter::new(self, chat.into(), topic_id)
+    }
+
     /// Iterate over the messages that match certain search criteria.
     ///
     /// This allows you to search by text within a chat or filter by media among other things.
@@ -982,6 +1752,49 @@ impl Client {
         GlobalSearchIter::new(self)
     }
 
+    /// Iterate over the messages containing the given query in all the chats the logged-in
+    /// account is in.
+    ///
+    /// Shorthand for `search_all_messages().query(query)`. Refer to [`GlobalSearchIter`] to
+    /// restrict the search by date or type of chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut messages = client.search_global("grammers").broadcasts_only();
+    ///
+    /// while let Some(message) = messages.next().await? {
+    ///     println!("{}", message.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_global(&self, query: &str) -> GlobalSearchIter {
+        GlobalSearchIter::new(self).query(query)
+    }
+
+    /// Iterate over the posts in public channels containing the given hashtag or cashtag.
+    ///
+    /// The leading `#` of the hashtag is optional. Unlike [`Client::search_global`], the search
+    /// is not limited to the chats the logged-in account is in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut posts = client.search_posts("#rustlang");
+    ///
+    /// while let Some(post) = posts.next().await? {
+    ///     println!("{:?}: {}", post.chat().name(), post.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_posts(&self, hashtag: &str) -> PostSearchIter {
+        PostSearchIter::new(self, hashtag)
+    }
+
     /// Get up to 100 messages using their ID.
     ///
     /// Returns the new retrieved messages in a list. Those messages that could not be retrieved
@@ -1040,6 +1853,54 @@ impl Client {
         Ok(message_ids.iter().map(|id| map.remove(id)).collect())
     }
 
+    /// Get the messages scheduled to be sent in a chat.
+    ///
+    /// Messages scheduled to be sent once the person comes online will have
+    /// [`Message::is_scheduled_when_online`] return `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for message in client.get_scheduled_messages(&chat).await? {
+    ///     if message.is_scheduled_when_online() {
+    ///         println!("Will be sent once online: {}", message.text());
+    ///     } else {
+    ///         println!("Will be sent on {}: {}", message.date(), message.text());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_scheduled_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Vec<Message>, InvocationError> {
+        let result = self
+            .invoke(&tl::functions::messages::GetScheduledHistory {
+                peer: chat.into().to_input_peer(),
+                hash: 0,
+            })
+            .await?;
+
+        let (messages, users, chats) = match result {
+            tl::enums::messages::Messages::Messages(m) => (m.messages, m.users, m.chats),
+            tl::enums::messages::Messages::Slice(m) => (m.messages, m.users, m.chats),
+            tl::enums::messages::Messages::ChannelMessages(m) => (m.messages, m.users, m.chats),
+            tl::enums::messages::Messages::NotModified(_) => {
+                panic!("API returned Messages::NotModified even though hash = 0")
+            }
+        };
+
+        self.0.chat_hashes.extend(&users, &chats);
+
+        let chats = ChatMap::new(users, chats);
+        Ok(messages
+            .into_iter()
+            .flat_map(|m| Message::from_raw(self, m, &chats))
+            .collect())
+    }
+
     /// Get the latest pin from a chat.
     ///
     /// # Examples
@@ -1226,4 +2087,346 @@ impl Client {
 
         Ok(())
     }
+
+    /// Fetch the reactions which can be used on messages, along with their animations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for reaction in client.get_available_reactions().await? {
+    ///     let animation = reaction.activate_animation();
+    ///     println!("{} ({}) is {} bytes", reaction.title(), reaction.reaction(), animation.size());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_available_reactions(&self) -> Result<Vec<AvailableReaction>, InvocationError> {
+        match self
+            .invoke(&tl::functions::messages::GetAvailableReactions { hash: 0 })
+            .await?
+        {
+            tl::enums::messages::AvailableReactions::Reactions(reactions) => Ok(reactions
+                .reactions
+                .into_iter()
+                .map(AvailableReaction::from_raw)
+                .collect()),
+            tl::enums::messages::AvailableReactions::NotModified => {
+                panic!("API returned AvailableReactions::NotModified even though hash = 0")
+            }
+        }
+    }
+
+    /// Fetch the animated effects which can be shown when sending messages to private chats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for effect in client.get_available_effects().await? {
+    ///     if let Some(animation) = effect.animation() {
+    ///         let path = format!("{}.json", effect.id());
+    ///         client.download_media(animation, path).await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_available_effects(&self) -> Result<Vec<MessageEffect>, InvocationError> {
+        match self
+            .invoke(&tl::functions::messages::GetAvailableEffects { hash: 0 })
+            .await?
+        {
+            tl::enums::messages::AvailableEffects::Effects(effects) => {
+                let documents = effects
+                    .documents
+                    .into_iter()
+                    .map(Document::from_raw)
+                    .collect::<Vec<_>>();
+                Ok(effects
+                    .effects
+                    .into_iter()
+                    .map(|tl::enums::AvailableEffect::Effect(effect)| {
+                        MessageEffect::from_raw(effect, &documents)
+                    })
+                    .collect())
+            }
+            tl::enums::messages::AvailableEffects::NotModified => {
+                panic!("API returned AvailableEffects::NotModified even though hash = 0")
+            }
+        }
+    }
+
+    /// Fetch a sticker set, along with its stickers.
+    ///
+    /// The last few sets fetched are remembered by the client (but not saved in the session), so
+    /// that fetching them again only downloads them if they changed since.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let set = client
+    ///     .get_sticker_set(tl::types::InputStickerSetShortName {
+    ///         short_name: "UtyaDuck".to_string(),
+    ///     }.into())
+    ///     .await?;
+    ///
+    /// println!("The set has {} stickers", set.documents.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sticker_set(
+        &self,
+        set: tl::enums::InputStickerSet,
+    ) -> Result<tl::types::messages::StickerSet, InvocationError> {
+        use tl::enums::InputStickerSet as Input;
+
+        let cached = self
+            .0
+            .sticker_set_cache
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|cached| {
+                let tl::enums::StickerSet::Set(cached) = &cached.set;
+                match &set {
+                    Input::Id(set) => cached.id == set.id,
+                    Input::ShortName(set) => {
+                        cached.short_name.eq_ignore_ascii_case(&set.short_name)
+                    }
+                    _ => false,
+                }
+            })
+            .cloned();
+        let hash = cached.as_ref().map_or(0, |cached| {
+            let tl::enums::StickerSet::Set(cached) = &cached.set;
+            cached.hash
+        });
+
+        match self
+            .invoke(&tl::functions::messages::GetStickerSet {
+                stickerset: set,
+                hash,
+            })
+            .await?
+        {
+            tl::enums::messages::StickerSet::Set(set) => {
+                let tl::enums::StickerSet::Set(raw) = &set.set;
+                let mut cache = self.0.sticker_set_cache.lock().unwrap();
+                cache.retain(|cached| {
+                    let tl::enums::StickerSet::Set(cached) = &cached.set;
+                    cached.id != raw.id
+                });
+                if cache.len() == MAX_CACHED_STICKER_SETS {
+                    cache.pop_front();
+                }
+                cache.push_back(set.clone());
+                Ok(set)
+            }
+            tl::enums::messages::StickerSet::NotModified => cached.ok_or_else(|| {
+                // Nothing was cached, so the hash sent was 0 and the set cannot be unmodified.
+                InvocationError::Read(
+                    DeserializeError::UnexpectedConstructor {
+                        id: tl::types::messages::StickerSetNotModified::CONSTRUCTOR_ID,
+                    }
+                    .into(),
+                )
+            }),
+        }
+    }
+
+    /// Iterate over the votes cast in a poll.
+    ///
+    /// If `option` is specified, only the votes for that option are returned (see
+    /// [`PollOption::option`]). Otherwise, the votes for all options are returned.
+    ///
+    /// Telegram only allows fetching the voters of polls which are not anonymous.
+    ///
+    /// [`PollOption::option`]: crate::types::media::PollOption::option
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut votes = client.iter_poll_voters(&message, None);
+    ///
+    /// while let Some(vote) = votes.next().await? {
+    ///     println!("{:?} voted {} option(s)", vote.voter.name(), vote.options.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_poll_voters(&self, message: &Message, option: Option<&[u8]>) -> PollVoteIter {
+        PollVoteIter::new(
+            self,
+            message.chat().pack(),
+            message.id(),
+            option.map(|option| option.to_vec()),
+        )
+    }
+
+    /// Get the keywords which can be used to find emoji in the given language.
+    ///
+    /// The language code is a two-letter ISO 639-1 code, such as `"en"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let keywords = client.get_emoji_keywords("en").await?;
+    /// println!("Emoji for 'cat': {:?}", keywords.search("cat"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_emoji_keywords(
+        &self,
+        lang_code: &str,
+    ) -> Result<EmojiKeywords, InvocationError> {
+        self.invoke(&tl::functions::messages::GetEmojiKeywords {
+            lang_code: lang_code.to_string(),
+        })
+        .await
+        .map(EmojiKeywords::from_raw)
+    }
+
+    /// Update the emoji keywords to their latest version, only fetching the changes since the
+    /// version already known.
+    ///
+    /// If Telegram is unable to provide the changes, all the keywords are fetched again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut keywords = client.get_emoji_keywords("en").await?;
+    /// // Some time later...
+    /// client.update_emoji_keywords(&mut keywords).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_emoji_keywords(
+        &self,
+        keywords: &mut EmojiKeywords,
+    ) -> Result<(), InvocationError> {
+        let difference = self
+            .invoke(&tl::functions::messages::GetEmojiKeywordsDifference {
+                lang_code: keywords.lang_code().to_string(),
+                from_version: keywords.version(),
+            })
+            .await?;
+
+        if !keywords.apply(difference) {
+            *keywords = self.get_emoji_keywords(keywords.lang_code()).await?;
+        }
+        Ok(())
+    }
+
+    /// Transcribe the voice message or round video message into text, waiting until the
+    /// transcription is complete.
+    ///
+    /// Telegram only allows Premium users to transcribe messages, with a limited amount of free
+    /// trials for other users. The transcription happens in the background, so this method
+    /// waits for the update with the final text, and may take a while with long messages. The
+    /// network is driven while waiting. If the text doesn't arrive within five minutes,
+    /// [`TranscribeError::TimedOut`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let message_id = 123;
+    /// let text = client.transcribe_audio(&chat, message_id).await?;
+    /// println!("The voice message says: {text}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transcribe_audio<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_id: i32,
+    ) -> Result<String, TranscribeError> {
+        let request = tl::functions::messages::TranscribeAudio {
+            peer: chat.into().to_input_peer(),
+            msg_id: message_id,
+        };
+        let tl::enums::messages::TranscribedAudio::Audio(transcribed) =
+            self.invoke(&request).await?;
+        if !transcribed.pending {
+            return Ok(transcribed.text);
+        }
+
+        let mut rx = {
+            let mut state = self.0.state.write().unwrap();
+            let completed = state
+                .completed_transcriptions
+                .iter()
+                .position(|(id, _)| *id == transcribed.transcription_id);
+            if let Some(i) = completed {
+                let (_, text) = state.completed_transcriptions.remove(i).unwrap();
+                return Ok(text);
+            }
+
+            let (tx, rx) = oneshot::channel();
+            state
+                .transcriptions
+                .insert(transcribed.transcription_id, tx);
+            rx
+        };
+
+        let deadline = Instant::now() + TRANSCRIPTION_TIMEOUT;
+        let text: Result<_, InvocationError> = async {
+            loop {
+                let step = pin!(async { self.step().await });
+                let timeout = pin!(async { sleep_until(deadline).await });
+                match select(&mut rx, select(step, timeout)).await {
+                    Either::Left((text, _)) => break Ok(text.ok()),
+                    Either::Right((Either::Left((step, _)), _)) => step?,
+                    Either::Right((Either::Right(_), _)) => break Ok(None),
+                }
+            }
+        }
+        .await;
+        self.0
+            .state
+            .write()
+            .unwrap()
+            .transcriptions
+            .remove(&transcribed.transcription_id);
+
+        text?.ok_or(TranscribeError::TimedOut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_pagination_continues_after_short_page() {
+        // (next_rate, count) for each page Telegram returns, with a limit of 100.
+        let pages = [(Some(30), 12), (Some(20), 100), (Some(10), 57), (None, 3)];
+
+        let fetched = pages
+            .iter()
+            .scan(false, |done, &(rate, count)| {
+                if *done {
+                    return None;
+                }
+                *done = is_last_rate_chunk(rate, count);
+                Some(count)
+            })
+            .sum::<usize>();
+
+        assert_eq!(fetched, 12 + 100 + 57 + 3);
+    }
+
+    #[test]
+    fn rate_pagination_stops_without_next_rate_or_results() {
+        assert!(!is_last_rate_chunk(Some(1), 1));
+        assert!(is_last_rate_chunk(None, 100));
+        assert!(is_last_rate_chunk(Some(1), 0));
+    }
 }
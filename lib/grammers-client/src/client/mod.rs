@@ -5,6 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+pub mod account;
 pub mod auth;
 pub mod bots;
 pub mod chats;
@@ -14,8 +15,12 @@ pub mod dialogs;
 pub mod files;
 pub mod messages;
 pub mod net;
+pub mod stories;
 pub mod updates;
 
 pub use auth::SignInError;
 pub(crate) use client::ClientInner;
-pub use client::{Client, Config, InitParams};
+pub use client::{
+    AvatarCache, Client, Config, InitParams, RawUpdateHandler, TimeOffsetHandler,
+    UpdateStateCheckpoint, UploadCache, UploadOptions,
+};
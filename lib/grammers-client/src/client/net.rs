@@ -5,25 +5,34 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use super::client::{ClientState, Connection};
-use super::{Client, ClientInner, Config};
+use super::client::{CdnConnection, ChatHashes, ClientState, Connection};
+use super::files;
+use super::{Client, ClientInner, Config, InitParams};
 use crate::utils;
+use futures_util::future::{Either, select};
+use grammers_crypto::rsa;
 use grammers_mtproto::mtp;
 use grammers_mtproto::transport;
 use grammers_mtsender::ServerAddr;
 use grammers_mtsender::{
-    self as sender, AuthorizationError, InvocationError, RpcError, Sender, utils::sleep,
+    self as sender, AuthorizationError, InvocationError, RpcError, Sender,
+    utils::{sleep, sleep_until},
 };
 use grammers_session::{ChatHashCache, MessageBox};
 use grammers_tl_types::{self as tl, Deserializable};
 use log::{debug, info};
 use sender::Enqueuer;
-use std::collections::{HashMap, VecDeque};
-use std::net::Ipv4Addr;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use tokio::sync::oneshot::error::TryRecvError;
-use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
+use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock, mpsc};
+use web_time::Instant;
 
 /// Socket addresses to Telegram datacenters, where the index into this array
 /// represents the data center ID.
@@ -78,11 +87,9 @@ pub(crate) type Transport = transport::Obfuscated<transport::Intermediate>;
 
 const DEFAULT_DC: i32 = 2;
 
-pub(crate) async fn connect_sender(
-    dc_id: i32,
-    config: &Config,
-) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
-    let addr: ServerAddr = if let Some(ref sa) = config.params.server_addr {
+/// The address used to connect to the given datacenter.
+fn server_addr(dc_id: i32, config: &Config) -> ServerAddr {
+    if let Some(ref sa) = config.params.server_addr {
         sa.clone()
     } else {
         #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
@@ -111,7 +118,14 @@ pub(crate) async fn connect_sender(
         };
 
         addr
-    };
+    }
+}
+
+pub(crate) async fn connect_sender(
+    dc_id: i32,
+    config: &Config,
+) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    let addr = server_addr(dc_id, config);
 
     // Select transport based on connection type.
     #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
@@ -190,6 +204,8 @@ pub(crate) async fn connect_sender(
         (sender, tx)
     };
 
+    sender.set_compression_threshold(config.params.compression_threshold);
+
     // TODO handle -404 (we had a previously-valid authkey, but server no longer knows about it)
     // TODO all up-to-date server addresses should be stored in the session for future initial connections
     let _remote_config = sender
@@ -213,6 +229,83 @@ pub(crate) async fn connect_sender(
     Ok((sender, request_tx))
 }
 
+/// Connect to a CDN data center, generating a new authorization key with its RSA keys.
+///
+/// The keys are not persisted in the session, since CDN data centers don't hold any user data.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+async fn connect_cdn_sender(
+    address: SocketAddr,
+    keys: Vec<rsa::Key>,
+    config: &Config,
+) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    #[cfg(not(feature = "proxy"))]
+    let addr = ServerAddr::Tcp { address };
+
+    #[cfg(feature = "proxy")]
+    let addr = if let Some(proxy) = &config.params.proxy_url {
+        ServerAddr::Proxied {
+            address,
+            proxy: proxy.to_owned(),
+        }
+    } else {
+        ServerAddr::Tcp { address }
+    };
+
+    info!("creating a new sender and auth key in cdn {:?}", addr);
+    let transport = transport::TransportMode::Full(transport::Full::new());
+    sender::connect_with_keys(transport, addr, config.params.reconnection_policy, keys).await
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+async fn connect_cdn_sender(
+    _address: SocketAddr,
+    _keys: Vec<rsa::Key>,
+    _config: &Config,
+) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "cdn data centers can only be reached over tcp",
+    )
+    .into())
+}
+
+tokio::task_local! {
+    // Set while running futures which handle flood waits on their own.
+    static NO_FLOOD_SLEEP: ();
+}
+
+/// Run the future without sleeping on flood waits, so that its caller can handle them instead
+/// of sleeping twice on them.
+pub(crate) async fn without_flood_sleep<F: Future>(future: F) -> F::Output {
+    NO_FLOOD_SLEEP.scope((), future).await
+}
+
+/// How long to sleep before retrying a request which failed with the given error, if it was a
+/// flood wait short enough to sleep on according to [`InitParams::flood_sleep_threshold`].
+fn flood_wait_delay<R>(error: &InvocationError, params: &InitParams) -> Option<Duration> {
+    if NO_FLOOD_SLEEP.try_with(|_| ()).is_ok() {
+        return None;
+    }
+    match error {
+        InvocationError::Rpc(RpcError {
+            name,
+            code: 420,
+            value: Some(seconds),
+            ..
+        }) if *seconds <= params.sleep_threshold(name) => {
+            let delay = Duration::from_secs(*seconds as _);
+            info!(
+                "sleeping on {} for {:?} before retrying {}",
+                name,
+                delay,
+                std::any::type_name::<R>()
+            );
+            Some(delay)
+        }
+        _ => None,
+    }
+}
+
 /// Method implementations directly related with network connectivity.
 impl Client {
     /// Creates and returns a new client instance upon successful connection to Telegram.
@@ -248,6 +341,11 @@ impl Client {
             .session
             .get_user()
             .map(|u| u.dc)
+            // Sessions imported from other libraries may only know the home datacenter's key.
+            .or_else(|| match config.session.get_dcs().as_slice() {
+                [dc] => Some(dc.id()),
+                _ => None,
+            })
             .unwrap_or(DEFAULT_DC);
         let (sender, request_tx) = connect_sender(dc_id, &config).await?;
         let message_box = if config.params.catch_up {
@@ -276,24 +374,56 @@ impl Client {
 
         let self_user = config.session.get_user();
 
+        let resolved_usernames = config
+            .session
+            .get_resolved_usernames()
+            .into_iter()
+            .map(|(username, chat, date)| (username, (date, crate::types::Chat::unpack(chat))))
+            .collect();
+
         // Don't bother getting pristine update state if we're not logged in.
         let should_get_state = message_box.is_empty() && config.session.signed_in();
 
+        // The photos are only downloaded if there's a cache and a runtime to download them in.
+        let avatar_worker = config
+            .params
+            .avatar_cache
+            .as_ref()
+            .and_then(|_| tokio::runtime::Handle::try_current().ok())
+            .map(|runtime| (runtime, mpsc::channel(files::MAX_PENDING_AVATARS)));
+        let avatar_tx = avatar_worker.as_ref().map(|(_, (tx, _))| tx.clone());
+
         // TODO Sender doesn't have a way to handle backpressure yet
         let client = Self(Arc::new(ClientInner {
             id: utils::generate_random_id(),
             config,
             conn: Connection::new(sender, request_tx),
+            dc_id: AtomicI32::new(dc_id),
+            chat_hashes: ChatHashes::new(ChatHashCache::new(self_user.map(|u| (u.id, u.bot)))),
             state: RwLock::new(ClientState {
-                dc_id,
                 message_box,
-                chat_hashes: ChatHashCache::new(self_user.map(|u| (u.id, u.bot))),
                 last_update_limit_warn: None,
+                last_update_checkpoint: None,
                 updates,
+                unqueued_batches: 0,
+                chat_updates: HashMap::new(),
+                avatar_downloads: HashSet::new(),
+                transcriptions: HashMap::new(),
+                completed_transcriptions: VecDeque::new(),
             }),
+            avatar_tx,
+            saving_checkpoint: AtomicBool::new(false),
             downloader_map: AsyncRwLock::new(HashMap::new()),
+            cdn_map: AsyncRwLock::new(HashMap::new()),
+            resolved_usernames: Mutex::new(resolved_usernames),
+            resolving_usernames: Mutex::new(HashMap::new()),
+            sticker_set_cache: Mutex::new(VecDeque::new()),
         }));
 
+        if let Some((runtime, (_, rx))) = avatar_worker {
+            runtime.spawn(Self::avatar_worker(Arc::downgrade(&client.0), rx));
+        }
+
         if should_get_state {
             match client.invoke(&tl::functions::updates::GetState {}).await {
                 Ok(state) => {
@@ -341,11 +471,53 @@ impl Client {
     ) -> Result<R::Return, InvocationError> {
         self.0
             .conn
-            .invoke(
-                request,
-                self.0.config.params.flood_sleep_threshold,
-                |updates| self.process_socket_updates(updates),
-            )
+            .invoke(request, &self.0.config.params, |updates| {
+                self.process_socket_updates(updates)
+            })
+            .await
+    }
+
+    /// Invoke multiple raw API calls at once, and return their results in the same order.
+    ///
+    /// All the requests are sent together, packed in as few containers as possible (usually
+    /// a single one), which saves round trips when making many independent requests, such as
+    /// fetching information about many chats.
+    ///
+    /// The outer error is only returned if the connection fails. Otherwise, each request
+    /// succeeds or fails on its own. Requests which hit a flood wait below
+    /// [`InitParams::flood_sleep_threshold`] are retried once it expires, while the rest are
+    /// still being processed.
+    ///
+    /// The same warning as in [`Client::invoke`] applies to this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let requests = ["telegram", "durov"].map(|username| {
+    ///     tl::functions::contacts::ResolveUsername {
+    ///         username: username.to_string(),
+    ///         referer: None,
+    ///     }
+    /// });
+    ///
+    /// for result in client.invoke_batch(&requests).await? {
+    ///     println!("{:?}", result?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn invoke_batch<R: tl::RemoteCall>(
+        &self,
+        requests: &[R],
+    ) -> Result<Vec<Result<R::Return, InvocationError>>, InvocationError> {
+        self.0
+            .conn
+            .invoke_batch(requests, &self.0.config.params, |updates| {
+                self.process_socket_updates(updates)
+            })
             .await
     }
 
@@ -366,6 +538,11 @@ impl Client {
 
     async fn connect_sender(&self, dc_id: i32) -> Result<Arc<Connection>, InvocationError> {
         let mut mutex = self.0.downloader_map.write().await;
+        // Another task may have connected while the lock was being acquired.
+        if let Some(connection) = mutex.get(&dc_id) {
+            return Ok(connection.clone());
+        }
+
         debug!("Connecting new datacenter {}", dc_id);
         match connect_sender(dc_id, &self.0.config).await {
             Ok((new_sender, new_tx)) => {
@@ -380,7 +557,7 @@ impl Client {
                     bytes: authorization.bytes,
                 };
                 new_downloader
-                    .invoke(&request, self.0.config.params.flood_sleep_threshold, drop)
+                    .invoke(&request, &self.0.config.params, drop)
                     .await?;
 
                 mutex.insert(dc_id, new_downloader.clone());
@@ -388,7 +565,7 @@ impl Client {
             }
             Err(AuthorizationError::Invoke(e)) => Err(e),
             Err(AuthorizationError::Gen(e)) => {
-                panic!("authorization key generation failed: {e}")
+                Err(sender::ReadError::from(io::Error::other(e)).into())
             }
         }
     }
@@ -400,18 +577,250 @@ impl Client {
         });
     }
 
+    /// Invoke a raw API call in the given datacenter, instead of the one the account belongs to.
+    ///
+    /// The first time a datacenter is used, a connection to it is made, and the current
+    /// authorization is exported from the home datacenter and imported into it. Later calls
+    /// reuse that connection. If `dc_id` is the home datacenter, this is the same as
+    /// [`Client::invoke`].
+    ///
+    /// This is needed by requests which only work in the datacenter where some data lives, such
+    /// as downloading files stored elsewhere or editing inline messages.
+    ///
+    /// Updates received from other datacenters are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// println!("{:?}", client.invoke_in_dc(&tl::functions::Ping { ping_id: 0 }, 4).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn invoke_in_dc<R: tl::RemoteCall>(
         &self,
         request: &R,
         dc_id: i32,
     ) -> Result<R::Return, InvocationError> {
+        if dc_id == self.0.dc_id.load(Ordering::Relaxed) {
+            return self.invoke(request).await;
+        }
+
         let downloader = match self.get_downloader(dc_id).await? {
             None => self.connect_sender(dc_id).await?,
             Some(fd) => fd,
         };
         downloader
-            .invoke(request, self.0.config.params.flood_sleep_threshold, drop)
+            .invoke(request, &self.0.config.params, drop)
+            .await
+    }
+
+    /// The ID of the datacenter the client is currently connected to.
+    ///
+    /// This is the datacenter where requests made with [`Client::invoke`] are sent, and should
+    /// match the one where the logged-in account lives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// println!("Connected to DC {}", client.dc_id());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dc_id(&self) -> i32 {
+        self.0.dc_id.load(Ordering::Relaxed)
+    }
+
+    /// The address used to connect to the current datacenter.
+    ///
+    /// If [`InitParams::server_addr`] was set, this is always that address.
+    ///
+    /// [`InitParams::server_addr`]: crate::InitParams::server_addr
+    pub fn current_address(&self) -> ServerAddr {
+        server_addr(self.dc_id(), &self.0.config)
+    }
+
+    /// Measure the round-trip time to the current datacenter by sending a ping.
+    ///
+    /// Note that the time includes waiting for any other request that is being sent, so a busy
+    /// connection will appear to be slower.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// println!("Latency: {:?}", client.latency().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn latency(&self) -> Result<Duration, InvocationError> {
+        let start = Instant::now();
+        self.invoke(&tl::functions::Ping {
+            ping_id: utils::generate_random_id(),
+        })
+        .await?;
+        Ok(start.elapsed())
+    }
+
+    /// Move the connection to a different datacenter.
+    ///
+    /// This is useful when the account lives in a datacenter other than the one the client
+    /// connected to. If the client is signed in, its authorization is exported from the current
+    /// datacenter and imported into the new one, and the session is updated to remember it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.switch_dc(4).await?;
+    /// assert_eq!(client.dc_id(), 4);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn switch_dc(&self, dc_id: i32) -> Result<(), AuthorizationError> {
+        if dc_id == self.dc_id() {
+            return Ok(());
+        }
+
+        let authorization = if self.0.config.session.signed_in() {
+            Some(self.export_authorization(dc_id).await?)
+        } else {
+            None
+        };
+
+        let (sender, request_tx) = connect_sender(dc_id, &self.0.config).await?;
+        {
+            *self.0.conn.sender.lock().await = sender;
+            *self.0.conn.request_tx.write().unwrap() = request_tx;
+            self.0.dc_id.store(dc_id, Ordering::Relaxed);
+        }
+
+        if let Some(authorization) = authorization {
+            self.invoke(&tl::functions::auth::ImportAuthorization {
+                id: authorization.id,
+                bytes: authorization.bytes,
+            })
+            .await?;
+        }
+        if let Some(user) = self.0.config.session.get_user() {
+            self.0.config.session.set_user(user.id, dc_id, user.bot);
+        }
+        Ok(())
+    }
+
+    async fn connect_cdn(&self, dc_id: i32) -> Result<Arc<CdnConnection>, InvocationError> {
+        if let Some(connection) = self.0.cdn_map.read().await.get(&dc_id) {
+            return Ok(connection.clone());
+        }
+
+        // The lock is not held while connecting, so that downloads from other CDN data centers
+        // are not blocked by it.
+        let tl::enums::CdnConfig::Config(cdn_config) =
+            self.invoke(&tl::functions::help::GetCdnConfig {}).await?;
+        let keys = cdn_config
+            .public_keys
+            .into_iter()
+            .filter_map(|key| {
+                let tl::enums::CdnPublicKey::Key(key) = key;
+                (key.dc_id == dc_id)
+                    .then(|| rsa::Key::from_pem(&key.public_key))
+                    .flatten()
+            })
+            .collect::<Vec<_>>();
+
+        let tl::enums::Config::Config(config) =
+            self.invoke(&tl::functions::help::GetConfig {}).await?;
+        let address = config
+            .dc_options
+            .into_iter()
+            .find_map(|option| {
+                let tl::enums::DcOption::Option(option) = option;
+                if option.cdn && option.id == dc_id && !option.ipv6 && !option.tcpo_only {
+                    let ip = option.ip_address.parse::<Ipv4Addr>().ok()?;
+                    Some(SocketAddr::from((ip, option.port as u16)))
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| {
+                InvocationError::Rpc(RpcError {
+                    code: 400,
+                    name: "DC_ID_INVALID".to_owned(),
+                    value: None,
+                    caused_by: None,
+                })
+            })?;
+
+        debug!("Connecting new cdn datacenter {}", dc_id);
+        let (sender, tx) = match connect_cdn_sender(address, keys, &self.0.config).await {
+            Ok(x) => x,
+            Err(AuthorizationError::Invoke(e)) => return Err(e),
+            Err(AuthorizationError::Gen(e)) => {
+                return Err(sender::ReadError::from(io::Error::other(e)).into());
+            }
+        };
+
+        // Another task may have connected in the meantime, in which case its connection is used.
+        let connection = Arc::new(CdnConnection {
+            conn: Connection::new(sender, tx),
+            initialized: AtomicBool::new(false),
+        });
+        Ok(self
+            .0
+            .cdn_map
+            .write()
             .await
+            .entry(dc_id)
+            .or_insert(connection)
+            .clone())
+    }
+
+    /// Download part of a file from one of Telegram's CDN data centers, connecting to it first
+    /// if needed.
+    pub(crate) async fn get_cdn_file(
+        &self,
+        dc_id: i32,
+        request: tl::functions::upload::GetCdnFile,
+    ) -> Result<tl::enums::upload::CdnFile, InvocationError> {
+        let connection = self.connect_cdn(dc_id).await?;
+        if connection.initialized.load(Ordering::Acquire) {
+            return connection
+                .conn
+                .invoke(&request, &self.0.config.params, drop)
+                .await;
+        }
+
+        // CDN data centers don't need the user to be authorized, but the connection must still
+        // be initialized once.
+        let result = connection
+            .conn
+            .invoke(
+                &tl::functions::InvokeWithLayer {
+                    layer: tl::LAYER,
+                    query: tl::functions::InitConnection {
+                        api_id: self.0.config.api_id,
+                        device_model: self.0.config.params.device_model.clone(),
+                        system_version: self.0.config.params.system_version.clone(),
+                        app_version: self.0.config.params.app_version.clone(),
+                        system_lang_code: self.0.config.params.system_lang_code.clone(),
+                        lang_pack: "".into(),
+                        lang_code: self.0.config.params.lang_code.clone(),
+                        proxy: None,
+                        params: None,
+                        query: request,
+                    },
+                },
+                &self.0.config.params,
+                drop,
+            )
+            .await;
+        if result.is_ok() {
+            connection.initialized.store(true, Ordering::Release);
+        }
+        result
     }
 
     /// Perform a single network step.
@@ -431,11 +840,41 @@ impl Client {
     /// # }
     /// ```
     pub async fn step(&self) -> Result<(), sender::ReadError> {
-        let updates = self.0.conn.step().await?;
+        let updates = self.0.conn.step(&self.0.config.params).await?;
         self.process_socket_updates(updates);
         Ok(())
     }
 
+    /// The offset from the server's time, in seconds, used to generate message identifiers.
+    ///
+    /// A large offset means the clock of the system is not accurate. The offset is corrected
+    /// automatically when Telegram reports it is wrong, which can be observed with
+    /// [`InitParams::time_offset_handler`].
+    pub fn time_offset(&self) -> i32 {
+        self.0.conn.time_offset.load(Ordering::SeqCst)
+    }
+
+    /// Statistics about the compression of the requests sent through the main connection.
+    ///
+    /// The threshold at which requests are compressed can be configured with
+    /// [`InitParams::compression_threshold`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(client: grammers_client::Client) {
+    /// let stats = client.compression_stats();
+    /// println!(
+    ///     "compressed {} requests, saving {} bytes",
+    ///     stats.compressed,
+    ///     stats.saved_bytes()
+    /// );
+    /// # }
+    /// ```
+    pub fn compression_stats(&self) -> mtp::CompressionStats {
+        *self.0.conn.compression_stats.lock().unwrap()
+    }
+
     /// Run the client by repeatedly calling [`Client::step`] until a graceful disconnection
     /// occurs, or a network error occurs. Incoming updates are ignored and simply dropped.
     /// instead.
@@ -459,6 +898,8 @@ impl Client {
 impl Connection {
     fn new(sender: Sender<Transport, mtp::Encrypted>, request_tx: Enqueuer) -> Self {
         Self {
+            time_offset: AtomicI32::new(sender.time_offset()),
+            compression_stats: Mutex::new(sender.compression_stats()),
             sender: AsyncMutex::new(sender),
             request_tx: RwLock::new(request_tx),
             step_counter: AtomicU32::new(0),
@@ -468,7 +909,7 @@ impl Connection {
     pub(crate) async fn invoke<R: tl::RemoteCall, F: Fn(Vec<tl::enums::Updates>)>(
         &self,
         request: &R,
-        flood_sleep_threshold: u32,
+        params: &InitParams,
         on_updates: F,
     ) -> Result<R::Return, InvocationError> {
         let mut slept_flood = false;
@@ -478,28 +919,21 @@ impl Connection {
             match rx.try_recv() {
                 Ok(response) => match response {
                     Ok(body) => break R::Return::from_bytes(&body).map_err(|e| e.into()),
-                    Err(InvocationError::Rpc(RpcError {
-                        name,
-                        code: 420,
-                        value: Some(seconds),
-                        ..
-                    })) if !slept_flood && seconds <= flood_sleep_threshold => {
-                        let delay = std::time::Duration::from_secs(seconds as _);
-                        info!(
-                            "sleeping on {} for {:?} before retrying {}",
-                            name,
-                            delay,
-                            std::any::type_name::<R>()
-                        );
+                    Err(e) => {
+                        let delay = (!slept_flood)
+                            .then(|| flood_wait_delay::<R>(&e, params))
+                            .flatten();
+                        let Some(delay) = delay else {
+                            break Err(e);
+                        };
                         sleep(delay).await;
                         slept_flood = true;
                         rx = self.request_tx.read().unwrap().enqueue(request);
                         continue;
                     }
-                    Err(e) => break Err(e),
                 },
                 Err(TryRecvError::Empty) => {
-                    on_updates(self.step().await?);
+                    on_updates(self.step(params).await?);
                 }
                 Err(TryRecvError::Closed) => {
                     panic!("request channel dropped before receiving a result")
@@ -508,7 +942,89 @@ impl Connection {
         }
     }
 
-    async fn step(&self) -> Result<Vec<tl::enums::Updates>, sender::ReadError> {
+    pub(crate) async fn invoke_batch<R: tl::RemoteCall, F: Fn(Vec<tl::enums::Updates>)>(
+        &self,
+        requests: &[R],
+        params: &InitParams,
+        on_updates: F,
+    ) -> Result<Vec<Result<R::Return, InvocationError>>, InvocationError> {
+        let mut slept_flood = vec![false; requests.len()];
+        // Requests which hit a flood wait are retried on their own once it expires, without
+        // holding back the rest.
+        let mut retry_at = vec![None::<Instant>; requests.len()];
+        let mut results = (0..requests.len()).map(|_| None).collect::<Vec<_>>();
+
+        // Enqueue everything before stepping so that the requests are sent together.
+        let mut pending = {
+            let request_tx = self.request_tx.read().unwrap();
+            requests
+                .iter()
+                .map(|request| Some(request_tx.enqueue(request)))
+                .collect::<Vec<_>>()
+        };
+
+        loop {
+            let now = Instant::now();
+            for (i, at) in retry_at.iter_mut().enumerate() {
+                if at.is_some_and(|at| at <= now) {
+                    *at = None;
+                    pending[i] = Some(self.request_tx.read().unwrap().enqueue(&requests[i]));
+                }
+            }
+
+            for (i, rx) in pending.iter_mut().enumerate() {
+                let Some(receiver) = rx.as_mut() else {
+                    continue;
+                };
+                let response = match receiver.try_recv() {
+                    Ok(response) => response,
+                    Err(TryRecvError::Empty) => continue,
+                    Err(TryRecvError::Closed) => {
+                        panic!("request channel dropped before receiving a result")
+                    }
+                };
+                *rx = None;
+                match response {
+                    Ok(body) => {
+                        results[i] = Some(R::Return::from_bytes(&body).map_err(|e| e.into()));
+                    }
+                    Err(e) => {
+                        let delay = (!slept_flood[i])
+                            .then(|| flood_wait_delay::<R>(&e, params))
+                            .flatten();
+                        match delay {
+                            Some(delay) => {
+                                slept_flood[i] = true;
+                                retry_at[i] = Some(now + delay);
+                            }
+                            None => results[i] = Some(Err(e)),
+                        }
+                    }
+                }
+            }
+
+            if results.iter().all(Option::is_some) {
+                break Ok(results.into_iter().map(Option::unwrap).collect());
+            }
+
+            let step = pin!(async { self.step(params).await });
+            match retry_at.iter().flatten().min() {
+                Some(&deadline) => {
+                    let sleep = pin!(async { sleep_until(deadline).await });
+                    match select(sleep, step).await {
+                        Either::Left(_) => {}
+                        Either::Right((updates, _)) => on_updates(updates?),
+                    }
+                }
+                None => on_updates(step.await?),
+            }
+        }
+    }
+
+    async fn step(
+        &self,
+        params: &InitParams,
+    ) -> Result<Vec<tl::enums::Updates>, sender::ReadError> {
         let ticket_number = self.step_counter.load(Ordering::SeqCst);
         let mut sender = self.sender.lock().await;
         match self.step_counter.compare_exchange(
@@ -518,8 +1034,19 @@ impl Connection {
             Ordering::SeqCst,
             Ordering::SeqCst,
         ) {
-            Ok(_) => sender.step().await, // We're the one to drive IO.
-            Err(_) => Ok(Vec::new()),     // A different task drove IO.
+            Ok(_) => {
+                // We're the one to drive IO.
+                let result = sender.step().await;
+                *self.compression_stats.lock().unwrap() = sender.compression_stats();
+                let time_offset = sender.time_offset();
+                if self.time_offset.swap(time_offset, Ordering::SeqCst) != time_offset
+                    && let Some(handler) = params.time_offset_handler.as_ref()
+                {
+                    handler(time_offset);
+                }
+                result
+            }
+            Err(_) => Ok(Vec::new()), // A different task drove IO.
         }
     }
 }
@@ -0,0 +1,289 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Methods related to stories.
+
+use super::Client;
+use crate::client::messages::parse_mention_entities;
+use crate::types::{ChatMap, InputMessage, InputReactions, Story, StoryPrivacy};
+use crate::utils::generate_random_id;
+pub use grammers_mtsender::InvocationError;
+use grammers_mtsender::RpcError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+fn find_story_id(updates: &tl::enums::Updates, random_id: i64) -> Option<i32> {
+    let updates = match updates {
+        tl::enums::Updates::Updates(updates) => &updates.updates,
+        tl::enums::Updates::Combined(updates) => &updates.updates,
+        _ => return None,
+    };
+    updates.iter().find_map(|update| match update {
+        tl::enums::Update::StoryId(update) if update.random_id == random_id => Some(update.id),
+        _ => None,
+    })
+}
+
+/// Method implementations related to viewing and interacting with stories.
+impl Client {
+    /// Fetch the stories posted by a chat with the given identifiers.
+    ///
+    /// Stories that have expired or were deleted will be missing from the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for story in client.get_stories_by_id(&chat, &[1, 2, 3]).await? {
+    ///     println!("Story {}: {:?}", story.id(), story.caption());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_stories_by_id<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        story_ids: &[i32],
+    ) -> Result<Vec<Story>, InvocationError> {
+        let chat = chat.into();
+        let tl::enums::stories::Stories::Stories(stories) = self
+            .invoke(&tl::functions::stories::GetStoriesById {
+                peer: chat.to_input_peer(),
+                id: story_ids.to_vec(),
+            })
+            .await?;
+
+        self.0.chat_hashes.extend(&stories.users, &stories.chats);
+
+        let chats = ChatMap::new(stories.users, stories.chats);
+        let peer = chat.to_peer();
+        Ok(stories
+            .stories
+            .into_iter()
+            .filter_map(|story| match story {
+                tl::enums::StoryItem::Item(item) => {
+                    Some(Story::from_raw(self, *item, &peer, &chats))
+                }
+                tl::enums::StoryItem::Deleted(_) | tl::enums::StoryItem::Skipped(_) => None,
+            })
+            .collect())
+    }
+
+    /// Mark the stories posted by a chat as read, up to and including `max_id`.
+    ///
+    /// Returns the identifiers of the stories that were newly marked as read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let max_id = 10;
+    /// client.read_stories(&chat, max_id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_stories<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        max_id: i32,
+    ) -> Result<Vec<i32>, InvocationError> {
+        self.invoke(&tl::functions::stories::ReadStories {
+            peer: chat.into().to_input_peer(),
+            max_id,
+        })
+        .await
+    }
+
+    /// Send a reaction to a story.
+    ///
+    /// Only the first reaction is used, because stories can only have one reaction per user.
+    /// Using [`InputReactions::remove`] will remove the previously-sent reaction.
+    ///
+    /// See also: [`Story::react`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let story_id = 123;
+    /// client.send_story_reaction(&chat, story_id, "❤").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_story_reaction<C: Into<PackedChat>, R: Into<InputReactions>>(
+        &self,
+        chat: C,
+        story_id: i32,
+        reactions: R,
+    ) -> Result<(), InvocationError> {
+        let reactions = reactions.into();
+
+        self.invoke(&tl::functions::stories::SendReaction {
+            add_to_recent: reactions.add_to_recent,
+            peer: chat.into().to_input_peer(),
+            story_id,
+            reaction: reactions
+                .reactions
+                .into_iter()
+                .next()
+                .unwrap_or(tl::enums::Reaction::Empty),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Post a new story as the given chat, which may be the logged-in account itself.
+    ///
+    /// The message must contain media (either a photo or a video). Its text will be used as
+    /// the caption of the story.
+    ///
+    /// Returns the identifier of the newly-posted story, if Telegram reported it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    /// use grammers_client::types::StoryPrivacy;
+    ///
+    /// let me = client.get_me().await?;
+    /// let photo = client.upload_file("photo.jpg").await?;
+    ///
+    /// client
+    ///     .post_story(&me, InputMessage::text("Look at this!").photo(photo), StoryPrivacy::close_friends())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn post_story<C: Into<PackedChat>, M: Into<InputMessage>>(
+        &self,
+        chat: C,
+        message: M,
+        privacy: StoryPrivacy,
+    ) -> Result<Option<i32>, InvocationError> {
+        let message = message.into();
+        let media = message.media.clone().ok_or_else(|| {
+            InvocationError::Rpc(RpcError {
+                code: 400,
+                name: "MEDIA_EMPTY".to_owned(),
+                value: None,
+                caused_by: None,
+            })
+        })?;
+        let entities = parse_mention_entities(self, message.entities.clone());
+        let random_id = generate_random_id();
+
+        let updates = self
+            .invoke(&tl::functions::stories::SendStory {
+                pinned: false,
+                noforwards: false,
+                fwd_modified: false,
+                peer: chat.into().to_input_peer(),
+                media,
+                media_areas: None,
+                caption: Some(message.text),
+                entities,
+                privacy_rules: privacy.rules,
+                random_id,
+                period: None,
+                fwd_from_id: None,
+                fwd_from_story: None,
+            })
+            .await?;
+
+        Ok(find_story_id(&updates, random_id))
+    }
+
+    /// Edit a story that was previously posted.
+    ///
+    /// The text of the message will replace the caption, and the media will be replaced only if
+    /// the message has any. The privacy will be left unchanged if `None` is given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::StoryPrivacy;
+    ///
+    /// let story_id = 123;
+    /// client
+    ///     .edit_story(&chat, story_id, "New caption", Some(StoryPrivacy::contacts()))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_story<C: Into<PackedChat>, M: Into<InputMessage>>(
+        &self,
+        chat: C,
+        story_id: i32,
+        message: M,
+        privacy: Option<StoryPrivacy>,
+    ) -> Result<(), InvocationError> {
+        let message = message.into();
+        let entities = parse_mention_entities(self, message.entities.clone());
+
+        self.invoke(&tl::functions::stories::EditStory {
+            peer: chat.into().to_input_peer(),
+            id: story_id,
+            media: message.media,
+            media_areas: None,
+            caption: Some(message.text),
+            entities,
+            privacy_rules: privacy.map(|privacy| privacy.rules),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Replace the list of close friends of the logged-in account.
+    ///
+    /// Stories posted with [`StoryPrivacy::close_friends`] will only be visible to these users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(friend: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_close_friends([&friend]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_close_friends<C: Into<PackedChat>, I: IntoIterator<Item = C>>(
+        &self,
+        users: I,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::contacts::EditCloseFriends {
+            id: users.into_iter().map(|user| user.into().id).collect(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Activate the stealth mode, which hides the logged-in account from the viewers list of
+    /// stories.
+    ///
+    /// If `past` is `true`, views from the last 5 minutes will be erased. If `future` is `true`,
+    /// views for the next 25 minutes will be hidden. Only premium accounts can use this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.activate_stealth_mode(true, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn activate_stealth_mode(
+        &self,
+        past: bool,
+        future: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::stories::ActivateStealthMode { past, future })
+            .await
+            .map(drop)
+    }
+}
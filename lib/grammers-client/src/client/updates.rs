@@ -9,20 +9,26 @@
 //! Methods to deal with and offer access to updates.
 
 use super::Client;
-use crate::types::{ChatMap, Update};
+use super::client::{ChatUpdateSender, ClientState};
+use crate::types::{ChatMap, Peer, Update};
 use futures_util::future::{Either, join_all, select};
 use grammers_mtsender::utils::sleep_until;
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
-use grammers_session::channel_id;
+use grammers_session::{PackedChat, channel_id};
 pub use grammers_session::{PrematureEndReason, UpdateState};
 use grammers_tl_types as tl;
+use std::collections::HashMap;
 use std::pin::pin;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use web_time::Instant;
 
 /// How long to wait after warning the user that the updates limit was exceeded.
 const UPDATE_LIMIT_EXCEEDED_LOG_COOLDOWN: Duration = Duration::from_secs(300);
+/// How many completed transcriptions are remembered for `Client::transcribe_audio`.
+const MAX_COMPLETED_TRANSCRIPTIONS: usize = 32;
 
 impl Client {
     /// Returns the next update from the buffer where they are queued until used.
@@ -46,6 +52,12 @@ impl Client {
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If the returned future is dropped before completing, no
+    /// update is lost, and it will be returned by the next call instead. To stop waiting for
+    /// updates in a loop, [`Client::next_update_until`] may be more convenient.
     pub async fn next_update(&self) -> Result<Update, InvocationError> {
         loop {
             let (update, chats) = self.next_raw_update().await?;
@@ -56,6 +68,98 @@ impl Client {
         }
     }
 
+    /// Like [`Client::next_update`], but stops waiting once the `cancel` future completes, in
+    /// which case `None` is returned.
+    ///
+    /// Any future can be used to cancel, such as a signal handler or a cancellation token.
+    /// If an update is ready by the time `cancel` completes, the update is returned first, and
+    /// updates which were not returned remain queued for the next call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let (stop_tx, mut stop_rx) = tokio::sync::oneshot::channel::<()>();
+    /// # drop(stop_tx);
+    ///
+    /// while let Some(update) = client.next_update_until(&mut stop_rx).await? {
+    ///     println!("{:?}", update);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn next_update_until<F: Future>(
+        &self,
+        cancel: F,
+    ) -> Result<Option<Update>, InvocationError> {
+        let mut cancel = pin!(cancel);
+        loop {
+            // `select` polls the first future first, so a ready update always wins. Updates are
+            // only removed from the queue right before being returned, so dropping the future
+            // midway through doesn't lose any.
+            let next = pin!(self.next_raw_update());
+            let (update, chats) = match select(next, cancel.as_mut()).await {
+                Either::Left((update, _)) => update?,
+                Either::Right(_) => return Ok(None),
+            };
+
+            if let Some(update) = Update::new(self, update, &chats) {
+                return Ok(Some(update));
+            }
+        }
+    }
+
+    /// Returns the next update if one is already queued, without waiting or driving the network.
+    ///
+    /// This is useful to integrate the client into event loops which cannot await, such as
+    /// those of GUI frameworks. Updates are only received while the client is being driven,
+    /// so something else must be doing that, for example, a task running
+    /// [`Client::run_until_disconnected`] or making requests.
+    ///
+    /// Gaps in the updates are only detected and resolved (by fetching the difference) by
+    /// [`Client::next_update`], so updates which were missed, for example due to a
+    /// disconnection, are not returned by this method until something calls it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(client: grammers_client::Client) {
+    /// if let Some(update) = client.try_next_update() {
+    ///     println!("{:?}", update);
+    /// }
+    /// # }
+    /// ```
+    pub fn try_next_update(&self) -> Option<Update> {
+        loop {
+            let (update, chats) = self.0.state.write().unwrap().updates.pop_front()?;
+            self.checkpoint_update_state();
+
+            if let Some(update) = Update::new(self, update, &chats) {
+                return Some(update);
+            }
+        }
+    }
+
+    /// Returns an iterator over the updates which are already queued, without waiting or
+    /// driving the network.
+    ///
+    /// The iterator ends once there are no more queued updates. Like with
+    /// [`Client::try_next_update`], gaps in the updates are only resolved by
+    /// [`Client::next_update`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(client: grammers_client::Client) {
+    /// for update in client.iter_buffered_updates() {
+    ///     println!("{:?}", update);
+    /// }
+    /// # }
+    /// ```
+    pub fn iter_buffered_updates(&self) -> impl Iterator<Item = Update> + '_ {
+        std::iter::from_fn(|| self.try_next_update())
+    }
+
     /// Returns the next raw update and associated chat map from the buffer where they are queued until used.
     ///
     /// # Example
@@ -78,16 +182,23 @@ impl Client {
     pub async fn next_raw_update(
         &self,
     ) -> Result<(tl::enums::Update, Arc<ChatMap>), InvocationError> {
+        let update = self.pop_raw_update().await?;
+        self.checkpoint_update_state();
+        Ok(update)
+    }
+
+    async fn pop_raw_update(&self) -> Result<(tl::enums::Update, Arc<ChatMap>), InvocationError> {
         loop {
             let (deadline, get_diff, channel_requests) = {
                 let state = &mut *self.0.state.write().unwrap();
                 if let Some(update) = state.updates.pop_front() {
                     return Ok(update);
                 }
+                let chat_hashes = self.0.chat_hashes.load();
                 (
                     state.message_box.check_deadlines(), // first, as it might trigger differences
                     state.message_box.get_difference(),
-                    state.message_box.get_all_channel_differences(&state.chat_hashes),
+                    state.message_box.get_all_channel_differences(&chat_hashes),
                 )
             };
 
@@ -133,19 +244,24 @@ impl Client {
                 let mut all_updates = Vec::new();
                 let mut all_users = Vec::new();
                 let mut all_chats = Vec::new();
+                let mut applied = 0;
                 let mut fatal_error = None;
 
                 for (request, result) in channel_requests.into_iter().zip(results) {
                     match result {
                         Ok(response) => {
+                            let mut chat_hashes = self.0.chat_hashes.edit();
                             let (updates, users, chats) = {
                                 let state = &mut *self.0.state.write().unwrap();
+                                state.unqueued_batches += 1;
                                 state.message_box.apply_channel_difference(
                                     request,
                                     response,
-                                    &mut state.chat_hashes,
+                                    &mut chat_hashes,
                                 )
                             };
+                            drop(chat_hashes);
+                            applied += 1;
                             all_updates.extend(updates);
                             all_users.extend(users);
                             all_chats.extend(chats);
@@ -203,10 +319,11 @@ impl Client {
                     }
                 }
 
-                if !all_updates.is_empty() {
+                if applied != 0 {
                     self.extend_update_queue(
                         all_updates,
                         ChatMap::new(all_users, all_chats),
+                        applied,
                     );
                 }
 
@@ -242,13 +359,16 @@ impl Client {
                     }
                 };
 
+                let mut chat_hashes = self.0.chat_hashes.edit();
                 let (updates, users, chats) = {
                     let state = &mut *self.0.state.write().unwrap();
+                    state.unqueued_batches += 1;
                     state
                         .message_box
-                        .apply_difference(response, &mut state.chat_hashes)
+                        .apply_difference(response, &mut chat_hashes)
                 };
-                self.extend_update_queue(updates, ChatMap::new(users, chats));
+                drop(chat_hashes);
+                self.extend_update_queue(updates, ChatMap::new(users, chats), 1);
                 continue;
             }
 
@@ -268,21 +388,19 @@ impl Client {
         }
 
         let mut result = Option::<(Vec<_>, Vec<_>, Vec<_>)>::None;
+        // The new chat hashes are only published once the state is no longer locked.
+        let mut chat_hashes = self.0.chat_hashes.edit();
         {
             let state = &mut *self.0.state.write().unwrap();
-
             for updates in all_updates {
                 if state
                     .message_box
-                    .ensure_known_peer_hashes(&updates, &mut state.chat_hashes)
+                    .ensure_known_peer_hashes(&updates, &mut chat_hashes)
                     .is_err()
                 {
                     continue;
                 }
-                match state
-                    .message_box
-                    .process_updates(updates, &state.chat_hashes)
-                {
+                match state.message_box.process_updates(updates, &chat_hashes) {
                     Ok(tup) => {
                         if let Some(res) = result.as_mut() {
                             res.0.extend(tup.0);
@@ -298,15 +416,54 @@ impl Client {
                     Err(_) => continue,
                 }
             }
+            if result.is_some() {
+                state.unqueued_batches += 1;
+            }
         }
+        drop(chat_hashes);
 
         if let Some((updates, users, chats)) = result {
-            self.extend_update_queue(updates, ChatMap::new(users, chats));
+            self.extend_update_queue(updates, ChatMap::new(users, chats), 1);
         }
     }
 
-    fn extend_update_queue(&self, mut updates: Vec<tl::enums::Update>, chat_map: Arc<ChatMap>) {
+    /// Queue the updates from `batches` which were applied to the message box.
+    fn extend_update_queue(
+        &self,
+        mut updates: Vec<tl::enums::Update>,
+        chat_map: Arc<ChatMap>,
+        batches: usize,
+    ) {
+        if let Some(handler) = self.0.config.params.raw_update_handler.as_ref() {
+            updates.iter().for_each(|update| handler(update, &chat_map));
+        }
+        if self.0.config.params.avatar_cache.is_some() && !updates.is_empty() {
+            self.prefetch_avatars(&chat_map);
+        }
+
         let mut state = self.0.state.write().unwrap();
+        state.unqueued_batches -= batches;
+
+        for update in updates.iter() {
+            if let tl::enums::Update::TranscribedAudio(update) = update
+                && !update.pending
+            {
+                if let Some(tx) = state.transcriptions.remove(&update.transcription_id) {
+                    let _ = tx.send(update.text.clone());
+                } else {
+                    if state.completed_transcriptions.len() == MAX_COMPLETED_TRANSCRIPTIONS {
+                        state.completed_transcriptions.pop_front();
+                    }
+                    state
+                        .completed_transcriptions
+                        .push_back((update.transcription_id, update.text.clone()));
+                }
+            }
+        }
+
+        if !state.chat_updates.is_empty() {
+            updates.retain(|update| !route_chat_update(&mut state.chat_updates, update, &chat_map));
+        }
 
         if let Some(limit) = self.0.config.params.update_queue_limit {
             if let Some(exceeds) = (state.updates.len() + updates.len()).checked_sub(limit + 1) {
@@ -334,6 +491,103 @@ impl Client {
             .extend(updates.into_iter().map(|u| (u, chat_map.clone())));
     }
 
+    /// Save the updates state if a checkpoint is due, as configured by
+    /// [`InitParams::update_state_checkpoint`](crate::InitParams::update_state_checkpoint).
+    fn checkpoint_update_state(&self) {
+        let Some(checkpoint) = self.0.config.params.update_state_checkpoint.as_ref() else {
+            return;
+        };
+
+        {
+            let state = &mut *self.0.state.write().unwrap();
+            if state
+                .last_update_checkpoint
+                .is_some_and(|last| last.elapsed() < checkpoint.interval)
+            {
+                return;
+            }
+            // Try again once the rest of the updates are returned, so none are lost on a crash.
+            let Some(update_state) = returned_update_state(state) else {
+                return;
+            };
+            state.last_update_checkpoint = Some(Instant::now());
+            self.0.config.session.set_state(update_state);
+        }
+
+        let Some(path) = checkpoint.session_path.clone() else {
+            return;
+        };
+        // The session remains dirty if a save is skipped, so the next checkpoint will save it.
+        if self.0.saving_checkpoint.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let inner = Arc::clone(&self.0);
+        let save = move || {
+            if let Err(e) = inner.config.session.save_if_dirty(&path) {
+                log::warn!(
+                    "failed to save session during update state checkpoint: {}",
+                    e
+                );
+            }
+            inner.saving_checkpoint.store(false, Ordering::Release);
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(save)),
+            Err(_) => save(),
+        }
+    }
+
+    /// Returns a stream with the updates which occur in the given chat.
+    ///
+    /// While the stream is alive, updates from this chat (new and edited messages, deleted
+    /// channel messages, callback queries and member changes) are sent to it directly, and will no longer be
+    /// returned by [`Client::next_update`]. Dropping the stream restores the previous behaviour.
+    ///
+    /// This is useful to have a separate task handle a conversation, without the need to buffer
+    /// and filter every update received by the client.
+    ///
+    /// Several streams may exist for the same chat, in which case each of them gets a copy of
+    /// the updates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::Update;
+    ///
+    /// let mut updates = client.updates_for_chat(&chat);
+    /// client.send_message(&chat, "What's your name?").await?;
+    ///
+    /// loop {
+    ///     if let Update::NewMessage(message) = updates.next().await? {
+    ///         if !message.outgoing() {
+    ///             client.send_message(&chat, format!("Hello, {}!", message.text())).await?;
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn updates_for_chat<C: Into<PackedChat>>(&self, chat: C) -> ChatUpdates {
+        let peer = Peer::from(&chat.into().to_peer());
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.0
+            .state
+            .write()
+            .unwrap()
+            .chat_updates
+            .entry(peer)
+            .or_default()
+            .push(tx);
+
+        ChatUpdates {
+            client: self.clone(),
+            peer,
+            rx,
+        }
+    }
+
     /// Synchronize the updates state to the session.
     pub fn sync_update_state(&self) {
         let state = self.0.state.read().unwrap();
@@ -343,6 +597,43 @@ impl Client {
             .set_state(state.message_box.session_state());
     }
 
+    /// Return the current update state, as known by the client.
+    ///
+    /// This includes the account-wide `pts`, `qts`, `seq` and `date`, along with the `pts` of
+    /// every channel being tracked. It is what [`Client::sync_update_state`] saves to the
+    /// session, and is mostly useful when debugging missed updates.
+    pub fn sync_state(&self) -> UpdateState {
+        self.0.state.read().unwrap().message_box.session_state()
+    }
+
+    /// Fetch the difference of the account-wide updates on the next call to
+    /// [`Client::next_update`], even if no gap was detected.
+    ///
+    /// Returns `false` if the update state is not known yet, in which case there is nothing to
+    /// catch up on.
+    pub fn request_difference(&self) -> bool {
+        self.0
+            .state
+            .write()
+            .unwrap()
+            .message_box
+            .request_difference()
+    }
+
+    /// Fetch the difference of a channel on the next call to [`Client::next_update`], even if
+    /// no gap was detected.
+    ///
+    /// Returns `false` if the channel is not being tracked, in which case
+    /// [`Client::subscribe_to_channel`] should be used instead.
+    pub fn request_channel_difference(&self, channel_id: i64) -> bool {
+        self.0
+            .state
+            .write()
+            .unwrap()
+            .message_box
+            .request_channel_difference(channel_id)
+    }
+
     /// Mark a channel for aggressive polling (100ms interval).
     ///
     /// By default, channels use the standard 15-minute polling interval and rely on
@@ -392,10 +683,123 @@ impl Client {
     }
 }
 
+/// Stream of the updates which occur in a single chat.
+///
+/// See [`Client::updates_for_chat`].
+pub struct ChatUpdates {
+    client: Client,
+    peer: Peer,
+    rx: mpsc::UnboundedReceiver<(tl::enums::Update, Arc<ChatMap>)>,
+}
+
+impl ChatUpdates {
+    /// Returns the next update which occurred in the chat.
+    ///
+    /// The network is driven while waiting, so this can be used without calling
+    /// [`Client::next_update`] elsewhere. However, updates which were missed (for example, due
+    /// to a disconnection) are only fetched by [`Client::next_update`].
+    pub async fn next(&mut self) -> Result<Update, InvocationError> {
+        loop {
+            let (update, chats) = match self.rx.try_recv() {
+                Ok(update) => update,
+                Err(_) => {
+                    let recv = pin!(self.rx.recv());
+                    let step = pin!(self.client.step());
+                    match select(recv, step).await {
+                        Either::Left((Some(update), _)) => update,
+                        // The client holds the sender for as long as this stream exists.
+                        Either::Left((None, _)) => unreachable!(),
+                        Either::Right((step, _)) => {
+                            step?;
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            if let Some(update) = Update::new(&self.client, update, &chats) {
+                return Ok(update);
+            }
+        }
+    }
+}
+
+impl Drop for ChatUpdates {
+    fn drop(&mut self) {
+        self.rx.close();
+        let mut state = self.client.0.state.write().unwrap();
+        if let Some(senders) = state.chat_updates.get_mut(&self.peer) {
+            senders.retain(|tx| !tx.is_closed());
+            if senders.is_empty() {
+                state.chat_updates.remove(&self.peer);
+            }
+        }
+    }
+}
+
+/// The updates state to checkpoint, unless it accounts for updates which were not returned yet.
+fn returned_update_state(state: &ClientState) -> Option<UpdateState> {
+    if state.updates.is_empty() && state.unqueued_batches == 0 {
+        Some(state.message_box.session_state())
+    } else {
+        None
+    }
+}
+
+/// Send the update to the streams of the chat it belongs to, if any.
+///
+/// Returns `true` if the update was delivered to at least one stream.
+fn route_chat_update(
+    chat_updates: &mut HashMap<Peer, Vec<ChatUpdateSender>>,
+    update: &tl::enums::Update,
+    chat_map: &Arc<ChatMap>,
+) -> bool {
+    let Some(peer) = update_peer(update) else {
+        return false;
+    };
+    let Some(senders) = chat_updates.get_mut(&peer) else {
+        return false;
+    };
+
+    senders.retain(|tx| tx.send((update.clone(), chat_map.clone())).is_ok());
+    if senders.is_empty() {
+        chat_updates.remove(&peer);
+        false
+    } else {
+        true
+    }
+}
+
+/// The chat in which the update occurred, if it belongs to one.
+fn update_peer(update: &tl::enums::Update) -> Option<Peer> {
+    use tl::enums::Update as U;
+
+    let message = match update {
+        U::NewMessage(update) => &update.message,
+        U::NewChannelMessage(update) => &update.message,
+        U::EditMessage(update) => &update.message,
+        U::EditChannelMessage(update) => &update.message,
+        U::DeleteChannelMessages(update) => return Some(Peer::Channel(update.channel_id)),
+        U::BotCallbackQuery(update) => return Some((&update.peer).into()),
+        U::ChannelParticipant(update) => return Some(Peer::Channel(update.channel_id)),
+        U::ChatParticipant(update) => return Some(Peer::Chat(update.chat_id)),
+        U::BotChatInviteRequester(update) => return Some((&update.peer).into()),
+        U::PendingJoinRequests(update) => return Some((&update.peer).into()),
+        _ => return None,
+    };
+
+    match message {
+        tl::enums::Message::Message(message) => Some((&message.peer_id).into()),
+        tl::enums::Message::Service(message) => Some((&message.peer_id).into()),
+        tl::enums::Message::Empty(message) => message.peer_id.as_ref().map(Peer::from),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use core::future::Future;
+    use grammers_session::{ChatHashCache, MessageBox};
 
     fn get_client() -> Client {
         panic!()
@@ -410,4 +814,87 @@ mod tests {
             typeck(get_client().next_update());
         }
     }
+
+    #[test]
+    fn route_chat_updates_by_peer() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut chat_updates = HashMap::from([(Peer::Channel(1), vec![tx])]);
+        let chats = ChatMap::empty();
+        let delete = |channel_id| {
+            tl::types::UpdateDeleteChannelMessages {
+                channel_id,
+                messages: vec![1],
+                pts: 1,
+                pts_count: 1,
+            }
+            .into()
+        };
+
+        assert!(route_chat_update(&mut chat_updates, &delete(1), &chats));
+        assert!(!route_chat_update(&mut chat_updates, &delete(2), &chats));
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+
+        // Once the stream is gone, the chat is no longer routed.
+        drop(rx);
+        assert!(!route_chat_update(&mut chat_updates, &delete(1), &chats));
+        assert!(chat_updates.is_empty());
+    }
+
+    #[test]
+    fn queued_updates_are_redelivered_after_reload() {
+        let session = grammers_session::Session::new();
+        session.set_state(UpdateState {
+            pts: 1,
+            qts: 0,
+            date: 1,
+            seq: 0,
+            channels: Vec::new(),
+        });
+        let mut message_box = MessageBox::load(session.get_state().unwrap());
+        let (updates, _, _) = message_box
+            .process_updates(
+                tl::types::UpdateShort {
+                    update: tl::types::UpdateDeleteMessages {
+                        messages: vec![1],
+                        pts: 2,
+                        pts_count: 1,
+                    }
+                    .into(),
+                    date: 2,
+                }
+                .into(),
+                &ChatHashCache::new(None),
+            )
+            .unwrap();
+        assert_eq!(message_box.session_state().pts, 2);
+
+        let chats = ChatMap::empty();
+        let mut state = ClientState {
+            message_box,
+            last_update_limit_warn: None,
+            last_update_checkpoint: None,
+            updates: updates.into_iter().map(|u| (u, chats.clone())).collect(),
+            unqueued_batches: 0,
+            chat_updates: HashMap::new(),
+            avatar_downloads: Default::default(),
+            transcriptions: HashMap::new(),
+            completed_transcriptions: Default::default(),
+        };
+
+        // The update is still queued, so the state which accounts for it must not be saved,
+        // and a client reloading the session fetches the update again.
+        assert!(returned_update_state(&state).is_none());
+        let reloaded = MessageBox::load(session.get_state().unwrap());
+        assert_eq!(reloaded.session_state().pts, 1);
+
+        // Updates applied to the message box but not queued yet must not be lost either.
+        state.updates.pop_front();
+        state.unqueued_batches = 1;
+        assert!(returned_update_state(&state).is_none());
+
+        state.unqueued_batches = 0;
+        session.set_state(returned_update_state(&state).unwrap());
+        assert_eq!(session.get_state().unwrap().pts, 2);
+    }
 }
@@ -0,0 +1,253 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Composable predicates to decide which messages or updates to handle.
+//!
+//! Filters can be combined with `&`, `|` and `!`, and checked with [`Filter::check`]:
+//!
+//! ```
+//! # fn f(update: grammers_client::Update) {
+//! use grammers_client::filters;
+//!
+//! let greeting = filters::is_private() & filters::text_glob("hello*");
+//! let filter = filters::message(greeting | filters::chat_ids([1234]));
+//!
+//! if filter.check(&update) {
+//!     println!("Should handle this update");
+//! }
+//! # }
+//! ```
+//!
+//! There is no built-in regular expression support, but any engine can be used through
+//! [`text`]:
+//!
+//! ```ignore
+//! let re = regex::Regex::new(r"^/ban (\d+)$").unwrap();
+//! let filter = filters::text(move |text| re.is_match(text));
+//! ```
+use crate::Update;
+use crate::types::{Chat, Media, Message};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not};
+use std::sync::Arc;
+
+/// A predicate over values of type `T`, usually [`Message`] or [`Update`].
+///
+/// Filters are cheap to clone and can be shared between tasks.
+pub struct Filter<T: ?Sized> {
+    predicate: Arc<dyn Fn(&T) -> bool + Send + Sync>,
+}
+
+impl<T: ?Sized> Filter<T> {
+    /// Create a new filter from an arbitrary predicate.
+    pub fn new<F: Fn(&T) -> bool + Send + Sync + 'static>(predicate: F) -> Self {
+        Self {
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    /// Check whether the value passes the filter.
+    pub fn check(&self, value: &T) -> bool {
+        (self.predicate)(value)
+    }
+}
+
+impl<T: ?Sized> Clone for Filter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            predicate: Arc::clone(&self.predicate),
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Filter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter").finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized + 'static> BitAnd for Filter<T> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self::new(move |value| self.check(value) && rhs.check(value))
+    }
+}
+
+impl<T: ?Sized + 'static> BitOr for Filter<T> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self::new(move |value| self.check(value) || rhs.check(value))
+    }
+}
+
+impl<T: ?Sized + 'static> Not for Filter<T> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::new(move |value| !self.check(value))
+    }
+}
+
+/// A filter which lets everything through.
+pub fn all<T: ?Sized + 'static>() -> Filter<T> {
+    Filter::new(|_| true)
+}
+
+/// Apply a message filter to the updates which carry a message: new and edited messages.
+///
+/// Any other update is filtered out.
+pub fn message(filter: Filter<Message>) -> Filter<Update> {
+    Filter::new(move |update| match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => filter.check(message),
+        _ => false,
+    })
+}
+
+/// Messages whose text passes the given predicate. This can be used with regular expressions.
+pub fn text<F: Fn(&str) -> bool + Send + Sync + 'static>(predicate: F) -> Filter<Message> {
+    Filter::new(move |message: &Message| predicate(message.text()))
+}
+
+/// Messages whose text matches the glob `pattern`, where `*` matches any sequence of
+/// characters and `?` matches a single character. The match is case-sensitive and must cover
+/// the whole text.
+pub fn text_glob(pattern: &str) -> Filter<Message> {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    text(move |text| glob_match(&pattern, text))
+}
+
+/// Messages sent in any of the chats with the given identifiers.
+pub fn chat_ids<I: IntoIterator<Item = i64>>(ids: I) -> Filter<Message> {
+    let ids = ids.into_iter().collect::<Vec<_>>();
+    Filter::new(move |message: &Message| ids.contains(&message.chat().id()))
+}
+
+/// Messages sent by any of the users or chats with the given identifiers.
+///
+/// This can be used to only let administrators through, by passing the identifiers of the
+/// administrators of a group (which can be fetched with
+/// [`Client::iter_participants`](crate::Client::iter_participants)).
+pub fn sender_ids<I: IntoIterator<Item = i64>>(ids: I) -> Filter<Message> {
+    let ids = ids.into_iter().collect::<Vec<_>>();
+    Filter::new(move |message: &Message| {
+        message
+            .sender()
+            .is_some_and(|sender| ids.contains(&sender.id()))
+    })
+}
+
+/// Messages with any kind of media.
+pub fn has_media() -> Filter<Message> {
+    Filter::new(|message: &Message| message.has_media())
+}
+
+/// Messages whose media passes the given predicate, such as
+/// `|media| matches!(media, Media::Photo(_))`.
+pub fn media<F: Fn(&Media) -> bool + Send + Sync + 'static>(predicate: F) -> Filter<Message> {
+    Filter::new(move |message: &Message| message.media().is_some_and(|media| predicate(&media)))
+}
+
+/// Messages sent in the given forum topic.
+pub fn is_forum_topic(topic_id: i32) -> Filter<Message> {
+    Filter::new(move |message: &Message| message.is_in_topics(&[topic_id]))
+}
+
+/// Messages sent in private conversations.
+pub fn is_private() -> Filter<Message> {
+    Filter::new(|message: &Message| matches!(message.chat(), Chat::User(_)))
+}
+
+/// Messages sent by the logged-in account.
+pub fn is_outgoing() -> Filter<Message> {
+    Filter::new(|message: &Message| message.outgoing())
+}
+
+/// Messages sent by an anonymous administrator on behalf of the group itself.
+///
+/// Whether other senders are administrators cannot be known from the message alone, so
+/// [`sender_ids`] should be used for those instead.
+pub fn is_anonymous_admin() -> Filter<Message> {
+    Filter::new(|message: &Message| {
+        let chat = message.chat();
+        matches!(chat, Chat::Group(_))
+            && message
+                .sender()
+                .is_some_and(|sender| sender.id() == chat.id())
+    })
+}
+
+fn glob_match(pattern: &[char], text: &str) -> bool {
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*`, if the match fails.
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        glob_match(&pattern.chars().collect::<Vec<_>>(), text)
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob("hello", "hello"));
+        assert!(!glob("hello", "hello world"));
+        assert!(glob("hello*", "hello world"));
+        assert!(glob("*world", "hello world"));
+        assert!(glob("h?llo*d", "hallo world"));
+        assert!(glob("*o*o*", "foo boo"));
+        assert!(!glob("*x*", "foo boo"));
+        assert!(glob("*", ""));
+        assert!(!glob("?", ""));
+        assert!(glob("ñ?*", "ñandú"));
+    }
+
+    #[test]
+    fn combinators() {
+        let even = Filter::new(|n: &i32| n % 2 == 0);
+        let positive = Filter::new(|n: &i32| *n > 0);
+
+        let filter = even.clone() & positive.clone();
+        assert!(filter.check(&2));
+        assert!(!filter.check(&-2));
+
+        let filter = even | !positive;
+        assert!(filter.check(&2));
+        assert!(filter.check(&-3));
+        assert!(!filter.check(&3));
+        assert!(all().check(&3));
+    }
+}
@@ -0,0 +1,429 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Detection of the type of a file from its contents.
+//!
+//! Only the first bytes of the file are inspected, which is enough to determine the type of the
+//! file, the dimensions of images, and the metadata of MP3 files in most cases.
+use grammers_tl_types as tl;
+
+/// Photos must be smaller than this on each side.
+const MAX_PHOTO_SIDE: i32 = 10000;
+/// Photos cannot be more than this many times wider than they are tall, or the other way round.
+const MAX_PHOTO_ASPECT_RATIO: i32 = 20;
+
+/// Information about a file inferred from its contents.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct FileInfo {
+    pub(crate) mime_type: Option<&'static str>,
+    pub(crate) dimensions: Option<(i32, i32)>,
+    pub(crate) audio: Option<AudioInfo>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct AudioInfo {
+    pub(crate) duration: Option<i32>,
+    pub(crate) title: Option<String>,
+    pub(crate) performer: Option<String>,
+}
+
+impl FileInfo {
+    /// The document attributes which can be derived from this information.
+    pub(crate) fn attributes(&self) -> Vec<tl::enums::DocumentAttribute> {
+        let mut attributes = Vec::new();
+        if let Some((w, h)) = self.dimensions {
+            attributes.push(tl::types::DocumentAttributeImageSize { w, h }.into());
+        }
+        if let Some(audio) = &self.audio {
+            attributes.push(
+                tl::types::DocumentAttributeAudio {
+                    voice: false,
+                    duration: audio.duration.unwrap_or(0),
+                    title: audio.title.clone(),
+                    performer: audio.performer.clone(),
+                    waveform: None,
+                }
+                .into(),
+            );
+        }
+        attributes
+    }
+
+    /// Whether an image with these dimensions can be sent as a photo, instead of a document.
+    ///
+    /// Images whose dimensions are unknown are assumed to fit.
+    pub(crate) fn fits_photo(&self) -> bool {
+        match self.dimensions {
+            Some((w, h)) => {
+                let (short, long) = (w.min(h), w.max(h));
+                short > 0
+                    && long < MAX_PHOTO_SIDE
+                    && long <= short.saturating_mul(MAX_PHOTO_ASPECT_RATIO)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Infer information about a file, given its first bytes and total size.
+pub(crate) fn infer(head: &[u8], size: usize) -> FileInfo {
+    let mut info = FileInfo::default();
+
+    if head.starts_with(&[0xff, 0xd8, 0xff]) {
+        info.mime_type = Some("image/jpeg");
+        info.dimensions = jpeg_dimensions(head);
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        info.mime_type = Some("image/png");
+        info.dimensions = png_dimensions(head);
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        info.mime_type = Some("image/gif");
+        info.dimensions = gif_dimensions(head);
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+        info.mime_type = Some("image/webp");
+        info.dimensions = webp_dimensions(head);
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WAVE") {
+        info.mime_type = Some("audio/wav");
+    } else if let Some(start) = id3_size(head) {
+        // ID3 tags may also precede other formats, such as FLAC, so probe what follows them.
+        let start = audio_start(head, start);
+        match head.get(start..).filter(|rest| !rest.is_empty()) {
+            Some(rest) if mpeg_frame_at(rest, 0).is_none() => {
+                info.mime_type = infer(rest, size.saturating_sub(start)).mime_type;
+            }
+            _ => {
+                info.mime_type = Some("audio/mpeg");
+                info.audio = Some(mp3_info(head, size));
+            }
+        }
+    } else if mpeg_frame_at(head, 0).is_some() {
+        info.mime_type = Some("audio/mpeg");
+        info.audio = Some(mp3_info(head, size));
+    } else if head.starts_with(b"OggS") {
+        info.mime_type = Some("audio/ogg");
+    } else if head.starts_with(b"fLaC") {
+        info.mime_type = Some("audio/flac");
+    } else if head.get(4..8) == Some(b"ftyp") {
+        info.mime_type = Some(match head.get(8..12) {
+            Some(b"M4A ") | Some(b"M4B ") => "audio/mp4",
+            Some(b"qt  ") => "video/quicktime",
+            _ => "video/mp4",
+        });
+    } else if head.starts_with(&[0x1a, 0x45, 0xdf, 0xa3]) {
+        info.mime_type = Some(if find(head, b"webm").is_some() {
+            "video/webm"
+        } else {
+            "video/x-matroska"
+        });
+    } else if head.starts_with(b"%PDF-") {
+        info.mime_type = Some("application/pdf");
+    } else if head.starts_with(b"PK\x03\x04") {
+        info.mime_type = Some("application/zip");
+    }
+
+    info
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn u16_be(bytes: &[u8], at: usize) -> Option<i32> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as i32)
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> Option<i32> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as i32)
+}
+
+fn u24_le(bytes: &[u8], at: usize) -> Option<i32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(b[0] as i32 | (b[1] as i32) << 8 | (b[2] as i32) << 16)
+}
+
+fn u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn png_dimensions(head: &[u8]) -> Option<(i32, i32)> {
+    // The IHDR chunk always comes first, right after the signature.
+    if head.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((u32_be(head, 16)? as i32, u32_be(head, 20)? as i32))
+}
+
+fn gif_dimensions(head: &[u8]) -> Option<(i32, i32)> {
+    Some((u16_le(head, 6)?, u16_le(head, 8)?))
+}
+
+fn jpeg_dimensions(head: &[u8]) -> Option<(i32, i32)> {
+    let mut i = 2;
+    loop {
+        if *head.get(i)? != 0xff {
+            return None;
+        }
+        let marker = *head.get(i + 1)?;
+        match marker {
+            // Padding.
+            0xff => i += 1,
+            // Markers without length.
+            0x01 | 0xd0..=0xd7 => i += 2,
+            // Start of frame, except for DHT, JPG and DAC which share the range.
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                return Some((u16_be(head, i + 7)?, u16_be(head, i + 5)?));
+            }
+            _ => i += 2 + u16_be(head, i + 2)? as usize,
+        }
+    }
+}
+
+fn webp_dimensions(head: &[u8]) -> Option<(i32, i32)> {
+    match head.get(12..16)? {
+        b"VP8 " => Some((u16_le(head, 26)? & 0x3fff, u16_le(head, 28)? & 0x3fff)),
+        b"VP8L" => {
+            let b = head.get(21..25)?;
+            let bits = u32::from_le_bytes(b.try_into().ok()?);
+            Some((
+                (bits & 0x3fff) as i32 + 1,
+                ((bits >> 14) & 0x3fff) as i32 + 1,
+            ))
+        }
+        b"VP8X" => Some((u24_le(head, 24)? + 1, u24_le(head, 27)? + 1)),
+        _ => None,
+    }
+}
+
+/// Size of the ID3v2 tag at the start of the file, including its header, if any.
+fn id3_size(head: &[u8]) -> Option<usize> {
+    if !head.starts_with(b"ID3") {
+        return None;
+    }
+    let size = head.get(6..10)?;
+    let size = size
+        .iter()
+        .fold(0usize, |acc, &b| (acc << 7) | (b & 0x7f) as usize);
+    Some(10 + size)
+}
+
+/// Position where the audio starts after the ID3v2 tag, skipping any padding after it.
+fn audio_start(head: &[u8], tag_size: usize) -> usize {
+    let padding = head
+        .get(tag_size..)
+        .map_or(0, |rest| rest.iter().take_while(|&&b| b == 0).count());
+    tag_size + padding
+}
+
+/// Bitrate in bits per second of the MPEG audio frame starting at `at`, if it's valid.
+fn mpeg_frame_at(head: &[u8], at: usize) -> Option<u32> {
+    const BITRATES_V1_L3: [u32; 16] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0,
+    ];
+    const BITRATES_V2_L3: [u32; 16] = [
+        0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0,
+    ];
+
+    let header = head.get(at..at + 4)?;
+    // Frame sync, and layer III.
+    if header[0] != 0xff || header[1] & 0xe0 != 0xe0 || (header[1] >> 1) & 0x03 != 0x01 {
+        return None;
+    }
+    let bitrates = match (header[1] >> 3) & 0x03 {
+        0b11 => &BITRATES_V1_L3,
+        0b10 | 0b00 => &BITRATES_V2_L3,
+        _ => return None,
+    };
+    match bitrates[(header[2] >> 4) as usize] {
+        0 => None,
+        kbps => Some(kbps * 1000),
+    }
+}
+
+fn mp3_info(head: &[u8], size: usize) -> AudioInfo {
+    let mut info = AudioInfo::default();
+    let tag_size = id3_size(head).unwrap_or(0);
+
+    if tag_size > 0 {
+        let version = head[3];
+        let mut i = 10;
+        while i + 10 <= tag_size.min(head.len()) {
+            let id = &head[i..i + 4];
+            if id[0] == 0 {
+                break;
+            }
+            let frame_size = match version {
+                // Version 2.4 uses synchsafe integers, like the tag header.
+                4 => head[i + 4..i + 8]
+                    .iter()
+                    .fold(0usize, |acc, &b| (acc << 7) | (b & 0x7f) as usize),
+                _ => u32_be(head, i + 4).unwrap_or(0) as usize,
+            };
+            let Some(data) = head.get(i + 10..i + 10 + frame_size) else {
+                break;
+            };
+            match id {
+                b"TIT2" => info.title = id3_text(data),
+                b"TPE1" => info.performer = id3_text(data),
+                b"TLEN" => {
+                    info.duration = id3_text(data)
+                        .and_then(|ms| ms.trim().parse::<i64>().ok())
+                        .map(|ms| (ms / 1000) as i32)
+                }
+                _ => {}
+            }
+            i += 10 + frame_size;
+        }
+    }
+
+    if info.duration.is_none() {
+        // Assume a constant bitrate, which is wrong for variable bitrate files, but it's the
+        // best that can be done without reading the entire file.
+        let start = audio_start(head, tag_size);
+        if let Some(bitrate) = mpeg_frame_at(head, start) {
+            let audio_bytes = size.saturating_sub(start) as u64;
+            info.duration = Some((audio_bytes * 8 / bitrate as u64) as i32);
+        }
+    }
+
+    info
+}
+
+/// Decode the text of an ID3v2 text frame.
+fn id3_text(data: &[u8]) -> Option<String> {
+    let (&encoding, text) = data.split_first()?;
+    let text = match encoding {
+        // ISO-8859-1, where every byte maps to the same code point.
+        0 => text.iter().map(|&b| b as char).collect(),
+        // UTF-16 with BOM, or UTF-16BE.
+        1 | 2 => {
+            let big_endian = encoding == 2 || text.starts_with(&[0xfe, 0xff]);
+            let text = if encoding == 1 { text.get(2..)? } else { text };
+            let units = text.chunks_exact(2).map(|b| {
+                if big_endian {
+                    u16::from_be_bytes([b[0], b[1]])
+                } else {
+                    u16::from_le_bytes([b[0], b[1]])
+                }
+            });
+            char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect()
+        }
+        3 => String::from_utf8_lossy(text).into_owned(),
+        _ => return None,
+    };
+    let text = text.trim_end_matches('\0').to_string();
+    if text.is_empty() { None } else { Some(text) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infer_png() {
+        let mut head = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        head.extend(640u32.to_be_bytes());
+        head.extend(480u32.to_be_bytes());
+
+        let info = infer(&head, head.len());
+        assert_eq!(info.mime_type, Some("image/png"));
+        assert_eq!(info.dimensions, Some((640, 480)));
+    }
+
+    #[test]
+    fn infer_jpeg() {
+        let head = [
+            0xff, 0xd8, // SOI
+            0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, // APP0 with 2 bytes of data
+            0xff, 0xc0, 0x00, 0x11, 0x08, 0x01, 0xe0, 0x02, 0x80, // SOF0, 480x640
+        ];
+
+        let info = infer(&head, head.len());
+        assert_eq!(info.mime_type, Some("image/jpeg"));
+        assert_eq!(info.dimensions, Some((640, 480)));
+    }
+
+    #[test]
+    fn photo_dimensions() {
+        let fits = |dimensions| {
+            FileInfo {
+                dimensions,
+                ..Default::default()
+            }
+            .fits_photo()
+        };
+        assert!(fits(None));
+        assert!(fits(Some((640, 480))));
+        assert!(fits(Some((9999, 500))));
+        assert!(fits(Some((50, 1000))));
+        assert!(!fits(Some((10000, 600))));
+        assert!(!fits(Some((50, 1001))));
+        assert!(!fits(Some((0, 480))));
+    }
+
+    #[test]
+    fn infer_mp3_with_id3() {
+        let mut frames = Vec::new();
+        for (id, text) in [(b"TIT2", "Title"), (b"TPE1", "Artist"), (b"TLEN", "61000")] {
+            frames.extend(id);
+            frames.extend((text.len() as u32 + 1).to_be_bytes());
+            frames.extend([0, 0, 0]);
+            frames.extend(text.as_bytes());
+        }
+        let mut head = b"ID3\x03\x00\x00".to_vec();
+        head.extend([0, 0, 0, frames.len() as u8]);
+        head.extend(frames);
+
+        let info = infer(&head, 1024 * 1024);
+        assert_eq!(info.mime_type, Some("audio/mpeg"));
+        assert_eq!(
+            info.audio,
+            Some(AudioInfo {
+                duration: Some(61),
+                title: Some("Title".to_string()),
+                performer: Some("Artist".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn infer_mp3_duration_from_bitrate() {
+        // MPEG-1 layer III, 128 kbps.
+        let head = [0xff, 0xfb, 0x90, 0x00];
+
+        let info = infer(&head, 16000 * 10);
+        assert_eq!(info.mime_type, Some("audio/mpeg"));
+        assert_eq!(info.audio.unwrap().duration, Some(10));
+    }
+
+    #[test]
+    fn infer_after_id3() {
+        let mut head = b"ID3\x04\x00\x00\x00\x00\x00\x04".to_vec();
+        head.extend([0; 4 + 2]);
+
+        // MPEG-1 layer III, 128 kbps, after the tag and some padding.
+        let mut mp3 = head.clone();
+        mp3.extend([0xff, 0xfb, 0x90, 0x00]);
+        let info = infer(&mp3, 16 + 16000 * 10);
+        assert_eq!(info.mime_type, Some("audio/mpeg"));
+        assert_eq!(info.audio.unwrap().duration, Some(10));
+
+        let mut flac = head;
+        flac.extend(b"fLaC");
+        let info = infer(&flac, 1024);
+        assert_eq!(info.mime_type, Some("audio/flac"));
+        assert_eq!(info.audio, None);
+    }
+
+    #[test]
+    fn infer_unknown() {
+        assert_eq!(infer(b"hello world", 11), FileInfo::default());
+    }
+}
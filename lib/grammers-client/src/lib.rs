@@ -45,16 +45,25 @@
 //! [Telegram Bot API]: https://core.telegram.org/bots/api
 //! [obtain a developer API ID]: https://my.telegram.org/auth
 pub mod client;
+pub mod filters;
+#[cfg(feature = "infer")]
+pub(crate) mod infer;
 pub mod parsers;
 pub mod types;
 pub(crate) mod utils;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
 #[cfg(all(feature = "fs", target_arch = "wasm32", target_os = "unknown"))]
 compile_error!("The `fs` feature is not supported on wasm32-unknown-unknown.");
 
-pub use client::{Client, Config, InitParams, SignInError};
+pub use client::{
+    AvatarCache, Client, Config, InitParams, RawUpdateHandler, SignInError, TimeOffsetHandler,
+    UpdateStateCheckpoint, UploadCache, UploadOptions,
+};
 pub use types::{ChatMap, InputMedia, InputMessage, Update, button, reply_markup};
 
+pub use grammers_mtproto::mtp::CompressionStats;
 pub use grammers_mtproto::transport;
 pub use grammers_mtsender::{
     FixedReconnect, InvocationError, NoReconnect, ReconnectionPolicy, ServerAddr,
@@ -0,0 +1,511 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![cfg(feature = "markdown")]
+
+use super::common::MENTION_URL_PREFIX;
+use crate::utils::entity_range;
+use grammers_tl_types as tl;
+
+const CUSTOM_EMOJI_URL_PREFIX: &str = "tg://emoji?id=";
+
+/// The markers which can wrap text (and other entities) in MarkdownV2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Marker {
+    Bold,
+    Italic,
+    Underline,
+    Strike,
+    Spoiler,
+    Link { emoji: bool },
+}
+
+impl Marker {
+    fn delimiter(self) -> &'static str {
+        match self {
+            Self::Bold => "*",
+            Self::Italic => "_",
+            Self::Underline => "__",
+            Self::Strike => "~",
+            Self::Spoiler => "||",
+            Self::Link { .. } => "]",
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    text: String,
+    offset: i32,
+    entities: Vec<tl::enums::MessageEntity>,
+    open: Vec<(Marker, i32)>,
+    // Start offset and whether it's collapsed, plus where the last quoted line ended.
+    quote: Option<(i32, bool)>,
+    quote_end: i32,
+}
+
+/// Parse a message in the [MarkdownV2] dialect of the Bot API.
+///
+/// Unlike the Bot API, reserved characters which are not escaped are not an error. If they
+/// don't form a valid entity, they are kept as-is.
+///
+/// [MarkdownV2]: https://core.telegram.org/bots/api#markdownv2-style
+pub fn parse_markdown_v2_message(message: &str) -> (String, Vec<tl::enums::MessageEntity>) {
+    let mut parser = Parser {
+        chars: message.chars().collect(),
+        pos: 0,
+        text: String::with_capacity(message.len()),
+        offset: 0,
+        entities: Vec::new(),
+        open: Vec::new(),
+        quote: None,
+        quote_end: 0,
+    };
+    parser.parse();
+
+    let Parser { text, entities, .. } = parser;
+    (text, entities)
+}
+
+impl Parser {
+    fn parse(&mut self) {
+        let mut line_start = true;
+        while self.pos < self.chars.len() {
+            if line_start {
+                self.quote_line();
+                line_start = false;
+                continue;
+            }
+
+            let c = self.chars[self.pos];
+            match c {
+                '\\' => match self.chars.get(self.pos + 1) {
+                    Some(&next) if is_escapable(next) => {
+                        self.push(next);
+                        self.pos += 2;
+                    }
+                    _ => self.literal(1),
+                },
+                '\n' => {
+                    if self.quote.is_some() {
+                        self.quote_end = self.offset;
+                    }
+                    self.literal(1);
+                    line_start = true;
+                }
+                '`' if self.starts_with("```") => self.pre(),
+                '`' => self.code(),
+                '|' if self.starts_with("||") => {
+                    if self.quote.is_some_and(|(_, collapsed)| collapsed)
+                        && !self.is_open(Marker::Spoiler)
+                        && self.at_line_end(2)
+                    {
+                        // The end of an expandable block quotation.
+                        self.pos += 2;
+                    } else {
+                        self.marker(Marker::Spoiler);
+                    }
+                }
+                '_' if self.starts_with("__") => self.marker(Marker::Underline),
+                '_' => self.marker(Marker::Italic),
+                '*' => self.marker(Marker::Bold),
+                '~' => self.marker(Marker::Strike),
+                '!' if self.starts_with("![") => {
+                    if self.link_follows(self.pos + 2) {
+                        self.open.push((Marker::Link { emoji: true }, self.offset));
+                        self.pos += 2;
+                    } else {
+                        self.literal(1);
+                    }
+                }
+                '[' => {
+                    if self.link_follows(self.pos + 1) {
+                        self.open.push((Marker::Link { emoji: false }, self.offset));
+                        self.pos += 1;
+                    } else {
+                        self.literal(1);
+                    }
+                }
+                ']' => self.link_end(),
+                _ => self.literal(1),
+            }
+        }
+
+        self.end_quote(self.offset);
+        self.entities.sort_by_key(|e| {
+            let (offset, length) = entity_range(e);
+            (offset, -length)
+        });
+    }
+
+    /// Handle the block quotation markers at the start of a line.
+    fn quote_line(&mut self) {
+        let collapsed = self.starts_with("**>");
+        if collapsed || self.starts_with(">") {
+            if self.quote.is_none() {
+                self.quote = Some((self.offset, collapsed));
+            }
+            self.pos += if collapsed { 3 } else { 1 };
+        } else {
+            self.end_quote(self.quote_end);
+        }
+    }
+
+    fn end_quote(&mut self, end: i32) {
+        if let Some((offset, collapsed)) = self.quote.take()
+            && end > offset
+        {
+            self.entities.push(
+                tl::types::MessageEntityBlockquote {
+                    collapsed,
+                    offset,
+                    length: end - offset,
+                }
+                .into(),
+            );
+        }
+    }
+
+    /// Open or close a marker, or keep it as-is if it's unmatched.
+    fn marker(&mut self, marker: Marker) {
+        let len = marker.delimiter().len();
+        if let Some(index) = self.open.iter().rposition(|(m, _)| *m == marker) {
+            let (_, offset) = self.open.remove(index);
+            self.pos += len;
+            let length = self.offset - offset;
+            if length == 0 {
+                return;
+            }
+            self.entities.push(match marker {
+                Marker::Bold => tl::types::MessageEntityBold { offset, length }.into(),
+                Marker::Italic => tl::types::MessageEntityItalic { offset, length }.into(),
+                Marker::Underline => tl::types::MessageEntityUnderline { offset, length }.into(),
+                Marker::Strike => tl::types::MessageEntityStrike { offset, length }.into(),
+                Marker::Spoiler => tl::types::MessageEntitySpoiler { offset, length }.into(),
+                Marker::Link { .. } => unreachable!(),
+            });
+        } else if self.find(self.pos + len, marker.delimiter()).is_some() {
+            self.open.push((marker, self.offset));
+            self.pos += len;
+        } else {
+            self.literal(len);
+        }
+    }
+
+    /// Close a link, mention or custom emoji opened with `[` or `![`.
+    fn link_end(&mut self) {
+        let Some(index) = self
+            .open
+            .iter()
+            .rposition(|(m, _)| matches!(m, Marker::Link { .. }))
+        else {
+            return self.literal(1);
+        };
+        if self.chars.get(self.pos + 1) != Some(&'(') {
+            return self.literal(1);
+        }
+        let Some(end) = self.find(self.pos + 2, ")") else {
+            return self.literal(1);
+        };
+
+        let url = unescape(&self.chars[self.pos + 2..end]);
+        self.pos = end + 1;
+        let (marker, offset) = self.open.remove(index);
+        let length = self.offset - offset;
+        if length == 0 {
+            return;
+        }
+
+        let entity = if let Some(id) = url.strip_prefix(MENTION_URL_PREFIX) {
+            match id.parse::<i64>() {
+                Ok(user_id) => tl::types::MessageEntityMentionName {
+                    offset,
+                    length,
+                    user_id,
+                }
+                .into(),
+                Err(_) => return,
+            }
+        } else if let Some(id) = url.strip_prefix(CUSTOM_EMOJI_URL_PREFIX) {
+            match id.parse::<i64>() {
+                Ok(document_id) => tl::types::MessageEntityCustomEmoji {
+                    offset,
+                    length,
+                    document_id,
+                }
+                .into(),
+                Err(_) => return,
+            }
+        } else if marker == (Marker::Link { emoji: true }) {
+            return;
+        } else {
+            tl::types::MessageEntityTextUrl {
+                offset,
+                length,
+                url,
+            }
+            .into()
+        };
+        self.entities.push(entity);
+    }
+
+    /// Parse inline code, where only `` ` `` and `\` need escaping.
+    fn code(&mut self) {
+        let Some(end) = self.find(self.pos + 1, "`") else {
+            return self.literal(1);
+        };
+        let code = unescape(&self.chars[self.pos + 1..end]);
+        self.pos = end + 1;
+        let offset = self.offset;
+        self.push_str(&code);
+        let length = self.offset - offset;
+        if length != 0 {
+            self.entities
+                .push(tl::types::MessageEntityCode { offset, length }.into());
+        }
+    }
+
+    /// Parse a pre-formatted block, with an optional language right after the opening.
+    fn pre(&mut self) {
+        let start = self.pos + 3;
+        let Some(end) = self.find(start, "```") else {
+            return self.literal(3);
+        };
+
+        let mut content = &self.chars[start..end];
+        let mut language = String::new();
+        if let Some(newline) = content.iter().position(|&c| c == '\n')
+            && content[..newline].iter().all(|c| !c.is_whitespace())
+        {
+            language = content[..newline].iter().collect();
+            content = &content[newline + 1..];
+        }
+
+        let code = unescape(content);
+        self.pos = end + 3;
+        let offset = self.offset;
+        self.push_str(&code);
+        let length = self.offset - offset;
+        if length != 0 {
+            self.entities.push(
+                tl::types::MessageEntityPre {
+                    offset,
+                    length,
+                    language,
+                }
+                .into(),
+            );
+        }
+    }
+
+    /// Whether `[text](url)` follows, with the text starting at `from`.
+    fn link_follows(&self, from: usize) -> bool {
+        self.find(from, "](")
+            .and_then(|end| self.find(end + 2, ")"))
+            .is_some()
+    }
+
+    /// Find the next unescaped occurrence of `needle`, starting at `from`.
+    fn find(&self, from: usize, needle: &str) -> Option<usize> {
+        let mut i = from;
+        while i < self.chars.len() {
+            if self.chars[i] == '\\' {
+                i += 2;
+            } else if matches_at(&self.chars, i, needle) {
+                return Some(i);
+            } else {
+                i += 1;
+            }
+        }
+        None
+    }
+
+    fn is_open(&self, marker: Marker) -> bool {
+        self.open.iter().any(|(m, _)| *m == marker)
+    }
+
+    fn starts_with(&self, needle: &str) -> bool {
+        matches_at(&self.chars, self.pos, needle)
+    }
+
+    fn at_line_end(&self, skip: usize) -> bool {
+        matches!(self.chars.get(self.pos + skip), None | Some('\n'))
+    }
+
+    /// Keep the next `count` characters as-is.
+    fn literal(&mut self, count: usize) {
+        for _ in 0..count {
+            self.push(self.chars[self.pos]);
+            self.pos += 1;
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.offset += c.len_utf16() as i32;
+    }
+
+    fn push_str(&mut self, s: &str) {
+        s.chars().for_each(|c| self.push(c));
+    }
+}
+
+/// Any character with a code between 1 and 126 can be escaped.
+fn is_escapable(c: char) -> bool {
+    (1..=126).contains(&(c as u32))
+}
+
+fn matches_at(chars: &[char], at: usize, needle: &str) -> bool {
+    needle
+        .chars()
+        .enumerate()
+        .all(|(i, c)| chars.get(at + i) == Some(&c))
+}
+
+fn unescape(chars: &[char]) -> String {
+    let mut result = String::with_capacity(chars.len());
+    let mut iter = chars.iter();
+    while let Some(&c) = iter.next() {
+        match (c, iter.as_slice().first()) {
+            ('\\', Some(&next)) if is_escapable(next) => {
+                result.push(next);
+                iter.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_escapes() {
+        let (text, entities) = parse_markdown_v2_message(r"1\.5 \* 2 \= 3\! (a_b) \\");
+        assert_eq!(text, r"1.5 * 2 = 3! (a_b) \");
+        assert_eq!(entities, vec![]);
+    }
+
+    #[test]
+    fn parse_nested_entities() {
+        let (text, entities) = parse_markdown_v2_message(
+            "*bold _italic ~strike~_* __under__ ||spoiler|| [*link*](https://example.com/\\))",
+        );
+        assert_eq!(text, "bold italic strike under spoiler link");
+        assert_eq!(
+            entities,
+            vec![
+                tl::types::MessageEntityBold {
+                    offset: 0,
+                    length: 18
+                }
+                .into(),
+                tl::types::MessageEntityItalic {
+                    offset: 5,
+                    length: 13
+                }
+                .into(),
+                tl::types::MessageEntityStrike {
+                    offset: 12,
+                    length: 6
+                }
+                .into(),
+                tl::types::MessageEntityUnderline {
+                    offset: 19,
+                    length: 5
+                }
+                .into(),
+                tl::types::MessageEntitySpoiler {
+                    offset: 25,
+                    length: 7
+                }
+                .into(),
+                tl::types::MessageEntityBold {
+                    offset: 33,
+                    length: 4
+                }
+                .into(),
+                tl::types::MessageEntityTextUrl {
+                    offset: 33,
+                    length: 4,
+                    url: "https://example.com/)".to_string(),
+                }
+                .into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_code_mentions_and_emoji() {
+        let (text, entities) = parse_markdown_v2_message(
+            "`a\\`*b*` [you](tg://user?id=123) ![👍](tg://emoji?id=456)\n```rust\nlet x = 1;```",
+        );
+        assert_eq!(text, "a`*b* you 👍\nlet x = 1;");
+        assert_eq!(
+            entities,
+            vec![
+                tl::types::MessageEntityCode {
+                    offset: 0,
+                    length: 5
+                }
+                .into(),
+                tl::types::MessageEntityMentionName {
+                    offset: 6,
+                    length: 3,
+                    user_id: 123
+                }
+                .into(),
+                tl::types::MessageEntityCustomEmoji {
+                    offset: 10,
+                    length: 2,
+                    document_id: 456
+                }
+                .into(),
+                tl::types::MessageEntityPre {
+                    offset: 13,
+                    length: 10,
+                    language: "rust".to_string()
+                }
+                .into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_quotes() {
+        let (text, entities) =
+            parse_markdown_v2_message(">quote\n>more\ntext\n**>hidden\n>lines||\nend");
+        assert_eq!(text, "quote\nmore\ntext\nhidden\nlines\nend");
+        assert_eq!(
+            entities,
+            vec![
+                tl::types::MessageEntityBlockquote {
+                    collapsed: false,
+                    offset: 0,
+                    length: 10
+                }
+                .into(),
+                tl::types::MessageEntityBlockquote {
+                    collapsed: true,
+                    offset: 16,
+                    length: 12
+                }
+                .into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unmatched_markers() {
+        let (text, entities) = parse_markdown_v2_message("2*3 = 6 [x] snake_case");
+        assert_eq!(text, "2*3 = 6 [x] snake_case");
+        assert_eq!(entities, vec![]);
+    }
+}
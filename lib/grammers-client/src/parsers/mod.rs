@@ -21,3 +21,8 @@ pub use html::{generate_html_message, parse_html_message};
 mod markdown;
 #[cfg(feature = "markdown")]
 pub use markdown::{generate_markdown_message, parse_markdown_message};
+
+#[cfg(feature = "markdown")]
+mod markdown_v2;
+#[cfg(feature = "markdown")]
+pub use markdown_v2::parse_markdown_v2_message;
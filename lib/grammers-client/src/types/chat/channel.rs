@@ -5,6 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use crate::types::PeerColor;
 use grammers_session::{PackedChat, PackedType};
 use grammers_tl_types as tl;
 use std::fmt;
@@ -183,6 +184,21 @@ impl Channel {
             None => None,
         }
     }
+
+    /// Return whether posts in this broadcast channel are signed with the name of their author.
+    ///
+    /// See [`Message::post_author`](crate::types::Message::post_author).
+    pub fn signatures(&self) -> bool {
+        self.raw.signatures
+    }
+
+    /// Return whether posts in this broadcast channel link to the profile of their author.
+    ///
+    /// Posts from channels with this enabled have the author as their
+    /// [`Message::sender`](crate::types::Message::sender).
+    pub fn signature_profiles(&self) -> bool {
+        self.raw.signature_profiles
+    }
     
     /// Return whether this channel has forums enabled.
     ///
@@ -191,6 +207,51 @@ impl Channel {
     pub fn is_forum(&self) -> bool {
         self.raw.forum
     }
+
+    /// Return whether this channel is a broadcast group (also known as gigagroup).
+    pub fn is_gigagroup(&self) -> bool {
+        self.raw.gigagroup
+    }
+
+    /// Return whether this channel has been verified by Telegram.
+    pub fn verified(&self) -> bool {
+        self.raw.verified
+    }
+
+    /// Return whether this channel has restrictions applied to it.
+    pub fn restricted(&self) -> bool {
+        self.raw.restricted
+    }
+
+    /// Return whether this channel has been flagged for trying to scam other people.
+    pub fn scam(&self) -> bool {
+        self.raw.scam
+    }
+
+    /// Return whether this channel has been flagged for impersonating someone else.
+    pub fn fake(&self) -> bool {
+        self.raw.fake
+    }
+
+    /// Return whether only the minimal information of this channel is known.
+    ///
+    /// See [`Chat::is_min`](crate::types::Chat::is_min) for details.
+    pub fn is_min(&self) -> bool {
+        self.raw.min
+    }
+
+    /// Return the color used to display the name of this channel.
+    ///
+    /// If the channel did not pick a color, the color official clients derive from the
+    /// identifier is returned.
+    pub fn color(&self) -> PeerColor {
+        PeerColor::name(self.raw.color.as_ref(), self.id())
+    }
+
+    /// Return the color used to display the profile of this channel, if it picked one.
+    pub fn profile_color(&self) -> Option<PeerColor> {
+        PeerColor::profile(self.raw.profile_color.as_ref())
+    }
 }
 
 impl From<Channel> for PackedChat {
@@ -5,6 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use crate::types::PeerColor;
 use grammers_session::{PackedChat, PackedType};
 use grammers_tl_types as tl;
 use std::fmt;
@@ -180,6 +181,70 @@ impl Group {
             C::Channel(_) | C::ChannelForbidden(_) => true,
         }
     }
+
+    /// Returns true if this group is a broadcast group (also known as gigagroup).
+    ///
+    /// Broadcast groups are megagroups without a member limit, where only administrators can
+    /// send messages.
+    pub fn is_gigagroup(&self) -> bool {
+        self.channel().is_some_and(|c| c.gigagroup)
+    }
+
+    /// Returns true if this group has forums enabled, organizing its messages into topics.
+    ///
+    /// Only megagroups can be forums.
+    pub fn is_forum(&self) -> bool {
+        self.channel().is_some_and(|c| c.forum)
+    }
+
+    fn channel(&self) -> Option<&tl::types::Channel> {
+        match &self.raw {
+            tl::enums::Chat::Channel(channel) => Some(channel),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this group has been verified by Telegram.
+    ///
+    /// Only megagroups can be verified.
+    pub fn verified(&self) -> bool {
+        self.channel().is_some_and(|c| c.verified)
+    }
+
+    /// Returns true if this group has restrictions applied to it.
+    pub fn restricted(&self) -> bool {
+        self.channel().is_some_and(|c| c.restricted)
+    }
+
+    /// Returns true if this group has been flagged for trying to scam other people.
+    pub fn scam(&self) -> bool {
+        self.channel().is_some_and(|c| c.scam)
+    }
+
+    /// Returns true if this group has been flagged for impersonating someone else.
+    pub fn fake(&self) -> bool {
+        self.channel().is_some_and(|c| c.fake)
+    }
+
+    /// Returns true if only the minimal information of this group is known.
+    ///
+    /// See [`Chat::is_min`](crate::types::Chat::is_min) for details.
+    pub fn is_min(&self) -> bool {
+        self.channel().is_some_and(|c| c.min)
+    }
+
+    /// Returns the color used to display the name of this group.
+    ///
+    /// Only megagroups can pick a color. Otherwise, the color official clients derive from the
+    /// identifier is returned.
+    pub fn color(&self) -> PeerColor {
+        PeerColor::name(self.channel().and_then(|c| c.color.as_ref()), self.id())
+    }
+
+    /// Returns the color used to display the profile of this group, if it picked one.
+    pub fn profile_color(&self) -> Option<PeerColor> {
+        PeerColor::profile(self.channel().and_then(|c| c.profile_color.as_ref()))
+    }
 }
 
 impl From<Group> for PackedChat {
@@ -15,7 +15,7 @@ use grammers_tl_types as tl;
 pub use channel::Channel;
 pub use grammers_session::PackedChat;
 pub use group::Group;
-pub use user::{Platform, RestrictionReason, User};
+pub use user::{Platform, RestrictionReason, User, UserStatus};
 
 /// A chat.
 ///
@@ -171,33 +171,183 @@ impl Chat {
         }
     }
 
-    // If `Self` has `min` `access_hash`, returns a mutable reference to both `min` and `access_hash`.
+    // If `Self` is `min` and has an `access_hash`, returns a mutable reference to it.
     //
     // This serves as a way of checking "is it min?" and "update the access hash" both in one.
-    // (Obtaining the non-min hash may require locking so it's desirable to check first, and also
-    // to avoid double work to update it later, but it may not be possible to update it if the hash
-    // is missing).
-    pub(crate) fn get_min_hash_ref(&mut self) -> Option<(&mut bool, &mut i64)> {
+    // (Obtaining the non-min hash may require locking so it's desirable to check first, but it
+    // may not be possible to update it if the hash is missing).
+    //
+    // The `min` flag itself is kept even if the hash is updated, because the rest of the data
+    // is still incomplete (see `is_min`).
+    pub(crate) fn get_min_hash_ref(&mut self) -> Option<&mut i64> {
         match self {
             Self::User(user) => match &mut user.raw {
-                tl::enums::User::User(raw) => match (&mut raw.min, raw.access_hash.as_mut()) {
-                    (m @ true, Some(ah)) => Some((m, ah)),
-                    _ => None,
-                },
-                tl::enums::User::Empty(_) => None,
+                tl::enums::User::User(raw) if raw.min => raw.access_hash.as_mut(),
+                _ => None,
             },
-            // Small group chats don't have an `access_hash` to begin with.
-            Self::Group(_group) => None,
-            Self::Channel(channel) => {
-                match (&mut channel.raw.min, channel.raw.access_hash.as_mut()) {
-                    (m @ true, Some(ah)) => Some((m, ah)),
-                    _ => None,
-                }
-            }
+            Self::Group(group) => match &mut group.raw {
+                tl::enums::Chat::Channel(raw) if raw.min => raw.access_hash.as_mut(),
+                _ => None,
+            },
+            Self::Channel(channel) if channel.raw.min => channel.raw.access_hash.as_mut(),
+            Self::Channel(_) => None,
         }
     }
 
-    // Return the profile picture or chat photo of this chat, if any.
+    /// Return whether only the minimal information of this chat is known.
+    ///
+    /// Telegram sends these "min" versions of users and channels, for example, along with
+    /// messages of groups, when the logged-in account is not supposed to know them fully. They
+    /// may lack usernames, profile photos, or be outdated. Use [`Client::unpack_chat`] with
+    /// [`Chat::pack`] to fetch the full version, if the account can access it.
+    ///
+    /// [`Client::unpack_chat`]: crate::Client::unpack_chat
+    pub fn is_min(&self) -> bool {
+        match self {
+            Self::User(user) => user.is_min(),
+            Self::Group(group) => group.is_min(),
+            Self::Channel(channel) => channel.is_min(),
+        }
+    }
+
+    /// Return what members of this group or channel can do by default.
+    ///
+    /// Returns `None` for users, and for chats where the default permissions are unknown, such
+    /// as those the logged-in account is no longer a member of.
+    pub fn default_permissions(&self) -> Option<crate::types::ChatPermissions> {
+        let rights = match self {
+            Self::User(_) => None,
+            Self::Group(group) => match &group.raw {
+                tl::enums::Chat::Chat(chat) => chat.default_banned_rights.as_ref(),
+                tl::enums::Chat::Channel(channel) => channel.default_banned_rights.as_ref(),
+                _ => None,
+            },
+            Self::Channel(channel) => channel.raw.default_banned_rights.as_ref(),
+        }?;
+        let tl::enums::ChatBannedRights::Rights(rights) = rights;
+        Some(crate::types::ChatPermissions::from_raw(rights))
+    }
+
+    /// Return whether slow mode is enabled in this group, which limits how often members can
+    /// send messages.
+    ///
+    /// The delay itself is part of the full information of the group, and can be fetched with
+    /// [`Client::get_slow_mode_delay`](crate::Client::get_slow_mode_delay).
+    pub fn slow_mode_enabled(&self) -> bool {
+        match self {
+            Self::Group(group) => match &group.raw {
+                tl::enums::Chat::Channel(channel) => channel.slowmode_enabled,
+                _ => false,
+            },
+            Self::User(_) | Self::Channel(_) => false,
+        }
+    }
+
+    /// Return whether this chat is a megagroup (also known as supergroup), which includes
+    /// broadcast groups.
+    pub fn is_megagroup(&self) -> bool {
+        match self {
+            Self::Group(group) => group.is_megagroup(),
+            Self::User(_) | Self::Channel(_) => false,
+        }
+    }
+
+    /// Return whether this chat is a broadcast channel.
+    pub fn is_broadcast(&self) -> bool {
+        matches!(self, Self::Channel(_))
+    }
+
+    /// Return whether this chat is a broadcast group (also known as gigagroup).
+    pub fn is_gigagroup(&self) -> bool {
+        match self {
+            Self::Group(group) => group.is_gigagroup(),
+            Self::Channel(channel) => channel.is_gigagroup(),
+            Self::User(_) => false,
+        }
+    }
+
+    /// Return whether this chat has forums enabled, organizing its messages into topics.
+    pub fn is_forum(&self) -> bool {
+        match self {
+            Self::Group(group) => group.is_forum(),
+            Self::Channel(channel) => channel.is_forum(),
+            Self::User(_) => false,
+        }
+    }
+
+    /// Return whether this chat has been verified by Telegram.
+    pub fn verified(&self) -> bool {
+        match self {
+            Self::User(user) => user.verified(),
+            Self::Group(group) => group.verified(),
+            Self::Channel(channel) => channel.verified(),
+        }
+    }
+
+    /// Return whether this chat has restrictions applied to it.
+    pub fn restricted(&self) -> bool {
+        match self {
+            Self::User(user) => user.restricted(),
+            Self::Group(group) => group.restricted(),
+            Self::Channel(channel) => channel.restricted(),
+        }
+    }
+
+    /// Return whether this chat has been flagged for trying to scam other people.
+    pub fn scam(&self) -> bool {
+        match self {
+            Self::User(user) => user.scam(),
+            Self::Group(group) => group.scam(),
+            Self::Channel(channel) => channel.scam(),
+        }
+    }
+
+    /// Return whether this chat has been flagged for impersonating someone else.
+    pub fn fake(&self) -> bool {
+        match self {
+            Self::User(user) => user.fake(),
+            Self::Group(group) => group.fake(),
+            Self::Channel(channel) => channel.fake(),
+        }
+    }
+
+    /// Return whether this chat is a user with a Telegram Premium subscription.
+    ///
+    /// Always `false` for groups and channels.
+    pub fn premium(&self) -> bool {
+        match self {
+            Self::User(user) => user.premium(),
+            Self::Group(_) | Self::Channel(_) => false,
+        }
+    }
+
+    /// Return the color used to display the name of this chat.
+    ///
+    /// If the chat did not pick a color, the color official clients derive from the identifier
+    /// is returned.
+    pub fn color(&self) -> crate::types::PeerColor {
+        match self {
+            Self::User(user) => user.color(),
+            Self::Group(group) => group.color(),
+            Self::Channel(channel) => channel.color(),
+        }
+    }
+
+    /// Return the color used to display the profile of this chat, if it picked one.
+    pub fn profile_color(&self) -> Option<crate::types::PeerColor> {
+        match self {
+            Self::User(user) => user.profile_color(),
+            Self::Group(group) => group.profile_color(),
+            Self::Channel(channel) => channel.profile_color(),
+        }
+    }
+
+    /// Return the profile picture or chat photo of this chat, if any.
+    ///
+    /// If `big` is `true`, the photo will be the larger 640x640 version, otherwise it will be
+    /// the smaller 160x160 one. The returned value can be downloaded with
+    /// [`Client::download_media`](crate::Client::download_media), or more conveniently with
+    /// [`Client::download_chat_photo`](crate::Client::download_chat_photo).
     pub fn photo(&self, big: bool) -> Option<crate::types::ChatPhoto> {
         let peer = self.pack().to_input_peer();
         match self {
@@ -5,6 +5,9 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use crate::types::PeerColor;
+use crate::utils;
+use chrono::{DateTime, Utc};
 use grammers_session::{PackedChat, PackedType};
 use grammers_tl_types as tl;
 use std::fmt;
@@ -48,6 +51,45 @@ impl RestrictionReason {
     }
 }
 
+/// The presence of a user, also known as their "last seen" status.
+///
+/// Users can hide their exact presence, in which case only an approximation is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UserStatus {
+    /// The status is unknown, for example because the user has never logged in.
+    Empty,
+    /// The user is currently online. The status should be considered offline after `expires`.
+    Online { expires: DateTime<Utc> },
+    /// The user is currently offline, and was last seen online at `was_online`.
+    Offline { was_online: DateTime<Utc> },
+    /// The user was online recently.
+    Recently,
+    /// The user was online in the last week.
+    LastWeek,
+    /// The user was online in the last month.
+    LastMonth,
+}
+
+impl UserStatus {
+    pub fn from_raw(status: &tl::enums::UserStatus) -> Self {
+        use tl::enums::UserStatus as S;
+
+        match status {
+            S::Empty => Self::Empty,
+            S::Online(status) => Self::Online {
+                expires: utils::date(status.expires),
+            },
+            S::Offline(status) => Self::Offline {
+                was_online: utils::date(status.was_online),
+            },
+            S::Recently(_) => Self::Recently,
+            S::LastWeek(_) => Self::LastWeek,
+            S::LastMonth(_) => Self::LastMonth,
+        }
+    }
+}
+
 /// A user.
 ///
 /// Users include your contacts, members of a group, bot accounts created by [@BotFather], or
@@ -136,10 +178,11 @@ impl User {
     }
 
     /// Return the user presence status (also known as "last seen").
-    pub fn status(&self) -> &grammers_tl_types::enums::UserStatus {
+    pub fn status(&self) -> UserStatus {
         self.user()
             .and_then(|u| u.status.as_ref())
-            .unwrap_or(&grammers_tl_types::enums::UserStatus::Empty)
+            .map(UserStatus::from_raw)
+            .unwrap_or(UserStatus::Empty)
     }
 
     /// Return the unique identifier for this user.
@@ -319,6 +362,16 @@ impl User {
         self.user().map(|u| u.scam).unwrap_or(false)
     }
 
+    /// Has this user been flagged for impersonating someone else?
+    pub fn fake(&self) -> bool {
+        self.user().map(|u| u.fake).unwrap_or(false)
+    }
+
+    /// Does this user have a Telegram Premium subscription?
+    pub fn premium(&self) -> bool {
+        self.user().map(|u| u.premium).unwrap_or(false)
+    }
+
     /// The reason(s) why this user is restricted, could be empty.
     pub fn restriction_reason(&self) -> Vec<RestrictionReason> {
         if let Some(reasons) = self.user().and_then(|u| u.restriction_reason.as_ref()) {
@@ -339,6 +392,37 @@ impl User {
     pub fn lang_code(&self) -> Option<&str> {
         self.user().and_then(|u| u.lang_code.as_deref())
     }
+
+    /// Return the emoji status shown next to the name of this user, if any.
+    ///
+    /// Only premium users can set an emoji status. The status may have an expiration date,
+    /// after which it should no longer be displayed.
+    pub fn emoji_status(&self) -> Option<&tl::enums::EmojiStatus> {
+        match self.user().and_then(|u| u.emoji_status.as_ref()) {
+            Some(tl::enums::EmojiStatus::Empty) | None => None,
+            Some(status) => Some(status),
+        }
+    }
+
+    /// Return whether only the minimal information of this user is known.
+    ///
+    /// See [`Chat::is_min`](crate::types::Chat::is_min) for details.
+    pub fn is_min(&self) -> bool {
+        self.user().is_some_and(|u| u.min)
+    }
+
+    /// Return the color used to display the name of this user.
+    ///
+    /// If the user did not pick a color, the color official clients derive from the identifier
+    /// is returned.
+    pub fn color(&self) -> PeerColor {
+        PeerColor::name(self.user().and_then(|u| u.color.as_ref()), self.id())
+    }
+
+    /// Return the color used to display the profile of this user, if they picked one.
+    pub fn profile_color(&self) -> Option<PeerColor> {
+        PeerColor::profile(self.user().and_then(|u| u.profile_color.as_ref()))
+    }
 }
 
 impl From<User> for PackedChat {
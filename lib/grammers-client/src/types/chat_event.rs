@@ -0,0 +1,90 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, Message, Photo};
+use crate::utils;
+use grammers_tl_types as tl;
+
+/// What happened in a chat, as described by a service message.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum ChatEvent {
+    /// The group or channel was created with the given title.
+    Created { title: String },
+    /// The actor added these users to the group.
+    MembersAdded(Vec<Chat>),
+    /// The actor joined the group through an invite link created by the inviter.
+    JoinedByLink { inviter: Chat },
+    /// The actor joined the group after their request to join was approved.
+    JoinedByRequest,
+    /// The actor left the group.
+    MemberLeft,
+    /// The actor removed this user from the group.
+    MemberRemoved(Chat),
+    /// The title of the chat was changed.
+    TitleChanged(String),
+    /// The photo of the chat was changed.
+    PhotoChanged(Photo),
+    /// The photo of the chat was removed.
+    PhotoRemoved,
+    /// A message was pinned, if it's still known.
+    MessagePinned(Option<i32>),
+    /// Any other action, which does not have a dedicated variant yet.
+    Other(tl::enums::MessageAction),
+}
+
+/// A [`ChatEvent`] along with who caused it. See [`Message::chat_event`].
+#[derive(Clone, Debug)]
+pub struct ServiceEvent {
+    /// Who caused the event.
+    ///
+    /// For events without a known sender, such as those in broadcast channels, this is the
+    /// chat itself.
+    pub actor: Chat,
+    /// What happened.
+    pub event: ChatEvent,
+}
+
+impl ServiceEvent {
+    pub(crate) fn from_message(message: &Message) -> Option<Self> {
+        use tl::enums::MessageAction as A;
+
+        let find_user = |user_id| {
+            utils::always_find_entity(
+                &tl::types::PeerUser { user_id }.into(),
+                &message.chats,
+                &message.client,
+            )
+        };
+
+        let actor = message.sender().unwrap_or_else(|| message.chat());
+        let event = match message.action()?.clone() {
+            A::ChatCreate(action) => ChatEvent::Created {
+                title: action.title,
+            },
+            A::ChannelCreate(action) => ChatEvent::Created {
+                title: action.title,
+            },
+            A::ChatAddUser(action) => {
+                ChatEvent::MembersAdded(action.users.into_iter().map(find_user).collect())
+            }
+            A::ChatJoinedByLink(action) => ChatEvent::JoinedByLink {
+                inviter: find_user(action.inviter_id),
+            },
+            A::ChatJoinedByRequest => ChatEvent::JoinedByRequest,
+            A::ChatDeleteUser(action) if action.user_id == actor.id() => ChatEvent::MemberLeft,
+            A::ChatDeleteUser(action) => ChatEvent::MemberRemoved(find_user(action.user_id)),
+            A::ChatEditTitle(action) => ChatEvent::TitleChanged(action.title),
+            A::ChatEditPhoto(action) => ChatEvent::PhotoChanged(Photo::from_raw(action.photo)),
+            A::ChatDeletePhoto => ChatEvent::PhotoRemoved,
+            A::PinMessage => ChatEvent::MessagePinned(message.reply_to_message_id()),
+            action => ChatEvent::Other(action),
+        };
+
+        Some(Self { actor, event })
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, Photo};
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// A preview of the chat behind an invite link, as returned by [`Client::check_invite_link`].
+///
+/// [`Client::check_invite_link`]: crate::Client::check_invite_link
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatInvite {
+    pub raw: tl::enums::ChatInvite,
+}
+
+impl ChatInvite {
+    pub(crate) fn from_raw(invite: tl::enums::ChatInvite) -> Self {
+        Self { raw: invite }
+    }
+
+    fn raw_chat(&self) -> Option<&tl::enums::Chat> {
+        match &self.raw {
+            tl::enums::ChatInvite::Already(invite) => Some(&invite.chat),
+            tl::enums::ChatInvite::Invite(_) => None,
+            tl::enums::ChatInvite::Peek(invite) => Some(&invite.chat),
+        }
+    }
+
+    fn raw_channel(&self) -> Option<&tl::types::Channel> {
+        match self.raw_chat() {
+            Some(tl::enums::Chat::Channel(channel)) => Some(channel),
+            _ => None,
+        }
+    }
+
+    /// The chat behind the invite, if the logged-in user is already a member or can
+    /// temporarily preview it.
+    pub fn chat(&self) -> Option<Chat> {
+        self.raw_chat().cloned().map(Chat::from_raw)
+    }
+
+    /// Whether the logged-in user is already a member of the chat.
+    pub fn is_joined(&self) -> bool {
+        matches!(self.raw, tl::enums::ChatInvite::Already(_))
+    }
+
+    /// The title of the chat.
+    pub fn title(&self) -> &str {
+        use tl::enums::Chat as C;
+
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => &invite.title,
+            _ => match self.raw_chat() {
+                Some(C::Chat(chat)) => &chat.title,
+                Some(C::Forbidden(chat)) => &chat.title,
+                Some(C::Channel(channel)) => &channel.title,
+                Some(C::ChannelForbidden(channel)) => &channel.title,
+                Some(C::Empty(_)) | None => "",
+            },
+        }
+    }
+
+    /// The description of the chat, if any.
+    ///
+    /// Only available if the logged-in user is not a member of the chat.
+    pub fn about(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => invite.about.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The photo of the chat, if any.
+    ///
+    /// Only available if the logged-in user is not a member of the chat.
+    pub fn photo(&self) -> Option<Photo> {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => match &invite.photo {
+                tl::enums::Photo::Empty(_) => None,
+                photo => Some(Photo::from_raw(photo.clone())),
+            },
+            _ => None,
+        }
+    }
+
+    /// How many members the chat has, if known.
+    pub fn participants_count(&self) -> Option<i32> {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => Some(invite.participants_count),
+            _ => match self.raw_chat() {
+                Some(tl::enums::Chat::Chat(chat)) => Some(chat.participants_count),
+                Some(tl::enums::Chat::Channel(channel)) => channel.participants_count,
+                _ => None,
+            },
+        }
+    }
+
+    /// Whether the chat is a broadcast channel, rather than a group.
+    pub fn is_channel(&self) -> bool {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => invite.broadcast,
+            _ => self.raw_channel().is_some_and(|channel| channel.broadcast),
+        }
+    }
+
+    /// Whether an administrator must approve the request before the logged-in user can join.
+    pub fn is_request_needed(&self) -> bool {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => invite.request_needed,
+            _ => false,
+        }
+    }
+
+    /// Whether the chat was verified by Telegram.
+    pub fn is_verified(&self) -> bool {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => invite.verified,
+            _ => self.raw_channel().is_some_and(|channel| channel.verified),
+        }
+    }
+
+    /// Whether many users reported the chat as a scam.
+    pub fn is_scam(&self) -> bool {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => invite.scam,
+            _ => self.raw_channel().is_some_and(|channel| channel.scam),
+        }
+    }
+
+    /// Whether many users reported the chat as impersonating someone else.
+    pub fn is_fake(&self) -> bool {
+        match &self.raw {
+            tl::enums::ChatInvite::Invite(invite) => invite.fake,
+            _ => self.raw_channel().is_some_and(|channel| channel.fake),
+        }
+    }
+
+    /// Until when the chat can be previewed without joining, if it can.
+    pub fn preview_expires(&self) -> Option<DateTime<Utc>> {
+        match &self.raw {
+            tl::enums::ChatInvite::Peek(invite) => Some(utils::date(invite.expires)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap, Participant, User};
+use crate::{Client, utils};
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// The kind of membership change described by a participant update.
+pub(crate) enum MemberChange {
+    Joined,
+    Left,
+    Banned,
+}
+
+/// Represents a change in the membership of a user in a group or channel, such as joining,
+/// leaving, or being banned.
+///
+/// Telegram only sends these to bots which are administrators in the chat, and to user accounts
+/// for some small groups.
+#[derive(Clone)]
+pub struct ChatMemberUpdate {
+    chat: tl::enums::Peer,
+    date: i32,
+    actor_id: i64,
+    user_id: i64,
+    old_participant: Option<Participant>,
+    new_participant: Option<Participant>,
+    invite: Option<tl::enums::ExportedChatInvite>,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+fn is_chat_member(participant: Option<&tl::enums::ChannelParticipant>) -> bool {
+    use tl::enums::ChannelParticipant as P;
+
+    match participant {
+        None | Some(P::Left(_)) => false,
+        Some(P::Banned(p)) => {
+            let tl::enums::ChatBannedRights::Rights(rights) = &p.banned_rights;
+            !p.left && !rights.view_messages
+        }
+        Some(_) => true,
+    }
+}
+
+fn is_kicked(participant: Option<&tl::enums::ChannelParticipant>) -> bool {
+    match participant {
+        Some(tl::enums::ChannelParticipant::Banned(p)) => {
+            let tl::enums::ChatBannedRights::Rights(rights) = &p.banned_rights;
+            rights.view_messages
+        }
+        _ => false,
+    }
+}
+
+impl ChatMemberUpdate {
+    /// Determine the kind of membership change in a channel, if any.
+    ///
+    /// Other changes, such as promotions, are not considered membership changes.
+    pub(crate) fn channel_change(
+        update: &tl::types::UpdateChannelParticipant,
+    ) -> Option<MemberChange> {
+        let prev = update.prev_participant.as_ref();
+        let new = update.new_participant.as_ref();
+
+        if is_kicked(new) && !is_kicked(prev) {
+            Some(MemberChange::Banned)
+        } else if !is_chat_member(prev) && is_chat_member(new) {
+            Some(MemberChange::Joined)
+        } else if is_chat_member(prev) && !is_chat_member(new) {
+            Some(MemberChange::Left)
+        } else {
+            None
+        }
+    }
+
+    /// Determine the kind of membership change in a small group, if any.
+    ///
+    /// Small groups have no ban list, so users removed by someone else are considered banned.
+    pub(crate) fn chat_change(update: &tl::types::UpdateChatParticipant) -> Option<MemberChange> {
+        match (&update.prev_participant, &update.new_participant) {
+            (None, Some(_)) => Some(MemberChange::Joined),
+            (Some(_), None) if update.actor_id == update.user_id => Some(MemberChange::Left),
+            (Some(_), None) => Some(MemberChange::Banned),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_raw_channel(
+        client: &Client,
+        update: tl::types::UpdateChannelParticipant,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        let resolve = |participant| Participant::resolve_channel(client, chats, participant);
+        Self {
+            chat: tl::types::PeerChannel {
+                channel_id: update.channel_id,
+            }
+            .into(),
+            date: update.date,
+            actor_id: update.actor_id,
+            user_id: update.user_id,
+            old_participant: update.prev_participant.and_then(resolve),
+            new_participant: update.new_participant.and_then(resolve),
+            invite: update.invite,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    pub(crate) fn from_raw_chat(
+        client: &Client,
+        update: tl::types::UpdateChatParticipant,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        let resolve = |participant| Participant::resolve_chat(client, chats, participant);
+        Self {
+            chat: tl::types::PeerChat {
+                chat_id: update.chat_id,
+            }
+            .into(),
+            date: update.date,
+            actor_id: update.actor_id,
+            user_id: update.user_id,
+            old_participant: update.prev_participant.and_then(resolve),
+            new_participant: update.new_participant.and_then(resolve),
+            invite: update.invite,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// The group or channel where the membership changed.
+    pub fn chat(&self) -> Chat {
+        utils::always_find_entity(&self.chat, &self.chats, &self.client)
+    }
+
+    /// The user whose membership changed.
+    pub fn user(&self) -> User {
+        let peer = tl::types::PeerUser {
+            user_id: self.user_id,
+        }
+        .into();
+        match utils::always_find_entity(&peer, &self.chats, &self.client) {
+            Chat::User(user) => user,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The user who caused the change.
+    ///
+    /// This is the same as [`ChatMemberUpdate::user`] when the user joined or left on their own,
+    /// and the administrator otherwise.
+    pub fn actor(&self) -> Chat {
+        let peer = tl::types::PeerUser {
+            user_id: self.actor_id,
+        }
+        .into();
+        utils::always_find_entity(&peer, &self.chats, &self.client)
+    }
+
+    /// When the change occurred.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.date)
+    }
+
+    /// The participant information before the change, if the user was a participant.
+    pub fn old_participant(&self) -> Option<&Participant> {
+        self.old_participant.as_ref()
+    }
+
+    /// The participant information after the change, if the user is still a participant.
+    pub fn new_participant(&self) -> Option<&Participant> {
+        self.new_participant.as_ref()
+    }
+
+    /// The invite link used to join, if any.
+    pub fn invite(&self) -> Option<&tl::enums::ExportedChatInvite> {
+        self.invite.as_ref()
+    }
+}
+
+impl fmt::Debug for ChatMemberUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatMemberUpdate")
+            .field("chat", &self.chat())
+            .field("user", &self.user())
+            .field("actor", &self.actor())
+            .field("date", &self.date())
+            .field("old_participant", &self.old_participant)
+            .field("new_participant", &self.new_participant)
+            .finish()
+    }
+}
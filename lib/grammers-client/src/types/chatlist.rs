@@ -0,0 +1,100 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap};
+use crate::{Client, utils};
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// Information about an invite link to a chat list (also known as a shared folder).
+///
+/// The invite may refer to a folder the logged-in account has already joined, in which case
+/// only the chats which have been added to it since are offered to join.
+#[derive(Clone)]
+pub struct ChatlistInvite {
+    pub raw: tl::enums::chatlists::ChatlistInvite,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+impl fmt::Debug for ChatlistInvite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.raw.fmt(f)
+    }
+}
+
+impl ChatlistInvite {
+    pub(crate) fn from_raw(client: &Client, invite: tl::enums::chatlists::ChatlistInvite) -> Self {
+        let chats = match &invite {
+            tl::enums::chatlists::ChatlistInvite::Already(invite) => {
+                ChatMap::new(invite.users.clone(), invite.chats.clone())
+            }
+            tl::enums::chatlists::ChatlistInvite::Invite(invite) => {
+                ChatMap::new(invite.users.clone(), invite.chats.clone())
+            }
+        };
+        Self {
+            raw: invite,
+            client: client.clone(),
+            chats,
+        }
+    }
+
+    /// The title of the chat list, if the logged-in account has not joined it yet.
+    pub fn title(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::chatlists::ChatlistInvite::Already(_) => None,
+            tl::enums::chatlists::ChatlistInvite::Invite(invite) => {
+                let tl::enums::TextWithEntities::Entities(title) = &invite.title;
+                Some(&title.text)
+            }
+        }
+    }
+
+    /// The emoticon used as the icon of the chat list, if any.
+    pub fn emoticon(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::chatlists::ChatlistInvite::Already(_) => None,
+            tl::enums::chatlists::ChatlistInvite::Invite(invite) => invite.emoticon.as_deref(),
+        }
+    }
+
+    /// The identifier of the folder, if the logged-in account has already joined it.
+    pub fn filter_id(&self) -> Option<i32> {
+        match &self.raw {
+            tl::enums::chatlists::ChatlistInvite::Already(invite) => Some(invite.filter_id),
+            tl::enums::chatlists::ChatlistInvite::Invite(_) => None,
+        }
+    }
+
+    /// The chats that can be joined through this invite.
+    pub fn chats(&self) -> Vec<Chat> {
+        let peers = match &self.raw {
+            tl::enums::chatlists::ChatlistInvite::Already(invite) => &invite.missing_peers,
+            tl::enums::chatlists::ChatlistInvite::Invite(invite) => &invite.peers,
+        };
+        self.find_chats(peers)
+    }
+
+    /// The chats in the folder which the logged-in account has already joined.
+    pub fn joined_chats(&self) -> Vec<Chat> {
+        match &self.raw {
+            tl::enums::chatlists::ChatlistInvite::Already(invite) => {
+                self.find_chats(&invite.already_peers)
+            }
+            tl::enums::chatlists::ChatlistInvite::Invite(_) => Vec::new(),
+        }
+    }
+
+    fn find_chats(&self, peers: &[tl::enums::Peer]) -> Vec<Chat> {
+        peers
+            .iter()
+            .map(|peer| utils::always_find_entity(peer, &self.chats, &self.client))
+            .collect()
+    }
+}
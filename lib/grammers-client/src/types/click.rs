@@ -0,0 +1,138 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Types used when clicking the buttons of a message as a user.
+//!
+//! See [`Message::click`](super::Message::click).
+use super::Message;
+use crate::client::bots::InlineResultIter;
+use grammers_tl_types as tl;
+
+/// Selects which button of a message's reply markup should be clicked.
+///
+/// Can be created from a `usize`, to select a button by its position (counting from the top-left
+/// button, left-to-right and then top-to-bottom), or from a string, to select the first button
+/// with the exact same text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ButtonSelector {
+    Index(usize),
+    Text(String),
+}
+
+impl From<usize> for ButtonSelector {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for ButtonSelector {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+}
+
+impl From<String> for ButtonSelector {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+/// The answer given by a bot after one of its callback or game buttons was clicked.
+#[derive(Clone, Debug)]
+pub struct CallbackAnswer {
+    pub raw: tl::types::messages::BotCallbackAnswer,
+}
+
+impl CallbackAnswer {
+    pub(crate) fn from_raw(answer: tl::enums::messages::BotCallbackAnswer) -> Self {
+        let tl::enums::messages::BotCallbackAnswer::Answer(answer) = answer;
+        Self { raw: answer }
+    }
+
+    /// The text of the answer, if any.
+    pub fn message(&self) -> Option<&str> {
+        self.raw.message.as_deref()
+    }
+
+    /// Whether the answer should be shown as a popup alert, rather than as a toast notification.
+    pub fn alert(&self) -> bool {
+        self.raw.alert
+    }
+
+    /// The URL the bot wants to be opened, such as the URL of a game.
+    pub fn url(&self) -> Option<&str> {
+        self.raw.url.as_deref()
+    }
+}
+
+/// The result of clicking a button.
+#[non_exhaustive]
+pub enum ClickResult {
+    /// The button was a callback or game button, and the bot answered it.
+    Answer(CallbackAnswer),
+    /// The button was a URL button. The URL is returned, but not opened.
+    Url(String),
+    /// The button was a switch-inline button. The inline query has been prepared with the
+    /// bot and query the button indicates, ready to be executed.
+    SwitchInline(InlineResultIter),
+    /// The button was a plain keyboard button, and its text was sent as a message.
+    Sent(Box<Message>),
+    /// The button cannot be clicked by this library, such as buttons requesting the phone number,
+    /// a location, payment, web apps, or callback buttons protected by the account password.
+    Unsupported(tl::enums::KeyboardButton),
+}
+
+/// The text displayed in a button.
+pub(crate) fn button_text(button: &tl::enums::KeyboardButton) -> &str {
+    use tl::enums::KeyboardButton as B;
+
+    match button {
+        B::Button(b) => &b.text,
+        B::Url(b) => &b.text,
+        B::Callback(b) => &b.text,
+        B::RequestPhone(b) => &b.text,
+        B::RequestGeoLocation(b) => &b.text,
+        B::SwitchInline(b) => &b.text,
+        B::Game(b) => &b.text,
+        B::Buy(b) => &b.text,
+        B::UrlAuth(b) => &b.text,
+        B::InputKeyboardButtonUrlAuth(b) => &b.text,
+        B::RequestPoll(b) => &b.text,
+        B::InputKeyboardButtonUserProfile(b) => &b.text,
+        B::UserProfile(b) => &b.text,
+        B::WebView(b) => &b.text,
+        B::SimpleWebView(b) => &b.text,
+        B::RequestPeer(b) => &b.text,
+        B::InputKeyboardButtonRequestPeer(b) => &b.text,
+        B::Copy(b) => &b.text,
+    }
+}
+
+/// Find the button matching the selector in the reply markup, if any.
+pub(crate) fn find_button(
+    markup: &tl::enums::ReplyMarkup,
+    selector: &ButtonSelector,
+) -> Option<tl::enums::KeyboardButton> {
+    let rows = match markup {
+        tl::enums::ReplyMarkup::ReplyKeyboardMarkup(markup) => &markup.rows,
+        tl::enums::ReplyMarkup::ReplyInlineMarkup(markup) => &markup.rows,
+        tl::enums::ReplyMarkup::ReplyKeyboardHide(_)
+        | tl::enums::ReplyMarkup::ReplyKeyboardForceReply(_) => return None,
+    };
+
+    let mut buttons = rows.iter().flat_map(|row| {
+        let tl::enums::KeyboardButtonRow::Row(row) = row;
+        row.buttons.iter()
+    });
+
+    match selector {
+        ButtonSelector::Index(index) => buttons.nth(*index),
+        ButtonSelector::Text(text) => buttons.find(|button| button_text(button) == text),
+    }
+    .cloned()
+}
@@ -0,0 +1,173 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+/// A bot command found at the start of a message, such as `/start@my_bot "some argument"`.
+///
+/// See [`Message::command`](crate::types::Message::command).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command {
+    /// The name of the command, without the leading slash nor the mention.
+    pub name: String,
+    /// The arguments after the command, split by whitespace. Quoted arguments (with either
+    /// single or double quotes) are kept together, and a backslash escapes the next character.
+    pub args: Vec<String>,
+    /// The username of the bot mentioned in the command (as in `/start@my_bot`), if any.
+    pub mention: Option<String>,
+}
+
+impl Command {
+    /// Parse the command at the start of `text`.
+    ///
+    /// If the text has `entities`, the command must be marked by a bot command entity at the
+    /// start. Otherwise, the text only needs to start with a slash.
+    pub(crate) fn parse(text: &str, entities: Option<&[tl::enums::MessageEntity]>) -> Option<Self> {
+        if !text.starts_with('/') {
+            return None;
+        }
+
+        let end = match entities.filter(|entities| !entities.is_empty()) {
+            Some(entities) => {
+                let length = entities.iter().find_map(|entity| match entity {
+                    tl::enums::MessageEntity::BotCommand(command) if command.offset == 0 => {
+                        Some(command.length as usize)
+                    }
+                    _ => None,
+                })?;
+                utf16_to_byte_offset(text, length)
+            }
+            None => text.find(char::is_whitespace).unwrap_or(text.len()),
+        };
+
+        let (command, rest) = text.split_at(end);
+        let (name, mention) = match command[1..].split_once('@') {
+            Some((name, mention)) => (name, Some(mention.to_string())),
+            None => (&command[1..], None),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            name: name.to_string(),
+            args: split_args(rest),
+            mention,
+        })
+    }
+
+    /// Whether the command is meant for the bot with the given username.
+    ///
+    /// This is the case when the command either mentions no bot, or mentions the given one
+    /// (ignoring case). Commands in groups with several bots should be ignored otherwise.
+    pub fn is_for(&self, username: &str) -> bool {
+        let username = username.strip_prefix('@').unwrap_or(username);
+        self.mention
+            .as_ref()
+            .is_none_or(|mention| mention.eq_ignore_ascii_case(username))
+    }
+}
+
+/// Convert an offset in UTF-16 code units into a byte offset, clamped to the text's length.
+fn utf16_to_byte_offset(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units >= offset {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+fn split_args(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', _) => {
+                current.extend(chars.next());
+                in_arg = true;
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => current.push(c),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (c, None) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (c, None) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bot_command(offset: i32, length: i32) -> tl::enums::MessageEntity {
+        tl::types::MessageEntityBotCommand { offset, length }.into()
+    }
+
+    #[test]
+    fn parse_plain_command() {
+        let command = Command::parse("/start", None).unwrap();
+        assert_eq!(command.name, "start");
+        assert!(command.args.is_empty());
+        assert_eq!(command.mention, None);
+
+        assert_eq!(Command::parse("hello /start", None), None);
+        assert_eq!(Command::parse("/ start", None), None);
+    }
+
+    #[test]
+    fn parse_mention_and_args() {
+        let text = "/Ban@My_Bot  @user  \"spamming links\" 'for days' it\\'s\n";
+        let command = Command::parse(text, Some(&[bot_command(0, 11)])).unwrap();
+        assert_eq!(command.name, "Ban");
+        assert_eq!(command.mention.as_deref(), Some("My_Bot"));
+        assert_eq!(
+            command.args,
+            ["@user", "spamming links", "for days", "it's"]
+        );
+
+        assert!(command.is_for("my_bot"));
+        assert!(command.is_for("@MY_BOT"));
+        assert!(!command.is_for("other_bot"));
+    }
+
+    #[test]
+    fn parse_respects_entities() {
+        // Without a command entity at the start, the text is not a command.
+        let bold = tl::types::MessageEntityBold {
+            offset: 0,
+            length: 5,
+        }
+        .into();
+        assert_eq!(Command::parse("/path/to/file", Some(&[bold])), None);
+
+        let command = Command::parse("/echo \"\" x", Some(&[bot_command(0, 5)])).unwrap();
+        assert_eq!(command.name, "echo");
+        assert_eq!(command.args, ["", "x"]);
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+/// Settings of the logged-in account about which content can be shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentSettings {
+    /// Whether sensitive content is shown instead of being hidden.
+    pub sensitive_enabled: bool,
+    /// Whether the account is allowed to change the previous setting.
+    ///
+    /// This depends, among other things, on the laws of the country of the account.
+    pub sensitive_can_change: bool,
+}
+
+impl ContentSettings {
+    pub(crate) fn from_raw(raw: tl::enums::account::ContentSettings) -> Self {
+        let tl::enums::account::ContentSettings::Settings(settings) = raw;
+        Self {
+            sensitive_enabled: settings.sensitive_enabled,
+            sensitive_can_change: settings.sensitive_can_change,
+        }
+    }
+}
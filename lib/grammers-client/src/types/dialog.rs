@@ -6,7 +6,10 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::{Chat, ChatMap, Message, Peer};
+use super::{Chat, ChatMap, InputMessage, Message, Peer};
+use crate::{Client, InvocationError, utils};
+use chrono::{DateTime, Utc};
+use grammers_session::PackedChat;
 use grammers_tl_types as tl;
 use std::collections::HashMap;
 
@@ -15,10 +18,12 @@ pub struct Dialog {
     pub raw: tl::enums::Dialog,
     pub chat: Chat,
     pub last_message: Option<Message>,
+    client: Client,
 }
 
 impl Dialog {
     pub(crate) fn new(
+        client: &Client,
         dialog: tl::enums::Dialog,
         messages: &mut HashMap<Peer, Message>,
         chats: &ChatMap,
@@ -36,10 +41,225 @@ impl Dialog {
                 .clone(),
             last_message: messages.remove(&peer.into()),
             raw: dialog,
+            client: client.clone(),
         }
     }
 
     pub fn chat(&self) -> &Chat {
         &self.chat
     }
+
+    /// The position right after this dialog in the dialog list, which can be used to resume
+    /// iterating dialogs later with [`DialogIter::offset`].
+    ///
+    /// [`DialogIter::offset`]: crate::client::dialogs::DialogIter::offset
+    pub fn offset(&self) -> DialogOffset {
+        DialogOffset {
+            date: self.last_message.as_ref().map_or(0, |m| m.raw.date),
+            id: self.last_message.as_ref().map_or(0, |m| m.raw.id),
+            peer: Some(self.chat.pack()),
+        }
+    }
+
+    /// The last message sent to the dialog, if any.
+    pub fn last_message(&self) -> Option<&Message> {
+        self.last_message.as_ref()
+    }
+
+    /// Whether the dialog has unread messages or was manually marked as unread.
+    pub fn unread(&self) -> bool {
+        self.unread_count() > 0 || self.marked_unread()
+    }
+
+    /// Until when notifications from the dialog are muted, if they are.
+    ///
+    /// Folders are never muted.
+    pub fn muted_until(&self) -> Option<DateTime<Utc>> {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => {
+                let tl::enums::PeerNotifySettings::Settings(settings) = &dialog.notify_settings;
+                settings
+                    .mute_until
+                    .filter(|&until| until > 0)
+                    .map(utils::date)
+            }
+            tl::enums::Dialog::Folder(_) => None,
+        }
+    }
+
+    /// Send a message to the chat of this dialog.
+    ///
+    /// Shorthand for [`Client::send_message`].
+    pub async fn send<M: Into<InputMessage>>(
+        &self,
+        message: M,
+    ) -> Result<Message, InvocationError> {
+        self.client.send_message(&self.chat, message).await
+    }
+
+    /// Mark all messages in the chat of this dialog as read.
+    ///
+    /// Shorthand for [`Client::mark_as_read`].
+    pub async fn mark_read(&self) -> Result<(), InvocationError> {
+        self.client.mark_as_read(&self.chat).await
+    }
+
+    /// Whether the dialog is pinned to the top of the dialog list.
+    pub fn pinned(&self) -> bool {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.pinned,
+            tl::enums::Dialog::Folder(dialog) => dialog.pinned,
+        }
+    }
+
+    /// How many messages in the dialog have not been read yet.
+    ///
+    /// For folders, this is the amount of unread messages in all the chats inside it.
+    pub fn unread_count(&self) -> i32 {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.unread_count,
+            tl::enums::Dialog::Folder(dialog) => {
+                dialog.unread_muted_messages_count + dialog.unread_unmuted_messages_count
+            }
+        }
+    }
+
+    /// How many unread messages mention the logged-in user.
+    pub fn unread_mentions_count(&self) -> i32 {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.unread_mentions_count,
+            tl::enums::Dialog::Folder(_) => 0,
+        }
+    }
+
+    /// How many messages sent by the logged-in user have unseen reactions.
+    pub fn unread_reactions_count(&self) -> i32 {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.unread_reactions_count,
+            tl::enums::Dialog::Folder(_) => 0,
+        }
+    }
+
+    /// Whether the dialog was manually marked as unread.
+    pub fn marked_unread(&self) -> bool {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.unread_mark,
+            tl::enums::Dialog::Folder(_) => false,
+        }
+    }
+
+    /// The unread counts of the chats inside the folder, if this dialog is a folder.
+    pub fn folder_unread_counts(&self) -> Option<FolderUnreadCounts> {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(_) => None,
+            tl::enums::Dialog::Folder(dialog) => Some(FolderUnreadCounts::from_raw(dialog)),
+        }
+    }
+}
+
+/// An opaque position in the dialog list, which can be persisted to resume iterating dialogs
+/// after the process restarts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DialogOffset {
+    pub(crate) date: i32,
+    pub(crate) id: i32,
+    pub(crate) peer: Option<PackedChat>,
+}
+
+impl DialogOffset {
+    /// Serialize the offset so that it can be persisted.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(25);
+        buffer.extend(self.date.to_le_bytes());
+        buffer.extend(self.id.to_le_bytes());
+        if let Some(peer) = &self.peer {
+            buffer.extend(peer.to_bytes());
+        }
+        buffer
+    }
+
+    /// Deserialize an offset previously serialized with [`DialogOffset::to_bytes`].
+    ///
+    /// Returns `None` if the data is not a valid offset.
+    pub fn from_bytes(buffer: &[u8]) -> Option<Self> {
+        let (date, rest) = buffer.split_first_chunk::<4>()?;
+        let (id, rest) = rest.split_first_chunk::<4>()?;
+        let peer = if rest.is_empty() {
+            None
+        } else {
+            Some(PackedChat::from_bytes(rest).ok()?)
+        };
+        Some(Self {
+            date: i32::from_le_bytes(*date),
+            id: i32::from_le_bytes(*id),
+            peer,
+        })
+    }
+}
+
+/// Unread counts of the chats inside a peer folder, such as the archive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FolderUnreadCounts {
+    /// How many muted chats have unread messages.
+    pub muted_chats: i32,
+    /// How many chats which are not muted have unread messages.
+    pub unmuted_chats: i32,
+    /// How many unread messages there are in muted chats.
+    pub muted_messages: i32,
+    /// How many unread messages there are in chats which are not muted.
+    pub unmuted_messages: i32,
+}
+
+impl FolderUnreadCounts {
+    pub(crate) fn from_raw(dialog: &tl::types::DialogFolder) -> Self {
+        Self {
+            muted_chats: dialog.unread_muted_peers_count,
+            unmuted_chats: dialog.unread_unmuted_peers_count,
+            muted_messages: dialog.unread_muted_messages_count,
+            unmuted_messages: dialog.unread_unmuted_messages_count,
+        }
+    }
+
+    /// The number to show in a badge, the way official applications do.
+    ///
+    /// Depending on the preference, either chats or messages are counted, and muted chats may
+    /// or may not be included.
+    pub fn badge(&self, count_chats: bool, include_muted: bool) -> i32 {
+        let (muted, unmuted) = if count_chats {
+            (self.muted_chats, self.unmuted_chats)
+        } else {
+            (self.muted_messages, self.unmuted_messages)
+        };
+        if include_muted {
+            muted + unmuted
+        } else {
+            unmuted
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grammers_session::PackedType;
+
+    #[test]
+    fn dialog_offset_roundtrip() {
+        let offset = DialogOffset {
+            date: 1_700_000_000,
+            id: 1234,
+            peer: Some(PackedChat {
+                ty: PackedType::Megagroup,
+                id: 5678,
+                access_hash: Some(-42),
+            }),
+        };
+        assert_eq!(DialogOffset::from_bytes(&offset.to_bytes()), Some(offset));
+
+        let start = DialogOffset::default();
+        assert_eq!(DialogOffset::from_bytes(&start.to_bytes()), Some(start));
+
+        assert_eq!(DialogOffset::from_bytes(&[0; 7]), None);
+        assert_eq!(DialogOffset::from_bytes(&[0; 9]), None);
+    }
 }
@@ -0,0 +1,72 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::media::Document;
+use grammers_tl_types as tl;
+
+/// An animated effect which can be shown when a message is sent to a private chat.
+///
+/// The documents behind the effect are usually Lottie animations or stickers, and can be
+/// downloaded with [`Client::download_media`](crate::Client::download_media).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageEffect {
+    pub raw: tl::types::AvailableEffect,
+    static_icon: Option<Document>,
+    sticker: Option<Document>,
+    animation: Option<Document>,
+}
+
+impl MessageEffect {
+    pub(crate) fn from_raw(effect: tl::types::AvailableEffect, documents: &[Document]) -> Self {
+        let find = |id: Option<i64>| {
+            id.and_then(|id| {
+                documents
+                    .iter()
+                    .find(|document| document.id() == id)
+                    .cloned()
+            })
+        };
+
+        Self {
+            static_icon: find(effect.static_icon_id),
+            sticker: find(Some(effect.effect_sticker_id)),
+            animation: find(effect.effect_animation_id),
+            raw: effect,
+        }
+    }
+
+    /// The identifier of the effect, used to send messages with it.
+    pub fn id(&self) -> i64 {
+        self.raw.id
+    }
+
+    /// The emoji associated with the effect.
+    pub fn emoticon(&self) -> &str {
+        &self.raw.emoticon
+    }
+
+    /// Whether the effect can only be used by Telegram Premium users.
+    pub fn premium_required(&self) -> bool {
+        self.raw.premium_required
+    }
+
+    /// The static icon shown when picking the effect, if any.
+    pub fn static_icon(&self) -> Option<&Document> {
+        self.static_icon.as_ref()
+    }
+
+    /// The sticker shown when picking the effect, which is also its animation if there is no
+    /// separate [`MessageEffect::animation`].
+    pub fn sticker(&self) -> Option<&Document> {
+        self.sticker.as_ref()
+    }
+
+    /// The animation played over the message when it is sent, if any.
+    pub fn animation(&self) -> Option<&Document> {
+        self.animation.as_ref()
+    }
+}
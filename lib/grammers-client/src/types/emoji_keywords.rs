@@ -0,0 +1,197 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+use std::collections::HashMap;
+
+/// The keywords which can be used to find emoji in a given language.
+///
+/// Telegram applications use these to suggest emoji (and stickers associated with them) while
+/// the user types. The keywords can be kept up-to-date with [`Client::update_emoji_keywords`],
+/// which only fetches the changes since the version already known.
+///
+/// [`Client::update_emoji_keywords`]: crate::Client::update_emoji_keywords
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmojiKeywords {
+    lang_code: String,
+    version: i32,
+    keywords: HashMap<String, Vec<String>>,
+}
+
+impl EmojiKeywords {
+    pub(crate) fn from_raw(difference: tl::enums::EmojiKeywordsDifference) -> Self {
+        let mut keywords = Self::default();
+        keywords.apply(difference);
+        keywords
+    }
+
+    /// Apply the changes in the difference, returning `false` if it did not apply to the
+    /// currently-known version.
+    pub(crate) fn apply(&mut self, difference: tl::enums::EmojiKeywordsDifference) -> bool {
+        let tl::enums::EmojiKeywordsDifference::Difference(difference) = difference;
+        if difference.from_version != self.version && !self.lang_code.is_empty() {
+            return false;
+        }
+
+        for keyword in difference.keywords {
+            match keyword {
+                tl::enums::EmojiKeyword::Keyword(keyword) => {
+                    let emoticons = self
+                        .keywords
+                        .entry(keyword.keyword.to_lowercase())
+                        .or_default();
+                    for emoticon in keyword.emoticons {
+                        if !emoticons.contains(&emoticon) {
+                            emoticons.push(emoticon);
+                        }
+                    }
+                }
+                tl::enums::EmojiKeyword::Deleted(keyword) => {
+                    let key = keyword.keyword.to_lowercase();
+                    if let Some(emoticons) = self.keywords.get_mut(&key) {
+                        emoticons.retain(|emoticon| !keyword.emoticons.contains(emoticon));
+                        if emoticons.is_empty() {
+                            self.keywords.remove(&key);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.lang_code = difference.lang_code;
+        self.version = difference.version;
+        true
+    }
+
+    /// The language code of these keywords.
+    pub fn lang_code(&self) -> &str {
+        &self.lang_code
+    }
+
+    /// The version of these keywords, which increases every time they change.
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// The emoji associated with the exact keyword, ignoring case.
+    pub fn get(&self, keyword: &str) -> &[String] {
+        self.keywords
+            .get(&keyword.to_lowercase())
+            .map(|emoticons| emoticons.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Find the emoji for a word, as the user may be typing it.
+    ///
+    /// All the keywords starting with the word (ignoring case) are considered. The emoji of the
+    /// keyword matching the word exactly come first, followed by the emoji of the shorter
+    /// keywords. Each emoji is only returned once.
+    pub fn search(&self, word: &str) -> Vec<&str> {
+        let word = word.trim().to_lowercase();
+        if word.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = self
+            .keywords
+            .iter()
+            .filter(|(keyword, _)| keyword.starts_with(&word))
+            .collect::<Vec<_>>();
+        matches.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+        let mut result = Vec::new();
+        for emoticon in matches.into_iter().flat_map(|(_, emoticons)| emoticons) {
+            if !result.contains(&emoticon.as_str()) {
+                result.push(emoticon.as_str());
+            }
+        }
+        result
+    }
+
+    /// Iterate over all the keywords and their emoji.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.keywords
+            .iter()
+            .map(|(keyword, emoticons)| (keyword.as_str(), emoticons.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn difference(
+        from_version: i32,
+        version: i32,
+        keywords: Vec<tl::enums::EmojiKeyword>,
+    ) -> tl::enums::EmojiKeywordsDifference {
+        tl::types::EmojiKeywordsDifference {
+            lang_code: "en".to_string(),
+            from_version,
+            version,
+            keywords,
+        }
+        .into()
+    }
+
+    fn keyword(keyword: &str, emoticons: &[&str]) -> tl::enums::EmojiKeyword {
+        tl::types::EmojiKeyword {
+            keyword: keyword.to_string(),
+            emoticons: emoticons.iter().map(|e| e.to_string()).collect(),
+        }
+        .into()
+    }
+
+    fn deleted(keyword: &str, emoticons: &[&str]) -> tl::enums::EmojiKeyword {
+        tl::types::EmojiKeywordDeleted {
+            keyword: keyword.to_string(),
+            emoticons: emoticons.iter().map(|e| e.to_string()).collect(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn search_prefers_exact_and_shorter_keywords() {
+        let keywords = EmojiKeywords::from_raw(difference(
+            0,
+            1,
+            vec![
+                keyword("Cat", &["🐱"]),
+                keyword("catapult", &["🪃"]),
+                keyword("cats", &["😺", "🐱"]),
+                keyword("dog", &["🐶"]),
+            ],
+        ));
+
+        assert_eq!(keywords.search("ca"), vec!["🐱", "😺", "🪃"]);
+        assert_eq!(keywords.search("CATS"), vec!["😺", "🐱"]);
+        assert!(keywords.search("").is_empty());
+        assert!(keywords.search("bird").is_empty());
+    }
+
+    #[test]
+    fn apply_difference() {
+        let mut keywords =
+            EmojiKeywords::from_raw(difference(0, 1, vec![keyword("cat", &["🐱", "😺"])]));
+
+        assert!(!keywords.apply(difference(5, 6, vec![keyword("dog", &["🐶"])])));
+        assert!(keywords.get("dog").is_empty());
+
+        assert!(keywords.apply(difference(
+            1,
+            2,
+            vec![deleted("cat", &["🐱"]), keyword("dog", &["🐶"])]
+        )));
+        assert_eq!(keywords.version(), 2);
+        assert_eq!(keywords.get("cat"), ["😺"]);
+        assert_eq!(keywords.get("dog"), ["🐶"]);
+
+        assert!(keywords.apply(difference(2, 3, vec![deleted("cat", &["😺"])])));
+        assert!(keywords.get("cat").is_empty());
+        assert_eq!(keywords.iter().count(), 1);
+    }
+}
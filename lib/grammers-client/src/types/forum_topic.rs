@@ -0,0 +1,88 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// A topic in a forum, along with the state of the logged-in user in it.
+///
+/// See [`Client::get_forum_topics_by_id`](crate::Client::get_forum_topics_by_id).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForumTopic {
+    pub raw: tl::types::ForumTopic,
+}
+
+impl ForumTopic {
+    pub(crate) fn from_raw(topic: tl::enums::ForumTopic) -> Option<Self> {
+        match topic {
+            tl::enums::ForumTopic::Topic(raw) => Some(Self { raw }),
+            tl::enums::ForumTopic::Deleted(_) => None,
+        }
+    }
+
+    /// The identifier of the topic, which is also the identifier of the message that created it.
+    pub fn id(&self) -> i32 {
+        self.raw.id
+    }
+
+    /// The title of the topic.
+    pub fn title(&self) -> &str {
+        &self.raw.title
+    }
+
+    /// When the topic was created.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The identifier of the last message sent to the topic.
+    pub fn top_message(&self) -> i32 {
+        self.raw.top_message
+    }
+
+    /// How many messages in the topic have not been read yet.
+    pub fn unread_count(&self) -> i32 {
+        self.raw.unread_count
+    }
+
+    /// How many unread messages in the topic mention the logged-in user.
+    pub fn unread_mentions_count(&self) -> i32 {
+        self.raw.unread_mentions_count
+    }
+
+    /// How many messages sent by the logged-in user to the topic have unseen reactions.
+    pub fn unread_reactions_count(&self) -> i32 {
+        self.raw.unread_reactions_count
+    }
+
+    /// Whether the topic was closed, so that only administrators can send messages to it.
+    pub fn is_closed(&self) -> bool {
+        self.raw.closed
+    }
+
+    /// Whether the topic is pinned to the top of the topic list.
+    pub fn is_pinned(&self) -> bool {
+        self.raw.pinned
+    }
+
+    /// Whether the topic is hidden. Only the General topic can be hidden.
+    pub fn is_hidden(&self) -> bool {
+        self.raw.hidden
+    }
+
+    /// Until when notifications from the topic are muted, if they are.
+    ///
+    /// `None` means the topic follows the settings of the forum itself.
+    pub fn muted_until(&self) -> Option<DateTime<Utc>> {
+        let tl::enums::PeerNotifySettings::Settings(settings) = &self.raw.notify_settings;
+        settings
+            .mute_until
+            .filter(|&until| until > 0)
+            .map(utils::date)
+    }
+}
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use super::attributes::Attribute;
-use crate::types::{Media, Uploaded};
+use crate::types::{Media, Message, Uploaded};
 use grammers_tl_types as tl;
 
 /// Construct and send albums.
@@ -77,10 +77,15 @@ impl InputMedia {
     ///
     /// You can use this to send videos, stickers, audios, or uncompressed photos.
     ///
+    /// With the `infer` feature enabled, the dimensions of images and the metadata of MP3 files
+    /// detected when uploading are included as attributes of the document.
+    ///
     /// The text will be the caption of the document, which may be empty for no caption.
     pub fn document(mut self, file: Uploaded) -> Self {
         let mime_type = self.get_file_mime(&file);
         let file_name = file.name().to_string();
+        let mut attributes = vec![(tl::types::DocumentAttributeFilename { file_name }).into()];
+        attributes.extend(file.inferred_attributes());
         self.media = Some(
             (tl::types::InputMediaUploadedDocument {
                 nosound_video: false,
@@ -89,7 +94,7 @@ impl InputMedia {
                 file: file.raw,
                 thumb: None,
                 mime_type,
-                attributes: vec![(tl::types::DocumentAttributeFilename { file_name }).into()],
+                attributes,
                 stickers: None,
                 ttl_seconds: self.media_ttl,
                 video_cover: None,
@@ -169,7 +174,13 @@ impl InputMedia {
     /// ```
     pub fn attribute(mut self, attr: Attribute) -> Self {
         if let Some(tl::enums::InputMedia::UploadedDocument(document)) = &mut self.media {
-            document.attributes.push(attr.into());
+            let attr = attr.into();
+            // Replace any attribute of the same kind inferred on upload.
+            #[cfg(feature = "infer")]
+            document
+                .attributes
+                .retain(|a| std::mem::discriminant(a) != std::mem::discriminant(&attr));
+            document.attributes.push(attr);
         }
         self
     }
@@ -240,6 +251,8 @@ impl InputMedia {
     fn get_file_mime(&self, file: &Uploaded) -> String {
         if let Some(mime) = self.mime_type.as_ref() {
             mime.clone()
+        } else if let Some(mime) = file.inferred_mime_type() {
+            mime.to_string()
         } else if let Some(mime) = mime_guess::from_path(file.name()).first() {
             mime.essence_str().to_string()
         } else {
@@ -255,6 +268,34 @@ impl InputMedia {
         }
     }
 
+    /// Builds a new media with the caption, formatting and media of an existing message.
+    ///
+    /// This is useful to send the messages of an album somewhere else without losing the
+    /// formatting of their captions. The media is not re-uploaded.
+    ///
+    /// Returns `None` if the message has no media, or if its media cannot be sent again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(album: Vec<grammers_client::types::Message>, chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMedia;
+    ///
+    /// let medias = album.iter().filter_map(InputMedia::from_message).collect();
+    /// client.send_album(&chat, medias).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let media = message.media()?.to_raw_input_media()?;
+        Some(Self {
+            caption: message.text().to_string(),
+            entities: message.fmt_entities().cloned().unwrap_or_default(),
+            media: Some(media),
+            ..Self::default()
+        })
+    }
+
     /// Builds a new media from the given markdown-formatted string as the
     /// caption contents and entities.
     ///
@@ -6,12 +6,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use super::attributes::Attribute;
-use crate::types::{Media, ReplyMarkup, Uploaded};
+use crate::types::{Media, ReplyMarkup, Uploaded, VCard};
+use crate::utils::entity_range;
 use grammers_tl_types as tl;
+use std::fmt;
+use std::time::Duration;
 use web_time::{SystemTime, UNIX_EPOCH};
 
 // https://github.com/telegramdesktop/tdesktop/blob/e7fbcce9d9f0a8944eb2c34e74bd01b8776cb891/Telegram/SourceFiles/data/data_scheduled_messages.h#L52
-const SCHEDULE_ONCE_ONLINE: i32 = 0x7ffffffe;
+pub(crate) const SCHEDULE_ONCE_ONLINE: i32 = 0x7ffffffe;
+
+/// Maximum length of the text of a message, in UTF-16 code units.
+const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// Maximum length of the caption of a media, in UTF-16 code units (for non-premium accounts).
+const MAX_CAPTION_LENGTH: usize = 1024;
 
 /// Construct and send rich text messages with various options.
 #[derive(Clone, Default)]
@@ -23,6 +32,7 @@ pub struct InputMessage {
     pub(crate) link_preview: bool,
     pub(crate) reply_markup: Option<tl::enums::ReplyMarkup>,
     pub(crate) reply_to: Option<i32>,
+    pub(crate) reply_to_story: Option<i32>,
     pub(crate) schedule_date: Option<i32>,
     pub(crate) silent: bool,
     pub(crate) text: String,
@@ -31,6 +41,52 @@ pub struct InputMessage {
     mime_type: Option<String>,
 }
 
+/// A problem with the formatting entities of a message, as found by
+/// [`InputMessage::validate_entities`].
+///
+/// Every variant refers to entities by their index in the list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntityError {
+    /// The entity has a negative offset, or a length which is not positive.
+    InvalidRange { index: usize },
+    /// The entity ends past the end of the text, which is `text_length` UTF-16 code units long.
+    OutOfBounds { index: usize, text_length: i32 },
+    /// The entity starts or ends between the two halves of a surrogate pair.
+    SplitsCharacter { index: usize },
+    /// The entity partially overlaps with another, instead of being fully inside or outside it.
+    Overlap { index: usize, other: usize },
+    /// The entity is inside another which cannot contain entities (such as code), or is a
+    /// block quotation inside another.
+    InvalidNesting { index: usize, parent: usize },
+}
+
+impl fmt::Display for EntityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EntityError::*;
+        match self {
+            InvalidRange { index } => write!(f, "entity {index} has an invalid offset or length"),
+            OutOfBounds { index, text_length } => write!(
+                f,
+                "entity {index} ends past the end of the text ({text_length} UTF-16 code units)"
+            ),
+            SplitsCharacter { index } => {
+                write!(
+                    f,
+                    "entity {index} starts or ends in the middle of a character"
+                )
+            }
+            Overlap { index, other } => {
+                write!(f, "entity {index} partially overlaps with entity {other}")
+            }
+            InvalidNesting { index, parent } => {
+                write!(f, "entity {index} cannot be nested inside entity {parent}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EntityError {}
+
 impl InputMessage {
     /// Whether to "send this message as a background message".
     ///
@@ -117,6 +173,14 @@ impl InputMessage {
         self
     }
 
+    /// Send the message as soon as the person comes online.
+    ///
+    /// This is the same as [`InputMessage::schedule_once_online`]. Messages scheduled this way
+    /// can be told apart with [`crate::types::Message::is_scheduled_when_online`].
+    pub fn send_when_online(self) -> Self {
+        self.schedule_once_online()
+    }
+
     /// Whether the message should notify people or not.
     ///
     /// Defaults to `false`, which means it will notify them. Set it to `true`
@@ -166,10 +230,15 @@ impl InputMessage {
     ///
     /// You can use this to send videos, stickers, audios, or uncompressed photos.
     ///
+    /// With the `infer` feature enabled, the dimensions of images and the metadata of MP3 files
+    /// detected when uploading are included as attributes of the document.
+    ///
     /// The text will be the caption of the document, which may be empty for no caption.
     pub fn document(mut self, file: Uploaded) -> Self {
         let mime_type = self.get_file_mime(&file);
         let file_name = file.name().to_string();
+        let mut attributes = vec![(tl::types::DocumentAttributeFilename { file_name }).into()];
+        attributes.extend(file.inferred_attributes());
         self.media = Some(
             (tl::types::InputMediaUploadedDocument {
                 nosound_video: false,
@@ -178,7 +247,7 @@ impl InputMessage {
                 file: file.raw,
                 thumb: None,
                 mime_type,
-                attributes: vec![(tl::types::DocumentAttributeFilename { file_name }).into()],
+                attributes,
                 stickers: None,
                 ttl_seconds: self.media_ttl,
                 video_cover: None,
@@ -189,6 +258,83 @@ impl InputMessage {
         self
     }
 
+    /// Include the uploaded file as a music file in the message, with the given metadata.
+    ///
+    /// Use [`InputMessage::thumbnail`] afterwards to include the cover art of the song.
+    ///
+    /// The text will be the caption of the audio, which may be empty for no caption.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: &mut grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    /// use grammers_client::InputMessage;
+    ///
+    /// let song = client.upload_file("song.mp3").await?;
+    /// let message = InputMessage::text("").audio(
+    ///     song,
+    ///     Duration::from_secs(213),
+    ///     Some("Song".to_string()),
+    ///     Some("Artist".to_string()),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn audio(
+        self,
+        file: Uploaded,
+        duration: Duration,
+        title: Option<String>,
+        performer: Option<String>,
+    ) -> Self {
+        self.document(file).attribute(Attribute::Audio {
+            duration,
+            title,
+            performer,
+        })
+    }
+
+    /// Include a contact in the message.
+    ///
+    /// If `vcard` is given, it is sent along with the contact so that more details (such as
+    /// other phone numbers or email addresses) can be shared.
+    ///
+    /// Contacts cannot have a caption, so the text should be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_client::{InputMessage, types::VCard};
+    ///
+    /// let card = VCard {
+    ///     first_name: "John".to_string(),
+    ///     last_name: "Doe".to_string(),
+    ///     phones: vec!["+1234567890".to_string()],
+    ///     emails: vec!["john@example.com".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// let message = InputMessage::text("").contact("+1234567890", "John", "Doe", Some(&card));
+    /// ```
+    pub fn contact(
+        mut self,
+        phone_number: &str,
+        first_name: &str,
+        last_name: &str,
+        vcard: Option<&VCard>,
+    ) -> Self {
+        self.media = Some(
+            (tl::types::InputMediaContact {
+                phone_number: phone_number.to_string(),
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+                vcard: vcard.map(|vcard| vcard.to_string()).unwrap_or_default(),
+            })
+            .into(),
+        );
+        self
+    }
+
     /// Include a media in the message using the raw TL types.
     ///
     /// You can use this to send any media using the raw TL types that don't have
@@ -272,7 +418,13 @@ impl InputMessage {
     /// ```
     pub fn attribute(mut self, attr: Attribute) -> Self {
         if let Some(tl::enums::InputMedia::UploadedDocument(document)) = &mut self.media {
-            document.attributes.push(attr.into());
+            let attr = attr.into();
+            // Replace any attribute of the same kind inferred on upload.
+            #[cfg(feature = "infer")]
+            document
+                .attributes
+                .retain(|a| std::mem::discriminant(a) != std::mem::discriminant(&attr));
+            document.attributes.push(attr);
         }
         self
     }
@@ -285,6 +437,23 @@ impl InputMessage {
         self
     }
 
+    /// Include a game in the message, by the short name given to it in @BotFather.
+    ///
+    /// Only bot accounts can send games, and only the games they own.
+    pub fn game(mut self, short_name: impl Into<String>) -> Self {
+        self.media = Some(
+            tl::types::InputMediaGame {
+                id: tl::types::InputGameShortName {
+                    bot_id: tl::enums::InputUser::UserSelf,
+                    short_name: short_name.into(),
+                }
+                .into(),
+            }
+            .into(),
+        );
+        self
+    }
+
     /// Include the uploaded file as a document file in the message.
     ///
     /// You can use this to send any type of media as a simple document file.
@@ -343,6 +512,8 @@ impl InputMessage {
     fn get_file_mime(&self, file: &Uploaded) -> String {
         if let Some(mime) = self.mime_type.as_ref() {
             mime.clone()
+        } else if let Some(mime) = file.inferred_mime_type() {
+            mime.to_string()
         } else if let Some(mime) = mime_guess::from_path(file.name()).first() {
             mime.essence_str().to_string()
         } else {
@@ -375,6 +546,23 @@ impl InputMessage {
         }
     }
 
+    /// Builds a new message from the given string in the [MarkdownV2] dialect of the Bot API
+    /// as the message contents and entities.
+    ///
+    /// This supports everything the dialect does, including spoilers, block quotations, custom
+    /// emoji and its escaping rules, which makes it easier to port Bot API bots.
+    ///
+    /// [MarkdownV2]: https://core.telegram.org/bots/api#markdownv2-style
+    #[cfg(feature = "markdown")]
+    pub fn markdown_v2<T: AsRef<str>>(s: T) -> Self {
+        let (text, entities) = crate::parsers::parse_markdown_v2_message(s.as_ref());
+        Self {
+            text,
+            entities,
+            ..Self::default()
+        }
+    }
+
     /// Builds a new message from the given HTML-formatted string as the
     /// message contents and entities.
     ///
@@ -391,6 +579,459 @@ impl InputMessage {
             ..Self::default()
         }
     }
+
+    /// Builds a new message with the same contents as an existing message.
+    ///
+    /// The text, formatting entities, media (without re-uploading it), reply markup and
+    /// whether the media is inverted are all copied, and can be changed before sending the
+    /// message again. Link previews are enabled if the existing message had one.
+    ///
+    /// Some media, such as stories, cannot be copied, in which case only the text is kept.
+    /// Only bot accounts can send messages with reply markup. Converting with [`From`] only
+    /// copies the text, formatting entities and media.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    ///
+    /// client
+    ///     .send_message(&chat, InputMessage::from_message(&message).silent(true))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_message(message: &super::Message) -> Self {
+        let media = message.media();
+        Self {
+            text: message.text().to_owned(),
+            entities: message.fmt_entities().cloned().unwrap_or_default(),
+            invert_media: message.raw.invert_media,
+            link_preview: matches!(media, Some(Media::WebPage(_))),
+            reply_markup: message.reply_markup(),
+            media: media.and_then(|m| m.to_raw_input_media()),
+            ..Self::default()
+        }
+    }
+}
+
+impl InputMessage {
+    /// Split this message into as many messages as needed so that none exceeds the maximum
+    /// length allowed by Telegram.
+    ///
+    /// Messages are preferably split at paragraph breaks, then at word boundaries. Formatting
+    /// entities spanning multiple messages are split so that each part retains its formatting,
+    /// but code blocks and entities which can't be split (such as mentions or URLs) are only
+    /// split when they're too long to fit in a single message.
+    ///
+    /// If the message has media, it will be sent with the first part, with the shorter caption
+    /// limit applied to it. The first part will also keep the message being replied to, and the
+    /// last part will keep the reply markup. All other options are kept by every part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    ///
+    /// let long_text = "Lorem ipsum. ".repeat(1000);
+    /// for part in InputMessage::text(long_text).split_long() {
+    ///     client.send_message(&chat, part).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_long(self) -> Vec<Self> {
+        let first_limit = if self.media.is_some() {
+            MAX_CAPTION_LENGTH
+        } else {
+            MAX_MESSAGE_LENGTH
+        };
+        let ranges = split_ranges(&self.text, &self.entities, first_limit, MAX_MESSAGE_LENGTH);
+        let last = ranges.len().saturating_sub(1);
+
+        if ranges.len() <= 1 {
+            return vec![self];
+        }
+
+        let utf16 = self.text.encode_utf16().collect::<Vec<_>>();
+        ranges
+            .into_iter()
+            .enumerate()
+            .map(|(i, (start, end))| Self {
+                text: String::from_utf16_lossy(&utf16[start..end]),
+                entities: clip_entities(&self.entities, start as i32, end as i32),
+                media: if i == 0 { self.media.clone() } else { None },
+                reply_to: if i == 0 { self.reply_to } else { None },
+                reply_to_story: if i == 0 { self.reply_to_story } else { None },
+                reply_markup: if i == last {
+                    self.reply_markup.clone()
+                } else {
+                    None
+                },
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+impl InputMessage {
+    /// Check that the formatting entities are valid for the text of the message, returning
+    /// every problem found.
+    ///
+    /// Telegram rejects invalid entities with errors such as `ENTITY_BOUNDS_INVALID`, which
+    /// don't say which entity is wrong. Use [`InputMessage::sanitize_entities`] to repair them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_client::InputMessage;
+    /// use grammers_client::grammers_tl_types as tl;
+    ///
+    /// let message = InputMessage::text("hi").fmt_entities(vec![
+    ///     tl::types::MessageEntityBold { offset: 0, length: 5 }.into(),
+    /// ]);
+    ///
+    /// for error in message.validate_entities().unwrap_err() {
+    ///     println!("{error}");
+    /// }
+    /// ```
+    pub fn validate_entities(&self) -> Result<(), Vec<EntityError>> {
+        let text_length = self.text.encode_utf16().count() as i32;
+        let splits = surrogate_splits(&self.text);
+        let mut errors = Vec::new();
+        let mut valid = Vec::with_capacity(self.entities.len());
+
+        for (index, entity) in self.entities.iter().enumerate() {
+            let (offset, length) = entity_range(entity);
+            if offset < 0 || length <= 0 {
+                errors.push(EntityError::InvalidRange { index });
+                continue;
+            }
+            match offset.checked_add(length) {
+                Some(end) if end <= text_length => {
+                    if splits.contains(&offset) || splits.contains(&end) {
+                        errors.push(EntityError::SplitsCharacter { index });
+                    } else {
+                        valid.push(index);
+                    }
+                }
+                _ => errors.push(EntityError::OutOfBounds { index, text_length }),
+            }
+        }
+
+        for (i, &index) in valid.iter().enumerate() {
+            let entity = &self.entities[index];
+            let (start, end) = entity_bounds(entity);
+            for &other in &valid[..i] {
+                let other_entity = &self.entities[other];
+                let (other_start, other_end) = entity_bounds(other_entity);
+                if end <= other_start || other_end <= start {
+                    continue;
+                }
+
+                let inside = other_start <= start && end <= other_end;
+                let outside = start <= other_start && other_end <= end;
+                if !inside && !outside {
+                    errors.push(EntityError::Overlap { index, other });
+                } else if (start, end) == (other_start, other_end) {
+                    if !can_nest(other_entity, entity) && !can_nest(entity, other_entity) {
+                        errors.push(EntityError::InvalidNesting {
+                            index,
+                            parent: other,
+                        });
+                    }
+                } else if inside && !can_nest(other_entity, entity) {
+                    errors.push(EntityError::InvalidNesting {
+                        index,
+                        parent: other,
+                    });
+                } else if outside && !can_nest(entity, other_entity) {
+                    errors.push(EntityError::InvalidNesting {
+                        index: other,
+                        parent: index,
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Repair the formatting entities so that they pass [`InputMessage::validate_entities`].
+    ///
+    /// Entities are clipped to the text and widened to whole characters, and those left empty
+    /// are removed. Formatting entities which partially overlap with a previous one are split
+    /// in two, and any other entity which overlaps or is nested where it can't be is removed.
+    /// The entities are also sorted by their offset.
+    pub fn sanitize_entities(mut self) -> Self {
+        let text_length = self.text.encode_utf16().count() as i32;
+        let splits = surrogate_splits(&self.text);
+
+        let mut pending = std::mem::take(&mut self.entities)
+            .into_iter()
+            .filter_map(|mut entity| {
+                let (offset, length) = entity_range(&entity);
+                let mut start = offset.clamp(0, text_length);
+                let mut end = offset.saturating_add(length).clamp(0, text_length);
+                if splits.contains(&start) {
+                    start -= 1;
+                }
+                if splits.contains(&end) {
+                    end += 1;
+                }
+                (start < end).then(|| {
+                    set_entity_range(&mut entity, start, end - start);
+                    entity
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Parents must come before their children, so sort by offset, then longest first, then
+        // those which can contain others first. Entities are popped from the end.
+        let sort = |entities: &mut Vec<tl::enums::MessageEntity>| {
+            entities.sort_by_key(|entity| {
+                let (start, end) = entity_bounds(entity);
+                std::cmp::Reverse((start, -end, !can_contain_entities(entity)))
+            })
+        };
+        sort(&mut pending);
+
+        let mut kept = Vec::<tl::enums::MessageEntity>::with_capacity(pending.len());
+        'pending: while let Some(mut entity) = pending.pop() {
+            let (start, mut end) = entity_bounds(&entity);
+            for parent in &kept {
+                let (_, parent_end) = entity_bounds(parent);
+                if parent_end <= start {
+                    continue;
+                }
+
+                if parent_end < end {
+                    if is_atomic_entity(&entity) || is_atomic_entity(parent) {
+                        continue 'pending;
+                    }
+                    // Keep the part inside the parent, and handle the rest later.
+                    let mut rest = entity.clone();
+                    set_entity_range(&mut rest, parent_end, end - parent_end);
+                    pending.push(rest);
+                    sort(&mut pending);
+                    end = parent_end;
+                    set_entity_range(&mut entity, start, end - start);
+                }
+                if !can_nest(parent, &entity) {
+                    continue 'pending;
+                }
+            }
+            kept.push(entity);
+        }
+
+        self.entities = kept;
+        self
+    }
+}
+
+/// Change the offset and length of any entity.
+fn set_entity_range(entity: &mut tl::enums::MessageEntity, offset: i32, length: i32) {
+    use tl::enums::MessageEntity as E;
+
+    let (o, l) = match entity {
+        E::Unknown(e) => (&mut e.offset, &mut e.length),
+        E::Mention(e) => (&mut e.offset, &mut e.length),
+        E::Hashtag(e) => (&mut e.offset, &mut e.length),
+        E::BotCommand(e) => (&mut e.offset, &mut e.length),
+        E::Url(e) => (&mut e.offset, &mut e.length),
+        E::Email(e) => (&mut e.offset, &mut e.length),
+        E::Bold(e) => (&mut e.offset, &mut e.length),
+        E::Italic(e) => (&mut e.offset, &mut e.length),
+        E::Code(e) => (&mut e.offset, &mut e.length),
+        E::Pre(e) => (&mut e.offset, &mut e.length),
+        E::TextUrl(e) => (&mut e.offset, &mut e.length),
+        E::MentionName(e) => (&mut e.offset, &mut e.length),
+        E::InputMessageEntityMentionName(e) => (&mut e.offset, &mut e.length),
+        E::Phone(e) => (&mut e.offset, &mut e.length),
+        E::Cashtag(e) => (&mut e.offset, &mut e.length),
+        E::Underline(e) => (&mut e.offset, &mut e.length),
+        E::Strike(e) => (&mut e.offset, &mut e.length),
+        E::BankCard(e) => (&mut e.offset, &mut e.length),
+        E::Spoiler(e) => (&mut e.offset, &mut e.length),
+        E::CustomEmoji(e) => (&mut e.offset, &mut e.length),
+        E::Blockquote(e) => (&mut e.offset, &mut e.length),
+    };
+    *o = offset;
+    *l = length;
+}
+
+/// Whether the entity loses its meaning if split, such as code blocks or mentions.
+fn is_atomic_entity(entity: &tl::enums::MessageEntity) -> bool {
+    use tl::enums::MessageEntity as E;
+
+    matches!(
+        entity,
+        E::Mention(_)
+            | E::Hashtag(_)
+            | E::BotCommand(_)
+            | E::Url(_)
+            | E::Email(_)
+            | E::Code(_)
+            | E::Pre(_)
+            | E::Phone(_)
+            | E::Cashtag(_)
+            | E::BankCard(_)
+            | E::CustomEmoji(_)
+    )
+}
+
+/// Get the start and end of any entity.
+fn entity_bounds(entity: &tl::enums::MessageEntity) -> (i32, i32) {
+    let (offset, length) = entity_range(entity);
+    (offset, offset + length)
+}
+
+/// Whether other entities can be nested inside the entity.
+fn can_contain_entities(entity: &tl::enums::MessageEntity) -> bool {
+    use tl::enums::MessageEntity as E;
+
+    !matches!(entity, E::Code(_) | E::Pre(_) | E::CustomEmoji(_))
+}
+
+/// Whether `child` can be nested inside `parent`.
+fn can_nest(parent: &tl::enums::MessageEntity, child: &tl::enums::MessageEntity) -> bool {
+    use tl::enums::MessageEntity as E;
+
+    can_contain_entities(parent) && !matches!((parent, child), (E::Blockquote(_), E::Blockquote(_)))
+}
+
+/// The UTF-16 positions which fall between the two halves of a surrogate pair.
+fn surrogate_splits(text: &str) -> Vec<i32> {
+    let mut position = 0;
+    let mut splits = Vec::new();
+    for c in text.chars() {
+        if c.len_utf16() == 2 {
+            splits.push(position + 1);
+        }
+        position += c.len_utf16() as i32;
+    }
+    splits
+}
+
+/// Keep the part of the entities within `start..end`, relative to `start`.
+fn clip_entities(
+    entities: &[tl::enums::MessageEntity],
+    start: i32,
+    end: i32,
+) -> Vec<tl::enums::MessageEntity> {
+    entities
+        .iter()
+        .filter_map(|entity| {
+            let (offset, length) = entity_range(entity);
+            let clipped_start = offset.max(start);
+            let clipped_end = offset.saturating_add(length).min(end);
+            (clipped_end > clipped_start).then(|| {
+                let mut entity = entity.clone();
+                set_entity_range(
+                    &mut entity,
+                    clipped_start - start,
+                    clipped_end - clipped_start,
+                );
+                entity
+            })
+        })
+        .collect()
+}
+
+/// Determine the ranges, in UTF-16 code units, in which the text should be split so that no
+/// range exceeds the limits. The first range uses `first_limit`, and the rest `limit`.
+///
+/// Whitespace around the split points is left out of the ranges.
+fn split_ranges(
+    text: &str,
+    entities: &[tl::enums::MessageEntity],
+    first_limit: usize,
+    limit: usize,
+) -> Vec<(usize, usize)> {
+    // Every character boundary, as (UTF-16 position, preceding character).
+    let mut boundaries = Vec::with_capacity(text.len());
+    let mut position = 0;
+    for c in text.chars() {
+        position += c.len_utf16();
+        boundaries.push((position, c));
+    }
+    let total = position;
+
+    let is_whitespace_at = |i: usize| boundaries.get(i).is_some_and(|(_, c)| c.is_whitespace());
+    let inside = |position: usize, atomic_only: bool| {
+        entities
+            .iter()
+            .filter(|entity| !atomic_only || is_atomic_entity(entity))
+            .any(|entity| {
+                let (offset, length) = entity_range(entity);
+                (offset as usize) < position && position < offset.saturating_add(length) as usize
+            })
+    };
+
+    let mut ranges = Vec::new();
+    // Index into `boundaries` of the first character of the current range.
+    let mut i = 0;
+    while i < boundaries.len() {
+        while is_whitespace_at(i) {
+            i += 1;
+        }
+        if i >= boundaries.len() {
+            break;
+        }
+
+        let start = boundaries[i].0 - boundaries[i].1.len_utf16();
+        let limit = if ranges.is_empty() {
+            first_limit
+        } else {
+            limit
+        };
+
+        let end_index = if total - start <= limit {
+            boundaries.len() - 1
+        } else {
+            // Candidate ends, as indices into `boundaries`, from furthest to closest.
+            let candidates =
+                i..boundaries.partition_point(|&(position, _)| position <= start + limit);
+            let half = start + limit / 2;
+
+            // Prefer paragraph breaks (unless the range would be too short), then word
+            // boundaries, and avoid splitting entities (and never split atomic ones if possible).
+            let preference = |j: usize| {
+                let position = boundaries[j].0;
+                let around = [boundaries[j].1, boundaries.get(j + 1).map_or(' ', |b| b.1)];
+                let newline = around.contains(&'\n') && position >= half;
+                let whitespace = around.iter().any(|c| c.is_whitespace());
+                match (inside(position, false), inside(position, true)) {
+                    (false, _) if newline => 0,
+                    (false, _) if whitespace => 1,
+                    (_, false) if newline => 2,
+                    (_, false) if whitespace => 3,
+                    (_, false) => 4,
+                    _ => 5,
+                }
+            };
+            candidates
+                .rev()
+                .min_by_key(|&j| preference(j))
+                // A single character may not fit if the limit is absurdly small.
+                .unwrap_or(i)
+        };
+
+        let mut trimmed_end = end_index;
+        while trimmed_end > i && boundaries[trimmed_end].1.is_whitespace() {
+            trimmed_end -= 1;
+        }
+        ranges.push((start, boundaries[trimmed_end].0));
+        i = end_index + 1;
+    }
+
+    ranges
 }
 
 impl From<&str> for InputMessage {
@@ -418,3 +1059,135 @@ impl From<&super::Message> for InputMessage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bold(offset: i32, length: i32) -> tl::enums::MessageEntity {
+        tl::types::MessageEntityBold { offset, length }.into()
+    }
+
+    fn code(offset: i32, length: i32) -> tl::enums::MessageEntity {
+        tl::types::MessageEntityCode { offset, length }.into()
+    }
+
+    #[test]
+    fn split_at_words() {
+        assert_eq!(
+            split_ranges("aaa bbb ccc", &[], 7, 7),
+            vec![(0, 7), (8, 11)]
+        );
+        assert_eq!(split_ranges("aaa", &[], 7, 7), vec![(0, 3)]);
+        assert_eq!(split_ranges("  ", &[], 7, 7), vec![]);
+    }
+
+    #[test]
+    fn split_prefers_paragraphs() {
+        assert_eq!(
+            split_ranges("aaaa\nbb cc", &[], 9, 9),
+            vec![(0, 4), (5, 10)]
+        );
+    }
+
+    #[test]
+    fn split_avoids_entities() {
+        let entities = [bold(3, 5)];
+        assert_eq!(
+            split_ranges("aa bb cc dd", &entities, 7, 7),
+            vec![(0, 2), (3, 8), (9, 11)]
+        );
+    }
+
+    #[test]
+    fn split_long_atomic_entities() {
+        let entities = [code(0, 10)];
+        assert_eq!(
+            split_ranges("abcdefghij", &entities, 4, 4),
+            vec![(0, 4), (4, 8), (8, 10)]
+        );
+        assert_eq!(clip_entities(&entities, 4, 8), vec![code(0, 4)]);
+    }
+
+    #[test]
+    fn split_surrogate_pairs() {
+        assert_eq!(split_ranges("😀😀😀", &[], 4, 4), vec![(0, 4), (4, 6)]);
+    }
+
+    #[test]
+    fn split_long_message() {
+        let text = format!("{} {}", "a".repeat(4000), "b".repeat(200));
+        let message = InputMessage::text(&text)
+            .fmt_entities(vec![bold(3990, 20)])
+            .reply_to(Some(1));
+
+        let parts = message.split_long();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].text, "a".repeat(4000));
+        assert_eq!(parts[0].entities, vec![bold(3990, 10)]);
+        assert_eq!(parts[0].reply_to, Some(1));
+        assert_eq!(parts[1].text, "b".repeat(200));
+        assert_eq!(parts[1].entities, vec![bold(0, 9)]);
+        assert_eq!(parts[1].reply_to, None);
+    }
+
+    #[test]
+    fn validate_entities() {
+        let message = InputMessage::text("hi 🦀 there").fmt_entities(vec![
+            bold(0, 20),
+            bold(-1, 2),
+            code(3, 1),
+            bold(0, 5),
+            code(3, 4),
+            bold(5, 2),
+            bold(1, i32::MAX),
+        ]);
+
+        assert_eq!(
+            message.validate_entities(),
+            Err(vec![
+                EntityError::OutOfBounds {
+                    index: 0,
+                    text_length: 11
+                },
+                EntityError::InvalidRange { index: 1 },
+                EntityError::SplitsCharacter { index: 2 },
+                EntityError::OutOfBounds {
+                    index: 6,
+                    text_length: 11
+                },
+                EntityError::Overlap { index: 4, other: 3 },
+                EntityError::InvalidNesting {
+                    index: 5,
+                    parent: 4
+                },
+            ])
+        );
+        assert_eq!(
+            InputMessage::text("hi")
+                .fmt_entities(vec![code(0, 2), bold(0, 2)])
+                .validate_entities(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn sanitize_entities() {
+        let message = InputMessage::text("hi 🦀 there")
+            .fmt_entities(vec![
+                bold(0, 20),
+                bold(-1, 2),
+                code(4, 1),
+                bold(5, 3),
+                code(2, 3),
+                bold(6, 1),
+            ])
+            .sanitize_entities();
+
+        assert_eq!(
+            message.entities,
+            vec![bold(0, 11), bold(0, 1), code(2, 3), bold(5, 3), bold(6, 1)]
+        );
+        assert_eq!(message.validate_entities(), Ok(()));
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap, User};
+use crate::{Client, InvocationError, utils};
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// Represents a request of a user to join a group or channel.
+///
+/// Telegram only sends these to bots which are administrators in the chat with permission to
+/// invite users. The request can be handled with [`JoinRequestUpdate::approve`] and
+/// [`JoinRequestUpdate::decline`].
+#[derive(Clone)]
+pub struct JoinRequestUpdate {
+    raw: tl::types::UpdateBotChatInviteRequester,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+impl JoinRequestUpdate {
+    pub(crate) fn from_raw(
+        client: &Client,
+        update: tl::types::UpdateBotChatInviteRequester,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        Self {
+            raw: update,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// The group or channel the user wants to join.
+    pub fn chat(&self) -> Chat {
+        utils::always_find_entity(&self.raw.peer, &self.chats, &self.client)
+    }
+
+    /// The user who requested to join.
+    pub fn user(&self) -> User {
+        let peer = tl::types::PeerUser {
+            user_id: self.raw.user_id,
+        }
+        .into();
+        match utils::always_find_entity(&peer, &self.chats, &self.client) {
+            Chat::User(user) => user,
+            _ => unreachable!(),
+        }
+    }
+
+    /// When the request was made.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The message the user sent along with the request. It may be empty.
+    pub fn about(&self) -> &str {
+        &self.raw.about
+    }
+
+    /// The invite link used to request joining.
+    pub fn invite(&self) -> &tl::enums::ExportedChatInvite {
+        &self.raw.invite
+    }
+
+    /// Approve the request, letting the user join the chat.
+    pub async fn approve(&self) -> Result<(), InvocationError> {
+        self.client
+            .approve_join_request(self.chat(), self.user())
+            .await
+    }
+
+    /// Decline the request.
+    pub async fn decline(&self) -> Result<(), InvocationError> {
+        self.client
+            .decline_join_request(self.chat(), self.user())
+            .await
+    }
+}
+
+impl fmt::Debug for JoinRequestUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinRequestUpdate")
+            .field("chat", &self.chat())
+            .field("user", &self.user())
+            .field("date", &self.date())
+            .field("about", &self.about())
+            .finish()
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::Chat;
+use crate::utils;
+use chrono::{DateTime, Utc};
+
+/// A user or location-based group found near a given location.
+///
+/// See [`Client::get_located_chats`](crate::Client::get_located_chats).
+#[derive(Clone, Debug)]
+pub struct LocatedChat {
+    chat: Chat,
+    distance: i32,
+    expires: i32,
+}
+
+impl LocatedChat {
+    pub(crate) fn new(chat: Chat, distance: i32, expires: i32) -> Self {
+        Self {
+            chat,
+            distance,
+            expires,
+        }
+    }
+
+    /// The user or group found nearby.
+    pub fn chat(&self) -> &Chat {
+        &self.chat
+    }
+
+    /// The distance to the chat, in meters.
+    pub fn distance(&self) -> i32 {
+        self.distance
+    }
+
+    /// When the chat will stop being shown nearby, unless its location is updated.
+    pub fn expires(&self) -> DateTime<Utc> {
+        utils::date(self.expires)
+    }
+}
@@ -6,7 +6,61 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use grammers_tl_types as tl;
+
 pub struct LoginToken {
     pub(crate) phone: String,
     pub(crate) phone_code_hash: String,
+    pub(crate) email_setup_required: bool,
+}
+
+/// Information about the verification code sent to an email address.
+#[derive(Clone, Debug)]
+pub struct SentEmailCode {
+    /// The email address the code was sent to, partially hidden.
+    pub email_pattern: String,
+    /// The length of the code.
+    pub length: i32,
+}
+
+impl LoginToken {
+    pub(crate) fn from_raw(phone: &str, sent_code: tl::types::auth::SentCode) -> Self {
+        Self {
+            phone: phone.to_string(),
+            phone_code_hash: sent_code.phone_code_hash,
+            email_setup_required: matches!(
+                sent_code.r#type,
+                tl::enums::auth::SentCodeType::SetUpEmailRequired(_)
+            ),
+        }
+    }
+
+    /// Whether a login email must be set up before the login code is sent.
+    ///
+    /// If it is, use [`Client::request_login_email_setup_code`] and
+    /// [`Client::verify_login_email_setup`] to obtain a new token.
+    ///
+    /// [`Client::request_login_email_setup_code`]: crate::Client::request_login_email_setup_code
+    /// [`Client::verify_login_email_setup`]: crate::Client::verify_login_email_setup
+    pub fn is_email_setup_required(&self) -> bool {
+        self.email_setup_required
+    }
+
+    pub(crate) fn email_setup_purpose(&self) -> tl::enums::EmailVerifyPurpose {
+        tl::types::EmailVerifyPurposeLoginSetup {
+            phone_number: self.phone.clone(),
+            phone_code_hash: self.phone_code_hash.clone(),
+        }
+        .into()
+    }
+}
+
+impl SentEmailCode {
+    pub(crate) fn from_raw(raw: tl::enums::account::SentEmailCode) -> Self {
+        let tl::enums::account::SentEmailCode::Code(code) = raw;
+        Self {
+            email_pattern: code.email_pattern,
+            length: code.length,
+        }
+    }
 }
@@ -5,13 +5,18 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use crate::types::VCard;
 use crate::types::photo_sizes::{PhotoSize, VecExt};
 use chrono::{DateTime, Utc};
 use grammers_tl_types as tl;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use super::Downloadable;
 
+/// Files larger than this cannot be sent as photos.
+const MAX_PHOTO_SIZE: usize = 10 * 1024 * 1024;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Photo {
     pub raw: tl::types::MessageMediaPhoto,
@@ -29,9 +34,22 @@ pub struct Sticker {
     animated: bool,
 }
 
+/// A music file, with its title and performer.
+///
+/// Voice notes are not considered audio files, and remain as [`Document`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Audio {
+    pub document: Document,
+    pub raw_attrs: tl::types::DocumentAttributeAudio,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Uploaded {
     pub raw: tl::enums::InputFile,
+    /// The size of the uploaded file in bytes, if known.
+    pub(crate) size: Option<usize>,
+    #[cfg(feature = "infer")]
+    pub(crate) info: Option<crate::infer::FileInfo>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -45,6 +63,13 @@ pub struct Poll {
     pub raw_results: tl::types::PollResults,
 }
 
+/// One of the options which can be voted in a [`Poll`], along with its results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollOption<'a> {
+    pub raw: &'a tl::types::PollAnswer,
+    pub raw_results: Option<&'a tl::types::PollAnswerVoters>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Geo {
     pub raw: tl::types::GeoPoint,
@@ -72,6 +97,16 @@ pub struct WebPage {
     pub raw: tl::types::MessageMediaWebPage,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedStory {
+    pub raw: tl::types::MessageMediaStory,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Game {
+    pub raw: tl::types::MessageMediaGame,
+}
+
 // Not `MessageMedia`, but media nonetheless.
 #[derive(Clone, Debug, PartialEq)]
 pub struct ChatPhoto {
@@ -84,6 +119,7 @@ pub enum Media {
     Photo(Photo),
     Document(Document),
     Sticker(Sticker),
+    Audio(Audio),
     Contact(Contact),
     Poll(Poll),
     Geo(Geo),
@@ -91,6 +127,8 @@ pub enum Media {
     Venue(Venue),
     GeoLive(GeoLive),
     WebPage(WebPage),
+    Story(SharedStory),
+    Game(Game),
 }
 
 impl Photo {
@@ -210,6 +248,23 @@ impl Downloadable for Photo {
 }
 
 impl Document {
+    pub fn from_raw(document: tl::enums::Document) -> Self {
+        Self {
+            raw: tl::types::MessageMediaDocument {
+                nopremium: false,
+                spoiler: false,
+                video: false,
+                round: false,
+                voice: false,
+                document: Some(document),
+                alt_documents: None,
+                video_cover: None,
+                video_timestamp: None,
+                ttl_seconds: None,
+            },
+        }
+    }
+
     pub fn from_raw_media(document: tl::types::MessageMediaDocument) -> Self {
         Self { raw: document }
     }
@@ -404,6 +459,63 @@ impl Document {
     pub fn is_spoiler(&self) -> bool {
         self.raw.spoiler
     }
+
+    /// Returns true if the document is a video, including round video messages.
+    pub fn is_video(&self) -> bool {
+        self.attributes()
+            .iter()
+            .any(|attr| matches!(attr, tl::enums::DocumentAttribute::Video(_)))
+    }
+
+    /// Returns true if the document is a round video message.
+    pub fn is_round_video(&self) -> bool {
+        self.attributes()
+            .iter()
+            .any(|attr| matches!(attr, tl::enums::DocumentAttribute::Video(v) if v.round_message))
+    }
+
+    /// Returns true if the document is an audio file, but not a voice message.
+    pub fn is_audio(&self) -> bool {
+        self.attributes()
+            .iter()
+            .any(|attr| matches!(attr, tl::enums::DocumentAttribute::Audio(a) if !a.voice))
+    }
+
+    /// Returns true if the document is a voice message.
+    pub fn is_voice(&self) -> bool {
+        self.attributes()
+            .iter()
+            .any(|attr| matches!(attr, tl::enums::DocumentAttribute::Audio(a) if a.voice))
+    }
+
+    /// Returns true if the document is a sticker, be it static, animated or a video.
+    pub fn is_sticker(&self) -> bool {
+        self.attributes()
+            .iter()
+            .any(|attr| matches!(attr, tl::enums::DocumentAttribute::Sticker(_)))
+    }
+
+    /// Return the same video in alternative qualities, if any.
+    ///
+    /// Telegram may encode videos posted in channels in several resolutions, so that a lower
+    /// quality can be downloaded instead. Use [`Document::resolution`] and [`Document::size`]
+    /// to pick one.
+    pub fn alt_documents(&self) -> Vec<Document> {
+        self.raw
+            .alt_documents
+            .iter()
+            .flatten()
+            .cloned()
+            .map(Document::from_raw)
+            .collect()
+    }
+
+    fn attributes(&self) -> &[tl::enums::DocumentAttribute] {
+        match self.raw.document.as_ref() {
+            Some(tl::enums::Document::Document(d)) => &d.attributes,
+            _ => &[],
+        }
+    }
 }
 
 impl Downloadable for Document {
@@ -463,6 +575,45 @@ impl Sticker {
     }
 }
 
+impl Audio {
+    pub fn from_document(document: &Document) -> Option<Self> {
+        match document.raw.document {
+            Some(tl::enums::Document::Document(ref doc)) => {
+                doc.attributes.iter().find_map(|attr| match attr {
+                    tl::enums::DocumentAttribute::Audio(audio) if !audio.voice => Some(Self {
+                        document: document.clone(),
+                        raw_attrs: audio.clone(),
+                    }),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The title of the song, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.raw_attrs.title.as_deref()
+    }
+
+    /// The performer (artist) of the song, if any.
+    pub fn performer(&self) -> Option<&str> {
+        self.raw_attrs.performer.as_deref()
+    }
+
+    /// The duration of the song.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.raw_attrs.duration.max(0) as u64)
+    }
+
+    /// The largest version of the cover art embedded in the file, if any.
+    ///
+    /// It can be downloaded with [`Client::download_media`](crate::Client::download_media).
+    pub fn cover(&self) -> Option<PhotoSize> {
+        self.document.thumbs().largest().cloned()
+    }
+}
+
 impl Contact {
     pub fn from_raw_media(contact: tl::types::MessageMediaContact) -> Self {
         Self { raw: contact }
@@ -502,6 +653,17 @@ impl Contact {
     pub fn vcard(&self) -> &str {
         self.raw.vcard.as_str()
     }
+
+    /// The contact information from [`Contact::vcard`], parsed. Returns `None` if the field
+    /// does not contain a valid vCard.
+    pub fn parsed_vcard(&self) -> Option<VCard> {
+        VCard::parse(&self.raw.vcard)
+    }
+
+    /// The identifier of the Telegram user with this phone number, if the sender knows them.
+    pub fn user_id(&self) -> Option<i64> {
+        Some(self.raw.user_id).filter(|&id| id != 0)
+    }
 }
 
 impl Poll {
@@ -565,6 +727,46 @@ impl Poll {
             })
         })
     }
+
+    /// The options of the poll, along with their results if they are known.
+    ///
+    /// Results are usually only known after voting, or if the poll is closed.
+    pub fn options(&self) -> impl Iterator<Item = PollOption<'_>> {
+        self.iter_answers().map(|answer| PollOption {
+            raw: answer,
+            raw_results: self
+                .iter_voters_summary()
+                .and_then(|mut results| results.find(|r| r.option == answer.option)),
+        })
+    }
+}
+
+impl PollOption<'_> {
+    /// The text of the option.
+    pub fn text(&self) -> &str {
+        let tl::enums::TextWithEntities::Entities(text) = &self.raw.text;
+        &text.text
+    }
+
+    /// The opaque identifier of the option, used to vote for it or to fetch its voters.
+    pub fn option(&self) -> &[u8] {
+        &self.raw.option
+    }
+
+    /// How many users voted for this option, if the results are known.
+    pub fn voters(&self) -> Option<i32> {
+        self.raw_results.map(|results| results.voters)
+    }
+
+    /// Whether the logged-in user voted for this option.
+    pub fn chosen(&self) -> bool {
+        self.raw_results.is_some_and(|results| results.chosen)
+    }
+
+    /// Whether this option is the correct answer of a quiz, if the results are known.
+    pub fn correct(&self) -> bool {
+        self.raw_results.is_some_and(|results| results.correct)
+    }
 }
 
 impl Geo {
@@ -735,9 +937,142 @@ impl WebPage {
     }
 }
 
+impl SharedStory {
+    pub fn from_raw_media(story: tl::types::MessageMediaStory) -> Self {
+        Self { raw: story }
+    }
+
+    /// Get the identifier of the story, unique only within the chat that posted it.
+    pub fn id(&self) -> i32 {
+        self.raw.id
+    }
+
+    /// Get the peer of the chat that posted the story.
+    pub fn peer(&self) -> &tl::enums::Peer {
+        &self.raw.peer
+    }
+
+    /// Whether this story was shared because the logged-in account was mentioned in it.
+    pub fn via_mention(&self) -> bool {
+        self.raw.via_mention
+    }
+
+    /// Get the contents of the story, if Telegram included them.
+    pub fn item(&self) -> Option<&tl::types::StoryItem> {
+        match self.raw.story.as_ref()? {
+            tl::enums::StoryItem::Item(item) => Some(item),
+            tl::enums::StoryItem::Deleted(_) | tl::enums::StoryItem::Skipped(_) => None,
+        }
+    }
+}
+
+impl Game {
+    pub fn from_raw_media(game: tl::types::MessageMediaGame) -> Self {
+        Self { raw: game }
+    }
+
+    pub fn to_raw_input_media(&self) -> tl::types::InputMediaGame {
+        let tl::enums::Game::Game(game) = &self.raw.game;
+        tl::types::InputMediaGame {
+            id: tl::types::InputGameId {
+                id: game.id,
+                access_hash: game.access_hash,
+            }
+            .into(),
+        }
+    }
+
+    fn game(&self) -> &tl::types::Game {
+        let tl::enums::Game::Game(game) = &self.raw.game;
+        game
+    }
+
+    /// Get the short name of the game, unique among the games of the bot that owns it.
+    pub fn short_name(&self) -> &str {
+        &self.game().short_name
+    }
+
+    /// Get the title of the game.
+    pub fn title(&self) -> &str {
+        &self.game().title
+    }
+
+    /// Get the description of the game.
+    pub fn description(&self) -> &str {
+        &self.game().description
+    }
+
+    /// Get the photo shown as the cover of the game.
+    pub fn photo(&self) -> Photo {
+        Photo::from_raw(self.game().photo.clone())
+    }
+
+    /// Get the animation shown as the cover of the game, if any.
+    pub fn document(&self) -> Option<Document> {
+        self.game().document.clone().map(|document| {
+            Document::from_raw_media(tl::types::MessageMediaDocument {
+                nopremium: false,
+                spoiler: false,
+                video: false,
+                round: false,
+                voice: false,
+                document: Some(document),
+                alt_documents: None,
+                video_cover: None,
+                video_timestamp: None,
+                ttl_seconds: None,
+            })
+        })
+    }
+}
+
 impl Uploaded {
     pub fn from_raw(input_file: tl::enums::InputFile) -> Self {
-        Self { raw: input_file }
+        Self {
+            raw: input_file,
+            size: None,
+            #[cfg(feature = "infer")]
+            info: None,
+        }
+    }
+
+    /// Change the name of the uploaded file, which does not require uploading it again.
+    #[cfg(feature = "fs")]
+    pub(crate) fn with_name(mut self, name: String) -> Self {
+        match &mut self.raw {
+            tl::enums::InputFile::File(file) => file.name = name,
+            tl::enums::InputFile::Big(file) => file.name = name,
+            tl::enums::InputFile::StoryDocument(_) => {}
+        }
+        self
+    }
+
+    /// The mime type detected from the contents of the file, if any.
+    pub(crate) fn inferred_mime_type(&self) -> Option<&'static str> {
+        #[cfg(feature = "infer")]
+        {
+            self.info.as_ref().and_then(|info| info.mime_type)
+        }
+        #[cfg(not(feature = "infer"))]
+        {
+            None
+        }
+    }
+
+    /// The document attributes detected from the contents of the file, such as the dimensions
+    /// of images or the metadata of audio files.
+    pub(crate) fn inferred_attributes(&self) -> Vec<tl::enums::DocumentAttribute> {
+        #[cfg(feature = "infer")]
+        {
+            self.info
+                .as_ref()
+                .map(|info| info.attributes())
+                .unwrap_or_default()
+        }
+        #[cfg(not(feature = "infer"))]
+        {
+            Vec::new()
+        }
     }
 
     pub(crate) fn name(&self) -> &str {
@@ -747,9 +1082,48 @@ impl Uploaded {
             tl::enums::InputFile::StoryDocument(_) => "",
         }
     }
+
+    /// Whether the file looks like an image Telegram can show as a photo.
+    ///
+    /// Only JPEG and PNG images are considered, and only if they're no larger than the size
+    /// limit for photos. Images whose inferred dimensions are too large or too elongated for a
+    /// photo are not considered either.
+    pub(crate) fn is_photo(&self) -> bool {
+        let fits_size = match self.size {
+            Some(size) => size <= MAX_PHOTO_SIZE,
+            // Files which were not uploaded as big files are never larger than the limit.
+            None => matches!(self.raw, tl::enums::InputFile::File(_)),
+        };
+        if !fits_size {
+            return false;
+        }
+        #[cfg(feature = "infer")]
+        if self.info.as_ref().is_some_and(|info| !info.fits_photo()) {
+            return false;
+        }
+        let mime = match self.inferred_mime_type() {
+            Some(mime) => Some(mime.to_string()),
+            None => mime_guess::from_path(self.name())
+                .first()
+                .map(|mime| mime.essence_str().to_string()),
+        };
+        matches!(mime.as_deref(), Some("image/jpeg" | "image/png"))
+    }
 }
 
 impl Media {
+    /// Whether [`Media::from_raw`] would return `Some` for this media, without cloning it.
+    pub(crate) fn is_supported(media: &tl::enums::MessageMedia) -> bool {
+        use tl::enums::MessageMedia as M;
+
+        match media {
+            M::Empty | M::Unsupported | M::Invoice(_) => false,
+            M::Giveaway(_) | M::GiveawayResults(_) | M::PaidMedia(_) => false,
+            M::Geo(geo) => matches!(geo.geo, tl::enums::GeoPoint::Point(_)),
+            _ => true,
+        }
+    }
+
     pub fn from_raw(media: tl::enums::MessageMedia) -> Option<Self> {
         use tl::enums::MessageMedia as M;
 
@@ -764,18 +1138,20 @@ impl Media {
                 let document = Document::from_raw_media(document);
                 Some(if let Some(sticker) = Sticker::from_document(&document) {
                     Self::Sticker(sticker)
+                } else if let Some(audio) = Audio::from_document(&document) {
+                    Self::Audio(audio)
                 } else {
                     Self::Document(document)
                 })
             }
             M::WebPage(webpage) => Some(Self::WebPage(WebPage::from_raw_media(webpage))),
             M::Venue(venue) => Some(Self::Venue(Venue::from_raw_media(venue))),
-            M::Game(_) => None,
+            M::Game(game) => Some(Self::Game(Game::from_raw_media(game))),
             M::Invoice(_) => None,
             M::GeoLive(geolive) => Some(Self::GeoLive(GeoLive::from_raw_media(geolive))),
             M::Poll(poll) => Some(Self::Poll(Poll::from_raw_media(poll))),
             M::Dice(dice) => Some(Self::Dice(Dice::from_raw_media(dice))),
-            M::Story(_) => None,
+            M::Story(story) => Some(Self::Story(SharedStory::from_raw_media(*story))),
             M::Giveaway(_) => None,
             M::GiveawayResults(_) => None,
             M::PaidMedia(_) => None,
@@ -787,6 +1163,7 @@ impl Media {
             Media::Photo(photo) => Some(photo.to_raw_input_media().into()),
             Media::Document(document) => Some(document.to_raw_input_media().into()),
             Media::Sticker(sticker) => Some(sticker.document.to_raw_input_media().into()),
+            Media::Audio(audio) => Some(audio.document.to_raw_input_media().into()),
             Media::Contact(contact) => Some(contact.to_raw_input_media().into()),
             Media::Poll(poll) => Some(poll.to_raw_input_media().into()),
             Media::Geo(geo) => Some(geo.to_raw_input_media().into()),
@@ -794,6 +1171,8 @@ impl Media {
             Media::Venue(venue) => Some(venue.to_raw_input_media().into()),
             Media::GeoLive(geolive) => Some(geolive.to_raw_input_media().into()),
             Media::WebPage(_) => None,
+            Media::Story(_) => None,
+            Media::Game(game) => Some(game.to_raw_input_media().into()),
         }
     }
 }
@@ -804,6 +1183,7 @@ impl Downloadable for Media {
             Media::Photo(photo) => photo.to_raw_input_location(),
             Media::Document(document) => document.to_raw_input_location(),
             Media::Sticker(sticker) => sticker.document.to_raw_input_location(),
+            Media::Audio(audio) => audio.document.to_raw_input_location(),
             Media::Contact(_) => None,
             Media::Poll(_) => None,
             Media::Geo(_) => None,
@@ -811,6 +1191,8 @@ impl Downloadable for Media {
             Media::Venue(_) => None,
             Media::GeoLive(_) => None,
             Media::WebPage(_) => None,
+            Media::Story(_) => None,
+            Media::Game(_) => None,
         }
     }
 }
@@ -826,3 +1208,28 @@ impl Downloadable for ChatPhoto {
         Some(self.raw.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uploaded_photo_size() {
+        let uploaded = |size| Uploaded {
+            size,
+            ..Uploaded::from_raw(
+                tl::types::InputFileBig {
+                    id: 1,
+                    parts: 2,
+                    name: "photo.jpg".to_string(),
+                }
+                .into(),
+            )
+        };
+
+        assert!(uploaded(Some(1024 * 1024)).is_photo());
+        assert!(uploaded(Some(MAX_PHOTO_SIZE)).is_photo());
+        assert!(!uploaded(Some(MAX_PHOTO_SIZE + 1)).is_photo());
+        assert!(!uploaded(None).is_photo());
+    }
+}
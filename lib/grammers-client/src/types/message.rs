@@ -6,8 +6,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use crate::ChatMap;
+use crate::client::messages::TranscribeError;
 #[cfg(any(feature = "markdown", feature = "html"))]
 use crate::parsers;
+use crate::types::click::{self, ButtonSelector, CallbackAnswer, ClickResult};
+use crate::types::input_message::SCHEDULE_ONCE_ONLINE;
 use crate::types::reactions::InputReactions;
 use crate::types::{InputMessage, Media, Photo};
 use crate::{Client, types};
@@ -20,6 +23,8 @@ use std::fmt;
 use std::sync::Arc;
 use types::Chat;
 
+#[cfg(any(feature = "markdown", feature = "html"))]
+use std::borrow::Cow;
 #[cfg(feature = "fs")]
 use std::{io, path::Path};
 
@@ -253,11 +258,28 @@ impl Message {
         self.raw.post
     }
 
+    /// Whether the video of this message is still being processed by Telegram, to generate its
+    /// alternative qualities.
+    ///
+    /// Such messages behave like scheduled messages, and will be sent once the processing
+    /// completes, around the [`Message::date`] of the message.
+    pub fn video_processing_pending(&self) -> bool {
+        self.raw.video_processing_pending
+    }
+
     /// Whether this message was originated from a previously-scheduled message or not.
     pub fn from_scheduled(&self) -> bool {
         self.raw.from_scheduled
     }
 
+    /// Whether this scheduled message will be sent once the person comes online, rather than
+    /// at a specific date.
+    ///
+    /// This can only be `true` for messages returned by [`Client::get_scheduled_messages`].
+    pub fn is_scheduled_when_online(&self) -> bool {
+        self.raw.date == SCHEDULE_ONCE_ONLINE
+    }
+
     // `legacy` is not exposed, though it can be if it proves to be useful
 
     /// Whether the edited mark of this message is edited should be hidden (e.g. in GUI clients)
@@ -351,6 +373,8 @@ impl Message {
     /// Like [`text`](Self::text), but with the [`fmt_entities`](Self::fmt_entities)
     /// applied to produce a markdown string instead.
     ///
+    /// The text is only copied if the message has formatting entities.
+    ///
     /// Some formatting entities automatically added by Telegram, such as bot commands or
     /// clickable emails, are ignored in the generated string, as those do not need to be
     /// sent for Telegram to include them in the message.
@@ -358,26 +382,30 @@ impl Message {
     /// Formatting entities which cannot be represented in CommonMark without resorting to HTML,
     /// such as underline, are also ignored.
     #[cfg(feature = "markdown")]
-    pub fn markdown_text(&self) -> String {
-        if let Some(entities) = self.raw.entities.as_ref() {
-            parsers::generate_markdown_message(&self.raw.message, entities)
-        } else {
-            self.raw.message.clone()
+    pub fn markdown_text(&self) -> Cow<'_, str> {
+        match self.raw.entities.as_deref() {
+            Some(entities) if !entities.is_empty() => Cow::Owned(
+                parsers::generate_markdown_message(&self.raw.message, entities),
+            ),
+            _ => Cow::Borrowed(&self.raw.message),
         }
     }
 
     /// Like [`text`](Self::text), but with the [`fmt_entities`](Self::fmt_entities)
     /// applied to produce a HTML string instead.
     ///
+    /// The text is only copied if the message has formatting entities.
+    ///
     /// Some formatting entities automatically added by Telegram, such as bot commands or
     /// clickable emails, are ignored in the generated string, as those do not need to be
     /// sent for Telegram to include them in the message.
     #[cfg(feature = "html")]
-    pub fn html_text(&self) -> String {
-        if let Some(entities) = self.raw.entities.as_ref() {
-            parsers::generate_html_message(&self.raw.message, entities)
-        } else {
-            self.raw.message.clone()
+    pub fn html_text(&self) -> Cow<'_, str> {
+        match self.raw.entities.as_deref() {
+            Some(entities) if !entities.is_empty() => {
+                Cow::Owned(parsers::generate_html_message(&self.raw.message, entities))
+            }
+            _ => Cow::Borrowed(&self.raw.message),
         }
     }
 
@@ -385,8 +413,48 @@ impl Message {
     ///
     /// This not only includes photos or videos, but also contacts, polls, documents, locations
     /// and many other types.
+    ///
+    /// The media is copied out of the message. [`Message::raw_media`] borrows it instead, and
+    /// [`Message::has_media`] only checks whether there is any.
     pub fn media(&self) -> Option<types::Media> {
-        self.raw.media.clone().and_then(Media::from_raw)
+        self.raw
+            .media
+            .as_ref()
+            .filter(|media| Media::is_supported(media))
+            .cloned()
+            .and_then(Media::from_raw)
+    }
+
+    /// Whether the message has any media which [`Message::media`] would return.
+    pub fn has_media(&self) -> bool {
+        self.raw.media.as_ref().is_some_and(Media::is_supported)
+    }
+
+    /// The raw media of this message, if any, without copying it.
+    ///
+    /// This is the borrowing counterpart of [`Message::media`], and should be preferred on hot
+    /// paths where a copy of the media is not needed.
+    pub fn raw_media(&self) -> Option<&tl::enums::MessageMedia> {
+        self.raw.media.as_ref()
+    }
+
+    /// If this message is sharing a story, return said story.
+    ///
+    /// Telegram may omit the contents of the story, in which case `None` is returned and
+    /// [`Client::get_stories_by_id`] can be used to fetch it instead.
+    pub fn story(&self) -> Option<types::Story> {
+        match self.raw.media.as_ref()? {
+            tl::enums::MessageMedia::Story(media) => match media.story.as_ref()? {
+                tl::enums::StoryItem::Item(item) => Some(types::Story::from_raw(
+                    &self.client,
+                    (**item).clone(),
+                    &media.peer,
+                    &self.chats,
+                )),
+                tl::enums::StoryItem::Deleted(_) | tl::enums::StoryItem::Skipped(_) => None,
+            },
+            _ => None,
+        }
     }
 
     /// If the message has a reply markup (which can happen for messages produced by bots),
@@ -395,6 +463,30 @@ impl Message {
         self.raw.reply_markup.clone()
     }
 
+    /// The bot command at the start of this message, if any, such as `/start` or
+    /// `/ban@my_bot "spamming links"`.
+    ///
+    /// In groups with several bots, [`Command::is_for`] should be used to check that the command
+    /// was meant for the logged-in bot.
+    ///
+    /// [`Command::is_for`]: crate::types::Command::is_for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message) {
+    /// if let Some(command) = message.command().filter(|c| c.is_for("my_bot")) {
+    ///     match command.name.as_str() {
+    ///         "start" => println!("starting with {:?}", command.args),
+    ///         _ => println!("unknown command"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn command(&self) -> Option<crate::types::Command> {
+        crate::types::Command::parse(self.text(), self.raw.entities.as_deref())
+    }
+
     /// The formatting entities used to format this message, such as bold, italic, with their
     /// offsets and lengths.
     pub fn fmt_entities(&self) -> Option<&Vec<tl::enums::MessageEntity>> {
@@ -416,6 +508,8 @@ impl Message {
     }
 
     /// How many replies does this message have, when applicable.
+    ///
+    /// See [`Message::replies_info`] for more details about the replies.
     pub fn reply_count(&self) -> Option<i32> {
         match &self.raw.replies {
             None => None,
@@ -426,6 +520,15 @@ impl Message {
         }
     }
 
+    /// The thread of replies to this message, or of comments if it's a channel post, when
+    /// applicable.
+    pub fn replies_info(&self) -> Option<types::MessageReplies> {
+        self.raw
+            .replies
+            .clone()
+            .map(|replies| types::MessageReplies::from_raw(&self.client, replies, &self.chats))
+    }
+
     /// React to this message.
     ///
     /// # Examples
@@ -495,6 +598,13 @@ impl Message {
     }
 
     /// If this message was sent to a channel, return the name used by the author to post it.
+    ///
+    /// Posts are only signed if the channel has [`Channel::signatures`] enabled, which can be
+    /// changed with [`Client::toggle_signatures`]. The author's name cannot be chosen when
+    /// posting, as Telegram always uses the name of the administrator who posted it.
+    ///
+    /// [`Channel::signatures`]: crate::types::Channel::signatures
+    /// [`Client::toggle_signatures`]: crate::Client::toggle_signatures
     pub fn post_author(&self) -> Option<&str> {
         self.raw.post_author.as_ref().map(|author| author.as_ref())
     }
@@ -521,6 +631,42 @@ impl Message {
         self.raw_action.as_ref()
     }
 
+    /// If this message is a service message, return what happened in the chat and who caused it.
+    ///
+    /// Users mentioned by the action are resolved into chats, so that it can be handled with
+    /// a single `match`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(message: grammers_client::types::Message) {
+    /// use grammers_client::types::ChatEvent;
+    ///
+    /// if let Some(service) = message.chat_event() {
+    ///     match service.event {
+    ///         ChatEvent::MembersAdded(users) => println!("{} users were added", users.len()),
+    ///         ChatEvent::MemberLeft => println!("{:?} left", service.actor.name()),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn chat_event(&self) -> Option<types::ServiceEvent> {
+        types::ServiceEvent::from_message(self)
+    }
+
+    /// If this message is a service message with the Telegram Passport data a user shared with
+    /// the logged-in bot, return said data.
+    #[cfg(feature = "passport")]
+    pub fn passport_data(&self) -> Option<types::PassportData> {
+        match self.raw_action.as_ref()? {
+            tl::enums::MessageAction::SecureValuesSentMe(action) => {
+                Some(types::PassportData::from_raw(action.clone()))
+            }
+            _ => None,
+        }
+    }
+
     /// If this message is replying to another message, return the replied message ID.
     pub fn reply_to_message_id(&self) -> Option<i32> {
         match &self.raw.reply_to {
@@ -640,6 +786,97 @@ impl Message {
         }
     }
 
+    /// Click one of the buttons in the reply markup of this message, as a user would.
+    ///
+    /// The button can be selected either by its position, or by its text (see
+    /// [`ButtonSelector`]). Returns `None` if the message has no button matching the selector.
+    ///
+    /// What clicking does depends on the kind of button (see [`ClickResult`]):
+    ///
+    /// * Callback and game buttons ask the bot for an answer.
+    /// * URL buttons return the URL without opening it.
+    /// * Switch-inline buttons prepare the inline query, without executing it.
+    /// * Plain keyboard buttons send their text as a message to the same chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ClickResult;
+    ///
+    /// match message.click("Next page").await? {
+    ///     Some(ClickResult::Answer(answer)) => println!("Bot said: {:?}", answer.message()),
+    ///     Some(ClickResult::Url(url)) => println!("Button points to {url}"),
+    ///     Some(_) => println!("Button clicked"),
+    ///     None => println!("No such button"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn click<S: Into<ButtonSelector>>(
+        &self,
+        selector: S,
+    ) -> Result<Option<ClickResult>, InvocationError> {
+        use tl::enums::KeyboardButton as B;
+
+        let button = match self
+            .raw
+            .reply_markup
+            .as_ref()
+            .and_then(|markup| click::find_button(markup, &selector.into()))
+        {
+            Some(button) => button,
+            None => return Ok(None),
+        };
+
+        let callback_answer = |data, game| async move {
+            self.client
+                .invoke(&tl::functions::messages::GetBotCallbackAnswer {
+                    game,
+                    peer: self.chat().pack().to_input_peer(),
+                    msg_id: self.raw.id,
+                    data,
+                    password: None,
+                })
+                .await
+                .map(|answer| ClickResult::Answer(CallbackAnswer::from_raw(answer)))
+        };
+
+        Ok(Some(match button {
+            B::Callback(b) if !b.requires_password => callback_answer(Some(b.data), false).await?,
+            B::Game(_) => callback_answer(None, true).await?,
+            B::Url(b) => ClickResult::Url(b.url),
+            B::UrlAuth(b) => ClickResult::Url(b.url),
+            B::SwitchInline(b) => {
+                let bot = match self.raw.via_bot_id {
+                    Some(user_id) => Some(utils::always_find_entity(
+                        &tl::types::PeerUser { user_id }.into(),
+                        &self.chats,
+                        &self.client,
+                    )),
+                    None => self.sender(),
+                };
+                match bot {
+                    Some(bot) => {
+                        let query = self.client.inline_query(&bot, &b.query);
+                        ClickResult::SwitchInline(if b.same_peer {
+                            query.chat(self.chat())
+                        } else {
+                            query
+                        })
+                    }
+                    None => ClickResult::Unsupported(B::SwitchInline(b)),
+                }
+            }
+            B::Button(b) => ClickResult::Sent(Box::new(
+                self.client
+                    .send_message(&self.chat(), InputMessage::text(b.text))
+                    .await?,
+            )),
+            button => ClickResult::Unsupported(button),
+        }))
+    }
+
     /// Fetch the message that this message is replying to, or `None` if this message is not a
     /// reply to a previous message.
     ///
@@ -651,6 +888,43 @@ impl Message {
             .await
     }
 
+    /// Fetch the story shared in this message, or the story which mentioned the logged-in
+    /// account, if the media of this message is a story and it still exists.
+    ///
+    /// The contents of the story are fetched with [`Client::get_stories_by_id`], unless
+    /// Telegram already included them along with the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(story) = message.get_story().await? {
+    ///     println!("Story {}: {:?}", story.id(), story.caption());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_story(&self) -> Result<Option<types::Story>, InvocationError> {
+        let Some(Media::Story(story)) = self.media() else {
+            return Ok(None);
+        };
+        if let Some(item) = story.item() {
+            return Ok(Some(types::Story::from_raw(
+                &self.client,
+                item.clone(),
+                story.peer(),
+                &self.chats,
+            )));
+        }
+
+        let chat = utils::always_find_entity(story.peer(), &self.chats, &self.client);
+        Ok(self
+            .client
+            .get_stories_by_id(&chat, &[story.id()])
+            .await?
+            .pop())
+    }
+
     /// Respond to this message by sending a new message in the same chat, but without directly
     /// replying to it.
     ///
@@ -769,6 +1043,15 @@ impl Message {
         self.client.unpin_message(&self.chat(), self.raw.id).await
     }
 
+    /// Transcribe this voice message or round video message into text.
+    ///
+    /// Shorthand for `Client::transcribe_audio`.
+    pub async fn transcribe(&self) -> Result<String, TranscribeError> {
+        self.client
+            .transcribe_audio(&self.chat(), self.raw.id)
+            .await
+    }
+
     /// Refetch this message, mutating all of its properties in-place.
     ///
     /// No changes will be made to the message if it fails to be fetched.
@@ -803,11 +1086,10 @@ impl Message {
 
     /// Get photo attached to the message if any.
     pub fn photo(&self) -> Option<Photo> {
-        if let Media::Photo(photo) = self.media()? {
-            return Some(photo);
+        match self.raw.media.as_ref()? {
+            tl::enums::MessageMedia::Photo(photo) => Some(Photo::from_raw_media(photo.clone())),
+            _ => None,
         }
-
-        None
     }
 }
 
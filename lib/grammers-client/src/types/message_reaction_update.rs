@@ -0,0 +1,156 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap};
+use crate::{Client, utils};
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// Represents a change in the reactions a user or chat left on a message.
+///
+/// Telegram only sends these to bots which are administrators in the chat and have asked to
+/// receive reaction updates.
+#[derive(Clone)]
+pub struct MessageReactionUpdate {
+    raw: tl::types::UpdateBotMessageReaction,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+/// Represents a change in the anonymous reaction counts of a message in a channel.
+///
+/// Telegram only sends these to bots which are administrators in the channel and have asked to
+/// receive reaction updates. Individual reactions are not known in this case.
+#[derive(Clone)]
+pub struct MessageReactionCountUpdate {
+    raw: tl::types::UpdateBotMessageReactions,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+impl MessageReactionUpdate {
+    pub(crate) fn from_raw(
+        client: &Client,
+        update: tl::types::UpdateBotMessageReaction,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        Self {
+            raw: update,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// The chat where the message is.
+    pub fn chat(&self) -> Chat {
+        utils::always_find_entity(&self.raw.peer, &self.chats, &self.client)
+    }
+
+    /// The identifier of the message which reactions changed.
+    pub fn message_id(&self) -> i32 {
+        self.raw.msg_id
+    }
+
+    /// When the reactions changed.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The user, or the chat for anonymous administrators, who changed their reactions.
+    pub fn actor(&self) -> Chat {
+        utils::always_find_entity(&self.raw.actor, &self.chats, &self.client)
+    }
+
+    /// The reactions the actor had left before the change.
+    pub fn old_reactions(&self) -> &[tl::enums::Reaction] {
+        &self.raw.old_reactions
+    }
+
+    /// The reactions the actor has left after the change.
+    pub fn new_reactions(&self) -> &[tl::enums::Reaction] {
+        &self.raw.new_reactions
+    }
+
+    /// The reactions which were added by the change.
+    pub fn added_reactions(&self) -> impl Iterator<Item = &tl::enums::Reaction> {
+        self.raw
+            .new_reactions
+            .iter()
+            .filter(|reaction| !self.raw.old_reactions.contains(reaction))
+    }
+
+    /// The reactions which were removed by the change.
+    pub fn removed_reactions(&self) -> impl Iterator<Item = &tl::enums::Reaction> {
+        self.raw
+            .old_reactions
+            .iter()
+            .filter(|reaction| !self.raw.new_reactions.contains(reaction))
+    }
+}
+
+impl MessageReactionCountUpdate {
+    pub(crate) fn from_raw(
+        client: &Client,
+        update: tl::types::UpdateBotMessageReactions,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        Self {
+            raw: update,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// The channel where the message is.
+    pub fn chat(&self) -> Chat {
+        utils::always_find_entity(&self.raw.peer, &self.chats, &self.client)
+    }
+
+    /// The identifier of the message which reactions changed.
+    pub fn message_id(&self) -> i32 {
+        self.raw.msg_id
+    }
+
+    /// When the reactions changed.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The new total count of each reaction on the message.
+    pub fn reactions(&self) -> impl Iterator<Item = (&tl::enums::Reaction, i32)> {
+        self.raw.reactions.iter().map(|reaction| {
+            let tl::enums::ReactionCount::Count(reaction) = reaction;
+            (&reaction.reaction, reaction.count)
+        })
+    }
+}
+
+impl fmt::Debug for MessageReactionUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageReactionUpdate")
+            .field("chat", &self.chat())
+            .field("message_id", &self.message_id())
+            .field("date", &self.date())
+            .field("actor", &self.actor())
+            .field("old_reactions", &self.old_reactions())
+            .field("new_reactions", &self.new_reactions())
+            .finish()
+    }
+}
+
+impl fmt::Debug for MessageReactionCountUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageReactionCountUpdate")
+            .field("chat", &self.chat())
+            .field("message_id", &self.message_id())
+            .field("date", &self.date())
+            .field("reactions", &self.reactions().collect::<Vec<_>>())
+            .finish()
+    }
+}
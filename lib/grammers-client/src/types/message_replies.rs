@@ -0,0 +1,94 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap};
+use crate::{Client, utils};
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// Information about the thread of replies to a message, or of comments to a channel post.
+#[derive(Clone)]
+pub struct MessageReplies {
+    pub raw: tl::types::MessageReplies,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+impl MessageReplies {
+    pub(crate) fn from_raw(
+        client: &Client,
+        replies: tl::enums::MessageReplies,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        let tl::enums::MessageReplies::Replies(raw) = replies;
+        Self {
+            raw,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// How many replies, or comments, there are in the thread.
+    pub fn count(&self) -> i32 {
+        self.raw.replies
+    }
+
+    /// Whether the thread holds the comments to a channel post, instead of replies.
+    pub fn is_comments(&self) -> bool {
+        self.raw.comments
+    }
+
+    /// The last few users or chats who replied or commented, for previews.
+    ///
+    /// Only known for comments to channel posts.
+    pub fn recent_repliers(&self) -> Vec<Chat> {
+        self.raw
+            .recent_repliers
+            .iter()
+            .flatten()
+            .map(|peer| utils::always_find_entity(peer, &self.chats, &self.client))
+            .collect()
+    }
+
+    /// The identifier of the discussion group where the comments are, for channel posts.
+    pub fn channel_id(&self) -> Option<i64> {
+        self.raw.channel_id
+    }
+
+    /// The identifier of the last reply or comment in the thread, if any.
+    pub fn max_id(&self) -> Option<i32> {
+        self.raw.max_id
+    }
+
+    /// The identifier of the last reply or comment which the logged-in account read, if any.
+    pub fn read_max_id(&self) -> Option<i32> {
+        self.raw.read_max_id
+    }
+
+    /// Whether there are replies or comments which the logged-in account has not read yet.
+    pub fn has_unread(&self) -> bool {
+        match (self.raw.max_id, self.raw.read_max_id) {
+            (Some(max_id), Some(read_max_id)) => max_id > read_max_id,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+impl fmt::Debug for MessageReplies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageReplies")
+            .field("count", &self.count())
+            .field("is_comments", &self.is_comments())
+            .field("recent_repliers", &self.recent_repliers())
+            .field("channel_id", &self.channel_id())
+            .field("max_id", &self.max_id())
+            .field("read_max_id", &self.read_max_id())
+            .finish()
+    }
+}
@@ -15,50 +15,103 @@ pub mod attributes;
 pub mod button;
 pub mod callback_query;
 pub mod chat;
+pub mod chat_event;
+pub mod chat_invite;
 pub mod chat_map;
+pub mod chat_member_update;
+pub mod chatlist;
 pub mod chats;
+pub mod click;
+pub mod command;
+pub mod content_settings;
 pub mod dialog;
 pub mod downloadable;
+pub mod effect;
+pub mod emoji_keywords;
+pub mod forum_topic;
 pub mod inline;
 pub mod input_media;
 pub mod input_message;
 pub mod iter_buffer;
+pub mod join_request_update;
+pub mod located_chat;
 pub mod login_token;
 pub mod media;
 pub mod message;
 pub mod message_deletion;
+pub mod message_reaction_update;
+pub mod message_replies;
+pub mod notify_settings;
 pub mod participant;
+#[cfg(feature = "passport")]
+pub mod passport;
 pub mod password_token;
+pub mod peer_color;
+pub mod peer_settings;
 pub mod permissions;
+pub mod phone_change_token;
 pub mod photo_sizes;
 pub mod reactions;
 pub mod reply_markup;
+pub mod story;
 pub mod terms_of_service;
+pub mod text_builder;
 pub mod update;
+pub mod user_full;
+pub mod vcard;
 
 pub use action::ActionSender;
 pub use attributes::Attribute;
 pub use callback_query::CallbackQuery;
-pub use chat::{Channel, Chat, Group, PackedChat, Platform, RestrictionReason, User};
+pub use chat::{Channel, Chat, Group, PackedChat, Platform, RestrictionReason, User, UserStatus};
+pub use chat_event::{ChatEvent, ServiceEvent};
+pub use chat_invite::ChatInvite;
 pub use chat_map::ChatMap;
 pub(crate) use chat_map::Peer;
+pub use chat_member_update::ChatMemberUpdate;
+pub use chatlist::ChatlistInvite;
 pub use chats::{AdminRightsBuilder, BannedRightsBuilder};
-pub use dialog::Dialog;
+pub use click::{ButtonSelector, CallbackAnswer, ClickResult};
+pub use command::Command;
+pub use content_settings::ContentSettings;
+pub use dialog::{Dialog, DialogOffset, FolderUnreadCounts};
 pub use downloadable::Downloadable;
+pub use effect::MessageEffect;
+pub use emoji_keywords::EmojiKeywords;
+pub use forum_topic::ForumTopic;
 pub use inline::query::InlineQuery;
 pub use inline::send::InlineSend;
 pub use input_media::InputMedia;
-pub use input_message::InputMessage;
+pub use input_message::{EntityError, InputMessage};
 pub use iter_buffer::IterBuffer;
-pub use login_token::LoginToken;
+pub use join_request_update::JoinRequestUpdate;
+pub use located_chat::LocatedChat;
+pub use login_token::{LoginToken, SentEmailCode};
 pub(crate) use media::Uploaded;
 pub use media::{ChatPhoto, Media, Photo};
 pub use message::Message;
 pub use message_deletion::MessageDeletion;
+pub use message_reaction_update::{MessageReactionCountUpdate, MessageReactionUpdate};
+pub use message_replies::MessageReplies;
+pub use notify_settings::{
+    NotifyScope, ReactionNotifyFrom, ReactionNotifySettings, StoryNotifySettings,
+};
 pub use participant::{Participant, Role};
+#[cfg(feature = "passport")]
+pub use passport::{
+    DecryptedPassport, IdDocumentData, PassportData, PassportElement, PassportError,
+    PassportFields, PassportFile, PassportKey, PersonalDetails, ResidentialAddress,
+};
 pub use password_token::PasswordToken;
-pub use permissions::{Permissions, Restrictions};
-pub use reactions::InputReactions;
+pub use peer_color::PeerColor;
+pub use peer_settings::PeerSettings;
+pub use permissions::{ChatPermissions, Permissions, Restrictions};
+pub use phone_change_token::PhoneChangeToken;
+pub use reactions::{AvailableReaction, InputReactions};
 pub(crate) use reply_markup::ReplyMarkup;
+pub use story::{Story, StoryPrivacy};
 pub use terms_of_service::TermsOfService;
+pub use text_builder::TextBuilder;
 pub use update::Update;
+pub use user_full::{Birthday, BusinessHours, BusinessLocation, UserFull};
+pub use vcard::VCard;
@@ -0,0 +1,122 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+/// Who can trigger a notification by reacting to the logged-in account's messages or stories.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReactionNotifyFrom {
+    /// Only reactions from contacts cause a notification.
+    Contacts,
+    /// Reactions from anyone cause a notification.
+    All,
+}
+
+/// Settings for the notifications caused by reactions, as returned by
+/// [`Client::get_reaction_notify_settings`](crate::Client::get_reaction_notify_settings).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReactionNotifySettings {
+    /// Who can cause a notification by reacting to messages, or `None` to disable them.
+    pub messages_from: Option<ReactionNotifyFrom>,
+    /// Who can cause a notification by reacting to stories, or `None` to disable them.
+    pub stories_from: Option<ReactionNotifyFrom>,
+    /// Whether the notifications show a preview of the message.
+    pub show_previews: bool,
+    /// The sound played on notification.
+    pub sound: tl::enums::NotificationSound,
+}
+
+/// Settings for the notifications caused by new stories, as returned by
+/// [`Client::get_story_notify_settings`](crate::Client::get_story_notify_settings).
+///
+/// Settings which are `None` are inherited from the more general scope (for a chat, the
+/// settings of its kind), or left unchanged when setting them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoryNotifySettings {
+    /// Whether new stories do not cause a notification.
+    pub muted: Option<bool>,
+    /// Whether the notifications hide the name of who posted the story.
+    pub hide_sender: Option<bool>,
+}
+
+/// The chats to which notification settings apply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotifyScope {
+    /// All private conversations.
+    Users,
+    /// All small groups and megagroups.
+    Groups,
+    /// All broadcast channels.
+    Channels,
+    /// A single chat, which overrides the settings of its kind.
+    Chat(PackedChat),
+    /// A single topic of a forum, identified by its identifier, which overrides the settings
+    /// of the forum.
+    Topic(PackedChat, i32),
+}
+
+impl ReactionNotifyFrom {
+    fn from_raw(from: tl::enums::ReactionNotificationsFrom) -> Self {
+        match from {
+            tl::enums::ReactionNotificationsFrom::Contacts => Self::Contacts,
+            tl::enums::ReactionNotificationsFrom::All => Self::All,
+        }
+    }
+
+    fn to_raw(self) -> tl::enums::ReactionNotificationsFrom {
+        match self {
+            Self::Contacts => tl::enums::ReactionNotificationsFrom::Contacts,
+            Self::All => tl::enums::ReactionNotificationsFrom::All,
+        }
+    }
+}
+
+impl ReactionNotifySettings {
+    pub(crate) fn from_raw(settings: tl::enums::ReactionsNotifySettings) -> Self {
+        let tl::enums::ReactionsNotifySettings::Settings(settings) = settings;
+        Self {
+            messages_from: settings
+                .messages_notify_from
+                .map(ReactionNotifyFrom::from_raw),
+            stories_from: settings
+                .stories_notify_from
+                .map(ReactionNotifyFrom::from_raw),
+            show_previews: settings.show_previews,
+            sound: settings.sound,
+        }
+    }
+
+    pub(crate) fn to_raw(&self) -> tl::enums::ReactionsNotifySettings {
+        tl::types::ReactionsNotifySettings {
+            messages_notify_from: self.messages_from.map(ReactionNotifyFrom::to_raw),
+            stories_notify_from: self.stories_from.map(ReactionNotifyFrom::to_raw),
+            sound: self.sound.clone(),
+            show_previews: self.show_previews,
+        }
+        .into()
+    }
+}
+
+impl NotifyScope {
+    pub(crate) fn to_raw(self) -> tl::enums::InputNotifyPeer {
+        match self {
+            Self::Users => tl::enums::InputNotifyPeer::InputNotifyUsers,
+            Self::Groups => tl::enums::InputNotifyPeer::InputNotifyChats,
+            Self::Channels => tl::enums::InputNotifyPeer::InputNotifyBroadcasts,
+            Self::Chat(chat) => tl::types::InputNotifyPeer {
+                peer: chat.to_input_peer(),
+            }
+            .into(),
+            Self::Topic(chat, topic_id) => tl::types::InputNotifyForumTopic {
+                peer: chat.to_input_peer(),
+                top_msg_id: topic_id,
+            }
+            .into(),
+        }
+    }
+}
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use super::{Chat, ChatMap, Permissions, Restrictions};
-use crate::utils;
+use crate::{Client, utils};
 use chrono::{DateTime, Utc};
 use grammers_tl_types as tl;
 
@@ -129,33 +129,87 @@ impl Participant {
         chats: &mut ChatMap,
         participant: tl::enums::ChannelParticipant,
     ) -> Self {
+        let (peer, role) = Self::channel_role(participant);
+        Self {
+            user: match chats.remove(&peer).unwrap() {
+                Chat::User(user) => user,
+                _ => todo!("figure out how to deal with non-user participants"),
+            },
+            role,
+        }
+    }
+
+    pub(crate) fn from_raw_chat(
+        chats: &mut ChatMap,
+        participant: tl::enums::ChatParticipant,
+    ) -> Self {
+        let (user_id, role) = Self::chat_role(participant);
+        Self {
+            user: chats.remove_user(user_id).unwrap(),
+            role,
+        }
+    }
+
+    /// Like [`Participant::from_raw_channel`], but without taking the user out of the map,
+    /// falling back to the cached hashes if the user is not present.
+    ///
+    /// Returns `None` if the participant is not a user (such as a banned channel).
+    pub(crate) fn resolve_channel(
+        client: &Client,
+        chats: &ChatMap,
+        participant: tl::enums::ChannelParticipant,
+    ) -> Option<Self> {
+        let (peer, role) = Self::channel_role(participant);
+        match utils::always_find_entity(&peer, chats, client) {
+            Chat::User(user) => Some(Self { user, role }),
+            _ => None,
+        }
+    }
+
+    /// Like [`Participant::from_raw_chat`], but without taking the user out of the map,
+    /// falling back to the cached hashes if the user is not present.
+    pub(crate) fn resolve_chat(
+        client: &Client,
+        chats: &ChatMap,
+        participant: tl::enums::ChatParticipant,
+    ) -> Option<Self> {
+        let (user_id, role) = Self::chat_role(participant);
+        let peer = tl::types::PeerUser { user_id }.into();
+        match utils::always_find_entity(&peer, chats, client) {
+            Chat::User(user) => Some(Self { user, role }),
+            _ => None,
+        }
+    }
+
+    fn channel_role(participant: tl::enums::ChannelParticipant) -> (tl::enums::Peer, Role) {
         use tl::enums::ChannelParticipant as P;
 
+        let user_peer = |user_id| tl::types::PeerUser { user_id }.into();
         match participant {
-            P::Participant(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::User(Normal {
+            P::Participant(p) => (
+                user_peer(p.user_id),
+                Role::User(Normal {
                     date: p.date,
                     inviter_id: None,
                 }),
-            },
-            P::ParticipantSelf(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::User(Normal {
+            ),
+            P::ParticipantSelf(p) => (
+                user_peer(p.user_id),
+                Role::User(Normal {
                     date: p.date,
                     inviter_id: Some(p.inviter_id),
                 }),
-            },
-            P::Creator(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::Creator(Creator {
+            ),
+            P::Creator(p) => (
+                user_peer(p.user_id),
+                Role::Creator(Creator {
                     permissions: Permissions::from_raw(p.admin_rights.into()),
                     rank: p.rank,
                 }),
-            },
-            P::Admin(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::Admin(Admin {
+            ),
+            P::Admin(p) => (
+                user_peer(p.user_id),
+                Role::Admin(Admin {
                     can_edit: p.can_edit,
                     inviter_id: p.inviter_id,
                     promoted_by: Some(p.promoted_by),
@@ -163,53 +217,41 @@ impl Participant {
                     permissions: Permissions::from_raw(p.admin_rights.into()),
                     rank: p.rank,
                 }),
-            },
-            P::Banned(p) => Self {
-                user: match chats.remove(&p.peer).unwrap() {
-                    Chat::User(user) => user,
-                    _ => todo!("figure out how to deal with non-user being banned"),
-                },
-                role: Role::Banned(Banned {
+            ),
+            P::Banned(p) => (
+                p.peer,
+                Role::Banned(Banned {
                     left: p.left,
                     kicked_by: p.kicked_by,
                     date: p.date,
                     restrictions: Restrictions::from_raw(p.banned_rights.into()),
                 }),
-            },
-            P::Left(p) => Self {
-                user: match chats.remove(&p.peer).unwrap() {
-                    Chat::User(user) => user,
-                    _ => todo!("figure out how to deal with non-user leaving"),
-                },
-                role: Role::Left(Left {}),
-            },
+            ),
+            P::Left(p) => (p.peer, Role::Left(Left {})),
         }
     }
 
-    pub(crate) fn from_raw_chat(
-        chats: &mut ChatMap,
-        participant: tl::enums::ChatParticipant,
-    ) -> Self {
+    fn chat_role(participant: tl::enums::ChatParticipant) -> (i64, Role) {
         use tl::enums::ChatParticipant as P;
 
         match participant {
-            P::Participant(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::User(Normal {
+            P::Participant(p) => (
+                p.user_id,
+                Role::User(Normal {
                     date: p.date,
                     inviter_id: Some(p.inviter_id),
                 }),
-            },
-            P::Creator(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::Creator(Creator {
+            ),
+            P::Creator(p) => (
+                p.user_id,
+                Role::Creator(Creator {
                     permissions: Permissions::new_full(),
                     rank: None,
                 }),
-            },
-            P::Admin(p) => Self {
-                user: chats.remove_user(p.user_id).unwrap(),
-                role: Role::Admin(Admin {
+            ),
+            P::Admin(p) => (
+                p.user_id,
+                Role::Admin(Admin {
                     can_edit: true,
                     inviter_id: Some(p.inviter_id),
                     promoted_by: None,
@@ -217,7 +259,7 @@ impl Participant {
                     permissions: Permissions::new_full(),
                     rank: None,
                 }),
-            },
+            ),
         }
     }
 }
@@ -0,0 +1,91 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+/// Amount of colors chats are assigned by default, based on their identifier.
+const DEFAULT_COLOR_COUNT: i64 = 7;
+
+/// The accent color of a chat, used to display its name or profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerColor {
+    /// Identifier of the color palette, as returned by `help.getPeerColors` or
+    /// `help.getPeerProfileColors`.
+    pub color: i32,
+    /// Identifier of the custom emoji drawn as a pattern in the background, if any.
+    pub background_emoji_id: Option<i64>,
+}
+
+impl PeerColor {
+    /// The color of the name of the chat with the given identifier, which uses the default
+    /// color derived from the identifier if the chat did not pick any.
+    pub(crate) fn name(raw: Option<&tl::enums::PeerColor>, id: i64) -> Self {
+        let raw = raw.map(|tl::enums::PeerColor::Color(color)| color);
+        Self {
+            color: raw
+                .and_then(|color| color.color)
+                .unwrap_or_else(|| default_color(id)),
+            background_emoji_id: raw.and_then(|color| color.background_emoji_id),
+        }
+    }
+
+    /// The color of the profile, which has no default.
+    pub(crate) fn profile(raw: Option<&tl::enums::PeerColor>) -> Option<Self> {
+        let tl::enums::PeerColor::Color(color) = raw?;
+        Some(Self {
+            color: color.color?,
+            background_emoji_id: color.background_emoji_id,
+        })
+    }
+}
+
+/// The color official clients use for chats which did not pick any.
+fn default_color(id: i64) -> i32 {
+    id.rem_euclid(DEFAULT_COLOR_COUNT) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_color_falls_back_to_id() {
+        assert_eq!(
+            PeerColor::name(None, 1234),
+            PeerColor {
+                color: 2,
+                background_emoji_id: None,
+            }
+        );
+
+        let raw = tl::types::PeerColor {
+            color: None,
+            background_emoji_id: Some(5),
+        }
+        .into();
+        assert_eq!(
+            PeerColor::name(Some(&raw), 14),
+            PeerColor {
+                color: 0,
+                background_emoji_id: Some(5),
+            }
+        );
+        assert_eq!(PeerColor::profile(Some(&raw)), None);
+    }
+
+    #[test]
+    fn picked_color_is_used() {
+        let raw = tl::types::PeerColor {
+            color: Some(9),
+            background_emoji_id: None,
+        }
+        .into();
+        assert_eq!(PeerColor::name(Some(&raw), 1234).color, 9);
+        assert_eq!(PeerColor::profile(Some(&raw)).map(|c| c.color), Some(9));
+        assert_eq!(PeerColor::profile(None), None);
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// The settings of the action bar that official clients show at the top of a chat, such as
+/// the "Report spam" or "Add contact" buttons in new private conversations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerSettings {
+    pub raw: tl::types::PeerSettings,
+}
+
+impl PeerSettings {
+    pub fn from_raw(settings: tl::enums::PeerSettings) -> Self {
+        let tl::enums::PeerSettings::Settings(settings) = settings;
+        Self { raw: settings }
+    }
+
+    /// Whether the chat can be reported as spam.
+    pub fn can_report_spam(&self) -> bool {
+        self.raw.report_spam
+    }
+
+    /// Whether the user can be added as a contact.
+    pub fn can_add_contact(&self) -> bool {
+        self.raw.add_contact
+    }
+
+    /// Whether the user can be blocked.
+    pub fn can_block_contact(&self) -> bool {
+        self.raw.block_contact
+    }
+
+    /// Whether the phone number of the logged-in account can be shared with the user.
+    pub fn can_share_contact(&self) -> bool {
+        self.raw.share_contact
+    }
+
+    /// Whether the location-based group can be reported as irrelevant to its location.
+    pub fn can_report_geo(&self) -> bool {
+        self.raw.report_geo
+    }
+
+    /// Whether the chat was archived automatically because it was likely to be spam.
+    pub fn autoarchived(&self) -> bool {
+        self.raw.autoarchived
+    }
+
+    /// Whether the logged-in account can be invited to the group or channel.
+    pub fn can_invite_members(&self) -> bool {
+        self.raw.invite_members
+    }
+
+    /// The distance to the user in meters, if they were found through "People nearby".
+    pub fn geo_distance(&self) -> Option<i32> {
+        self.raw.geo_distance
+    }
+
+    /// If the user contacted the logged-in account because they sent a join request to one of
+    /// the chats it administers, return the title of said chat.
+    pub fn request_chat_title(&self) -> Option<&str> {
+        self.raw.request_chat_title.as_deref()
+    }
+
+    /// If the user contacted the logged-in account because of a join request, return the date
+    /// when said request was sent.
+    pub fn request_chat_date(&self) -> Option<DateTime<Utc>> {
+        self.raw.request_chat_date.map(utils::date)
+    }
+
+    /// If the user contacted the logged-in account because of a join request, whether the chat
+    /// the request was sent to is a broadcast channel.
+    pub fn request_chat_broadcast(&self) -> bool {
+        self.raw.request_chat_broadcast
+    }
+}
@@ -19,6 +19,38 @@ pub struct Restrictions {
     pub raw: tl::types::ChatBannedRights,
 }
 
+/// What members of a group can do by default, unless they are given different rights.
+///
+/// Every field is `true` if members are allowed to perform that action. Unlike
+/// [`Restrictions`], which mirrors the raw banned rights, there is no need to negate anything.
+///
+/// The [`Default`] value allows everything, like newly-created groups do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatPermissions {
+    /// Send text messages.
+    pub send_messages: bool,
+    pub send_photos: bool,
+    pub send_videos: bool,
+    pub send_round_videos: bool,
+    pub send_audios: bool,
+    pub send_voices: bool,
+    pub send_documents: bool,
+    pub send_stickers: bool,
+    pub send_gifs: bool,
+    pub send_games: bool,
+    /// Use inline bots.
+    pub send_inline: bool,
+    /// Send messages with link previews.
+    pub embed_links: bool,
+    pub send_polls: bool,
+    /// Change the title, photo and other information of the group.
+    pub change_info: bool,
+    pub invite_users: bool,
+    pub pin_messages: bool,
+    /// Create topics in forums.
+    pub manage_topics: bool,
+}
+
 impl Permissions {
     pub(crate) fn new_full() -> Self {
         Self {
@@ -144,3 +176,114 @@ impl Restrictions {
         utils::date(self.raw.until_date)
     }
 }
+
+impl Default for ChatPermissions {
+    fn default() -> Self {
+        Self {
+            send_messages: true,
+            send_photos: true,
+            send_videos: true,
+            send_round_videos: true,
+            send_audios: true,
+            send_voices: true,
+            send_documents: true,
+            send_stickers: true,
+            send_gifs: true,
+            send_games: true,
+            send_inline: true,
+            embed_links: true,
+            send_polls: true,
+            change_info: true,
+            invite_users: true,
+            pin_messages: true,
+            manage_topics: true,
+        }
+    }
+}
+
+impl ChatPermissions {
+    pub(crate) fn from_raw(rights: &tl::types::ChatBannedRights) -> Self {
+        Self {
+            send_messages: !rights.send_messages && !rights.send_plain,
+            send_photos: !rights.send_media && !rights.send_photos,
+            send_videos: !rights.send_media && !rights.send_videos,
+            send_round_videos: !rights.send_media && !rights.send_roundvideos,
+            send_audios: !rights.send_media && !rights.send_audios,
+            send_voices: !rights.send_media && !rights.send_voices,
+            send_documents: !rights.send_media && !rights.send_docs,
+            send_stickers: !rights.send_stickers,
+            send_gifs: !rights.send_gifs,
+            send_games: !rights.send_games,
+            send_inline: !rights.send_inline,
+            embed_links: !rights.embed_links,
+            send_polls: !rights.send_polls,
+            change_info: !rights.change_info,
+            invite_users: !rights.invite_users,
+            pin_messages: !rights.pin_messages,
+            manage_topics: !rights.manage_topics,
+        }
+    }
+
+    pub(crate) fn to_raw(&self) -> tl::types::ChatBannedRights {
+        tl::types::ChatBannedRights {
+            view_messages: false,
+            send_messages: !self.send_messages,
+            // Official clients only set this when no media at all can be sent.
+            send_media: !(self.send_photos
+                || self.send_videos
+                || self.send_round_videos
+                || self.send_audios
+                || self.send_voices
+                || self.send_documents),
+            send_stickers: !self.send_stickers,
+            send_gifs: !self.send_gifs,
+            send_games: !self.send_games,
+            send_inline: !self.send_inline,
+            embed_links: !self.embed_links,
+            send_polls: !self.send_polls,
+            change_info: !self.change_info,
+            invite_users: !self.invite_users,
+            pin_messages: !self.pin_messages,
+            manage_topics: !self.manage_topics,
+            send_photos: !self.send_photos,
+            send_videos: !self.send_videos,
+            send_roundvideos: !self.send_round_videos,
+            send_audios: !self.send_audios,
+            send_voices: !self.send_voices,
+            send_docs: !self.send_documents,
+            send_plain: !self.send_messages,
+            until_date: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_permissions_roundtrip() {
+        let permissions = ChatPermissions {
+            send_photos: false,
+            pin_messages: false,
+            ..Default::default()
+        };
+
+        let raw = permissions.to_raw();
+        assert!(raw.send_photos && raw.pin_messages);
+        assert!(!raw.send_media && !raw.send_videos);
+        assert_eq!(ChatPermissions::from_raw(&raw), permissions);
+    }
+
+    #[test]
+    fn chat_permissions_media_restricted() {
+        let raw = tl::types::ChatBannedRights {
+            send_media: true,
+            ..ChatPermissions::default().to_raw()
+        };
+
+        let permissions = ChatPermissions::from_raw(&raw);
+        assert!(permissions.send_messages);
+        assert!(!permissions.send_photos && !permissions.send_documents);
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Token returned when requesting the code needed to change the phone number of the logged-in
+/// account, to be used in [`Client::change_phone`].
+///
+/// [`Client::change_phone`]: crate::Client::change_phone
+pub struct PhoneChangeToken {
+    pub(crate) phone: String,
+    pub(crate) phone_code_hash: String,
+}
+
+impl PhoneChangeToken {
+    /// The new phone number the code was sent to.
+    pub fn phone(&self) -> &str {
+        &self.phone
+    }
+}
@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use super::media::Document;
 use grammers_tl_types as tl;
 use tl::enums::Reaction;
 
@@ -84,3 +85,75 @@ impl From<InputReactions> for Vec<Reaction> {
         val.reactions
     }
 }
+
+/// A reaction which can be used on messages, along with the animations official clients use to
+/// render it.
+///
+/// The animations are usually Lottie files, and can be downloaded with
+/// [`Client::download_media`](crate::Client::download_media).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvailableReaction {
+    pub raw: tl::types::AvailableReaction,
+}
+
+impl AvailableReaction {
+    pub(crate) fn from_raw(reaction: tl::enums::AvailableReaction) -> Self {
+        let tl::enums::AvailableReaction::Reaction(reaction) = reaction;
+        Self { raw: reaction }
+    }
+
+    /// The emoji of the reaction, which can be used with [`InputReactions::emoticon`].
+    pub fn reaction(&self) -> &str {
+        &self.raw.reaction
+    }
+
+    /// The human-readable name of the reaction.
+    pub fn title(&self) -> &str {
+        &self.raw.title
+    }
+
+    /// Whether the reaction can no longer be used, although existing ones are still shown.
+    pub fn is_inactive(&self) -> bool {
+        self.raw.inactive
+    }
+
+    /// Whether the reaction can only be used by Telegram Premium users.
+    pub fn is_premium(&self) -> bool {
+        self.raw.premium
+    }
+
+    /// The static icon of the reaction.
+    pub fn static_icon(&self) -> Document {
+        Document::from_raw(self.raw.static_icon.clone())
+    }
+
+    /// The animation played when the reaction appears in the list of reactions.
+    pub fn appear_animation(&self) -> Document {
+        Document::from_raw(self.raw.appear_animation.clone())
+    }
+
+    /// The animation played when the reaction is hovered or selected in the list of reactions.
+    pub fn select_animation(&self) -> Document {
+        Document::from_raw(self.raw.select_animation.clone())
+    }
+
+    /// The animation played on the message once the reaction is added.
+    pub fn activate_animation(&self) -> Document {
+        Document::from_raw(self.raw.activate_animation.clone())
+    }
+
+    /// The animation played around the message once the reaction is added.
+    pub fn effect_animation(&self) -> Document {
+        Document::from_raw(self.raw.effect_animation.clone())
+    }
+
+    /// The animation played around the reaction once it is added, if any.
+    pub fn around_animation(&self) -> Option<Document> {
+        self.raw.around_animation.clone().map(Document::from_raw)
+    }
+
+    /// The icon shown in the center of the reaction once it is added, if any.
+    pub fn center_icon(&self) -> Option<Document> {
+        self.raw.center_icon.clone().map(Document::from_raw)
+    }
+}
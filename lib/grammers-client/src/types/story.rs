@@ -0,0 +1,197 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{Chat, ChatMap, InputMessage, InputReactions, Media, Message};
+use crate::{Client, utils};
+use chrono::{DateTime, Utc};
+use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+use std::fmt;
+
+/// Represents a story posted by a user or a channel.
+///
+/// Stories expire after a certain amount of time, unless they are pinned to the profile.
+#[derive(Clone)]
+pub struct Story {
+    pub raw: tl::types::StoryItem,
+    client: Client,
+    chat: Chat,
+}
+
+impl fmt::Debug for Story {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Story")
+            .field("chat", &self.chat)
+            .field("raw", &self.raw)
+            .finish()
+    }
+}
+
+impl Story {
+    pub(crate) fn from_raw(
+        client: &Client,
+        story: tl::types::StoryItem,
+        peer: &tl::enums::Peer,
+        chats: &ChatMap,
+    ) -> Self {
+        Self {
+            raw: story,
+            client: client.clone(),
+            chat: utils::always_find_entity(peer, chats, client),
+        }
+    }
+
+    /// The identifier of this story, unique only within the chat that posted it.
+    pub fn id(&self) -> i32 {
+        self.raw.id
+    }
+
+    /// The chat that posted this story.
+    pub fn chat(&self) -> &Chat {
+        &self.chat
+    }
+
+    /// The date when this story was posted.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The date when this story will expire.
+    pub fn expire_date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.expire_date)
+    }
+
+    /// The caption of this story, if any.
+    pub fn caption(&self) -> Option<&str> {
+        self.raw.caption.as_deref()
+    }
+
+    /// The media shown in this story.
+    pub fn media(&self) -> Option<Media> {
+        Media::from_raw(self.raw.media.clone())
+    }
+
+    /// Whether this story is pinned to the profile of the chat that posted it.
+    pub fn pinned(&self) -> bool {
+        self.raw.pinned
+    }
+
+    /// Whether this story was posted by the logged-in account.
+    pub fn outgoing(&self) -> bool {
+        self.raw.out
+    }
+
+    /// Reply to this story by sending a message to the user who posted it.
+    ///
+    /// This methods overrides the `reply_to` on the `InputMessage` to point to `self`.
+    ///
+    /// Shorthand for `Client::send_message`.
+    pub async fn reply<M: Into<InputMessage>>(
+        &self,
+        message: M,
+    ) -> Result<Message, InvocationError> {
+        let mut message = message.into().reply_to(None);
+        message.reply_to_story = Some(self.raw.id);
+        self.client.send_message(&self.chat, message).await
+    }
+
+    /// React to this story.
+    ///
+    /// Shorthand for `Client::send_story_reaction`.
+    pub async fn react<R: Into<InputReactions>>(
+        &self,
+        reactions: R,
+    ) -> Result<(), InvocationError> {
+        self.client
+            .send_story_reaction(&self.chat, self.raw.id, reactions)
+            .await
+    }
+
+    /// Mark this story, and all the previous stories posted by the same chat, as read.
+    ///
+    /// Shorthand for `Client::read_stories`.
+    pub async fn mark_as_read(&self) -> Result<(), InvocationError> {
+        self.client
+            .read_stories(&self.chat, self.raw.id)
+            .await
+            .map(drop)
+    }
+}
+
+/// Builder to define who can see a story.
+///
+/// Start with one of the constructors to define the base audience, and optionally exclude
+/// some users from it.
+#[derive(Clone, Debug)]
+pub struct StoryPrivacy {
+    pub(crate) rules: Vec<tl::enums::InputPrivacyRule>,
+}
+
+impl StoryPrivacy {
+    /// Everyone will be able to see the story.
+    pub fn everyone() -> Self {
+        Self {
+            rules: vec![tl::enums::InputPrivacyRule::InputPrivacyValueAllowAll],
+        }
+    }
+
+    /// Only the contacts of the logged-in account will be able to see the story.
+    pub fn contacts() -> Self {
+        Self {
+            rules: vec![tl::enums::InputPrivacyRule::InputPrivacyValueAllowContacts],
+        }
+    }
+
+    /// Only the users in the close friends list will be able to see the story.
+    ///
+    /// The list can be changed with [`Client::set_close_friends`].
+    pub fn close_friends() -> Self {
+        Self {
+            rules: vec![tl::enums::InputPrivacyRule::InputPrivacyValueAllowCloseFriends],
+        }
+    }
+
+    /// Only the given users will be able to see the story.
+    pub fn selected_users<C: Into<PackedChat>, I: IntoIterator<Item = C>>(users: I) -> Self {
+        Self {
+            rules: vec![
+                tl::types::InputPrivacyValueAllowUsers {
+                    users: users
+                        .into_iter()
+                        .map(|user| user.into().to_input_user_lossy())
+                        .collect(),
+                }
+                .into(),
+                tl::enums::InputPrivacyRule::InputPrivacyValueDisallowAll,
+            ],
+        }
+    }
+
+    /// Prevent the given users from seeing the story, even if they are part of the audience.
+    ///
+    /// This is mostly useful along with [`StoryPrivacy::everyone`] and
+    /// [`StoryPrivacy::contacts`].
+    pub fn except<C: Into<PackedChat>, I: IntoIterator<Item = C>>(mut self, users: I) -> Self {
+        self.rules.push(
+            tl::types::InputPrivacyValueDisallowUsers {
+                users: users
+                    .into_iter()
+                    .map(|user| user.into().to_input_user_lossy())
+                    .collect(),
+            }
+            .into(),
+        );
+        self
+    }
+}
+
+impl Default for StoryPrivacy {
+    fn default() -> Self {
+        Self::everyone()
+    }
+}
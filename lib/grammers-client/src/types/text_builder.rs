@@ -0,0 +1,234 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{InputMessage, User};
+use grammers_tl_types as tl;
+
+/// Builder to compose formatted text without going through a markup language.
+///
+/// Every method appends a piece of text, formatted with the corresponding entity. Offsets are
+/// computed in UTF-16 code units, as Telegram expects.
+///
+/// # Examples
+///
+/// ```
+/// # fn f(user: grammers_client::types::User) {
+/// use grammers_client::types::TextBuilder;
+/// use grammers_client::InputMessage;
+///
+/// let message: InputMessage = TextBuilder::new()
+///     .bold("hi ")
+///     .mention(&user)
+///     .text(", try ")
+///     .code("x=1")
+///     .into();
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TextBuilder {
+    text: String,
+    entities: Vec<tl::enums::MessageEntity>,
+}
+
+impl TextBuilder {
+    /// Create a new, empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append plain text, without any formatting.
+    pub fn text(mut self, text: &str) -> Self {
+        self.text.push_str(text);
+        self
+    }
+
+    /// Append bold text.
+    pub fn bold(self, text: &str) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityBold { offset, length }.into()
+        })
+    }
+
+    /// Append italic text.
+    pub fn italic(self, text: &str) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityItalic { offset, length }.into()
+        })
+    }
+
+    /// Append underlined text.
+    pub fn underline(self, text: &str) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityUnderline { offset, length }.into()
+        })
+    }
+
+    /// Append struck-through text.
+    pub fn strikethrough(self, text: &str) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityStrike { offset, length }.into()
+        })
+    }
+
+    /// Append text hidden behind a spoiler.
+    pub fn spoiler(self, text: &str) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntitySpoiler { offset, length }.into()
+        })
+    }
+
+    /// Append inline monospace text.
+    pub fn code(self, text: &str) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityCode { offset, length }.into()
+        })
+    }
+
+    /// Append a pre-formatted code block, optionally highlighted as the given language.
+    pub fn pre(self, text: &str, language: Option<&str>) -> Self {
+        let language = language.unwrap_or_default().to_string();
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityPre {
+                offset,
+                length,
+                language,
+            }
+            .into()
+        })
+    }
+
+    /// Append a block quotation, which can be collapsed to show only its first lines.
+    pub fn blockquote(self, text: &str, collapsed: bool) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityBlockquote {
+                collapsed,
+                offset,
+                length,
+            }
+            .into()
+        })
+    }
+
+    /// Append text linking to the given URL.
+    pub fn link(self, text: &str, url: &str) -> Self {
+        let url = url.to_string();
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityTextUrl {
+                offset,
+                length,
+                url,
+            }
+            .into()
+        })
+    }
+
+    /// Append a mention of the user, using their full name as the text.
+    pub fn mention(self, user: &User) -> Self {
+        self.mention_with(&user.full_name(), user.id())
+    }
+
+    /// Append text mentioning the user with the given identifier.
+    ///
+    /// The mention will only work if the user is known to the client when sending the message.
+    pub fn mention_with(self, text: &str, user_id: i64) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityMentionName {
+                offset,
+                length,
+                user_id,
+            }
+            .into()
+        })
+    }
+
+    /// Append a custom emoji with the given document identifier. The text should be a single
+    /// emoji, shown to clients which cannot display the custom one.
+    pub fn custom_emoji(self, text: &str, document_id: i64) -> Self {
+        self.push(text, |offset, length| {
+            tl::types::MessageEntityCustomEmoji {
+                offset,
+                length,
+                document_id,
+            }
+            .into()
+        })
+    }
+
+    /// The text built so far.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Finish building, returning the text and its formatting entities.
+    pub fn build(self) -> (String, Vec<tl::enums::MessageEntity>) {
+        (self.text, self.entities)
+    }
+
+    fn push<F: FnOnce(i32, i32) -> tl::enums::MessageEntity>(
+        mut self,
+        text: &str,
+        entity: F,
+    ) -> Self {
+        let length = utf16_len(text);
+        if length != 0 {
+            let offset = utf16_len(&self.text);
+            self.entities.push(entity(offset, length));
+        }
+        self.text.push_str(text);
+        self
+    }
+}
+
+impl From<TextBuilder> for InputMessage {
+    fn from(builder: TextBuilder) -> Self {
+        let (text, entities) = builder.build();
+        InputMessage::text(text).fmt_entities(entities)
+    }
+}
+
+fn utf16_len(text: &str) -> i32 {
+    text.encode_utf16().count() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entities_use_utf16_offsets() {
+        let (text, entities) = TextBuilder::new()
+            .bold("hi ")
+            .text("👋 ")
+            .code("x=1")
+            .italic("")
+            .link("site", "https://example.com")
+            .build();
+
+        assert_eq!(text, "hi 👋 x=1site");
+        assert_eq!(
+            entities,
+            vec![
+                tl::types::MessageEntityBold {
+                    offset: 0,
+                    length: 3
+                }
+                .into(),
+                tl::types::MessageEntityCode {
+                    offset: 6,
+                    length: 3
+                }
+                .into(),
+                tl::types::MessageEntityTextUrl {
+                    offset: 9,
+                    length: 4,
+                    url: "https://example.com".to_string(),
+                }
+                .into(),
+            ]
+        );
+    }
+}
@@ -8,7 +8,11 @@
 
 use std::sync::Arc;
 
-use super::{CallbackQuery, ChatMap, InlineQuery, InlineSend, Message};
+use super::chat_member_update::MemberChange;
+use super::{
+    CallbackQuery, Chat, ChatMap, ChatMemberUpdate, InlineQuery, InlineSend, JoinRequestUpdate,
+    Message, MessageReactionCountUpdate, MessageReactionUpdate, UserStatus,
+};
 use crate::{Client, types::MessageDeletion};
 use grammers_tl_types as tl;
 
@@ -29,6 +33,66 @@ pub enum Update {
     InlineQuery(InlineQuery),
     /// Represents an update of user choosing the result of inline query and sending it to their chat partner.
     InlineSend(InlineSend),
+    /// Occurs when the presence of a user changes, such as going online or offline.
+    UserStatus { user_id: i64, status: UserStatus },
+    /// Occurs when a user joins a group or channel.
+    ChatMemberJoined(ChatMemberUpdate),
+    /// Occurs when a user leaves a group or channel.
+    ChatMemberLeft(ChatMemberUpdate),
+    /// Occurs when a user is banned from a channel, or removed from a small group.
+    ChatMemberBanned(ChatMemberUpdate),
+    /// Occurs when a user requests to join a group or channel where the logged-in bot can
+    /// approve the request.
+    JoinRequest(JoinRequestUpdate),
+    /// Occurs when the number of pending requests to join a group or channel changes, for
+    /// user accounts which can approve them. The identifiers of the users who requested to
+    /// join most recently are included.
+    PendingJoinRequests {
+        chat: Chat,
+        count: i32,
+        recent_requester_ids: Vec<i64>,
+    },
+    /// Occurs when a user or chat changes their reactions to a message, for bots which are
+    /// administrators in the chat.
+    MessageReaction(MessageReactionUpdate),
+    /// Occurs when the anonymous reaction counts of a channel message change, for bots which
+    /// are administrators in the channel.
+    MessageReactionCount(MessageReactionCountUpdate),
+    /// Occurs when a video chat is started in a group or channel.
+    ///
+    /// This and the following service message variants only occur if enabled with
+    /// [`InitParams::service_message_updates`](crate::InitParams::service_message_updates).
+    /// Otherwise, they're returned as [`Update::NewMessage`].
+    VideoChatStarted(Message),
+    /// Occurs when a video chat ends. The duration of the video chat is in seconds.
+    VideoChatEnded { message: Message, duration: i32 },
+    /// Occurs when a forum topic is created. The message ID is the ID of the new topic.
+    TopicCreated {
+        message: Message,
+        title: String,
+        icon_emoji_id: Option<i64>,
+    },
+    /// Occurs when the title or icon of a forum topic is changed, or the general topic is
+    /// hidden or shown. Only the fields which changed are present.
+    TopicEdited {
+        message: Message,
+        title: Option<String>,
+        icon_emoji_id: Option<i64>,
+        hidden: Option<bool>,
+    },
+    /// Occurs when a forum topic is closed, so that only administrators can post in it.
+    TopicClosed(Message),
+    /// Occurs when a closed forum topic is reopened.
+    TopicReopened(Message),
+    /// Occurs when a small group is migrated into a megagroup.
+    ///
+    /// Telegram sends a service message to both the old group and the new megagroup, so this
+    /// update may occur twice for the same migration, once with each message.
+    ChatMigrated {
+        message: Message,
+        chat_id: i64,
+        channel_id: i64,
+    },
     /// Raw events are not actual events.
     /// Instead, they are the raw Update object that Telegram sends. You
     /// normally shouldn’t need these.
@@ -45,12 +109,14 @@ impl Update {
         match update {
             // NewMessage
             tl::enums::Update::NewMessage(tl::types::UpdateNewMessage { message, .. }) => {
-                Message::from_raw(client, message, chats).map(Self::NewMessage)
+                Message::from_raw(client, message, chats).map(|m| Self::from_new_message(client, m))
             }
             tl::enums::Update::NewChannelMessage(tl::types::UpdateNewChannelMessage {
                 message,
                 ..
-            }) => Message::from_raw(client, message, chats).map(Self::NewMessage),
+            }) => {
+                Message::from_raw(client, message, chats).map(|m| Self::from_new_message(client, m))
+            }
 
             // MessageEdited
             tl::enums::Update::EditMessage(tl::types::UpdateEditMessage { message, .. }) => {
@@ -93,8 +159,121 @@ impl Update {
                 Some(Self::InlineSend(InlineSend::from_raw(query, client, chats)))
             }
 
+            // UserStatus
+            tl::enums::Update::UserStatus(tl::types::UpdateUserStatus { user_id, status }) => {
+                Some(Self::UserStatus {
+                    user_id,
+                    status: UserStatus::from_raw(&status),
+                })
+            }
+
+            // ChatMember
+            tl::enums::Update::ChannelParticipant(update) => {
+                match ChatMemberUpdate::channel_change(&update) {
+                    Some(change) => Some(Self::from_member_change(
+                        change,
+                        ChatMemberUpdate::from_raw_channel(client, update, chats),
+                    )),
+                    None => Some(Self::Raw(update.into())),
+                }
+            }
+            tl::enums::Update::ChatParticipant(update) => {
+                match ChatMemberUpdate::chat_change(&update) {
+                    Some(change) => Some(Self::from_member_change(
+                        change,
+                        ChatMemberUpdate::from_raw_chat(client, update, chats),
+                    )),
+                    None => Some(Self::Raw(update.into())),
+                }
+            }
+
+            // JoinRequest
+            tl::enums::Update::BotChatInviteRequester(update) => Some(Self::JoinRequest(
+                JoinRequestUpdate::from_raw(client, update, chats),
+            )),
+
+            // PendingJoinRequests
+            tl::enums::Update::PendingJoinRequests(update) => Some(Self::PendingJoinRequests {
+                chat: crate::utils::always_find_entity(&update.peer, chats, client),
+                count: update.requests_pending,
+                recent_requester_ids: update.recent_requesters,
+            }),
+
+            // MessageReaction
+            tl::enums::Update::BotMessageReaction(update) => Some(Self::MessageReaction(
+                MessageReactionUpdate::from_raw(client, update, chats),
+            )),
+
+            // MessageReactionCount
+            tl::enums::Update::BotMessageReactions(update) => Some(Self::MessageReactionCount(
+                MessageReactionCountUpdate::from_raw(client, update, chats),
+            )),
+
             // Raw
             update => Some(Self::Raw(update)),
         }
     }
+
+    /// Turn service messages with a dedicated variant into said variant, if enabled.
+    fn from_new_message(client: &Client, message: Message) -> Self {
+        use tl::enums::MessageAction as A;
+
+        if !client.0.config.params.service_message_updates {
+            return Self::NewMessage(message);
+        }
+
+        match (message.action(), &message.raw.peer_id) {
+            (Some(A::GroupCall(call)), _) => match call.duration {
+                Some(duration) => Self::VideoChatEnded { message, duration },
+                None => Self::VideoChatStarted(message),
+            },
+            (Some(A::TopicCreate(topic)), _) => {
+                let (title, icon_emoji_id) = (topic.title.clone(), topic.icon_emoji_id);
+                Self::TopicCreated {
+                    message,
+                    title,
+                    icon_emoji_id,
+                }
+            }
+            (Some(A::TopicEdit(topic)), _) => match topic.closed {
+                Some(true) => Self::TopicClosed(message),
+                Some(false) => Self::TopicReopened(message),
+                None => {
+                    let (title, icon_emoji_id, hidden) =
+                        (topic.title.clone(), topic.icon_emoji_id, topic.hidden);
+                    Self::TopicEdited {
+                        message,
+                        title,
+                        icon_emoji_id,
+                        hidden,
+                    }
+                }
+            },
+            (Some(A::ChatMigrateTo(migrate)), tl::enums::Peer::Chat(chat)) => {
+                let (chat_id, channel_id) = (chat.chat_id, migrate.channel_id);
+                Self::ChatMigrated {
+                    message,
+                    chat_id,
+                    channel_id,
+                }
+            }
+            (Some(A::ChannelMigrateFrom(migrate)), tl::enums::Peer::Channel(channel)) => {
+                let (chat_id, channel_id) = (migrate.chat_id, channel.channel_id);
+                Self::ChatMigrated {
+                    message,
+                    chat_id,
+                    channel_id,
+                }
+            }
+            _ => Self::NewMessage(message),
+        }
+    }
+
+    fn from_member_change(change: MemberChange, update: ChatMemberUpdate) -> Self {
+        match change {
+            MemberChange::Joined => Self::ChatMemberJoined(update),
+            MemberChange::Left => Self::ChatMemberLeft(update),
+            MemberChange::Banned => Self::ChatMemberBanned(update),
+        }
+    }
 }
@@ -0,0 +1,193 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{Chat, ChatMap, User};
+use grammers_tl_types as tl;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The birthday of a user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Birthday {
+    /// Day of the month, starting at 1.
+    pub day: i32,
+    /// Month of the year, starting at 1.
+    pub month: i32,
+    /// Year of birth, if the user chose to share it.
+    pub year: Option<i32>,
+}
+
+/// The opening hours of a business account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BusinessHours {
+    /// Identifier of the timezone the hours are expressed in, as returned by `help.getTimezonesList`.
+    pub timezone_id: String,
+    /// Whether the business is open right now. Ignored when changing the opening hours.
+    pub open_now: bool,
+    /// Intervals during which the business is open, in minutes since Monday at midnight.
+    ///
+    /// The intervals may exceed the length of a week, to represent periods which wrap around
+    /// from Sunday to Monday.
+    pub weekly_open: Vec<Range<i32>>,
+}
+
+/// The physical location of a business account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BusinessLocation {
+    /// Address of the business, as free-form text.
+    pub address: String,
+    /// Latitude and longitude of the business, if it is shown on the map.
+    pub coordinates: Option<(f64, f64)>,
+}
+
+/// Extended information about a user, as returned by [`crate::Client::get_full_user`].
+#[derive(Clone)]
+pub struct UserFull {
+    pub raw: tl::types::UserFull,
+    pub user: User,
+    chats: Arc<ChatMap>,
+}
+
+impl fmt::Debug for UserFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.raw.fmt(f)
+    }
+}
+
+impl From<tl::enums::Birthday> for Birthday {
+    fn from(birthday: tl::enums::Birthday) -> Self {
+        let tl::enums::Birthday::Birthday(birthday) = birthday;
+        Self {
+            day: birthday.day,
+            month: birthday.month,
+            year: birthday.year,
+        }
+    }
+}
+
+impl From<Birthday> for tl::enums::Birthday {
+    fn from(birthday: Birthday) -> Self {
+        tl::types::Birthday {
+            day: birthday.day,
+            month: birthday.month,
+            year: birthday.year,
+        }
+        .into()
+    }
+}
+
+impl From<tl::enums::BusinessWorkHours> for BusinessHours {
+    fn from(hours: tl::enums::BusinessWorkHours) -> Self {
+        let tl::enums::BusinessWorkHours::Hours(hours) = hours;
+        Self {
+            timezone_id: hours.timezone_id,
+            open_now: hours.open_now,
+            weekly_open: hours
+                .weekly_open
+                .into_iter()
+                .map(|tl::enums::BusinessWeeklyOpen::Open(open)| open.start_minute..open.end_minute)
+                .collect(),
+        }
+    }
+}
+
+impl From<BusinessHours> for tl::enums::BusinessWorkHours {
+    fn from(hours: BusinessHours) -> Self {
+        tl::types::BusinessWorkHours {
+            open_now: hours.open_now,
+            timezone_id: hours.timezone_id,
+            weekly_open: hours
+                .weekly_open
+                .into_iter()
+                .map(|range| {
+                    tl::types::BusinessWeeklyOpen {
+                        start_minute: range.start,
+                        end_minute: range.end,
+                    }
+                    .into()
+                })
+                .collect(),
+        }
+        .into()
+    }
+}
+
+impl From<tl::enums::BusinessLocation> for BusinessLocation {
+    fn from(location: tl::enums::BusinessLocation) -> Self {
+        let tl::enums::BusinessLocation::Location(location) = location;
+        Self {
+            address: location.address,
+            coordinates: match location.geo_point {
+                Some(tl::enums::GeoPoint::Point(point)) => Some((point.lat, point.long)),
+                Some(tl::enums::GeoPoint::Empty) | None => None,
+            },
+        }
+    }
+}
+
+impl UserFull {
+    pub(crate) fn from_raw(full: tl::types::users::UserFull) -> Self {
+        let tl::enums::UserFull::Full(raw) = full.full_user;
+        let chats = ChatMap::new(full.users, full.chats);
+        let user = match chats.get(&tl::types::PeerUser { user_id: raw.id }.into()) {
+            Some(Chat::User(user)) => user.clone(),
+            _ => User::from_raw(tl::types::UserEmpty { id: raw.id }.into()),
+        };
+        Self { raw, user, chats }
+    }
+
+    /// Return the user this extended information belongs to.
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// The biography of the user, if any.
+    pub fn about(&self) -> Option<&str> {
+        self.raw.about.as_deref()
+    }
+
+    /// The birthday of the user, if they chose to share it.
+    pub fn birthday(&self) -> Option<Birthday> {
+        self.raw.birthday.clone().map(Birthday::from)
+    }
+
+    /// The identifier of the channel the user chose to show in their profile, if any.
+    pub fn personal_channel_id(&self) -> Option<i64> {
+        self.raw.personal_channel_id
+    }
+
+    /// The channel the user chose to show in their profile, if any.
+    pub fn personal_channel(&self) -> Option<&Chat> {
+        self.raw.personal_channel_id.and_then(|channel_id| {
+            self.chats
+                .get(&tl::types::PeerChannel { channel_id }.into())
+        })
+    }
+
+    /// The opening hours of the business, if the user is a business account and set them.
+    pub fn business_hours(&self) -> Option<BusinessHours> {
+        self.raw
+            .business_work_hours
+            .clone()
+            .map(BusinessHours::from)
+    }
+
+    /// The location of the business, if the user is a business account and set it.
+    pub fn business_location(&self) -> Option<BusinessLocation> {
+        self.raw
+            .business_location
+            .clone()
+            .map(BusinessLocation::from)
+    }
+
+    /// Whether the button to translate messages is hidden in the conversation with this user.
+    pub fn translations_disabled(&self) -> bool {
+        self.raw.translations_disabled
+    }
+}
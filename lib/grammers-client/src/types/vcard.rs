@@ -0,0 +1,221 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::fmt;
+
+/// The most common fields of a contact card in [vCard format][1].
+///
+/// Cards can be parsed from the text of a [`Contact`](crate::types::media::Contact), and
+/// formatted with `to_string` (as vCard 3.0) to be sent with [`InputMessage::contact`].
+///
+/// [1]: https://en.wikipedia.org/wiki/VCard
+/// [`InputMessage::contact`]: crate::InputMessage::contact
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VCard {
+    /// The formatted name (`FN`). When formatting, it defaults to the first and last name.
+    pub full_name: Option<String>,
+    /// The given name, from the structured name (`N`).
+    pub first_name: String,
+    /// The family name, from the structured name (`N`).
+    pub last_name: String,
+    /// The phone numbers (`TEL`), in the order they appear.
+    pub phones: Vec<String>,
+    /// The email addresses (`EMAIL`), in the order they appear.
+    pub emails: Vec<String>,
+    /// The name of the organization (`ORG`).
+    pub organization: Option<String>,
+    /// A free-form note (`NOTE`).
+    pub note: Option<String>,
+}
+
+impl VCard {
+    /// Parse the first card in `text`.
+    ///
+    /// Properties which are not supported are ignored. Returns `None` if the text contains
+    /// no card.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut card = None::<Self>;
+
+        for line in unfold_lines(text) {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            // Drop the parameters (as in `TEL;TYPE=CELL`) and the group (as in `item1.TEL`).
+            let name = name.split(';').next().unwrap_or_default();
+            let name = name.rsplit('.').next().unwrap_or_default();
+            let name = name.to_ascii_uppercase();
+
+            if name == "BEGIN" && value.eq_ignore_ascii_case("VCARD") {
+                card = Some(Self::default());
+                continue;
+            }
+            let Some(card) = card.as_mut() else {
+                continue;
+            };
+
+            match name.as_str() {
+                "END" => break,
+                "FN" => card.full_name = Some(unescape(value)),
+                "N" => {
+                    let mut parts = split_components(value).into_iter();
+                    card.last_name = parts.next().unwrap_or_default();
+                    card.first_name = parts.next().unwrap_or_default();
+                }
+                "TEL" => card.phones.push(unescape(value)),
+                "EMAIL" => card.emails.push(unescape(value)),
+                "ORG" => card.organization = split_components(value).into_iter().next(),
+                "NOTE" => card.note = Some(unescape(value)),
+                _ => {}
+            }
+        }
+
+        card
+    }
+}
+
+impl fmt::Display for VCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let full_name = match &self.full_name {
+            Some(name) => name.clone(),
+            None => format!("{} {}", self.first_name, self.last_name)
+                .trim()
+                .to_string(),
+        };
+
+        write!(f, "BEGIN:VCARD\r\nVERSION:3.0\r\n")?;
+        write!(
+            f,
+            "N:{};{};;;\r\n",
+            escape(&self.last_name),
+            escape(&self.first_name)
+        )?;
+        write!(f, "FN:{}\r\n", escape(&full_name))?;
+        for phone in &self.phones {
+            write!(f, "TEL:{}\r\n", escape(phone))?;
+        }
+        for email in &self.emails {
+            write!(f, "EMAIL:{}\r\n", escape(email))?;
+        }
+        if let Some(organization) = &self.organization {
+            write!(f, "ORG:{}\r\n", escape(organization))?;
+        }
+        if let Some(note) = &self.note {
+            write!(f, "NOTE:{}\r\n", escape(note))?;
+        }
+        write!(f, "END:VCARD\r\n")
+    }
+}
+
+/// Join lines which were folded by starting them with whitespace.
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines = Vec::<String>::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split a structured value by its unescaped semicolons.
+fn split_components(value: &str) -> Vec<String> {
+    let mut components = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let component = components.last_mut().unwrap();
+                component.push('\\');
+                component.extend(chars.next());
+            }
+            ';' => components.push(String::new()),
+            c => components.last_mut().unwrap().push(c),
+        }
+    }
+    components.iter().map(|c| unescape(c)).collect()
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => result.push('\n'),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            },
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => {}
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_card() {
+        let text = "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Doe;John;;;\r\nFN:John\r\n  Doe\r\n\
+            item1.TEL;TYPE=CELL:+1 234\r\nTEL:+5 678\r\nEMAIL:john@example.com\r\n\
+            ORG:Acme\\, Inc.;Sales\r\nNOTE:Line one\\nLine two\r\nEND:VCARD\r\n";
+
+        let card = VCard::parse(text).unwrap();
+        assert_eq!(card.full_name.as_deref(), Some("John Doe"));
+        assert_eq!(card.first_name, "John");
+        assert_eq!(card.last_name, "Doe");
+        assert_eq!(card.phones, ["+1 234", "+5 678"]);
+        assert_eq!(card.emails, ["john@example.com"]);
+        assert_eq!(card.organization.as_deref(), Some("Acme, Inc."));
+        assert_eq!(card.note.as_deref(), Some("Line one\nLine two"));
+
+        assert_eq!(VCard::parse("TEL:+1 234"), None);
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let card = VCard {
+            first_name: "Jane".to_string(),
+            last_name: "Roe; Jr.".to_string(),
+            phones: vec!["+1 234".to_string()],
+            note: Some("a\\b".to_string()),
+            ..Default::default()
+        };
+
+        let text = card.to_string();
+        assert!(text.starts_with("BEGIN:VCARD\r\nVERSION:3.0\r\nN:Roe\\; Jr.;Jane;;;\r\n"));
+        assert!(text.contains("FN:Jane Roe\\; Jr.\r\n"));
+
+        let parsed = VCard::parse(&text).unwrap();
+        assert_eq!(parsed.full_name.as_deref(), Some("Jane Roe; Jr."));
+        assert_eq!(
+            VCard {
+                full_name: None,
+                ..parsed
+            },
+            card
+        );
+    }
+}
@@ -42,6 +42,16 @@ pub(crate) fn generate_random_ids(n: usize) -> Vec<i64> {
     (0..n).map(|_| generate_random_id()).collect()
 }
 
+/// Combine the identifiers into the hash Telegram uses to tell whether a cached result changed.
+pub(crate) fn combine_hash(ids: impl IntoIterator<Item = i64>) -> i64 {
+    ids.into_iter().fold(0u64, |hash, id| {
+        let hash = hash ^ (hash >> 21);
+        let hash = hash ^ (hash << 35);
+        let hash = hash ^ (hash >> 4);
+        hash.wrapping_add(id as u64)
+    }) as i64
+}
+
 pub(crate) fn date(date: i32) -> DateTime<Utc> {
     DateTime::<Utc>::from_timestamp(date as i64, 0).expect("date out of range")
 }
@@ -79,18 +89,11 @@ pub(crate) fn always_find_entity(
             tl::enums::Peer::Chat(chat) => (chat.chat_id, PackedType::Chat),
             tl::enums::Peer::Channel(channel) => (channel.channel_id, PackedType::Broadcast),
         };
-        client
-            .0
-            .state
-            .read()
-            .unwrap()
-            .chat_hashes
-            .get(id)
-            .unwrap_or(PackedChat {
-                ty,
-                id,
-                access_hash: None,
-            })
+        client.0.chat_hashes.load().get(id).unwrap_or(PackedChat {
+            ty,
+            id,
+            access_hash: None,
+        })
     };
 
     match map.get(peer).cloned() {
@@ -98,11 +101,10 @@ pub(crate) fn always_find_entity(
             // As a best-effort, attempt to replace any `min` `access_hash` with the non-`min`
             // version. The `min` hash is only usable to download profile photos (if the user
             // tried to pack it for later use, like sending a message, it would fail).
-            if let Some((min, access_hash)) = chat.get_min_hash_ref() {
+            if let Some(access_hash) = chat.get_min_hash_ref() {
                 let packed = get_packed();
                 if let Some(ah) = packed.access_hash {
                     *access_hash = ah;
-                    *min = false;
                 }
             }
             chat
@@ -110,3 +112,45 @@ pub(crate) fn always_find_entity(
         None => types::Chat::unpack(get_packed()),
     }
 }
+
+/// Get the offset and length of any entity.
+pub(crate) fn entity_range(entity: &tl::enums::MessageEntity) -> (i32, i32) {
+    use tl::enums::MessageEntity as E;
+
+    match entity {
+        E::Unknown(e) => (e.offset, e.length),
+        E::Mention(e) => (e.offset, e.length),
+        E::Hashtag(e) => (e.offset, e.length),
+        E::BotCommand(e) => (e.offset, e.length),
+        E::Url(e) => (e.offset, e.length),
+        E::Email(e) => (e.offset, e.length),
+        E::Bold(e) => (e.offset, e.length),
+        E::Italic(e) => (e.offset, e.length),
+        E::Code(e) => (e.offset, e.length),
+        E::Pre(e) => (e.offset, e.length),
+        E::TextUrl(e) => (e.offset, e.length),
+        E::MentionName(e) => (e.offset, e.length),
+        E::InputMessageEntityMentionName(e) => (e.offset, e.length),
+        E::Phone(e) => (e.offset, e.length),
+        E::Cashtag(e) => (e.offset, e.length),
+        E::Underline(e) => (e.offset, e.length),
+        E::Strike(e) => (e.offset, e.length),
+        E::BankCard(e) => (e.offset, e.length),
+        E::Spoiler(e) => (e.offset, e.length),
+        E::CustomEmoji(e) => (e.offset, e.length),
+        E::Blockquote(e) => (e.offset, e.length),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_hash_matches_telegram() {
+        assert_eq!(combine_hash([]), 0);
+        assert_eq!(combine_hash([5]), 5);
+        assert_eq!(combine_hash([2, 1000, 1001, 777000]), -8158357410778163090);
+        assert_eq!(combine_hash([-1, 1]), -1152913258269638655);
+    }
+}
@@ -0,0 +1,1074 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Exchange of updates with HTTP endpoints in the shape of the [Bot API][1].
+//!
+//! This eases migrating a stack where some services still speak the Bot API. Updates received
+//! by the client can be converted with [`to_bot_api`] and delivered with a [`Webhook`]:
+//!
+//! ```
+//! # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+//! use grammers_client::webhook::Webhook;
+//!
+//! let webhook = Webhook::new("http://127.0.0.1:8080/bot").unwrap();
+//! loop {
+//!     let update = client.next_update().await?;
+//!     webhook.send(&update).await?;
+//! }
+//! # }
+//! ```
+//!
+//! And updates sent by the webhooks of the Bot API (or by a [`Webhook`]) can be received with
+//! a [`WebhookListener`]:
+//!
+//! ```
+//! # async fn f() -> Result<(), Box<dyn std::error::Error>> {
+//! use grammers_client::webhook::WebhookListener;
+//!
+//! let listener = WebhookListener::bind("127.0.0.1:8080", "/bot").await?;
+//! loop {
+//!     let update = listener.next().await?;
+//!     if let Some(message) = update.message {
+//!         println!("Received: {:?}", message.text);
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! Only messages (including channel posts and edits), callback queries and inline queries are
+//! supported, with their most common fields. Only plain `http` endpoints are supported, which
+//! is intended for services running locally.
+//!
+//! [1]: https://core.telegram.org/bots/api#update
+use crate::Update;
+use crate::types::{CallbackQuery, Chat, ChatMap, Message, User};
+use grammers_session::{PackedChat, PackedType};
+use grammers_tl_types as tl;
+use serde_derive::{Deserialize, Serialize};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{Mutex as AsyncMutex, Semaphore, mpsc};
+use tokio::task::JoinHandle;
+
+// Bot API identifiers of channels are prefixed by `-100`, followed by the raw identifier.
+const CHANNEL_ID_OFFSET: i64 = 1_000_000_000_000;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum size of the HTTP requests and responses read, including their headers.
+const MAX_HTTP_MESSAGE_SIZE: u64 = 1024 * 1024;
+/// Maximum amount of connections a `WebhookListener` handles at the same time.
+const MAX_CONNECTIONS: usize = 64;
+
+/// A Bot API [`Update`](https://core.telegram.org/bots/api#update).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiUpdate {
+    pub update_id: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<BotApiMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_message: Option<BotApiMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_post: Option<BotApiMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_channel_post: Option<BotApiMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_query: Option<BotApiCallbackQuery>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_query: Option<BotApiInlineQuery>,
+}
+
+/// A Bot API [`Message`](https://core.telegram.org/bots/api#message).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiMessage {
+    pub message_id: i32,
+    /// The date of the message, missing for messages which are only referenced, such as the
+    /// message being replied to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<i64>,
+    pub chat: BotApiChat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<BotApiUser>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_chat: Option<BotApiChat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<BotApiMessageEntity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub caption_entities: Vec<BotApiMessageEntity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to_message: Option<Box<BotApiMessage>>,
+}
+
+/// A Bot API [`MessageEntity`](https://core.telegram.org/bots/api#messageentity).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiMessageEntity {
+    /// One of the entity types of the Bot API, such as `bold`, `text_link` or `pre`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Offset in UTF-16 code units to the start of the entity.
+    pub offset: i32,
+    /// Length of the entity in UTF-16 code units.
+    pub length: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<BotApiUser>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_emoji_id: Option<String>,
+}
+
+/// A Bot API [`User`](https://core.telegram.org/bots/api#user).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiUser {
+    pub id: i64,
+    pub is_bot: bool,
+    pub first_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+/// A Bot API [`Chat`](https://core.telegram.org/bots/api#chat).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiChat {
+    pub id: i64,
+    /// One of `private`, `group`, `supergroup` or `channel`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+}
+
+/// A Bot API [`CallbackQuery`](https://core.telegram.org/bots/api#callbackquery).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiCallbackQuery {
+    pub id: String,
+    pub from: BotApiUser,
+    /// The message with the button, unless the query comes from an inline message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<BotApiMessage>,
+    pub chat_instance: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+/// A Bot API [`InlineQuery`](https://core.telegram.org/bots/api#inlinequery).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotApiInlineQuery {
+    pub id: String,
+    pub from: BotApiUser,
+    pub query: String,
+    pub offset: String,
+}
+
+/// An HTTP endpoint to which updates are delivered as Bot API JSON.
+///
+/// The connection to the endpoint is kept open between updates when the endpoint allows it.
+///
+/// Updates are numbered sequentially, starting from the current time in microseconds, so that
+/// their `update_id` keeps growing when the process is restarted.
+#[derive(Debug)]
+pub struct Webhook {
+    host: String,
+    path: String,
+    next_update_id: AtomicI64,
+    connection: AsyncMutex<Option<BufReader<TcpStream>>>,
+}
+
+/// A local HTTP endpoint on which Bot API updates are received, such as those sent by the
+/// webhooks of the Bot API or by a [`Webhook`].
+///
+/// Each connection is handled by its own task, so slow clients don't hold back the rest, and
+/// closed after its update. The connections are accepted in the background until the listener
+/// is dropped.
+#[derive(Debug)]
+pub struct WebhookListener {
+    local_addr: SocketAddr,
+    updates: AsyncMutex<mpsc::Receiver<io::Result<BotApiUpdate>>>,
+    accept_task: JoinHandle<()>,
+}
+
+/// A parsed HTTP request or response.
+#[derive(Debug)]
+struct HttpMessage {
+    start_line: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    // Whether the end of the body was known without reading until the connection closed.
+    delimited: bool,
+}
+
+impl Webhook {
+    /// Create a webhook for an `http://host[:port][/path]` URL.
+    ///
+    /// Returns `None` if the URL is not a plain `http` URL.
+    pub fn new(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("http://")?;
+        let (host, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        let host = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{host}:80")
+        };
+
+        Some(Self {
+            host,
+            path: path.to_string(),
+            next_update_id: AtomicI64::new(initial_update_id()),
+            connection: AsyncMutex::new(None),
+        })
+    }
+
+    /// Convert the update and POST it to the endpoint.
+    ///
+    /// Returns `false` without sending anything if the update has no Bot API equivalent.
+    /// Responses with a status code other than `2xx` are returned as an error, as are
+    /// endpoints which take too long to respond.
+    pub async fn send(&self, update: &Update) -> io::Result<bool> {
+        let update_id = self.next_update_id.fetch_add(1, Ordering::Relaxed);
+        let Some(update) = to_bot_api(update, update_id) else {
+            return Ok(false);
+        };
+        self.post(&serde_json::to_vec(&update)?).await?;
+        Ok(true)
+    }
+
+    async fn post(&self, body: &[u8]) -> io::Result<()> {
+        let mut connection = self.connection.lock().await;
+        let reused = connection.is_some();
+        match self.post_on(&mut connection, body).await {
+            // The endpoint may have closed the idle connection, so try again with a new one.
+            Err(e) if reused && is_closed(&e) => self.post_on(&mut connection, body).await,
+            result => result,
+        }
+    }
+
+    async fn post_on(
+        &self,
+        connection: &mut Option<BufReader<TcpStream>>,
+        body: &[u8],
+    ) -> io::Result<()> {
+        let stream = match connection {
+            Some(stream) => stream,
+            None => {
+                let stream = with_timeout(CONNECT_TIMEOUT, TcpStream::connect(&self.host)).await?;
+                connection.insert(BufReader::new(stream))
+            }
+        };
+
+        let head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            self.path,
+            self.host,
+            body.len(),
+        );
+        let result = async {
+            with_timeout(WRITE_TIMEOUT, async {
+                stream.get_mut().write_all(head.as_bytes()).await?;
+                stream.get_mut().write_all(body).await
+            })
+            .await?;
+            with_timeout(READ_TIMEOUT, read_message(&mut *stream, true)).await
+        }
+        .await;
+
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                *connection = None;
+                return Err(e);
+            }
+        };
+        if !response.keep_alive() {
+            *connection = None;
+        }
+
+        match parse_status(&response.start_line) {
+            Some(status) if (200..300).contains(&status) => Ok(()),
+            Some(status) => Err(io::Error::other(format!(
+                "webhook responded with status {status}"
+            ))),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "webhook responded with invalid HTTP",
+            )),
+        }
+    }
+}
+
+impl WebhookListener {
+    /// Listen on the given address for updates POSTed to the given path.
+    ///
+    /// Must be called from within a Tokio runtime, which the connections are handled on.
+    pub async fn bind<A: ToSocketAddrs>(addr: A, path: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::channel(MAX_CONNECTIONS);
+        let accept_task = tokio::spawn(accept_connections(listener, Arc::from(path), tx));
+        Ok(Self {
+            local_addr,
+            updates: AsyncMutex::new(rx),
+            accept_task,
+        })
+    }
+
+    /// The address the listener is bound to, useful when binding to port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    /// Wait for the next update.
+    ///
+    /// Requests which are not a valid update, or which take too long to arrive, are answered
+    /// with an error status (if possible) and skipped. Errors accepting connections are
+    /// returned, after which the listener keeps accepting new ones.
+    pub async fn next(&self) -> io::Result<BotApiUpdate> {
+        self.updates
+            .lock()
+            .await
+            .recv()
+            .await
+            .unwrap_or_else(|| Err(io::Error::other("webhook listener stopped")))
+    }
+}
+
+impl Drop for WebhookListener {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+/// Accept connections and receive an update from each in its own task, until the listener
+/// is dropped. At most `MAX_CONNECTIONS` are handled at the same time.
+async fn accept_connections(
+    listener: TcpListener,
+    path: Arc<str>,
+    tx: mpsc::Sender<io::Result<BotApiUpdate>>,
+) {
+    let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    loop {
+        // The semaphore is never closed.
+        let permit = Arc::clone(&connections).acquire_owned().await.unwrap();
+        let (stream, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                if tx.send(Err(e)).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let path = Arc::clone(&path);
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            match receive(&path, BufReader::new(stream)).await {
+                Ok(update) => drop(tx.send(Ok(update)).await),
+                Err(e) => log::debug!("skipped webhook request from {addr}: {e}"),
+            }
+        });
+    }
+}
+
+async fn receive(path: &str, mut stream: BufReader<TcpStream>) -> io::Result<BotApiUpdate> {
+    let request = with_timeout(READ_TIMEOUT, read_message(&mut stream, false)).await?;
+    let mut parts = request.start_line.split_whitespace();
+    let (status, result) = match (parts.next(), parts.next()) {
+        (Some("POST"), Some(request_path)) if request_path == path => {
+            match serde_json::from_slice(&request.body) {
+                Ok(update) => ("200 OK", Ok(update)),
+                Err(e) => ("400 Bad Request", Err(e.into())),
+            }
+        }
+        _ => (
+            "404 Not Found",
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("unexpected request: {}", request.start_line),
+            )),
+        ),
+    };
+
+    let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    with_timeout(
+        WRITE_TIMEOUT,
+        stream.get_mut().write_all(response.as_bytes()),
+    )
+    .await?;
+    result
+}
+
+impl HttpMessage {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the connection can be used for another request after this message.
+    fn keep_alive(&self) -> bool {
+        let connection = self.header("Connection").unwrap_or_default();
+        self.delimited
+            && if self.start_line.starts_with("HTTP/1.0") {
+                connection.eq_ignore_ascii_case("keep-alive")
+            } else {
+                !connection.eq_ignore_ascii_case("close")
+            }
+    }
+}
+
+/// The identifier of the first update sent by a new [`Webhook`].
+///
+/// Receivers may ignore updates whose identifier is not larger than the last one they saw, so
+/// identifiers are based on the current time in microseconds to keep growing across restarts.
+fn initial_update_id() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(1, |elapsed| elapsed.as_micros() as i64)
+}
+
+/// Run the I/O operation, failing with `TimedOut` if it takes longer than the duration.
+async fn with_timeout<T>(
+    duration: Duration,
+    future: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    tokio::time::timeout(duration, future)
+        .await
+        .unwrap_or_else(|_| {
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "webhook connection timed out",
+            ))
+        })
+}
+
+/// Whether the error means the connection was closed before the request could be delivered.
+fn is_closed(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "HTTP message is too large")
+}
+
+/// Read an HTTP message, up to `MAX_HTTP_MESSAGE_SIZE` bytes.
+///
+/// Responses without a length are read until the connection is closed, while requests
+/// without one have no body.
+async fn read_message<R: AsyncBufRead + Unpin>(
+    reader: R,
+    response: bool,
+) -> io::Result<HttpMessage> {
+    let mut reader = reader.take(MAX_HTTP_MESSAGE_SIZE);
+
+    let start_line = read_line(&mut reader).await?;
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(&mut reader).await?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut message = HttpMessage {
+        start_line,
+        headers,
+        body: Vec::new(),
+        delimited: true,
+    };
+    let chunked = message
+        .header("Transfer-Encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"));
+    let length = message.header("Content-Length").map(str::parse::<u64>);
+
+    if chunked {
+        loop {
+            let line = read_line(&mut reader).await?;
+            let size = line.split(';').next().unwrap_or_default().trim();
+            let size = u64::from_str_radix(size, 16)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if size == 0 {
+                while !read_line(&mut reader).await?.is_empty() {}
+                break;
+            }
+            read_body(&mut reader, &mut message.body, size).await?;
+            read_line(&mut reader).await?;
+        }
+    } else if let Some(length) = length {
+        let length = length.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        read_body(&mut reader, &mut message.body, length).await?;
+    } else if response {
+        reader.read_to_end(&mut message.body).await?;
+        if reader.limit() == 0 {
+            return Err(too_large());
+        }
+        message.delimited = false;
+    }
+
+    Ok(message)
+}
+
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    if !line.ends_with('\n') {
+        return Err(too_large());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn read_body<R: AsyncBufRead + Unpin>(
+    reader: &mut tokio::io::Take<R>,
+    body: &mut Vec<u8>,
+    length: u64,
+) -> io::Result<()> {
+    if length > reader.limit() {
+        return Err(too_large());
+    }
+    let start = body.len();
+    body.resize(start + length as usize, 0);
+    reader.read_exact(&mut body[start..]).await.map(drop)
+}
+
+/// Convert an update into a Bot API `Update` with the given `update_id`.
+///
+/// Returns `None` if the update has no Bot API equivalent.
+pub fn to_bot_api(update: &Update, update_id: i64) -> Option<BotApiUpdate> {
+    let mut result = BotApiUpdate {
+        update_id,
+        ..Default::default()
+    };
+    match update {
+        Update::NewMessage(message) if message.post() => {
+            result.channel_post = Some(message_to_bot_api(message))
+        }
+        Update::NewMessage(message) => result.message = Some(message_to_bot_api(message)),
+        Update::MessageEdited(message) if message.post() => {
+            result.edited_channel_post = Some(message_to_bot_api(message))
+        }
+        Update::MessageEdited(message) => result.edited_message = Some(message_to_bot_api(message)),
+        Update::CallbackQuery(query) => {
+            result.callback_query = Some(callback_query_to_bot_api(query)?)
+        }
+        Update::InlineQuery(query) => {
+            result.inline_query = Some(BotApiInlineQuery {
+                id: query.query_id().to_string(),
+                from: user_to_bot_api(query.sender()),
+                query: query.text().to_string(),
+                offset: query.offset().to_string(),
+            })
+        }
+        _ => return None,
+    }
+    Some(result)
+}
+
+/// A message which is only referenced by its identifier, without any of its contents.
+fn message_reference(message_id: i32, chat: &Chat) -> BotApiMessage {
+    BotApiMessage {
+        message_id,
+        chat: chat_to_bot_api(chat),
+        ..Default::default()
+    }
+}
+
+fn message_to_bot_api(message: &Message) -> BotApiMessage {
+    let chat = message.chat();
+    let (from, sender_chat) = match message.sender() {
+        Some(Chat::User(user)) => (Some(user_to_bot_api(&user)), None),
+        Some(chat) => (None, Some(chat_to_bot_api(&chat))),
+        None => (None, None),
+    };
+    let text = (!message.text().is_empty()).then(|| message.text().to_string());
+    let entities = message
+        .fmt_entities()
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| entity_to_bot_api(entity, &message.chats))
+        .collect();
+    let (text, entities, caption, caption_entities) = match message.raw_media() {
+        Some(media) if has_caption(media) => (None, Vec::new(), text, entities),
+        _ => (text, entities, None, Vec::new()),
+    };
+
+    BotApiMessage {
+        message_id: message.id(),
+        date: Some(message.date().timestamp()),
+        chat: chat_to_bot_api(&chat),
+        from,
+        sender_chat,
+        edit_date: message.edit_date().map(|date| date.timestamp()),
+        text,
+        entities,
+        caption,
+        caption_entities,
+        reply_to_message: message
+            .reply_to_message_id()
+            .map(|id| Box::new(message_reference(id, &chat))),
+    }
+}
+
+/// Convert a formatting entity, or return `None` if it has no Bot API equivalent (such as bank
+/// cards), or if it mentions a user whose information is not available.
+fn entity_to_bot_api(
+    entity: &tl::enums::MessageEntity,
+    chats: &ChatMap,
+) -> Option<BotApiMessageEntity> {
+    use tl::enums::MessageEntity as E;
+
+    let simple = |kind: &str, offset, length| BotApiMessageEntity {
+        kind: kind.to_string(),
+        offset,
+        length,
+        ..Default::default()
+    };
+    Some(match entity {
+        E::Mention(e) => simple("mention", e.offset, e.length),
+        E::Hashtag(e) => simple("hashtag", e.offset, e.length),
+        E::Cashtag(e) => simple("cashtag", e.offset, e.length),
+        E::BotCommand(e) => simple("bot_command", e.offset, e.length),
+        E::Url(e) => simple("url", e.offset, e.length),
+        E::Email(e) => simple("email", e.offset, e.length),
+        E::Phone(e) => simple("phone_number", e.offset, e.length),
+        E::Bold(e) => simple("bold", e.offset, e.length),
+        E::Italic(e) => simple("italic", e.offset, e.length),
+        E::Underline(e) => simple("underline", e.offset, e.length),
+        E::Strike(e) => simple("strikethrough", e.offset, e.length),
+        E::Spoiler(e) => simple("spoiler", e.offset, e.length),
+        E::Code(e) => simple("code", e.offset, e.length),
+        E::Blockquote(e) if e.collapsed => simple("expandable_blockquote", e.offset, e.length),
+        E::Blockquote(e) => simple("blockquote", e.offset, e.length),
+        E::Pre(e) => BotApiMessageEntity {
+            language: (!e.language.is_empty()).then(|| e.language.clone()),
+            ..simple("pre", e.offset, e.length)
+        },
+        E::TextUrl(e) => BotApiMessageEntity {
+            url: Some(e.url.clone()),
+            ..simple("text_link", e.offset, e.length)
+        },
+        E::MentionName(e) => {
+            let peer = tl::types::PeerUser { user_id: e.user_id }.into();
+            let Some(Chat::User(user)) = chats.get(&peer) else {
+                return None;
+            };
+            BotApiMessageEntity {
+                user: Some(user_to_bot_api(user)),
+                ..simple("text_mention", e.offset, e.length)
+            }
+        }
+        E::CustomEmoji(e) => BotApiMessageEntity {
+            custom_emoji_id: Some(e.document_id.to_string()),
+            ..simple("custom_emoji", e.offset, e.length)
+        },
+        E::Unknown(_) | E::BankCard(_) | E::InputMessageEntityMentionName(_) => return None,
+    })
+}
+
+/// Whether the text of a message with this media is a `caption` in the Bot API.
+///
+/// Only photos and documents (which include audio, videos, animations and voice messages) have
+/// captions, except for stickers and round videos which have no text at all.
+fn has_caption(media: &tl::enums::MessageMedia) -> bool {
+    match media {
+        tl::enums::MessageMedia::Photo(_) => true,
+        tl::enums::MessageMedia::Document(media) => {
+            let sticker = match &media.document {
+                Some(tl::enums::Document::Document(document)) => document
+                    .attributes
+                    .iter()
+                    .any(|attr| matches!(attr, tl::enums::DocumentAttribute::Sticker(_))),
+                _ => false,
+            };
+            !media.round && !sticker
+        }
+        _ => false,
+    }
+}
+
+fn callback_query_to_bot_api(query: &CallbackQuery) -> Option<BotApiCallbackQuery> {
+    let Chat::User(user) = query.sender() else {
+        return None;
+    };
+    Some(BotApiCallbackQuery {
+        id: query.raw.query_id.to_string(),
+        from: user_to_bot_api(user),
+        message: (!query.is_from_inline())
+            .then(|| message_reference(query.raw.msg_id, query.chat())),
+        chat_instance: query.raw.chat_instance.to_string(),
+        data: query
+            .raw
+            .data
+            .as_deref()
+            .map(|data| String::from_utf8_lossy(data).into_owned()),
+    })
+}
+
+fn user_to_bot_api(user: &User) -> BotApiUser {
+    BotApiUser {
+        id: user.id(),
+        is_bot: user.is_bot(),
+        first_name: user.first_name().unwrap_or_default().to_string(),
+        last_name: user.last_name().map(str::to_string),
+        username: user.username().map(str::to_string),
+        language_code: user.lang_code().map(str::to_string),
+    }
+}
+
+fn chat_to_bot_api(chat: &Chat) -> BotApiChat {
+    let packed = chat.pack();
+    let mut result = BotApiChat {
+        id: bot_api_id(&packed),
+        kind: bot_api_type(packed.ty).to_string(),
+        username: chat.username().map(str::to_string),
+        ..Default::default()
+    };
+    match chat {
+        Chat::User(user) => {
+            result.first_name = Some(user.first_name().unwrap_or_default().to_string());
+            result.last_name = user.last_name().map(str::to_string);
+        }
+        _ => result.title = Some(chat.name().unwrap_or_default().to_string()),
+    }
+    result
+}
+
+/// The identifier the Bot API uses for the chat, which encodes its type in the sign and prefix.
+fn bot_api_id(packed: &PackedChat) -> i64 {
+    match packed.ty {
+        PackedType::User | PackedType::Bot => packed.id,
+        PackedType::Chat => -packed.id,
+        PackedType::Megagroup | PackedType::Broadcast | PackedType::Gigagroup => {
+            -(CHANNEL_ID_OFFSET + packed.id)
+        }
+    }
+}
+
+fn bot_api_type(ty: PackedType) -> &'static str {
+    match ty {
+        PackedType::User | PackedType::Bot => "private",
+        PackedType::Chat => "group",
+        PackedType::Megagroup | PackedType::Gigagroup => "supergroup",
+        PackedType::Broadcast => "channel",
+    }
+}
+
+/// Parse the status code from the status line of an HTTP response.
+fn parse_status(line: &str) -> Option<u16> {
+    let mut parts = line.split_whitespace();
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_webhook_url() {
+        let webhook = Webhook::new("http://localhost:8080/bot/updates").unwrap();
+        assert_eq!(webhook.host, "localhost:8080");
+        assert_eq!(webhook.path, "/bot/updates");
+
+        let webhook = Webhook::new("http://127.0.0.1").unwrap();
+        assert_eq!(webhook.host, "127.0.0.1:80");
+        assert_eq!(webhook.path, "/");
+
+        assert!(Webhook::new("https://example.com/").is_none());
+        assert!(Webhook::new("http:///path").is_none());
+    }
+
+    #[test]
+    fn bot_api_ids() {
+        let packed = |ty, id| PackedChat {
+            ty,
+            id,
+            access_hash: None,
+        };
+        assert_eq!(bot_api_id(&packed(PackedType::User, 123)), 123);
+        assert_eq!(bot_api_id(&packed(PackedType::Chat, 123)), -123);
+        assert_eq!(
+            bot_api_id(&packed(PackedType::Broadcast, 123)),
+            -1_000_000_000_123
+        );
+        assert_eq!(bot_api_type(PackedType::Gigagroup), "supergroup");
+    }
+
+    #[test]
+    fn convert_entities() {
+        let chats = ChatMap::new(Vec::new(), Vec::new());
+        let convert = |entity: tl::enums::MessageEntity| entity_to_bot_api(&entity, &chats);
+
+        let bold = convert(
+            tl::types::MessageEntityBold {
+                offset: 1,
+                length: 2,
+            }
+            .into(),
+        )
+        .unwrap();
+        assert_eq!(
+            (bold.kind.as_str(), bold.offset, bold.length),
+            ("bold", 1, 2)
+        );
+
+        let link = convert(
+            tl::types::MessageEntityTextUrl {
+                offset: 0,
+                length: 4,
+                url: "https://example.com".to_string(),
+            }
+            .into(),
+        )
+        .unwrap();
+        assert_eq!(link.kind, "text_link");
+        assert_eq!(link.url.as_deref(), Some("https://example.com"));
+
+        let pre = convert(
+            tl::types::MessageEntityPre {
+                offset: 0,
+                length: 4,
+                language: String::new(),
+            }
+            .into(),
+        )
+        .unwrap();
+        assert_eq!(pre.kind, "pre");
+        assert_eq!(pre.language, None);
+
+        // The mentioned user is not known, so there's nothing to put in the entity.
+        let mention = tl::types::MessageEntityMentionName {
+            offset: 0,
+            length: 4,
+            user_id: 123,
+        };
+        assert_eq!(convert(mention.into()), None);
+        assert_eq!(
+            convert(
+                tl::types::MessageEntityBankCard {
+                    offset: 0,
+                    length: 4
+                }
+                .into()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn update_ids_grow_across_restarts() {
+        let first = initial_update_id();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(initial_update_id() > first);
+    }
+
+    #[test]
+    fn caption_media() {
+        let document = |round, attributes| {
+            tl::types::MessageMediaDocument {
+                nopremium: false,
+                spoiler: false,
+                video: round,
+                round,
+                voice: false,
+                document: Some(
+                    tl::types::Document {
+                        id: 1,
+                        access_hash: 2,
+                        file_reference: Vec::new(),
+                        date: 0,
+                        mime_type: "video/mp4".to_string(),
+                        size: 0,
+                        thumbs: None,
+                        video_thumbs: None,
+                        dc_id: 2,
+                        attributes,
+                    }
+                    .into(),
+                ),
+                alt_documents: None,
+                video_cover: None,
+                video_timestamp: None,
+                ttl_seconds: None,
+            }
+            .into()
+        };
+        let sticker = tl::types::DocumentAttributeSticker {
+            mask: false,
+            alt: String::new(),
+            stickerset: tl::enums::InputStickerSet::Empty,
+            mask_coords: None,
+        }
+        .into();
+
+        assert!(has_caption(
+            &tl::types::MessageMediaPhoto {
+                spoiler: false,
+                photo: None,
+                ttl_seconds: None,
+            }
+            .into()
+        ));
+        assert!(has_caption(&document(false, Vec::new())));
+        assert!(!has_caption(&document(true, Vec::new())));
+        assert!(!has_caption(&document(false, vec![sticker])));
+        assert!(!has_caption(
+            &tl::types::MessageMediaDice {
+                value: 6,
+                emoticon: "🎲".to_string(),
+            }
+            .into()
+        ));
+        assert!(!has_caption(
+            &tl::types::MessageMediaGeo {
+                geo: tl::enums::GeoPoint::Empty,
+            }
+            .into()
+        ));
+    }
+
+    #[test]
+    fn parse_status_line() {
+        assert_eq!(parse_status("HTTP/1.1 204 No Content"), Some(204));
+        assert_eq!(parse_status("HTTP/1.0 500"), Some(500));
+        assert_eq!(parse_status("garbage"), None);
+        assert_eq!(parse_status(""), None);
+    }
+
+    #[test]
+    fn read_http_messages() {
+        let read = |data: &'static [u8], response| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            runtime.block_on(read_message(data, response))
+        };
+
+        let message = read(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nokextra", true).unwrap();
+        assert_eq!(message.body, b"ok");
+        assert!(message.keep_alive());
+
+        let message = read(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nok\r\n1\r\n!\r\n0\r\n\r\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(message.body, b"ok!");
+
+        let message = read(b"HTTP/1.0 200 OK\r\n\r\nuntil closed", true).unwrap();
+        assert_eq!(message.body, b"until closed");
+        assert!(!message.keep_alive());
+
+        let message = read(b"POST / HTTP/1.1\r\nConnection: close\r\n\r\n", false).unwrap();
+        assert!(message.body.is_empty());
+        assert!(!message.keep_alive());
+
+        let error = read(b"HTTP/1.1 200 OK\r\nContent-Length: 99999999\r\n\r\n", true);
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn deliver_update() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let listener = WebhookListener::bind("127.0.0.1:0", "/bot").await.unwrap();
+            let webhook =
+                Webhook::new(&format!("http://{}/bot", listener.local_addr().unwrap())).unwrap();
+            let update: BotApiUpdate = serde_json::from_str(
+                r#"{
+                    "update_id": 7,
+                    "message": {
+                        "message_id": 1,
+                        "date": 1700000000,
+                        "chat": {"id": 5, "type": "private", "first_name": "Ferris"},
+                        "text": "hi",
+                        "entities": []
+                    }
+                }"#,
+            )
+            .unwrap();
+
+            let body = serde_json::to_vec(&update).unwrap();
+            let (sent, received) =
+                futures_util::future::join(webhook.post(&body), listener.next()).await;
+            sent.unwrap();
+            assert_eq!(received.unwrap(), update);
+        });
+    }
+
+    #[test]
+    fn idle_connection_does_not_block() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let listener = WebhookListener::bind("127.0.0.1:0", "/bot").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let _idle = TcpStream::connect(addr).await.unwrap();
+
+            let webhook = Webhook::new(&format!("http://{addr}/bot")).unwrap();
+            let body = br#"{"update_id": 1}"#;
+            let (sent, received) = futures_util::future::join(
+                webhook.post(body),
+                with_timeout(Duration::from_secs(5), listener.next()),
+            )
+            .await;
+            sent.unwrap();
+            assert_eq!(received.unwrap().update_id, 1);
+        });
+    }
+}
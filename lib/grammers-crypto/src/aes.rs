@@ -84,3 +84,32 @@ pub fn ige_decrypt(ciphertext: &[u8], key: &[u8; 32], iv: &[u8; 32]) -> Vec<u8>
 
     plaintext
 }
+
+/// Decrypt the input ciphertext using the AES-CBC mode.
+pub fn cbc_decrypt(ciphertext: &[u8], key: &[u8; 32], iv: &[u8; 16]) -> Vec<u8> {
+    let size = ciphertext.len();
+    assert!(size.is_multiple_of(16));
+    let mut plaintext = vec![0; size];
+
+    let key = GenericArray::from_slice(key);
+    let cipher = aes::Aes256::new(key);
+    let mut iv = *iv;
+
+    for (ciphertext_block, plaintext_block) in ciphertext.chunks(16).zip(plaintext.chunks_mut(16)) {
+        // block = decrypt(block);
+        plaintext_block.copy_from_slice(ciphertext_block);
+        let plaintext_block = GenericArray::from_mut_slice(plaintext_block);
+        cipher.decrypt_block(plaintext_block);
+
+        // block = block XOR iv
+        plaintext_block
+            .iter_mut()
+            .zip(iv.as_ref())
+            .for_each(|(a, b)| *a ^= b);
+
+        // adjust iv
+        iv.copy_from_slice(ciphertext_block);
+    }
+
+    plaintext
+}
@@ -0,0 +1,60 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Functions used to download files from Telegram's CDN data centers.
+use aes::cipher::{KeyIvInit, StreamCipher, generic_array::GenericArray};
+
+/// Check whether a part of a file downloaded from a CDN matches the SHA-256 hash given by
+/// Telegram for it.
+pub fn check_hash(data: &[u8], hash: &[u8]) -> bool {
+    crate::sha256!(data).as_slice() == hash
+}
+
+/// Decrypt, in place, the part of a file downloaded from a CDN at the given offset.
+///
+/// CDN files are encrypted with AES-256-CTR, where the last 4 bytes of the IV are replaced by
+/// the offset divided by 16, in big-endian.
+pub fn decrypt(data: &mut [u8], key: &[u8; 32], iv: &[u8; 16], offset: i64) {
+    let mut iv = *iv;
+    iv[12..].copy_from_slice(&((offset / 16) as u32).to_be_bytes());
+    let mut cipher = ctr::Ctr128BE::<aes::Aes256>::new(
+        GenericArray::from_slice(key),
+        GenericArray::from_slice(&iv),
+    );
+    cipher.apply_keystream(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn check_known_hash() {
+        let hash =
+            hex::from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert!(check_hash(b"abc", &hash));
+        assert!(!check_hash(b"abd", &hash));
+        assert!(!check_hash(b"abc", &hash[..31]));
+    }
+
+    #[test]
+    fn decrypt_at_offset() {
+        let key = [1; 32];
+        let iv = [2; 16];
+        let mut whole = (0..64).collect::<Vec<u8>>();
+        decrypt(&mut whole, &key, &iv, 0);
+
+        let mut tail = (32..64).collect::<Vec<u8>>();
+        decrypt(&mut tail, &key, &iv, 32);
+        assert_eq!(tail, whole[32..]);
+
+        decrypt(&mut whole, &key, &iv, 0);
+        assert_eq!(whole, (0..64).collect::<Vec<u8>>());
+    }
+}
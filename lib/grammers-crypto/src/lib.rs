@@ -10,10 +10,12 @@
 
 pub mod aes;
 mod auth_key;
+pub mod cdn;
 pub mod deque_buffer;
 pub mod factorize;
 pub mod hex;
 pub mod obfuscated;
+pub mod passport;
 pub mod rsa;
 pub mod sha;
 pub mod two_factor_auth;
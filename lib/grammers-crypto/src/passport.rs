@@ -0,0 +1,69 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Functions used by bots to decrypt the data users share through Telegram Passport.
+//!
+//! The secret of the credentials is first decrypted with the private key of the bot (RSA-OAEP),
+//! and then [`decrypt_data`] is used for the credentials themselves and everything they unlock.
+//!
+//! See <https://core.telegram.org/passport#decrypting-data>.
+use crate::aes::cbc_decrypt;
+use sha2::{Digest, Sha512};
+
+/// Decrypt Passport data (the credentials, a field, or a file) given its secret and hash.
+///
+/// The hash is verified against the decrypted data, and the random padding is removed.
+/// Returns `None` if the secret or hash don't match the data.
+pub fn decrypt_data(data: &[u8], secret: &[u8], hash: &[u8]) -> Option<Vec<u8>> {
+    if data.is_empty() || !data.len().is_multiple_of(16) {
+        return None;
+    }
+
+    let secret_hash = Sha512::new()
+        .chain_update(secret)
+        .chain_update(hash)
+        .finalize();
+    let key = secret_hash[..32].try_into().unwrap();
+    let iv = secret_hash[32..48].try_into().unwrap();
+
+    let mut decrypted = cbc_decrypt(data, key, iv);
+    if crate::sha256!(&decrypted).as_slice() != hash {
+        return None;
+    }
+
+    let padding = decrypted[0] as usize;
+    if !(32..=255).contains(&padding) || padding > decrypted.len() {
+        return None;
+    }
+    decrypted.drain(..padding);
+    Some(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn decrypt_known_data() {
+        let data = hex::from_hex(
+            "b4cc36d6b710599a5eb1188750011e9037ab1f2f2fe6e31de3acbab2a2eba1e99775654764c009f9659ea98253ac3d78818ac15a3c6532495e4e396875624e77",
+        );
+        let hash =
+            hex::from_hex("1064fc916e34c2af4d7ee8aee41b633c535c2461300c60a4c9354b6e5afad156");
+        let secret = (0..32).collect::<Vec<u8>>();
+
+        assert_eq!(
+            decrypt_data(&data, &secret, &hash).as_deref(),
+            Some(br#"{"first_name":"Ferris"}"#.as_slice())
+        );
+        assert!(decrypt_data(&data, &secret[1..], &hash).is_none());
+        assert!(decrypt_data(&data[16..], &secret, &hash).is_none());
+        assert!(decrypt_data(&data[1..], &secret, &hash).is_none());
+    }
+}
@@ -7,7 +7,7 @@
 // except according to those terms.
 use num_bigint::BigUint;
 
-use crate::{aes::ige_encrypt, sha256};
+use crate::{aes::ige_encrypt, sha1, sha256};
 
 /// RSA key.
 pub struct Key {
@@ -22,6 +22,100 @@ impl Key {
             e: BigUint::parse_bytes(e.as_bytes(), 10)?,
         })
     }
+
+    /// Parse a PEM-encoded `RSA PUBLIC KEY`, such as the keys Telegram provides for its CDN
+    /// data centers.
+    pub fn from_pem(pem: &str) -> Option<Self> {
+        let encoded = pem
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with("-----"))
+            .collect::<String>();
+        let der = decode_base64(&encoded)?;
+
+        // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+        let (0x30, sequence, _) = read_der(&der)? else {
+            return None;
+        };
+        let (0x02, n, rest) = read_der(sequence)? else {
+            return None;
+        };
+        let (0x02, e, _) = read_der(rest)? else {
+            return None;
+        };
+
+        Some(Self {
+            n: BigUint::from_bytes_be(n),
+            e: BigUint::from_bytes_be(e),
+        })
+    }
+
+    /// Calculate the fingerprint Telegram uses to refer to this key.
+    ///
+    /// The fingerprint is the lower 64 bits of the SHA-1 of the serialized modulus and exponent.
+    pub fn fingerprint(&self) -> i64 {
+        let mut buffer = Vec::new();
+        serialize_bytes(&mut buffer, &self.n.to_bytes_be());
+        serialize_bytes(&mut buffer, &self.e.to_bytes_be());
+        let sha = sha1!(&buffer);
+        i64::from_le_bytes(sha[12..].try_into().unwrap())
+    }
+}
+
+/// Serialize the data as TL `bytes`, which is how Telegram hashes keys for their fingerprint.
+fn serialize_bytes(buffer: &mut Vec<u8>, data: &[u8]) {
+    let len = if data.len() <= 253 {
+        buffer.push(data.len() as u8);
+        data.len() + 1
+    } else {
+        buffer.push(254);
+        buffer.extend(&(data.len() as u32).to_le_bytes()[..3]);
+        data.len() + 4
+    };
+    buffer.extend(data);
+    buffer.extend(std::iter::repeat_n(0, (4 - len % 4) % 4));
+}
+
+/// Read a DER value, returning its tag, contents, and the remaining data.
+fn read_der(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, data) = data.split_first()?;
+    let (&len, data) = data.split_first()?;
+    let (len, data) = if len & 0x80 == 0 {
+        (len as usize, data)
+    } else {
+        let (len, data) = data.split_at_checked((len & 0x7f) as usize)?;
+        if len.is_empty() || len.len() > 4 {
+            return None;
+        }
+        let len = len.iter().fold(0, |len, &b| (len << 8) | b as usize);
+        (len, data)
+    };
+    let (contents, rest) = data.split_at_checked(len)?;
+    Some((tag, contents, rest))
+}
+
+/// Decode standard base64, with optional padding.
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in encoded.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((acc >> bits) as u8);
+        }
+    }
+    Some(result)
 }
 
 /// Increment data by 1 when interpreted as a big-endian big int.
@@ -119,6 +213,24 @@ mod tests {
     use super::*;
     use crate::hex;
 
+    #[test]
+    fn test_key_from_pem() {
+        let key = Key::from_pem(
+            "-----BEGIN RSA PUBLIC KEY-----
+MIIBCgKCAQEA6LszBcC1LGzyr992NzE0ieY+BSaOW622Aa9Bd4ZHLl+TuFQ4lo4g
+5nKaMBwK/BIb9xUfg0Q29/2mgIR6Zr9krM7HjuIcCzFvDtr+L0GQjae9H0pRB2OO
+62cECs5HKhT5DZ98K33vmWiLowc621dQuwKWSQKjWf50XYFw42h21P2KXUGyp2y/
++aEyZ+uVgLLQbRA1dEjSDZ2iGRy12Mk5gpYc397aYp438fsJoHIgJ2lgMv5h7WY9
+t6N/byY9Nw9p21Og3AoXSL2q/2IJ1WRUhebgAdGVMlV1fkuOQoEzR7EdpqtQD9Cs
+5+bfo3Nhmcyvk5ftB0WkJ9z6bNZ7yxrP8wIDAQAB
+-----END RSA PUBLIC KEY-----",
+        )
+        .unwrap();
+        assert_eq!(key.e, BigUint::from(65537u32));
+        assert_eq!(key.fingerprint(), -3414540481677951611);
+        assert!(Key::from_pem("-----BEGIN RSA PUBLIC KEY-----\nMIIB\n").is_none());
+    }
+
     #[test]
     fn test_rsa_encryption() {
         let key = Key::new("25342889448840415564971689590713473206898847759084779052582026594546022463853940585885215951168491965708222649399180603818074200620463776135424884632162512403163793083921641631564740959529419359595852941166848940585952337613333022396096584117954892216031229237302943701877588456738335398602461675225081791820393153757504952636234951323237820036543581047826906120927972487366805292115792231423684261262330394324750785450942589751755390156647751460719351439969059949569615302809050721500330239005077889855323917509948255722081644689442127297605422579707142646660768825302832201908302295573257427896031830742328565032949", "65537").unwrap();
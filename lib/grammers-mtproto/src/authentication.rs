@@ -186,6 +186,7 @@ impl From<tl::deserialize::Error> for Error {
 /// [`step2`]: fn.step2.html
 pub struct Step1 {
     nonce: [u8; 16],
+    keys: Vec<rsa::Key>,
 }
 
 /// The data generated by [`step2`], needed for [`step3`].
@@ -212,6 +213,12 @@ pub struct Step3 {
 
 /// The first step of the process to generate an authorization key.
 pub fn step1() -> Result<(Vec<u8>, Step1), Error> {
+    step1_with_keys(Vec::new())
+}
+
+/// Like [`step1`], but the server may also use any of the given RSA keys, besides the ones
+/// known by the library. This is needed to generate keys in CDN data centers.
+pub fn step1_with_keys(keys: Vec<rsa::Key>) -> Result<(Vec<u8>, Step1), Error> {
     let random_bytes = {
         let mut buffer = [0; 16];
         getrandom(&mut buffer).expect("failed to generate secure data for auth key");
@@ -222,7 +229,7 @@ pub fn step1() -> Result<(Vec<u8>, Step1), Error> {
         println!("r {}", hex::to_hex(&random_bytes));
     }
 
-    let res = do_step1(&random_bytes);
+    let res = do_step1(&random_bytes).map(|(request, data)| (request, Step1 { keys, ..data }));
     if TRACE_AUTH_GEN {
         if let Ok((x, _)) = &res {
             println!("> {}", hex::to_hex(x));
@@ -237,7 +244,10 @@ fn do_step1(random_bytes: &[u8; 16]) -> Result<(Vec<u8>, Step1), Error> {
     let nonce = *random_bytes;
     Ok((
         tl::functions::ReqPqMulti { nonce }.to_bytes(),
-        Step1 { nonce },
+        Step1 {
+            nonce,
+            keys: Vec::new(),
+        },
     ))
 }
 
@@ -272,7 +282,7 @@ fn do_step2(
     random_bytes: &[u8; 32 + 224],
 ) -> Result<(Vec<u8>, Step2), Error> {
     // Step 2. Validate the PQ response. Return `(p, q)` if it's valid.
-    let Step1 { nonce } = data;
+    let Step1 { nonce, keys } = data;
     let tl::enums::ResPq::Pq(res_pq) =
         <tl::functions::ReqPqMulti as RemoteCall>::Return::from_bytes(response)?;
 
@@ -334,22 +344,23 @@ fn do_step2(
     .to_bytes();
 
     // sha_digest + data + random_bytes
-    let fingerprint = match res_pq
-        .server_public_key_fingerprints
+    let fingerprints = &res_pq.server_public_key_fingerprints;
+    let (fingerprint, key) = match fingerprints
         .iter()
-        .cloned()
-        .find(|&fingerprint| key_for_fingerprint(fingerprint).is_some())
-    {
+        .find_map(|&fingerprint| key_for_fingerprint(fingerprint).map(|key| (fingerprint, key)))
+        .or_else(|| {
+            keys.into_iter()
+                .map(|key| (key.fingerprint(), key))
+                .find(|(fingerprint, _)| fingerprints.contains(fingerprint))
+        }) {
         Some(x) => x,
         None => {
             return Err(Error::UnknownFingerprints {
-                fingerprints: res_pq.server_public_key_fingerprints.clone(),
+                fingerprints: fingerprints.clone(),
             });
         }
     };
 
-    // Safe to unwrap because we found it just above
-    let key = key_for_fingerprint(fingerprint).unwrap();
     let ciphertext = rsa::encrypt_hashed(&pq_inner_data, &key, &random_bytes);
 
     Ok((
@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use super::{
-    Deserialization, DeserializationFailure, DeserializeError, Mtp, RpcResult, RpcResultError,
+    CompressionStats, Deserialization, DeserializationFailure, DeserializeError, Mtp, RpcResult,
+    RpcResultError,
 };
 use crate::utils::StackBuffer;
 use crate::{MsgId, manual_tl};
@@ -97,6 +98,9 @@ pub struct Encrypted {
     /// outgoing messages will never be compressed.
     compression_threshold: Option<usize>,
 
+    /// Statistics about the compression of outgoing messages.
+    compression_stats: CompressionStats,
+
     /// Temporary deserialization results.
     deserialization: Vec<Deserialization>,
 
@@ -144,6 +148,7 @@ impl Builder {
             last_msg_id: 0,
             pending_ack: vec![],
             compression_threshold: self.compression_threshold,
+            compression_stats: CompressionStats::default(),
             deserialization: Vec::new(),
             msg_count: 0,
         }
@@ -165,6 +170,27 @@ impl Encrypted {
         self.auth_key.to_bytes()
     }
 
+    /// Change the compression threshold for outgoing messages.
+    ///
+    /// Messages at least this large, in bytes, will be compressed if doing so makes them
+    /// smaller. With `None`, messages are never compressed.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    /// Statistics about the compression of the messages sent so far.
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.compression_stats
+    }
+
+    /// The current time offset from the server's time, in seconds.
+    ///
+    /// The offset is corrected automatically whenever the server reports that the time used to
+    /// generate message identifiers was too far off.
+    pub fn time_offset(&self) -> i32 {
+        self.time_offset
+    }
+
     /// Correct our time offset based on a known valid message ID.
     fn correct_time_offset(&mut self, msg_id: i64) {
         let now = SystemTime::now()
@@ -173,7 +199,12 @@ impl Encrypted {
             .as_secs() as i32;
 
         let correct = (msg_id >> 32) as i32;
-        self.time_offset = correct - now;
+        let time_offset = correct - now;
+        info!(
+            "correcting time offset from {}s to {}s",
+            self.time_offset, time_offset
+        );
+        self.time_offset = time_offset;
     }
 
     /// Generates a new unique message ID based on the current
@@ -1246,8 +1277,12 @@ impl Mtp for Encrypted {
         if let Some(threshold) = self.compression_threshold {
             if request.len() >= threshold {
                 compressed = manual_tl::GzipPacked::new(request).to_bytes();
+                self.compression_stats.attempted += 1;
                 if compressed.len() < request.len() {
                     body = &compressed;
+                    self.compression_stats.compressed += 1;
+                    self.compression_stats.original_bytes += request.len() as u64;
+                    self.compression_stats.compressed_bytes += compressed.len() as u64;
                 }
             }
         }
@@ -1496,4 +1531,27 @@ mod tests {
             assert!(buffer.as_ref().windows(4).any(|w| w == GZIP_PACKED_HEADER));
         }
     }
+
+    #[test]
+    fn ensure_compression_stats_are_tracked() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build()
+            .compression_threshold(Some(1024))
+            .finish(auth_key());
+
+        mtproto.push(&mut buffer, &[0; 512]);
+        assert_eq!(mtproto.compression_stats(), CompressionStats::default());
+
+        mtproto.push(&mut buffer, &[0; 4096]);
+        let stats = mtproto.compression_stats();
+        assert_eq!(stats.attempted, 1);
+        assert_eq!(stats.compressed, 1);
+        assert_eq!(stats.original_bytes, 4096);
+        assert!(stats.compressed_bytes < stats.original_bytes);
+        assert!(stats.ratio() < 1.0);
+
+        mtproto.set_compression_threshold(None);
+        mtproto.push(&mut buffer, &[0; 4096]);
+        assert_eq!(mtproto.compression_stats(), stats);
+    }
 }
@@ -45,6 +45,38 @@ pub struct BadMessage {
     pub code: i32,
 }
 
+/// Statistics about the compression of outgoing messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// How many messages were large enough to attempt compressing them.
+    pub attempted: u64,
+    /// How many messages were actually sent compressed, because compressing them saved space.
+    pub compressed: u64,
+    /// Size, in bytes, of the messages which were sent compressed, before compression.
+    pub original_bytes: u64,
+    /// Size, in bytes, of the messages which were sent compressed, after compression.
+    pub compressed_bytes: u64,
+}
+
+impl CompressionStats {
+    /// The average size of the compressed messages relative to their original size, between
+    /// `0.0` and `1.0`. Lower values mean better compression.
+    ///
+    /// Returns `1.0` if no messages were compressed.
+    pub fn ratio(&self) -> f64 {
+        if self.original_bytes == 0 {
+            1.0
+        } else {
+            self.compressed_bytes as f64 / self.original_bytes as f64
+        }
+    }
+
+    /// How many bytes were saved by compressing messages.
+    pub fn saved_bytes(&self) -> u64 {
+        self.original_bytes - self.compressed_bytes
+    }
+}
+
 pub struct DeserializationFailure {
     pub msg_id: MsgId,
     pub error: DeserializeError,
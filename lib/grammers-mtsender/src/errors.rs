@@ -9,6 +9,15 @@ use grammers_mtproto::{authentication, mtp, transport};
 use grammers_tl_types as tl;
 use std::fmt;
 use std::io;
+use std::time::Duration;
+
+/// Names of the errors whose value is how many seconds to wait before retrying.
+const WAIT_ERRORS: [&str; 4] = [
+    "FLOOD_WAIT",
+    "FLOOD_PREMIUM_WAIT",
+    "SLOWMODE_WAIT",
+    "TAKEOUT_INIT_DELAY",
+];
 
 #[derive(Debug)]
 pub enum ReadError {
@@ -156,6 +165,41 @@ impl RpcError {
         }
     }
 
+    /// How long to wait before retrying the request, if this error asks to wait.
+    ///
+    /// This is the case for `FLOOD_WAIT`, `FLOOD_PREMIUM_WAIT`, `SLOWMODE_WAIT` and
+    /// `TAKEOUT_INIT_DELAY` errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// let error = grammers_mtsender::RpcError {
+    ///     code: 420, name: "FLOOD_WAIT".to_string(), value: Some(31), caused_by: None };
+    ///
+    /// assert_eq!(error.retry_after(), Some(Duration::from_secs(31)));
+    /// ```
+    pub fn retry_after(&self) -> Option<Duration> {
+        if WAIT_ERRORS.contains(&self.name.as_str()) {
+            self.value
+                .map(|seconds| Duration::from_secs(seconds as u64))
+        } else {
+            None
+        }
+    }
+
+    /// How many Telegram Stars must be paid per message to send messages to the chat, if the
+    /// request failed because the chat only accepts paid messages.
+    ///
+    /// This is the case for `ALLOW_PAYMENT_REQUIRED` errors.
+    pub fn required_payment_stars(&self) -> Option<u32> {
+        if self.name == "ALLOW_PAYMENT_REQUIRED" {
+            self.value
+        } else {
+            None
+        }
+    }
+
     pub fn with_caused_by(mut self, constructor_id: u32) -> Self {
         self.caused_by = Some(constructor_id);
         self
@@ -236,6 +280,31 @@ impl InvocationError {
             _ => false,
         }
     }
+
+    /// How long to wait before retrying the request, if the error asks to wait.
+    ///
+    /// See [`RpcError::retry_after`] for details. If the error is not a RPC error, returns
+    /// `None`.
+    #[inline]
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Rpc(rpc) => rpc.retry_after(),
+            _ => None,
+        }
+    }
+
+    /// How many Telegram Stars must be paid per message to send messages to the chat, if the
+    /// request failed because the chat only accepts paid messages.
+    ///
+    /// See [`RpcError::required_payment_stars`] for details. If the error is not a RPC error,
+    /// returns `None`.
+    #[inline]
+    pub fn required_payment_stars(&self) -> Option<u32> {
+        match self {
+            Self::Rpc(rpc) => rpc.required_payment_stars(),
+            _ => None,
+        }
+    }
 }
 
 /// This error occurs when the process to generate an authorization key fails.
@@ -324,4 +393,44 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn check_rpc_error_retry_after() {
+        let retry_after = |code, message: &str| {
+            RpcError::from(tl::types::RpcError {
+                error_code: code,
+                error_message: message.into(),
+            })
+            .retry_after()
+        };
+
+        assert_eq!(
+            retry_after(420, "FLOOD_WAIT_31"),
+            Some(Duration::from_secs(31))
+        );
+        assert_eq!(
+            retry_after(420, "FLOOD_PREMIUM_WAIT_5"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            retry_after(420, "SLOWMODE_WAIT_60"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            retry_after(420, "TAKEOUT_INIT_DELAY_3600"),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(retry_after(500, "INTERDC_2_CALL_ERROR"), None);
+        assert_eq!(retry_after(400, "CHAT_INVALID"), None);
+    }
+
+    #[test]
+    fn check_rpc_error_required_payment_stars() {
+        let error = RpcError::from(tl::types::RpcError {
+            error_code: 403,
+            error_message: "ALLOW_PAYMENT_REQUIRED_250".into(),
+        });
+        assert_eq!(error.required_payment_stars(), Some(250));
+        assert_eq!(error.retry_after(), None);
+    }
 }
@@ -16,7 +16,7 @@ pub mod utils;
 pub use crate::reconnection::*;
 pub use errors::{AuthorizationError, InvocationError, ReadError, RpcError};
 use futures_util::future::{Either, pending, select};
-use grammers_crypto::DequeBuffer;
+use grammers_crypto::{DequeBuffer, rsa};
 use grammers_mtproto::mtp::{
     self, BadMessage, Deserialization, DeserializationFailure, Mtp, RpcResult, RpcResultError,
 };
@@ -705,6 +705,23 @@ impl<T: Transport> Sender<T, mtp::Encrypted> {
     pub fn auth_key(&self) -> [u8; 256] {
         self.mtp.auth_key()
     }
+
+    /// Change the threshold, in bytes, at which outgoing messages are compressed.
+    /// With `None`, messages are never compressed.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.mtp.set_compression_threshold(threshold);
+    }
+
+    /// Statistics about the compression of the messages sent so far.
+    pub fn compression_stats(&self) -> mtp::CompressionStats {
+        self.mtp.compression_stats()
+    }
+
+    /// The time offset from the server's time, in seconds, used when generating message
+    /// identifiers.
+    pub fn time_offset(&self) -> i32 {
+        self.mtp.time_offset()
+    }
 }
 
 pub async fn connect<T: Transport>(
@@ -716,12 +733,33 @@ pub async fn connect<T: Transport>(
     generate_auth_key(sender, enqueuer).await
 }
 
+/// Like [`connect`], but the authorization key may also be generated with any of the given RSA
+/// keys, such as the ones used by CDN data centers.
+pub async fn connect_with_keys<T: Transport>(
+    transport: T,
+    addr: ServerAddr,
+    rc_policy: &'static dyn ReconnectionPolicy,
+    keys: Vec<rsa::Key>,
+) -> Result<(Sender<T, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    let (sender, enqueuer) = Sender::connect(transport, mtp::Plain::new(), addr, rc_policy).await?;
+    generate_auth_key_with_keys(sender, enqueuer, keys).await
+}
+
 pub async fn generate_auth_key<T: Transport>(
+    sender: Sender<T, mtp::Plain>,
+    enqueuer: Enqueuer,
+) -> Result<(Sender<T, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    generate_auth_key_with_keys(sender, enqueuer, Vec::new()).await
+}
+
+/// Like [`generate_auth_key`], but the server may also use any of the given RSA keys.
+pub async fn generate_auth_key_with_keys<T: Transport>(
     mut sender: Sender<T, mtp::Plain>,
     enqueuer: Enqueuer,
+    keys: Vec<rsa::Key>,
 ) -> Result<(Sender<T, mtp::Encrypted>, Enqueuer), AuthorizationError> {
     info!("generating new authorization key...");
-    let (request, data) = authentication::step1()?;
+    let (request, data) = authentication::step1_with_keys(keys)?;
     debug!("gen auth key: sending step 1");
     let response = sender.send(request).await?;
     debug!("gen auth key: starting step 2");
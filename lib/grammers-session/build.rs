@@ -24,6 +24,8 @@ fn main() -> std::io::Result<()> {
     };
 
     // Using boxed variants in the definitions so that deserialization fails if any constructor ID changes.
+    // The session keeps its original constructor ID, as new fields are optional and older
+    // sessions can still be loaded.
     let definitions = parse_tl_file(
         r#"
         dataCenter flags:# id:int ipv4:flags.0?int ipv6:flags.1?int128 port:int auth:flags.2?bytes = DataCenter;
@@ -31,7 +33,9 @@ fn main() -> std::io::Result<()> {
         user id:long dc:int bot:Bool = User;
         channelState channel_id:long pts:int = ChannelState;
         updateState pts:int qts:int date:int seq:int channels:Vector<ChannelState> = UpdateState;
-        session flags:# dcs:Vector<DataCenter> user:flags.0?User state:flags.1?UpdateState = Session;
+        resolvedUsername username:string chat:bytes date:int = ResolvedUsername;
+        cachedResult key:string hash:long data:bytes = CachedResult;
+        session#a73eb8ce flags:# dcs:Vector<DataCenter> user:flags.0?User state:flags.1?UpdateState usernames:flags.2?Vector<ResolvedUsername> results:flags.3?Vector<CachedResult> = Session;
         "#,
     )
     .map(Result::unwrap)
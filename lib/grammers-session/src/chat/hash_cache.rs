@@ -8,12 +8,17 @@
 use super::{PackedChat, PackedType};
 use grammers_tl_types as tl;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// In-memory chat cache, mapping peers to their respective access hashes.
+///
+/// Cloning the cache is cheap. The hashes are shared between the clones until one of them
+/// learns about a new or changed hash.
+#[derive(Clone)]
 pub struct ChatHashCache {
     // As far as I've observed, user, chat and channel IDs cannot collide,
     // but it will be an interesting moment if they ever do.
-    hash_map: HashMap<i64, (i64, PackedType)>,
+    hash_map: Arc<HashMap<i64, (i64, PackedType)>>,
     self_id: Option<i64>,
     self_bot: bool,
 }
@@ -21,7 +26,7 @@ pub struct ChatHashCache {
 impl ChatHashCache {
     pub fn new(self_user: Option<(i64, bool)>) -> Self {
         Self {
-            hash_map: HashMap::new(),
+            hash_map: Arc::new(HashMap::new()),
             self_id: self_user.map(|user| user.0),
             self_bot: self_user.map(|user| user.1).unwrap_or(false),
         }
@@ -53,6 +58,13 @@ impl ChatHashCache {
         })
     }
 
+    // Only copies the hashes if they're shared with a clone and the entry actually changes.
+    fn insert(&mut self, id: i64, hash: i64, ty: PackedType) {
+        if self.hash_map.get(&id) != Some(&(hash, ty)) {
+            Arc::make_mut(&mut self.hash_map).insert(id, (hash, ty));
+        }
+    }
+
     #[inline]
     fn has(&self, id: i64) -> bool {
         self.hash_map.contains_key(&id)
@@ -140,7 +152,7 @@ impl ChatHashCache {
                     } else {
                         PackedType::User
                     };
-                    self.hash_map.insert(u.id, (hash, ty));
+                    self.insert(u.id, hash, ty);
                 }
                 _ => success &= self.hash_map.contains_key(&u.id),
             },
@@ -157,7 +169,7 @@ impl ChatHashCache {
                     } else {
                         PackedType::Broadcast
                     };
-                    self.hash_map.insert(c.id, (hash, ty));
+                    self.insert(c.id, hash, ty);
                 }
                 _ => success &= self.hash_map.contains_key(&c.id),
             },
@@ -167,7 +179,7 @@ impl ChatHashCache {
                 } else {
                     PackedType::Broadcast
                 };
-                self.hash_map.insert(c.id, (c.access_hash, ty));
+                self.insert(c.id, c.access_hash, ty);
             }
         });
 
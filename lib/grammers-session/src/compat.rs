@@ -0,0 +1,533 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversions from and to the session formats used by other Telegram libraries.
+use crate::{Error, Session, enums};
+use base64::Engine as _;
+use base64::engine::DecodePaddingMode;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, URL_SAFE};
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+
+/// The version prefix of Telethon's string sessions.
+const TELETHON_STRING_VERSION: char = '1';
+
+/// Socket addresses to Telegram datacenters, where the index into this array
+/// represents the data center ID.
+///
+/// Needed for formats which only store the datacenter ID.
+const DC_ADDRESSES: [(Ipv4Addr, u16); 6] = [
+    (Ipv4Addr::new(0, 0, 0, 0), 0),
+    (Ipv4Addr::new(149, 154, 175, 53), 443),
+    (Ipv4Addr::new(149, 154, 167, 51), 443),
+    (Ipv4Addr::new(149, 154, 175, 100), 443),
+    (Ipv4Addr::new(149, 154, 167, 92), 443),
+    (Ipv4Addr::new(91, 108, 56, 190), 443),
+];
+
+/// Both libraries use URL-safe base64, but not all of them keep the padding.
+const URL_SAFE_LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+impl Session {
+    /// Import a Telethon string session, as produced by Telethon's `StringSession.save`.
+    ///
+    /// Telethon does not store the logged-in user, so the session will only contain the
+    /// authorization key to its datacenter. The user will be filled in once they sign in again,
+    /// or it can be set manually with [`Session::set_user`].
+    pub fn from_telethon_string(string: &str) -> Result<Self, Error> {
+        let data = string
+            .strip_prefix(TELETHON_STRING_VERSION)
+            .ok_or(Error::UnsupportedVersion)?;
+        let data = URL_SAFE_LENIENT
+            .decode(data.trim())
+            .map_err(|_| Error::MalformedData)?;
+
+        let (dc_id, ip, rest) = match data.len() {
+            263 => (
+                data[0],
+                IpAddr::from(<[u8; 4]>::try_from(&data[1..5]).unwrap()),
+                &data[5..],
+            ),
+            275 => (
+                data[0],
+                IpAddr::from(<[u8; 16]>::try_from(&data[1..17]).unwrap()),
+                &data[17..],
+            ),
+            _ => return Err(Error::MalformedData),
+        };
+        let port = u16::from_be_bytes([rest[0], rest[1]]);
+        let auth_key = rest[2..].try_into().unwrap();
+
+        let session = Self::new();
+        session.insert_dc_tcp(dc_id as i32, &SocketAddr::new(ip, port), auth_key);
+        Ok(session)
+    }
+
+    /// Import a Telethon session file, which is an SQLite database.
+    ///
+    /// Only the authorization key is imported. See [`Session::from_telethon_string`] for details.
+    pub fn from_telethon_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let data = fs::read(path.as_ref())?;
+        Self::from_telethon_database(&data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn from_telethon_database(data: &[u8]) -> Result<Self, Error> {
+        let db = sqlite::Database::new(data)?;
+        let root = db
+            .rows(1)?
+            .into_iter()
+            .find_map(|(_, row)| match row.as_slice() {
+                [
+                    sqlite::Value::Text(kind),
+                    sqlite::Value::Text(name),
+                    _,
+                    sqlite::Value::Int(root),
+                    ..,
+                ] if kind == "table" && name == "sessions" => Some(*root as u32),
+                _ => None,
+            })
+            .ok_or(Error::MalformedData)?;
+
+        // The table is `sessions (dc_id integer primary key, server_address text, port integer,
+        // auth_key blob, takeout_id integer)`. Being the primary key, `dc_id` is the row ID.
+        let session = Self::new();
+        for (row_id, row) in db.rows(root)? {
+            if let [
+                _,
+                sqlite::Value::Text(address),
+                sqlite::Value::Int(port),
+                sqlite::Value::Blob(auth_key),
+                ..,
+            ] = row.as_slice()
+            {
+                let ip = address
+                    .parse::<IpAddr>()
+                    .map_err(|_| Error::MalformedData)?;
+                let auth_key = auth_key
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| Error::MalformedData)?;
+                session.insert_dc_tcp(row_id as i32, &SocketAddr::new(ip, *port as u16), auth_key);
+            }
+        }
+
+        if session.get_dcs().is_empty() {
+            return Err(Error::MalformedData);
+        }
+        Ok(session)
+    }
+
+    /// Import a Pyrogram string session, as produced by Pyrogram's `export_session_string`.
+    ///
+    /// Sessions for Telegram's test servers are not supported, and fail with
+    /// [`Error::UnsupportedVersion`].
+    pub fn from_pyrogram_string(string: &str) -> Result<Self, Error> {
+        let data = URL_SAFE_LENIENT
+            .decode(string.trim())
+            .map_err(|_| Error::MalformedData)?;
+
+        // The current format is `>BI?256sQ?`, and older ones `>B?256sI?` and `>B?256sQ?`.
+        let (dc_id, test_mode, auth_key, user_id, bot) = match data.len() {
+            271 => (
+                data[0],
+                data[5],
+                &data[6..262],
+                u64::from_be_bytes(data[262..270].try_into().unwrap()),
+                data[270],
+            ),
+            267 => (
+                data[0],
+                data[1],
+                &data[2..258],
+                u64::from_be_bytes(data[258..266].try_into().unwrap()),
+                data[266],
+            ),
+            263 => (
+                data[0],
+                data[1],
+                &data[2..258],
+                u32::from_be_bytes(data[258..262].try_into().unwrap()) as u64,
+                data[262],
+            ),
+            _ => return Err(Error::MalformedData),
+        };
+        if test_mode != 0 {
+            return Err(Error::UnsupportedVersion);
+        }
+        let address = DC_ADDRESSES
+            .get(dc_id as usize)
+            .filter(|_| dc_id != 0)
+            .ok_or(Error::MalformedData)?;
+
+        let session = Self::new();
+        session.insert_dc_tcp(
+            dc_id as i32,
+            &SocketAddr::from(*address),
+            auth_key.try_into().unwrap(),
+        );
+        session.set_user(user_id as i64, dc_id as i32, bot != 0);
+        Ok(session)
+    }
+
+    /// Export the authorization to the home datacenter as a portable string session.
+    ///
+    /// The string uses Telethon's format, so it can be loaded both with
+    /// [`Session::from_telethon_string`] and by Telethon itself. Note that the logged-in user and
+    /// the update state are not part of the string.
+    ///
+    /// Returns `None` if there is no authorization key to the home datacenter, or if its
+    /// address is not known (as is the case with WebSocket connections).
+    pub fn to_string_session(&self) -> Option<String> {
+        let dcs = self.get_dcs();
+        let home_dc = match self.get_user() {
+            Some(user) => user.dc,
+            None => match dcs.as_slice() {
+                [dc] => dc.id(),
+                _ => return None,
+            },
+        };
+
+        let dc = dcs.into_iter().find_map(|dc| match dc {
+            enums::DataCenter::Center(dc) if dc.id == home_dc => Some(dc),
+            _ => None,
+        })?;
+        let auth_key = dc.auth.filter(|auth| auth.len() == 256)?;
+
+        let mut data = Vec::with_capacity(275);
+        data.push(u8::try_from(dc.id).ok()?);
+        if let Some(ipv4) = dc.ipv4 {
+            data.extend(ipv4.to_le_bytes());
+        } else {
+            data.extend(Ipv6Addr::from(dc.ipv6?).octets());
+        }
+        data.extend(u16::try_from(dc.port).ok()?.to_be_bytes());
+        data.extend(auth_key);
+
+        let mut string = TELETHON_STRING_VERSION.to_string();
+        URL_SAFE.encode_string(data, &mut string);
+        Some(string)
+    }
+}
+
+/// A minimal, read-only SQLite reader, enough to load the tables of a Telethon session file.
+///
+/// See [Database File Format](https://www.sqlite.org/fileformat.html).
+mod sqlite {
+    use crate::Error;
+
+    const HEADER: &[u8] = b"SQLite format 3\0";
+    const MIN_PAGE_SIZE: usize = 512;
+    const MIN_USABLE_SIZE: usize = 480;
+    // Cells larger than this spill into overflow pages.
+    const MAX_LOCAL_PAYLOAD_OVERHEAD: usize = 35;
+
+    pub enum Value {
+        Null,
+        Int(i64),
+        Text(String),
+        Blob(Vec<u8>),
+    }
+
+    pub struct Database<'a> {
+        data: &'a [u8],
+        page_size: usize,
+        usable_size: usize,
+    }
+
+    impl<'a> Database<'a> {
+        pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+            if data.len() < 100 || !data.starts_with(HEADER) {
+                return Err(Error::MalformedData);
+            }
+            let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+                1 => 65536,
+                n => n as usize,
+            };
+            if page_size < MIN_PAGE_SIZE || !page_size.is_power_of_two() {
+                return Err(Error::MalformedData);
+            }
+            let usable_size = page_size
+                .checked_sub(data[20] as usize)
+                .filter(|&size| size >= MIN_USABLE_SIZE)
+                .ok_or(Error::MalformedData)?;
+
+            Ok(Self {
+                data,
+                page_size,
+                usable_size,
+            })
+        }
+
+        /// Read all the rows and their IDs in the table whose B-tree starts at the given page.
+        pub fn rows(&self, root_page: u32) -> Result<Vec<(i64, Vec<Value>)>, Error> {
+            let mut rows = Vec::new();
+            let mut pending = vec![root_page];
+            // A valid B-tree can't visit more pages than there are, so this also stops cycles.
+            let mut remaining = self
+                .data
+                .len()
+                .checked_div(self.page_size)
+                .ok_or(Error::MalformedData)?;
+            while let Some(number) = pending.pop() {
+                remaining = remaining.checked_sub(1).ok_or(Error::MalformedData)?;
+                self.read_page(number, &mut pending, &mut rows)?;
+            }
+            Ok(rows)
+        }
+
+        fn read_page(
+            &self,
+            number: u32,
+            pending: &mut Vec<u32>,
+            rows: &mut Vec<(i64, Vec<Value>)>,
+        ) -> Result<(), Error> {
+            let start = (number as usize)
+                .checked_sub(1)
+                .and_then(|index| index.checked_mul(self.page_size))
+                .ok_or(Error::MalformedData)?;
+            let page = self
+                .data
+                .get(start..)
+                .and_then(|rest| rest.get(..self.page_size))
+                .ok_or(Error::MalformedData)?;
+            // The first page also contains the database header.
+            let header = if number == 1 { 100 } else { 0 };
+
+            let kind = *page.get(header).ok_or(Error::MalformedData)?;
+            let cell_count = read_u16(page, header + 3)? as usize;
+            let (header_size, leaf) = match kind {
+                0x0d => (8, true),
+                0x05 => (12, false),
+                _ => return Err(Error::MalformedData),
+            };
+            if !leaf {
+                pending.push(read_u32(page, header + 8)?);
+            }
+
+            for i in 0..cell_count {
+                let mut pos = read_u16(page, header + header_size + i * 2)? as usize;
+                if leaf {
+                    let size = read_varint(page, &mut pos)? as usize;
+                    let row_id = read_varint(page, &mut pos)?;
+                    // Payloads spilling into overflow pages are not needed for sessions.
+                    let max_local = self
+                        .usable_size
+                        .checked_sub(MAX_LOCAL_PAYLOAD_OVERHEAD)
+                        .ok_or(Error::MalformedData)?;
+                    if size > max_local {
+                        return Err(Error::MalformedData);
+                    }
+                    let payload = page
+                        .get(pos..)
+                        .and_then(|rest| rest.get(..size))
+                        .ok_or(Error::MalformedData)?;
+                    rows.push((row_id, read_record(payload)?));
+                } else {
+                    pending.push(read_u32(page, pos)?);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn read_u16(data: &[u8], pos: usize) -> Result<u16, Error> {
+        let bytes = data.get(pos..pos + 2).ok_or(Error::MalformedData)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(data: &[u8], pos: usize) -> Result<u32, Error> {
+        let bytes = data.get(pos..pos + 4).ok_or(Error::MalformedData)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_varint(data: &[u8], pos: &mut usize) -> Result<i64, Error> {
+        let mut value = 0u64;
+        for i in 0..9 {
+            let byte = *data.get(*pos).ok_or(Error::MalformedData)?;
+            *pos += 1;
+            if i == 8 {
+                return Ok(((value << 8) | byte as u64) as i64);
+            }
+            value = (value << 7) | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok(value as i64)
+    }
+
+    pub fn read_record(payload: &[u8]) -> Result<Vec<Value>, Error> {
+        let mut pos = 0;
+        let header_size = read_varint(payload, &mut pos)? as usize;
+        let mut serial_types = Vec::new();
+        while pos < header_size {
+            serial_types.push(read_varint(payload, &mut pos)?);
+        }
+
+        let mut pos = header_size;
+        let mut take = |len: usize| {
+            let bytes = payload.get(pos..pos + len).ok_or(Error::MalformedData);
+            pos += len;
+            bytes
+        };
+        let read_int = |bytes: &[u8]| {
+            let sign = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+            let mut buf = [sign; 8];
+            buf[8 - bytes.len()..].copy_from_slice(bytes);
+            i64::from_be_bytes(buf)
+        };
+
+        serial_types
+            .into_iter()
+            .map(|serial_type| {
+                Ok(match serial_type {
+                    0 => Value::Null,
+                    1..=4 => Value::Int(read_int(take(serial_type as usize)?)),
+                    5 => Value::Int(read_int(take(6)?)),
+                    6 => Value::Int(read_int(take(8)?)),
+                    // Floating point values are not needed for sessions.
+                    7 => {
+                        take(8)?;
+                        Value::Null
+                    }
+                    8 => Value::Int(0),
+                    9 => Value::Int(1),
+                    n if n >= 12 && n % 2 == 0 => {
+                        Value::Blob(take((n as usize - 12) / 2)?.to_vec())
+                    }
+                    n if n >= 13 => Value::Text(
+                        String::from_utf8(take((n as usize - 13) / 2)?.to_vec())
+                            .map_err(|_| Error::MalformedData)?,
+                    ),
+                    _ => return Err(Error::MalformedData),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose;
+
+    fn auth_key() -> [u8; 256] {
+        std::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn telethon_string_roundtrip() {
+        let session = Session::new();
+        session.insert_dc_tcp(4, &SocketAddr::from(([149, 154, 167, 91], 443)), auth_key());
+
+        let string = session.to_string_session().unwrap();
+        assert!(string.starts_with('1'));
+        assert_eq!(string.len(), 1 + 352);
+
+        let imported = Session::from_telethon_string(&string).unwrap();
+        assert_eq!(imported.dc_auth_key(4), Some(auth_key()));
+        assert_eq!(imported.to_string_session(), Some(string));
+    }
+
+    #[test]
+    fn telethon_string_ipv6() {
+        let mut data = vec![2];
+        data.extend(Ipv6Addr::LOCALHOST.octets());
+        data.extend(443u16.to_be_bytes());
+        data.extend(auth_key());
+        let string = format!("1{}", general_purpose::URL_SAFE.encode(data));
+
+        let session = Session::from_telethon_string(&string).unwrap();
+        assert_eq!(session.dc_auth_key(2), Some(auth_key()));
+        assert!(Session::from_telethon_string(&string[1..]).is_err());
+    }
+
+    #[test]
+    fn pyrogram_string() {
+        let mut data = vec![5];
+        data.extend(12345u32.to_be_bytes());
+        data.push(0);
+        data.extend(auth_key());
+        data.extend(777000u64.to_be_bytes());
+        data.push(1);
+        let string = general_purpose::URL_SAFE_NO_PAD.encode(&data);
+
+        let session = Session::from_pyrogram_string(&string).unwrap();
+        assert_eq!(session.dc_auth_key(5), Some(auth_key()));
+        let user = session.get_user().unwrap();
+        assert_eq!((user.id, user.dc, user.bot), (777000, 5, true));
+
+        data[5] = 1;
+        let string = general_purpose::URL_SAFE_NO_PAD.encode(&data);
+        assert!(matches!(
+            Session::from_pyrogram_string(&string),
+            Err(Error::UnsupportedVersion)
+        ));
+    }
+
+    fn fixture_path() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/telethon.session")
+    }
+
+    #[test]
+    fn telethon_file() {
+        let session = Session::from_telethon_file(fixture_path()).unwrap();
+        assert_eq!(session.dc_auth_key(2), Some(auth_key()));
+        assert!(session.to_string_session().is_some());
+    }
+
+    #[test]
+    fn telethon_file_malformed_header() {
+        let data = fs::read(fixture_path()).unwrap();
+
+        // Page sizes of zero, not powers of two, or smaller than allowed.
+        for page_size in [[0, 0], [0x10, 0x01], [0x01, 0x00]] {
+            let mut data = data.clone();
+            data[16..18].copy_from_slice(&page_size);
+            assert!(matches!(
+                sqlite::Database::new(&data),
+                Err(Error::MalformedData)
+            ));
+        }
+
+        // Reserved space leaving less usable space than allowed.
+        let mut small = data.clone();
+        small[16..18].copy_from_slice(&512u16.to_be_bytes());
+        small[20] = 64;
+        assert!(matches!(
+            sqlite::Database::new(&small),
+            Err(Error::MalformedData)
+        ));
+
+        // Files truncated before the end of the `sessions` table (the third page of the fixture)
+        // must fail instead of panicking.
+        for len in (0..3 * 4096).step_by(509) {
+            assert!(Session::from_telethon_database(&data[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn sqlite_record() {
+        // Header size 4, then NULL, an 8-bit integer, and a 3-character text.
+        let payload = [4, 0, 1, 19, 42, b'a', b'b', b'c'];
+        match sqlite::read_record(&payload).unwrap().as_slice() {
+            [
+                sqlite::Value::Null,
+                sqlite::Value::Int(42),
+                sqlite::Value::Text(text),
+            ] => assert_eq!(text, "abc"),
+            _ => panic!("unexpected record"),
+        }
+    }
+}
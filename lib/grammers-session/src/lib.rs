@@ -9,6 +9,7 @@
 #![deny(unsafe_code)]
 
 mod chat;
+mod compat;
 mod generated;
 mod message_box;
 
@@ -26,12 +27,14 @@ use std::io::{self, Read, Seek, Write};
 use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::path::Path;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 // Needed for auto-generated definitions.
 use grammers_tl_types::{Deserializable, Identifiable, Serializable, deserialize};
 
 pub struct Session {
     session: Mutex<types::Session>,
+    dirty: AtomicBool,
 }
 
 #[allow(clippy::new_without_default)]
@@ -42,7 +45,10 @@ impl Session {
                 dcs: Vec::new(),
                 user: None,
                 state: None,
+                usernames: None,
+                results: None,
             }),
+            dirty: AtomicBool::new(true),
         }
     }
 
@@ -78,6 +84,7 @@ impl Session {
                     })?
                     .into(),
             ),
+            dirty: AtomicBool::new(false),
         })
     }
 
@@ -113,6 +120,7 @@ impl Session {
             session.dcs.remove(pos);
         }
         session.dcs.push(dc);
+        self.mark_dirty();
     }
 
     pub fn insert_dc_tcp(&self, id: i32, addr: &SocketAddr, auth: [u8; 256]) {
@@ -145,7 +153,12 @@ impl Session {
     }
 
     pub fn set_user(&self, id: i64, dc: i32, bot: bool) {
-        self.session.lock().unwrap().user = Some(User { id, dc, bot }.into())
+        let user = Some(User { id, dc, bot }.into());
+        let mut session = self.session.lock().unwrap();
+        if session.user != user {
+            session.user = user;
+            self.mark_dirty();
+        }
     }
 
     /// Returns the stored user
@@ -165,7 +178,91 @@ impl Session {
     }
 
     pub fn set_state(&self, state: UpdateState) {
-        self.session.lock().unwrap().state = Some(state.into())
+        let state = Some(state.into());
+        let mut session = self.session.lock().unwrap();
+        if session.state != state {
+            session.state = state;
+            self.mark_dirty();
+        }
+    }
+
+    /// Returns the usernames which were resolved, along with the chat they resolved to and the
+    /// date when they were resolved.
+    pub fn get_resolved_usernames(&self) -> Vec<(String, PackedChat, i32)> {
+        let session = self.session.lock().unwrap();
+        session
+            .usernames
+            .iter()
+            .flatten()
+            .filter_map(|enums::ResolvedUsername::Username(resolved)| {
+                Some((
+                    resolved.username.clone(),
+                    PackedChat::from_bytes(&resolved.chat).ok()?,
+                    resolved.date,
+                ))
+            })
+            .collect()
+    }
+
+    /// Remembers the chat a username resolved to, replacing the previous one if any.
+    pub fn insert_resolved_username(&self, username: &str, chat: PackedChat, date: i32) {
+        let mut session = self.session.lock().unwrap();
+        let usernames = session.usernames.get_or_insert_with(Vec::new);
+        usernames
+            .retain(|enums::ResolvedUsername::Username(resolved)| resolved.username != username);
+        usernames.push(
+            types::ResolvedUsername {
+                username: username.to_string(),
+                chat: chat.to_bytes().to_vec(),
+                date,
+            }
+            .into(),
+        );
+        self.mark_dirty();
+    }
+
+    /// Forgets the chat a username resolved to.
+    pub fn remove_resolved_username(&self, username: &str) {
+        let mut session = self.session.lock().unwrap();
+        let Some(usernames) = session.usernames.as_mut() else {
+            return;
+        };
+        let len = usernames.len();
+        usernames
+            .retain(|enums::ResolvedUsername::Username(resolved)| resolved.username != username);
+        if usernames.len() != len {
+            self.mark_dirty();
+        }
+    }
+
+    /// Returns the last result saved under `key` with [`Session::set_cached_result`], along
+    /// with its hash.
+    pub fn get_cached_result(&self, key: &str) -> Option<(i64, Vec<u8>)> {
+        let session = self.session.lock().unwrap();
+        session
+            .results
+            .iter()
+            .flatten()
+            .find_map(|enums::CachedResult::Result(result)| {
+                (result.key == key).then(|| (result.hash, result.data.clone()))
+            })
+    }
+
+    /// Remembers the serialized result of a request which accepts a `hash`, so that it does
+    /// not have to be downloaded again if it did not change, replacing the previous one if any.
+    pub fn set_cached_result(&self, key: &str, hash: i64, data: Vec<u8>) {
+        let mut session = self.session.lock().unwrap();
+        let results = session.results.get_or_insert_with(Vec::new);
+        results.retain(|enums::CachedResult::Result(result)| result.key != key);
+        results.push(
+            types::CachedResult {
+                key: key.to_string(),
+                hash,
+                data,
+            }
+            .into(),
+        );
+        self.mark_dirty();
     }
 
     pub fn get_dcs(&self) -> Vec<enums::DataCenter> {
@@ -177,9 +274,43 @@ impl Session {
         enums::Session::Session(self.session.lock().unwrap().clone()).to_bytes()
     }
 
+    /// Whether the session has changed since it was loaded or last saved to a file.
+    ///
+    /// New sessions are always dirty, since they have never been saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
     /// Saves the session to a file.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
-        let mut file = OpenOptions::new().write(true).open(path.as_ref())?;
+        // Cleared before taking the snapshot, so that changes made while the file
+        // is being written mark the session as dirty again.
+        self.dirty.store(false, Ordering::Release);
+        let result = self.write_file(path.as_ref());
+        if result.is_err() {
+            self.mark_dirty();
+        }
+        result
+    }
+
+    /// Saves the session to a file, but only if it changed since the last time it was saved.
+    ///
+    /// Returns whether the file was written. This can be called as often as desired, for
+    /// example, after every update, without rewriting the file when nothing changed.
+    pub fn save_if_dirty<P: AsRef<Path>>(&self, path: P) -> io::Result<bool> {
+        if self.is_dirty() {
+            self.save_to_file(path).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn write_file(&self, path: &Path) -> io::Result<()> {
+        let mut file = OpenOptions::new().write(true).open(path)?;
         file.seek(io::SeekFrom::Start(0))?;
         file.set_len(0)?;
         file.write_all(&self.save())?;
@@ -203,3 +334,58 @@ impl fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_tracking() {
+        let session = Session::load(&Session::new().save()).unwrap();
+        assert!(!session.is_dirty());
+
+        session.set_user(1, 2, false);
+        assert!(session.is_dirty());
+
+        session.dirty.store(false, Ordering::Release);
+        session.set_user(1, 2, false);
+        assert!(!session.is_dirty());
+
+        session.insert_dc_ws(2, "wss://example.com", [0; 256]);
+        assert!(session.is_dirty());
+    }
+
+    #[test]
+    fn resolved_usernames_roundtrip() {
+        let chat = PackedChat {
+            ty: PackedType::Broadcast,
+            id: 123,
+            access_hash: Some(456),
+        };
+
+        let session = Session::new();
+        session.insert_resolved_username("telegram", chat, 1);
+        session.insert_resolved_username("telegram", chat, 2);
+        session.insert_resolved_username("durov", chat, 3);
+        session.remove_resolved_username("durov");
+
+        let session = Session::load(&session.save()).unwrap();
+        assert_eq!(
+            session.get_resolved_usernames(),
+            vec![("telegram".to_string(), chat, 2)]
+        );
+    }
+
+    #[test]
+    fn cached_results_roundtrip() {
+        let session = Session::new();
+        session.set_cached_result("contacts", 1, vec![1, 2, 3]);
+        session.set_cached_result("contacts", 2, vec![4, 5]);
+        session.set_cached_result("dialogs", 3, vec![6]);
+
+        let session = Session::load(&session.save()).unwrap();
+        assert_eq!(session.get_cached_result("contacts"), Some((2, vec![4, 5])));
+        assert_eq!(session.get_cached_result("dialogs"), Some((3, vec![6])));
+        assert_eq!(session.get_cached_result("stickers"), None);
+    }
+}
@@ -120,6 +120,30 @@ impl MessageBox {
         self.getting_diff_for.insert(entry);
     }
 
+    /// Force getting the difference for the account-wide entry, even if no gap was detected.
+    ///
+    /// Returns `false` if there is no known state yet, in which case there is nothing to fetch.
+    pub fn request_difference(&mut self) -> bool {
+        self.request_difference_for(Entry::AccountWide)
+    }
+
+    /// Force getting the difference for the given channel, even if no gap was detected.
+    ///
+    /// Returns `false` if the channel has no known state, in which case
+    /// [`MessageBox::subscribe_to_channel`] should be used instead.
+    pub fn request_channel_difference(&mut self, channel_id: i64) -> bool {
+        self.request_difference_for(Entry::Channel(channel_id))
+    }
+
+    fn request_difference_for(&mut self, entry: Entry) -> bool {
+        if self.map.get(&entry).is_none_or(|s| s.pts == NO_PTS) {
+            return false;
+        }
+        info!("forcing difference for {:?}", entry);
+        self.try_begin_get_diff(entry);
+        true
+    }
+
     /// Create a [`MessageBox`] from a previously known update state.
     pub fn load(state: UpdateState) -> Self {
         trace!("created new message box with state: {:?}", state);
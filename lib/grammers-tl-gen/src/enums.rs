@@ -100,7 +100,7 @@ fn write_common_field_impl<W: Write>(
     indent: &str,
     ty: &Type,
     metadata: &Metadata,
-    _config: &Config,
+    config: &Config,
 ) -> io::Result<()> {
     // Don't generate if only one type
     let definitions = metadata.defs_with_type(ty);
@@ -117,7 +117,7 @@ fn write_common_field_impl<W: Write>(
             .filter(|p| match p.ty {
                 ParameterType::Flags => false,
                 ParameterType::Normal { .. } => {
-                    !rustifier::parameters::qual_name(p).contains("Option<")
+                    !rustifier::parameters::qual_name(p, config).contains("Option<")
                 }
             })
             .collect();
@@ -143,7 +143,7 @@ fn write_common_field_impl<W: Write>(
         rustifier::types::type_name(ty)
     )?;
     for param in common_params {
-        let qual_name = rustifier::parameters::qual_name(param);
+        let qual_name = rustifier::parameters::qual_name(param, config);
         writeln!(
             file,
             "{}    pub fn {}(&self) -> {} {{\n{}        match self {{",
@@ -0,0 +1,101 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module selects which definitions should have code generated.
+
+use grammers_tl_parser::tl::{Category, Definition, ParameterType, Type};
+use std::collections::HashSet;
+
+/// Which definitions to generate code for.
+pub struct Filter<'a> {
+    /// Namespaces to leave out. Types in these namespaces are still kept
+    /// if any of the definitions being generated needs them.
+    pub skip_namespaces: &'a [&'a str],
+    /// Whether functions should be generated at all.
+    pub functions: bool,
+}
+
+impl Default for Filter<'_> {
+    fn default() -> Self {
+        Self {
+            skip_namespaces: &[],
+            functions: true,
+        }
+    }
+}
+
+/// Boxed type name a definition or parameter type refers to.
+fn type_key(ty: &Type) -> (Vec<String>, String) {
+    let mut chars = ty.name.chars();
+    let name = match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+    (ty.namespace.clone(), name)
+}
+
+fn collect_type(ty: &Type, used: &mut HashSet<(Vec<String>, String)>) {
+    used.insert(type_key(ty));
+    if let Some(arg) = &ty.generic_arg {
+        collect_type(arg, used);
+    }
+}
+
+fn collect_used(def: &Definition, used: &mut HashSet<(Vec<String>, String)>) {
+    for param in def.params.iter() {
+        if let ParameterType::Normal { ty, .. } = &param.ty {
+            collect_type(ty, used);
+        }
+    }
+    if def.category == Category::Functions {
+        collect_type(&def.ty, used);
+    }
+}
+
+/// Filter the definitions according to the given [`Filter`].
+///
+/// Functions in skipped namespaces (or all of them, if functions are disabled)
+/// are removed. Types in skipped namespaces are only removed if none of the
+/// remaining definitions reference them, directly or indirectly.
+pub fn filter_definitions(definitions: Vec<Definition>, filter: &Filter) -> Vec<Definition> {
+    let skipped = |def: &Definition| {
+        def.namespace
+            .first()
+            .is_some_and(|ns| filter.skip_namespaces.contains(&ns.as_str()))
+    };
+
+    let (mut keep, mut pending): (Vec<_>, Vec<_>) = definitions
+        .into_iter()
+        .filter(|def| filter.functions || def.category != Category::Functions)
+        .enumerate()
+        .partition(|(_, def)| !skipped(def));
+    pending.retain(|(_, def)| def.category == Category::Types);
+
+    let mut used = HashSet::new();
+    for (_, def) in keep.iter() {
+        collect_used(def, &mut used);
+    }
+
+    loop {
+        let (needed, rest): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .partition(|(_, def)| used.contains(&type_key(&def.ty)));
+        pending = rest;
+        if needed.is_empty() {
+            break;
+        }
+        for (_, def) in needed.iter() {
+            collect_used(def, &mut used);
+        }
+        keep.extend(needed);
+    }
+
+    // Preserve the original order so the generated code stays stable.
+    keep.sort_by_key(|(i, _)| *i);
+    keep.into_iter().map(|(_, def)| def).collect()
+}
@@ -12,11 +12,13 @@
 #![deny(unsafe_code)]
 
 mod enums;
+mod filter;
 mod grouper;
 mod metadata;
 mod rustifier;
 mod structs;
 
+pub use filter::{Filter, filter_definitions};
 use grammers_tl_parser::tl::{Category, Definition, Type};
 use std::io::{self, Write};
 
@@ -43,6 +45,9 @@ pub struct Config {
     pub impl_from_type: bool,
     pub impl_from_enum: bool,
     pub impl_serde: bool,
+    /// Generate `bytes` and `string` fields as `bytes::Bytes` and `crate::SharedString`,
+    /// which can share the buffer they were deserialized from instead of copying it.
+    pub shared_bytes: bool,
 }
 
 impl Default for Config {
@@ -54,6 +59,7 @@ impl Default for Config {
             impl_from_type: true,
             impl_from_enum: true,
             impl_serde: false,
+            shared_bytes: false,
         }
     }
 }
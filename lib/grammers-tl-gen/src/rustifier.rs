@@ -17,6 +17,7 @@
 //! * `item_path` for use as a qualified item path (`Vec::<u8>`).
 //! * `attr_name` for use as an attribute name (`foo_bar: ()`).
 
+use crate::Config;
 use grammers_tl_parser::tl::{Definition, Parameter, ParameterType, Type};
 
 /// Get the rusty type name for a certain definition, excluding namespace.
@@ -125,11 +126,13 @@ pub mod definitions {
 pub mod types {
     use super::*;
 
-    fn builtin_type(ty: &Type, path: bool) -> Option<&'static str> {
+    fn builtin_type(ty: &Type, path: bool, shared: bool) -> Option<&'static str> {
         Some(match ty.name.as_ref() {
             "Bool" => "bool",
             "bytes" => {
-                if path {
+                if shared {
+                    "bytes::Bytes"
+                } else if path {
                     "Vec::<u8>"
                 } else {
                     "Vec<u8>"
@@ -152,7 +155,13 @@ pub mod types {
                 }
             }
             "long" => "i64",
-            "string" => "String",
+            "string" => {
+                if shared {
+                    "crate::SharedString"
+                } else {
+                    "String"
+                }
+            }
             "true" => "bool",
             "vector" => "crate::RawVec",
             "Vector" => "Vec",
@@ -164,12 +173,12 @@ pub mod types {
     // name and item paths so this method is used for both:
     // 1. use `::<...>` instead of `<...>` to specify type arguments
     // 2. missing angle brackets in associated item path
-    fn get_path(ty: &Type, path: bool) -> String {
+    pub(super) fn get_path(ty: &Type, path: bool, shared: bool) -> String {
         if ty.generic_ref {
             return ty.name.clone();
         }
 
-        let mut result = if let Some(name) = builtin_type(ty, path) {
+        let mut result = if let Some(name) = builtin_type(ty, path, shared) {
             name.to_string()
         } else {
             let mut result = String::new();
@@ -191,7 +200,7 @@ pub mod types {
                 result.push_str("::");
             }
             result.push('<');
-            result.push_str(&qual_name(generic_ty));
+            result.push_str(&get_path(generic_ty, false, shared));
             result.push('>');
         }
 
@@ -203,18 +212,18 @@ pub mod types {
     }
 
     pub fn qual_name(ty: &Type) -> String {
-        get_path(ty, false)
+        get_path(ty, false, false)
     }
 
-    pub fn item_path(ty: &Type) -> String {
-        get_path(ty, true)
+    pub fn item_path(ty: &Type, config: &Config) -> String {
+        get_path(ty, true, config.shared_bytes)
     }
 }
 
 pub mod parameters {
     use super::*;
 
-    pub fn qual_name(param: &Parameter) -> String {
+    pub fn qual_name(param: &Parameter, config: &Config) -> String {
         match &param.ty {
             ParameterType::Flags => "u32".into(),
             ParameterType::Normal { ty, flag } if flag.is_some() && ty.name == "true" => {
@@ -225,7 +234,7 @@ pub mod parameters {
                 if flag.is_some() {
                     result.push_str("Option<");
                 }
-                result.push_str(&types::qual_name(ty));
+                result.push_str(&types::get_path(ty, false, config.shared_bytes));
                 if flag.is_some() {
                     result.push('>');
                 }
@@ -390,21 +399,32 @@ mod tests {
     #[test]
     fn check_type_item_path() {
         let ty = "Vector<FileHash>".parse().unwrap();
-        let name = types::item_path(&ty);
+        let name = types::item_path(&ty, &Config::default());
         assert_eq!(name, "Vec::<crate::enums::FileHash>");
     }
 
     #[test]
     fn check_type_bytes_item_path() {
         let ty = "bytes".parse().unwrap();
-        let name = types::item_path(&ty);
+        let name = types::item_path(&ty, &Config::default());
         assert_eq!(name, "Vec::<u8>");
     }
 
+    #[test]
+    fn check_type_shared_bytes_item_path() {
+        let config = Config {
+            shared_bytes: true,
+            ..Default::default()
+        };
+        let ty = "bytes".parse().unwrap();
+        let name = types::item_path(&ty, &config);
+        assert_eq!(name, "bytes::Bytes");
+    }
+
     #[test]
     fn check_type_large_int_item_path() {
         let ty = "int256".parse().unwrap();
-        let name = types::item_path(&ty);
+        let name = types::item_path(&ty, &Config::default());
         assert_eq!(name, "<[u8; 32]>");
     }
 
@@ -413,31 +433,42 @@ mod tests {
     #[test]
     fn check_param_qual_name() {
         let param = "pts:int".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "i32");
     }
 
     #[test]
     fn check_param_flag_def_qual_name() {
         let param = "flags:#".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "u32");
     }
 
     #[test]
     fn check_param_flags_qual_name() {
         let param = "timeout:flags.1?int".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "Option<i32>");
     }
 
     #[test]
     fn check_param_true_flags_qual_name() {
         let param = "big:flags.0?true".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "bool");
     }
 
+    #[test]
+    fn check_param_shared_string_qual_name() {
+        let config = Config {
+            shared_bytes: true,
+            ..Default::default()
+        };
+        let param = "titles:flags.2?Vector<string>".parse().unwrap();
+        let name = parameters::qual_name(&param, &config);
+        assert_eq!(name, "Option<Vec<crate::SharedString>>");
+    }
+
     #[test]
     fn check_param_attr_name() {
         let param = "access_hash:long".parse().unwrap();
@@ -86,7 +86,7 @@ fn write_struct<W: Write>(
                 // Flags are computed on-the-fly, not stored
             }
             ParameterType::Normal { ty, .. } => {
-                if config.impl_serde && ty.name.as_str() == "bytes" {
+                if config.impl_serde && !config.shared_bytes && ty.name.as_str() == "bytes" {
                     writeln!(file, "{}    #[serde(with = \"serde_bytes\")]", indent)?;
                 }
                 writeln!(
@@ -94,7 +94,7 @@ fn write_struct<W: Write>(
                     "{}    pub {}: {},",
                     indent,
                     rustifier::parameters::attr_name(param),
-                    rustifier::parameters::qual_name(param),
+                    rustifier::parameters::qual_name(param, config),
                 )?;
             }
         }
@@ -255,6 +255,7 @@ fn write_deserializable<W: Write>(
     indent: &str,
     def: &Definition,
     metadata: &Metadata,
+    config: &Config,
 ) -> io::Result<()> {
     writeln!(
         file,
@@ -310,7 +311,7 @@ fn write_deserializable<W: Write>(
                         write!(
                             file,
                             "{}::deserialize(buf)?",
-                            rustifier::types::item_path(ty)
+                            rustifier::types::item_path(ty, config)
                         )?;
                     }
                     if flag.is_some() {
@@ -461,7 +462,7 @@ fn write_definition<W: Write>(
     write_identifiable(file, indent, def, metadata)?;
     write_serializable(file, indent, def, metadata)?;
     if def.category == Category::Types || config.deserializable_functions {
-        write_deserializable(file, indent, def, metadata)?;
+        write_deserializable(file, indent, def, metadata, config)?;
     }
     if def.category == Category::Functions {
         write_rpc(file, indent, def, metadata)?;
@@ -5,7 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use grammers_tl_gen::{Config, Outputs, generate_rust_code};
+use grammers_tl_gen::{Config, Filter, Outputs, filter_definitions, generate_rust_code};
 use grammers_tl_parser::parse_tl_file;
 use grammers_tl_parser::tl::Definition;
 use std::io;
@@ -17,17 +17,8 @@ fn get_definitions(contents: &str) -> Vec<Definition> {
 }
 
 fn gen_rust_code(definitions: &[Definition]) -> io::Result<(String, String, String, String)> {
-    let mut outputs = Outputs {
-        common: Vec::new(),
-        types: Vec::new(),
-        functions: Vec::new(),
-        enums: Vec::new(),
-    };
-
-    generate_rust_code(
-        &mut outputs,
+    gen_rust_code_with(
         definitions,
-        LAYER,
         &Config {
             gen_name_for_id: false,
             deserializable_functions: true,
@@ -35,8 +26,23 @@ fn gen_rust_code(definitions: &[Definition]) -> io::Result<(String, String, Stri
             impl_from_enum: true,
             impl_from_type: true,
             impl_serde: true,
+            shared_bytes: false,
         },
-    )?;
+    )
+}
+
+fn gen_rust_code_with(
+    definitions: &[Definition],
+    config: &Config,
+) -> io::Result<(String, String, String, String)> {
+    let mut outputs = Outputs {
+        common: Vec::new(),
+        types: Vec::new(),
+        functions: Vec::new(),
+        enums: Vec::new(),
+    };
+
+    generate_rust_code(&mut outputs, definitions, LAYER, config)?;
 
     Ok((
         String::from_utf8(outputs.common).unwrap(),
@@ -155,3 +161,85 @@ fn generic_bytes_with_serde_bytes() -> io::Result<()> {
     assert!(types.contains("pub stripped_thumb: Option<Vec<u8>>,"));
     Ok(())
 }
+
+#[test]
+fn shared_bytes_fields() -> io::Result<()> {
+    let definitions = get_definitions(
+        r#"
+        chatPhotoEmpty#37c1011c = ChatPhoto;
+        chatPhoto#1c6e1c11 flags:# has_video:flags.0?true photo_id:long stripped_thumb:flags.1?bytes dc_id:int = ChatPhoto;
+        user#12345678 id:long first_name:string usernames:Vector<string> = User;
+        "#,
+    );
+
+    let (_, types, _, _) = gen_rust_code_with(
+        &definitions,
+        &Config {
+            impl_serde: true,
+            shared_bytes: true,
+            ..Default::default()
+        },
+    )?;
+    eprintln!("{types}");
+    assert!(!types.contains("serde_bytes"));
+    assert!(types.contains("pub stripped_thumb: Option<bytes::Bytes>,"));
+    assert!(types.contains("Some(bytes::Bytes::deserialize(buf)?)"));
+    assert!(types.contains("pub first_name: crate::SharedString,"));
+    assert!(types.contains("pub usernames: Vec<crate::SharedString>,"));
+    Ok(())
+}
+
+#[test]
+fn skipped_namespaces_keep_used_types() {
+    let tl = "
+        inputPeerEmpty#7f3b18ea = InputPeer;
+        stats.url#47a971e0 url:string = StatsURL;
+        stats.graph#8ea464b6 json:DataJSON = stats.Graph;
+        dataJSON#7d748d04 data:string = DataJSON;
+        stats.broadcastStats#396ca5fc followers:stats.Graph = stats.BroadcastStats;
+        ---functions---
+        messages.getPeer#aaaaaaaa peer:InputPeer = StatsURL;
+        stats.getBroadcastStats#ab42441a channel:InputPeer = stats.BroadcastStats;
+    ";
+    let names = |definitions: Vec<Definition>| {
+        definitions
+            .into_iter()
+            .map(|d| d.full_name())
+            .collect::<Vec<_>>()
+    };
+
+    let filtered = filter_definitions(
+        get_definitions(tl),
+        &Filter {
+            skip_namespaces: &["stats"],
+            functions: true,
+        },
+    );
+    assert_eq!(
+        names(filtered),
+        [
+            "inputPeerEmpty",
+            "stats.url",
+            "dataJSON",
+            "messages.getPeer"
+        ]
+    );
+
+    let filtered = filter_definitions(
+        get_definitions(tl),
+        &Filter {
+            skip_namespaces: &["messages"],
+            functions: false,
+        },
+    );
+    assert_eq!(
+        names(filtered),
+        [
+            "inputPeerEmpty",
+            "stats.url",
+            "stats.graph",
+            "dataJSON",
+            "stats.broadcastStats"
+        ]
+    );
+}
@@ -5,7 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use grammers_tl_gen::{Config, Outputs, generate_rust_code};
+use grammers_tl_gen::{Config, Filter, Outputs, filter_definitions, generate_rust_code};
 use grammers_tl_parser::parse_tl_file;
 use grammers_tl_parser::tl::Definition;
 use std::env;
@@ -13,6 +13,33 @@ use std::fs::File;
 use std::io::{self, BufRead, BufReader, BufWriter, Read};
 use std::path::Path;
 
+/// Namespaces in the `api.tl` which can be individually enabled with `ns-*` features.
+const NAMESPACES: [&str; 23] = [
+    "account",
+    "auth",
+    "bots",
+    "channels",
+    "chatlists",
+    "contacts",
+    "folders",
+    "fragment",
+    "help",
+    "langpack",
+    "messages",
+    "payments",
+    "phone",
+    "photos",
+    "premium",
+    "smsjobs",
+    "stats",
+    "stickers",
+    "storage",
+    "stories",
+    "updates",
+    "upload",
+    "users",
+];
+
 /// Load the type language definitions from a certain file.
 /// Parse errors will be printed to `stderr`, and only the
 /// valid results will be returned.
@@ -31,6 +58,20 @@ fn load_tl(file: &str) -> io::Result<Vec<Definition>> {
         .collect())
 }
 
+/// Namespaces whose `ns-*` feature is not enabled, unless `all-namespaces` is.
+fn skipped_namespaces() -> Vec<&'static str> {
+    if cfg!(feature = "all-namespaces") {
+        return Vec::new();
+    }
+    NAMESPACES
+        .into_iter()
+        .filter(|ns| {
+            let feature = format!("CARGO_FEATURE_NS_{}", ns.to_ascii_uppercase());
+            env::var_os(feature).is_none()
+        })
+        .collect()
+}
+
 /// Find the `// LAYER #` comment, and return its value if it's valid.
 fn find_layer(file: &str) -> io::Result<Option<i32>> {
     const LAYER_MARK: &str = "LAYER";
@@ -63,7 +104,14 @@ fn main() -> std::io::Result<()> {
         if cfg!(feature = "tl-mtproto") {
             definitions.extend(load_tl("tl/mtproto.tl")?);
         }
-        definitions
+        let skip_namespaces = skipped_namespaces();
+        filter_definitions(
+            definitions,
+            &Filter {
+                skip_namespaces: &skip_namespaces,
+                functions: cfg!(feature = "functions"),
+            },
+        )
     };
 
     let output_dir = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
@@ -81,6 +129,7 @@ fn main() -> std::io::Result<()> {
         impl_from_enum: cfg!(feature = "impl-from-enum"),
         impl_from_type: cfg!(feature = "impl-from-type"),
         impl_serde: cfg!(feature = "impl-serde"),
+        shared_bytes: cfg!(feature = "shared-bytes"),
     };
 
     generate_rust_code(&mut outputs, &definitions, layer, &config)?;
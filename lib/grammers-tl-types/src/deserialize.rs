@@ -48,11 +48,29 @@ impl fmt::Display for Error {
 pub struct Cursor<'a> {
     buf: &'a [u8],
     pos: usize,
+    #[cfg(feature = "shared-bytes")]
+    shared: Option<&'a bytes::Bytes>,
 }
 
 impl<'a> Cursor<'a> {
     pub fn from_slice(buf: &'a [u8]) -> Self {
-        Self { buf, pos: 0 }
+        Self {
+            buf,
+            pos: 0,
+            #[cfg(feature = "shared-bytes")]
+            shared: None,
+        }
+    }
+
+    /// Like [`Cursor::from_slice`], but byte-strings read from the buffer share it
+    /// instead of being copied.
+    #[cfg(feature = "shared-bytes")]
+    pub fn from_shared(buf: &'a bytes::Bytes) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            shared: Some(buf),
+        }
     }
 
     // TODO not a fan we need to expose this (and a way to create `Cursor`),
@@ -88,6 +106,46 @@ impl<'a> Cursor<'a> {
         self.pos = self.buf.len();
         Ok(self.pos - old)
     }
+
+    /// Read the contents of a byte-string, along with its length and padding.
+    fn read_tl_bytes(&mut self) -> Result<&'a [u8]> {
+        let first_byte = self.read_byte()?;
+        let (len, padding) = if first_byte == 254 {
+            let mut buffer = [0u8; 3];
+            self.read_exact(&mut buffer)?;
+            let len =
+                (buffer[0] as usize) | ((buffer[1] as usize) << 8) | ((buffer[2] as usize) << 16);
+
+            (len, len % 4)
+        } else {
+            let len = first_byte as usize;
+            (len, (len + 1) % 4)
+        };
+
+        if self.pos + len > self.buf.len() {
+            return Err(Error::UnexpectedEof);
+        }
+        let result = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+
+        if padding > 0 {
+            for _ in 0..(4 - padding) {
+                self.read_byte()?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Read a byte-string, sharing the buffer if the cursor was created from one.
+    #[cfg(feature = "shared-bytes")]
+    fn read_shared_bytes(&mut self) -> Result<bytes::Bytes> {
+        let result = self.read_tl_bytes()?;
+        Ok(match self.shared {
+            Some(shared) => shared.slice_ref(result),
+            None => bytes::Bytes::copy_from_slice(result),
+        })
+    }
 }
 
 /// The problem with being generic over `std::io::Read` is that it's
@@ -122,6 +180,28 @@ pub trait Deserializable {
     {
         Self::deserialize(&mut Cursor::from_slice(buf))
     }
+
+    /// Like [`Deserializable::from_bytes`], but the byte-strings in the instance share
+    /// the given buffer instead of copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_tl_types::Deserializable;
+    /// use bytes::Bytes;
+    ///
+    /// let buf = Bytes::from_static(&[0x02, 0x48, 0x69, 0x00]);
+    /// let value = Bytes::from_shared(&buf).unwrap();
+    /// assert_eq!(value, &b"Hi"[..]);
+    /// assert_eq!(value.as_ptr(), buf[1..].as_ptr());
+    /// ```
+    #[cfg(feature = "shared-bytes")]
+    fn from_shared(buf: &bytes::Bytes) -> Result<Self>
+    where
+        Self: std::marker::Sized,
+    {
+        Self::deserialize(&mut Cursor::from_shared(buf))
+    }
 }
 
 impl Deserializable for bool {
@@ -397,28 +477,61 @@ impl Deserializable for Vec<u8> {
     /// assert_eq!(Vec::<u8>::from_bytes(&[0x01, 0x7f, 0x00, 0x00]).unwrap(), vec![0x7f_u8]);
     /// ```
     fn deserialize(buf: Buffer) -> Result<Self> {
-        let first_byte = buf.read_byte()?;
-        let (len, padding) = if first_byte == 254 {
-            let mut buffer = [0u8; 3];
-            buf.read_exact(&mut buffer)?;
-            let len =
-                (buffer[0] as usize) | ((buffer[1] as usize) << 8) | ((buffer[2] as usize) << 16);
-
-            (len, len % 4)
-        } else {
-            let len = first_byte as usize;
-            (len, (len + 1) % 4)
-        };
-
-        let mut result = vec![0u8; len];
-        buf.read_exact(&mut result)?;
+        Ok(buf.read_tl_bytes()?.to_vec())
+    }
+}
 
-        if padding > 0 {
-            for _ in 0..(4 - padding) {
-                buf.read_byte()?;
-            }
-        }
+#[cfg(feature = "shared-bytes")]
+impl Deserializable for bytes::Bytes {
+    /// Deserializes a byte-string according to the following definition:
+    ///
+    /// * `string ? = String;`.
+    ///
+    /// The result shares the buffer if the cursor was created with
+    /// [`Cursor::from_shared`], and is a copy otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_tl_types::Deserializable;
+    /// use bytes::Bytes;
+    ///
+    /// assert_eq!(Bytes::from_bytes(&[0x00, 0x00, 0x00, 0x00]).unwrap(), Bytes::new());
+    /// assert_eq!(Bytes::from_bytes(&[0x01, 0x7f, 0x00, 0x00]).unwrap(), vec![0x7f_u8]);
+    /// ```
+    fn deserialize(buf: Buffer) -> Result<Self> {
+        buf.read_shared_bytes()
+    }
+}
 
-        Ok(result)
+#[cfg(feature = "shared-bytes")]
+impl Deserializable for crate::SharedString {
+    /// Deserializes a UTF-8 string according to the following definition:
+    ///
+    /// * `string ? = String;`.
+    ///
+    /// Like [`String`], invalid UTF-8 is replaced, in which case the result is
+    /// a copy even if the cursor was created with [`Cursor::from_shared`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_tl_types::{Deserializable, SharedString};
+    /// use bytes::Bytes;
+    ///
+    /// let buf = Bytes::from_static(&[0x02, 0x48, 0x69, 0x00]);
+    /// let string = SharedString::from_shared(&buf).unwrap();
+    /// assert_eq!(string, "Hi");
+    /// assert_eq!(string.as_ptr(), buf[1..].as_ptr());
+    ///
+    /// let string = SharedString::from_bytes(&[0x02, 0x48, 0xff, 0x00]).unwrap();
+    /// assert_eq!(string, "H\u{fffd}");
+    /// ```
+    fn deserialize(buf: Buffer) -> Result<Self> {
+        let bytes = buf.read_shared_bytes()?;
+        Ok(match std::str::from_utf8(&bytes) {
+            Ok(_) => Self(bytes),
+            Err(_) => String::from_utf8_lossy(&bytes).into_owned().into(),
+        })
     }
 }
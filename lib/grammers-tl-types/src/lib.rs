@@ -43,6 +43,8 @@
 //!
 //! The default feature set includes:
 //!
+//! * `all-namespaces`.
+//! * `functions`.
 //! * `impl-debug`.
 //! * `impl-from-enum`.
 //! * `impl-from-type`.
@@ -50,11 +52,22 @@
 //!
 //! The available features are:
 //!
+//! * `all-namespaces`: generates code for every namespace in the `api.tl`.
+//!   Without it, only definitions outside of a namespace and those in the
+//!   namespaces enabled with an `ns-*` feature (such as `ns-messages` or
+//!   `ns-upload`) are generated. Types from other namespaces are still
+//!   generated if any of the enabled definitions need them.
+//!
 //! * `deserializable-functions`: implements [`Deserializable`] for
 //!   [`functions`]. This might be of interest for server implementations,
 //!   which need to deserialize the client's requests, but is otherwise not
 //!   required.
 //!
+//! * `functions`: generates the [`functions`]. Without it, only the
+//!   [`types`] and [`enums`] needed to (de)serialize data are generated.
+//!   There is no equivalent to generate only functions, because they
+//!   cannot be used without the types they take and return.
+//!
 //! * `impl-debug`: implements `Debug` for the generated code.
 //!
 //! * `impl-from-enum`: implements `TryFrom<Enum> for Type`.
@@ -69,8 +82,16 @@
 //!
 //! * `impl-serde`: generates code for serde support
 //!
+//! * `shared-bytes`: generates `bytes` fields as `bytes::Bytes` and `string`
+//!   fields as `SharedString`. When deserializing with `Deserializable::from_shared`,
+//!   these share the input buffer instead of copying it, which saves allocations
+//!   when lots of data is received. Other buffers are still copied. Note that this
+//!   changes the type of the generated fields, so crates which build the owned
+//!   fields, such as `grammers-client`, cannot be used along with it.
+//!
 //! [`types`]: types/index.html
 //! [`functions`]: functions/index.html
+//! [`enums`]: enums/index.html
 //! [`RemoteCall`]: trait.RemoteCall.html
 //! [`Serializable`]: trait.Serializable.html
 //! [`Deserializable`]: trait.Deserializable.html
@@ -111,6 +132,98 @@ impl From<Vec<u8>> for Blob {
     }
 }
 
+/// This struct represents a `string` field when the `shared-bytes` feature is enabled.
+/// It holds UTF-8 text which may share the buffer it was deserialized from, and
+/// dereferences to a [`str`].
+#[cfg(feature = "shared-bytes")]
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedString(bytes::Bytes);
+
+#[cfg(feature = "shared-bytes")]
+impl SharedString {
+    pub fn as_str(&self) -> &str {
+        // Only valid UTF-8 is ever stored, and checking it again is still cheaper than a copy.
+        std::str::from_utf8(&self.0).expect("shared strings should be valid UTF-8")
+    }
+
+    /// The underlying buffer, which may be shared with the data it was deserialized from.
+    pub fn into_bytes(self) -> bytes::Bytes {
+        self.0
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl std::ops::Deref for SharedString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl From<String> for SharedString {
+    fn from(value: String) -> Self {
+        Self(value.into_bytes().into())
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl From<&str> for SharedString {
+    fn from(value: &str) -> Self {
+        Self(bytes::Bytes::copy_from_slice(value.as_bytes()))
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl From<SharedString> for String {
+    fn from(value: SharedString) -> Self {
+        value.as_str().to_owned()
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl PartialEq<str> for SharedString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl PartialEq<&str> for SharedString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl std::fmt::Debug for SharedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl std::fmt::Display for SharedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(all(feature = "shared-bytes", feature = "impl-serde"))]
+impl serde::Serialize for SharedString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(all(feature = "shared-bytes", feature = "impl-serde"))]
+impl<'de> serde::Deserialize<'de> for SharedString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer).map(Self::from)
+    }
+}
+
 /// Anything implementing this trait is identifiable by both ends (client-server)
 /// when performing Remote Procedure Calls (RPC) and transmission of objects.
 pub trait Identifiable {
@@ -256,6 +256,43 @@ impl Serializable for String {
     }
 }
 
+#[cfg(feature = "shared-bytes")]
+impl Serializable for bytes::Bytes {
+    /// Serializes a byte-string according to the following definition:
+    ///
+    /// * `string ? = String;`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_tl_types::Serializable;
+    /// use bytes::Bytes;
+    ///
+    /// assert_eq!(Bytes::from_static(&[0x7f]).to_bytes(), [0x01, 0x7f, 0x00, 0x00]);
+    /// ```
+    fn serialize(&self, buf: &mut impl Extend<u8>) {
+        (&self[..]).serialize(buf)
+    }
+}
+
+#[cfg(feature = "shared-bytes")]
+impl Serializable for crate::SharedString {
+    /// Serializes a UTF-8 string according to the following definition:
+    ///
+    /// * `string ? = String;`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_tl_types::{Serializable, SharedString};
+    ///
+    /// assert_eq!(SharedString::from("Hi").to_bytes(), [0x02, 0x48, 0x69, 0x00]);
+    /// ```
+    fn serialize(&self, buf: &mut impl Extend<u8>) {
+        (&self.0[..]).serialize(buf)
+    }
+}
+
 impl Serializable for Vec<u8> {
     /// Serializes a vector of bytes as a byte-string according to the following
     /// definition: